@@ -1,9 +1,9 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::config::Config;
+use crate::config::{ApprovalPolicy, Config, SandboxMode};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Agent {
@@ -11,7 +11,7 @@ pub struct Agent {
     pub description: String,
     pub system_prompt: String,
     pub temperature: f32,
-    #[serde(default)]
+    #[serde(default, alias = "tools")]
     pub tools_enabled: bool,
     #[serde(default = "default_color")]
     pub color: String,
@@ -19,6 +19,12 @@ pub struct Agent {
     pub title: Option<String>,
     #[serde(default)]
     pub preferred_preset: Option<String>,
+    /// Most access the agent gets, whatever the global sandbox_mode says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_sandbox_mode: Option<SandboxMode>,
+    /// Least supervision the agent gets, whatever the global approval_policy says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_approval_policy: Option<ApprovalPolicy>,
 }
 
 fn default_color() -> String {
@@ -31,12 +37,82 @@ impl Agent {
         Ok(config_dir.join("agents"))
     }
 
+    /// Load `<name>.md` or, failing that, `<name>.json` from the agents directory
     pub fn load(name: &str) -> Result<Self> {
-        let path = Self::agents_dir()?.join(format!("{}.json", name));
-        let json = fs::read_to_string(&path)
+        let path = Self::file_path(name)?
+            .with_context(|| format!("Failed to load agent: {} (not found)", name))?;
+        let text = fs::read_to_string(&path)
             .with_context(|| format!("Failed to load agent: {}", name))?;
-        let agent: Agent = serde_json::from_str(&json)?;
-        Ok(agent)
+        Self::parse(&text, &path).with_context(|| format!("Failed to load agent: {}", name))
+    }
+
+    /// The file an agent is loaded from, if it exists; markdown wins over JSON
+    pub fn file_path(name: &str) -> Result<Option<PathBuf>> {
+        let dir = Self::agents_dir()?;
+        Ok(["md", "json"]
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", name, ext)))
+            .find(|path| path.exists()))
+    }
+
+    /// Parse an agent definition written for `path`, which decides the format
+    pub fn parse(text: &str, path: &Path) -> Result<Self> {
+        if path.extension().and_then(|s| s.to_str()) == Some("md") {
+            Self::from_markdown(text, path)
+        } else {
+            Ok(serde_json::from_str(text)?)
+        }
+    }
+
+    /// Parse an agent written as markdown: `key: value` frontmatter between `---`
+    /// lines holds the settings, and the body is the system prompt.
+    /// The name defaults to the file name.
+    fn from_markdown(text: &str, path: &Path) -> Result<Self> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let rest = text
+            .strip_prefix("---")
+            .and_then(|rest| rest.strip_prefix('\n').or_else(|| rest.strip_prefix("\r\n")))
+            .context("Agent markdown must start with YAML frontmatter between --- lines")?;
+        // The closing line is exactly ---; a ---- rule or "--- notes" is not it
+        let mut start = 0;
+        let mut closing = None;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == "---" {
+                closing = Some((start, start + line.len()));
+                break;
+            }
+            start += line.len();
+        }
+        let Some((end, body_start)) = closing else {
+            anyhow::bail!("Agent frontmatter is missing its closing --- line");
+        };
+        let (frontmatter, body) = (&rest[..end], &rest[body_start..]);
+
+        let mut settings = parse_frontmatter(frontmatter).context("Invalid agent frontmatter")?;
+        if !settings.contains_key("name") {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            settings.insert("name".into(), stem.into());
+        }
+        settings.insert("system_prompt".into(), body.trim().into());
+        Ok(serde_json::from_value(serde_json::Value::Object(settings))?)
+    }
+
+    /// Load an agent, creating the bundled defaults first if none exist yet.
+    /// Built-in agents added in newer versions are installed on first use.
+    pub fn load_or_create_defaults(name: &str) -> Result<Self> {
+        if !Self::agents_dir()?.exists() {
+            Self::create_default_agents()?;
+        }
+        match Self::load(name) {
+            Ok(agent) => Ok(agent),
+            Err(e) => match Self::builtin(name) {
+                Some(agent) => {
+                    agent.save()?;
+                    Ok(agent)
+                }
+                None => Err(e),
+            },
+        }
     }
 
     pub fn save(&self) -> Result<()> {
@@ -45,10 +121,52 @@ impl Agent {
 
         let path = dir.join(format!("{}.json", self.name));
         let json = serde_json::to_string_pretty(self)?;
+        if fs::read_to_string(&path).is_ok_and(|current| current != json) {
+            Self::archive(&self.name)?;
+        }
         fs::write(path, json)?;
         Ok(())
     }
 
+    /// Where previous versions of an agent are kept
+    pub fn history_dir(name: &str) -> Result<PathBuf> {
+        Ok(Self::agents_dir()?.join("history").join(name))
+    }
+
+    /// Copy the agent's current file into its history before it is changed
+    /// or removed. Versions are named by time, so they sort oldest first.
+    pub fn archive(name: &str) -> Result<Option<PathBuf>> {
+        let Some(path) = Self::file_path(name)? else {
+            return Ok(None);
+        };
+        let dir = Self::history_dir(name)?;
+        fs::create_dir_all(&dir)?;
+
+        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("json");
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+        let version = dir.join(format!("{}.{}", stamp, extension));
+        fs::copy(&path, &version)
+            .with_context(|| format!("Failed to keep the previous version of agent {}", name))?;
+        Ok(Some(version))
+    }
+
+    /// Previous versions of an agent, newest first
+    pub fn history(name: &str) -> Result<Vec<PathBuf>> {
+        let dir = Self::history_dir(name)?;
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut versions: Vec<PathBuf> = fs::read_dir(dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| matches!(path.extension().and_then(|s| s.to_str()), Some("json" | "md")))
+            .collect();
+        versions.sort();
+        versions.reverse();
+        Ok(versions)
+    }
+
     pub fn list_agents() -> Result<Vec<String>> {
         let dir = Self::agents_dir()?;
 
@@ -61,14 +179,16 @@ impl Agent {
             let entry = entry?;
             let path = entry.path();
 
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            if matches!(path.extension().and_then(|s| s.to_str()), Some("json" | "md")) {
                 if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
                     agents.push(name.to_string());
                 }
             }
         }
 
+        // An agent can exist as both; load prefers the markdown one
         agents.sort();
+        agents.dedup();
         Ok(agents)
     }
 
@@ -112,6 +232,22 @@ impl Agent {
         let dir = Self::agents_dir()?;
         fs::create_dir_all(&dir)?;
 
+        for agent in Self::builtin_agents() {
+            agent.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Look up the bundled definition of a built-in agent
+    pub fn builtin(name: &str) -> Option<Self> {
+        Self::builtin_agents().into_iter().find(|a| a.name == name)
+    }
+
+    /// The agents shipped with vork, in their original form
+    pub fn builtin_agents() -> Vec<Self> {
+        let mut agents = vec![];
+
         // Default coding assistant
         let default = Agent {
             name: "default".to_string(),
@@ -123,8 +259,21 @@ You have access to the following tools:
 - read_file: Read the contents of files
 - write_file: Create or overwrite files with new content
 - list_files: List files in a directory
+- workspace_stats: Lines of code per language, largest files and git churn hotspots
 - bash_exec: Execute bash commands
 - search_files: Search for patterns in files using grep
+- clipboard_read / clipboard_write: Read what the user copied, or copy text for them
+- bash_background / background_output / background_stop: Run a dev server or watcher without blocking, check its output, stop it
+- list_processes / kill_process: Find and stop processes you started, such as dev servers
+- run_snippet: Run a short Python or Node program in a temporary directory
+- cargo_check / cargo_clippy / cargo_test: Build, lint and test Rust code with compact diagnostics
+- coverage_report: Per-file test coverage and uncovered line ranges (cargo llvm-cov, tarpaulin or nyc)
+- profile_run: Profile a command (perf, flamegraph) for its hottest functions, or time it with hyperfine
+- binary_info: Format, hardening, sections, imports, exports and largest functions of a binary
+- binary_strings: Strings in a binary with their offsets, filtered and paged
+- disassemble_function: Disassemble one function by name or address, with the functions it calls
+- sql_query: Query a SQLite file or a database configured in .vork.toml (read-only by default)
+- add_finding / list_findings / resolve_finding: Track audit findings across sessions in .vork/findings.json
 
 CRITICAL: All user requests are WORKSPACE-RELATIVE by default.
 - When user says "put it in /docs/", they mean "./docs/" (relative to current workspace)
@@ -150,8 +299,10 @@ You should be proactive in using tools to help solve problems. Don't just sugges
             tools_enabled: true,
             color: "cyan".to_string(),
             title: Some("🚀 VORK - AI Coding Assistant".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        default.save()?;
+        agents.push(default);
 
         // Rust expert
         let rust_expert = Agent {
@@ -177,13 +328,16 @@ When writing Rust code:
 4. Prefer composition over inheritance
 5. Write comprehensive tests and documentation
 
-Always use the available tools to read existing code, make changes, and run tests."#.to_string(),
+Always use the available tools to read existing code, make changes, and run tests.
+Check your work with cargo_check, cargo_clippy and cargo_test rather than running cargo through bash_exec: they return just the diagnostics and failing tests."#.to_string(),
             temperature: 0.6,
             tools_enabled: true,
             color: "red".to_string(),
             title: Some("🦀 Rust Expert".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        rust_expert.save()?;
+        agents.push(rust_expert);
 
         // Code reviewer
         let reviewer = Agent {
@@ -213,8 +367,10 @@ Use tools to read files and search for patterns. Be thorough but constructive."#
             tools_enabled: true,
             color: "magenta".to_string(),
             title: Some("🔍 Code Reviewer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        reviewer.save()?;
+        agents.push(reviewer);
 
         // Documenter
         let documenter = Agent {
@@ -244,8 +400,10 @@ Use tools to read files and add documentation where needed."#.to_string(),
             tools_enabled: true,
             color: "blue".to_string(),
             title: Some("📝 Documentation Specialist".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        documenter.save()?;
+        agents.push(documenter);
 
         // Debug assistant
         let debugger = Agent {
@@ -276,8 +434,10 @@ Use tools to read code, search for patterns, run tests, and apply fixes."#.to_st
             tools_enabled: true,
             color: "yellow".to_string(),
             title: Some("🐛 Debug Specialist".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        debugger.save()?;
+        agents.push(debugger);
 
         // Code auditor
         let auditor = Agent {
@@ -290,11 +450,11 @@ CRITICAL: All user paths are WORKSPACE-RELATIVE by default.
 - "/src/" means "./src/" (workspace-relative)
 - Only absolute for explicit system paths like /usr/, /etc/, /home/username/
 
-OUTPUT LOCATION:
-- ALWAYS write detailed audit reports to ./docs/audit/ directory
-- Create comprehensive markdown files with full issue details
-- Include timestamps and project structure analysis
-- Generate both summary and detailed reports
+FINDINGS:
+- Record EVERY issue with add_finding (file, line, severity, rule, message); findings are kept in .vork/findings.json across audits and duplicates are merged
+- Call list_findings first to see what earlier audits found, and resolve_finding for issues that have since been fixed
+- Write a markdown report to ./docs/audit/ only when the user asks for one
+- Start with workspace_stats to see which languages and files dominate the codebase and which change most often
 
 CRITICAL ISSUES TO FIND:
 1. Stub Implementations (HIGHEST PRIORITY):
@@ -351,7 +511,7 @@ VERBOSE AUDIT PROCESS:
    - Specific recommended fix with code examples
    - Impact assessment (what could break)
 
-REPORT FORMAT (save to ./docs/audit/):
+REPORT FORMAT (when the user asks for a written report, save to ./docs/audit/):
 ```markdown
 # Code Audit Report - [Timestamp]
 
@@ -391,23 +551,26 @@ EXTREME VERBOSITY REQUIRED:
 OUTPUT WORKFLOW:
 1. Search for all TODO/FIXME/unwrap/panic patterns
 2. Read each file containing issues
-3. Write detailed report to ./docs/audit/report-[date].md
-4. Write summary to ./docs/audit/summary.md
-5. Show user the report location and key findings
+3. Record each confirmed issue with add_finding
+4. Resolve findings from earlier audits that are fixed
+5. Summarize the open findings for the user (list_findings)
 
 Tools to use heavily:
 - search_files: Find ALL TODOs, unwraps, panics, stubs
 - list_files: Enumerate all source files
 - read_file: Analyze implementation details
-- write_file: Save comprehensive audit reports to ./docs/audit/
+- add_finding / list_findings / resolve_finding: Track every issue across audits
+- write_file: Save a written report to ./docs/audit/ when asked
 
 Be thorough, verbose, and detailed. Flag EVERYTHING that needs attention."#.to_string(),
             temperature: 0.4,
             tools_enabled: true,
             color: "lightred".to_string(),
             title: Some("🔍 Code Auditor".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        auditor.save()?;
+        agents.push(auditor);
 
         // Reverse engineer
         let reverse_engineer = Agent {
@@ -420,6 +583,14 @@ CRITICAL: All user paths are WORKSPACE-RELATIVE by default.
 - "/bin/" means "./bin/" (workspace-relative)
 - Only absolute for explicit system paths like /usr/bin/, /etc/, /home/username/
 
+STRUCTURED TOOLS (use these first; their output is bounded and parsed):
+- binary_info: Format, architecture, entry point, PIE/NX/RELRO/canary, sections, imports, exports, largest functions
+- binary_strings: Strings with file offsets; use contains to search (http, key, password, %s) and offset to page
+- disassemble_function: One function by symbol name or 0x address, with the functions it calls
+
+Raw r2 or objdump through bash_exec can print megabytes; only use it for what the tools above cannot
+do, and always bound it (pdf @ one function, | head).
+
 TOOLS AND TECHNIQUES:
 - radare2 (r2): Disassembly, debugging, binary analysis
 - Ghidra: Decompilation, code flow analysis, symbol recovery
@@ -472,13 +643,16 @@ RADARE2 COMMANDS YOU USE:
 - s addr: Seek to address
 - V: Visual mode
 
-Use tools to execute r2, ghidra, objdump, and other RE utilities. Always provide detailed explanations of your findings."#.to_string(),
+Start with binary_info, then binary_strings and disassemble_function; use bash_exec for dynamic analysis
+(gdb, strace, ltrace) and for Ghidra. Always provide detailed explanations of your findings."#.to_string(),
             temperature: 0.5,
             tools_enabled: true,
             color: "lightmagenta".to_string(),
             title: Some("🔬 Reverse Engineer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        reverse_engineer.save()?;
+        agents.push(reverse_engineer);
 
         // Code editor
         let code_editor = Agent {
@@ -533,8 +707,10 @@ You ARE for:
             tools_enabled: true,
             color: "lightblue".to_string(),
             title: Some("✏️  Code Editor".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        code_editor.save()?;
+        agents.push(code_editor);
 
         // Release manager
         let release_manager = Agent {
@@ -598,8 +774,10 @@ Use tools to:
             tools_enabled: true,
             color: "lightgreen".to_string(),
             title: Some("🚀 Release Manager".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        release_manager.save()?;
+        agents.push(release_manager);
 
         // Performance optimizer
         let performance_optimizer = Agent {
@@ -613,13 +791,15 @@ CRITICAL: All user paths are WORKSPACE-RELATIVE by default.
 - Only absolute for explicit system paths like /usr/, /etc/, /home/username/
 
 PROFILING TOOLS:
-- perf: CPU profiling on Linux
+- profile_run: Sample a command with perf or flamegraph and get the hottest functions; start every investigation here
+- profile_run with tool hyperfine (and compare): Time a command, or before and after versions side by side
 - valgrind: Memory profiling and leak detection
-- flamegraphs: Visualization of hot paths
 - criterion: Rust benchmarking
-- hyperfine: Command-line benchmarking
 - time, /usr/bin/time: Basic timing
 
+Profile release builds with debug symbols (CARGO_PROFILE_RELEASE_DEBUG=true cargo build --release),
+otherwise the hot symbols are addresses. Profiles and flamegraphs are kept in .vork/profiles.
+
 OPTIMIZATION AREAS:
 1. CPU Performance:
    - Algorithm complexity (O(n²) → O(n log n))
@@ -674,8 +854,10 @@ Always provide before/after benchmarks and explain the optimization."#.to_string
             tools_enabled: true,
             color: "lightyellow".to_string(),
             title: Some("⚡ Performance Optimizer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        performance_optimizer.save()?;
+        agents.push(performance_optimizer);
 
         // Security auditor
         let security_auditor = Agent {
@@ -746,6 +928,7 @@ AUDIT PROCESS:
 5. Check dependencies for vulnerabilities
 6. Examine error handling
 7. Test for common vulnerabilities
+8. Record each vulnerability with add_finding (rule: security or a CWE id) so it is tracked across audits; check list_findings first and resolve_finding for ones that are fixed
 
 REPORT FORMAT:
 - CVE/CWE references where applicable
@@ -759,8 +942,10 @@ Always prioritize findings by exploitability and impact."#.to_string(),
             tools_enabled: true,
             color: "red".to_string(),
             title: Some("🛡️  Security Auditor".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        security_auditor.save()?;
+        agents.push(security_auditor);
 
         // Test writer
         let test_writer = Agent {
@@ -835,8 +1020,8 @@ TEST MAINTENANCE:
 - Update tests when requirements change
 
 TOOLS:
-- cargo test: Run Rust tests
-- cargo tarpaulin: Code coverage
+- cargo_test: Run Rust tests and see just the failures
+- coverage_report: Find the files and line ranges no test reaches; start there, and run it again to confirm new tests cover them
 - pytest, jest, etc.: Framework-specific
 
 Always ensure tests are valuable, maintainable, and actually test what they claim to test."#.to_string(),
@@ -844,8 +1029,10 @@ Always ensure tests are valuable, maintainable, and actually test what they clai
             tools_enabled: true,
             color: "lightcyan".to_string(),
             title: Some("🧪 Test Engineer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        test_writer.save()?;
+        agents.push(test_writer);
 
         // DevOps engineer
         let devops = Agent {
@@ -928,8 +1115,10 @@ Use tools to create Dockerfiles, CI/CD configs, deployment scripts, and infrastr
             tools_enabled: true,
             color: "blue".to_string(),
             title: Some("🔧 DevOps Engineer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        devops.save()?;
+        agents.push(devops);
 
         // Online researcher
         let researcher = Agent {
@@ -1006,8 +1195,59 @@ Example response format:
             tools_enabled: true,
             color: "lightgreen".to_string(),
             title: Some("🔬 Research Specialist".to_string()),
+            // Research never changes the workspace, whatever the global config allows
+            max_sandbox_mode: Some(SandboxMode::ReadOnly),
+            max_approval_policy: None,
+        };
+        agents.push(researcher);
+
+        // Commit message writer (used by the prepare-commit-msg hook)
+        let commit_writer = Agent {
+            name: "commit-writer".to_string(),
+            description: "Writes concise commit messages from staged diffs".to_string(),
+            preferred_preset: None,
+            system_prompt: r#"You write git commit messages from diffs.
+
+Rules:
+1. First line: imperative mood summary, at most 72 characters, no trailing period
+2. Blank line, then a short body explaining WHAT changed and WHY (wrap at 72 columns)
+3. Skip the body for trivial changes
+4. Never invent changes that are not in the diff
+5. Output ONLY the commit message - no code fences, no preamble, no commentary"#.to_string(),
+            temperature: 0.3,
+            tools_enabled: false,
+            color: "yellow".to_string(),
+            title: Some("✍️ Commit Writer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        researcher.save()?;
+        agents.push(commit_writer);
+
+        // Pre-push reviewer (used by the pre-push hook)
+        let push_reviewer = Agent {
+            name: "push-reviewer".to_string(),
+            description: "Quick review of outgoing commits before a push".to_string(),
+            preferred_preset: None,
+            system_prompt: r#"You are doing a fast pre-push review of an outgoing diff.
+
+Only report things worth stopping a push for:
+- Obvious bugs, panics, or broken logic
+- Leftover debug output, commented-out code, or TODOs added in this diff
+- Secrets, credentials, or private keys
+- Accidentally committed build artifacts or large binaries
+
+Format:
+- One bullet per issue with file:line and a one-sentence explanation
+- If nothing is worth blocking on, reply with exactly: LGTM
+Be brief. Do not restate the diff."#.to_string(),
+            temperature: 0.3,
+            tools_enabled: false,
+            color: "magenta".to_string(),
+            title: Some("🚦 Pre-push Reviewer".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
+        };
+        agents.push(push_reviewer);
 
         // Template agent
         let template = Agent {
@@ -1045,9 +1285,105 @@ Remember to:
             tools_enabled: true,
             color: "green".to_string(),
             title: Some("🤖 [AGENT_TITLE]".to_string()),
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
-        template.save()?;
+        agents.push(template);
 
-        Ok(())
+        agents
+    }
+}
+
+/// Frontmatter settings: one `key: value` per line, with `#` comments.
+/// Values are booleans, numbers or strings, optionally quoted; `null` or
+/// `~` leaves the setting at its default.
+fn parse_frontmatter(frontmatter: &str) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let mut settings = serde_json::Map::new();
+    for (number, line) in frontmatter.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .with_context(|| format!("line {}: expected `key: value`", number + 1))?;
+        let key = key.trim();
+        if key.is_empty() {
+            anyhow::bail!("line {}: missing key", number + 1);
+        }
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let inner = value[1..]
+                    .strip_suffix(quote)
+                    .with_context(|| format!("line {}: unterminated quote", number + 1))?;
+                serde_json::Value::String(inner.to_string())
+            }
+            _ => {
+                let value = value.split_once(" #").map_or(value, |(value, _)| value).trim_end();
+                match value {
+                    "" | "null" | "~" => continue,
+                    "true" => true.into(),
+                    "false" => false.into(),
+                    _ => match value.parse::<f64>() {
+                        Ok(number) if number.is_finite() => number.into(),
+                        _ => value.into(),
+                    },
+                }
+            }
+        };
+        settings.insert(key.to_string(), value);
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Agent> {
+        Agent::parse(text, Path::new("/agents/reviewer.md"))
+    }
+
+    #[test]
+    fn frontmatter_and_prompt() {
+        let agent = parse("---\ndescription: Reviews code\ntemperature: 0.2\ncolor: blue\n---\n\nYou review code.\n").unwrap();
+        assert_eq!(agent.name, "reviewer");
+        assert_eq!(agent.description, "Reviews code");
+        assert_eq!(agent.temperature, 0.2);
+        assert_eq!(agent.color, "blue");
+        assert_eq!(agent.system_prompt, "You review code.");
+    }
+
+    #[test]
+    fn windows_line_endings_and_a_byte_order_mark() {
+        let agent = parse("\u{feff}---\r\nname: critic\r\ndescription: d\r\ntemperature: 1\r\n---  \r\nBe blunt.\r\n").unwrap();
+        assert_eq!(agent.name, "critic");
+        assert_eq!(agent.system_prompt, "Be blunt.");
+    }
+
+    #[test]
+    fn dash_lines_in_the_prompt_are_kept() {
+        let agent = parse("---\ndescription: d\ntemperature: 0.5\n---\nPrompt\n\n----\n--- notes\n---\nmore").unwrap();
+        assert_eq!(agent.system_prompt, "Prompt\n\n----\n--- notes\n---\nmore");
+    }
+
+    #[test]
+    fn only_a_bare_dash_line_closes_the_frontmatter() {
+        assert!(parse("You review code.").is_err());
+        assert!(parse("---\ndescription: d\ntemperature: 0.5\n----\nPrompt").is_err());
+        assert!(parse("---\ndescription: d\ntemperature: 0.5\n--- notes\nPrompt").is_err());
+    }
+
+    #[test]
+    fn quoted_values_comments_and_booleans() {
+        let text = "---\n# a reviewer\ndescription: \"Reviews: code # carefully\"\ntemperature: 0.3 # low\ntools: true\ntitle: ~\n---\nPrompt";
+        let agent = parse(text).unwrap();
+        assert_eq!(agent.description, "Reviews: code # carefully");
+        assert_eq!(agent.temperature, 0.3);
+        assert!(agent.tools_enabled);
+        assert_eq!(agent.title, None);
+        assert!(parse("---\ndescription d\n---\nPrompt").is_err());
+        assert!(parse("---\ndescription: 'open\ntemperature: 1\n---\nPrompt").is_err());
     }
 }
@@ -40,36 +40,13 @@ impl LlamaCppBackend {
         None
     }
 
-    fn scan_models_dir(&self) -> Result<Vec<PathBuf>> {
-        let models_dir = shellexpand::tilde(&self.config.llamacpp.models_dir).to_string();
-        let path = Path::new(&models_dir);
-
-        if !path.exists() {
-            return Ok(vec![]);
-        }
-
-        let mut models = vec![];
-
-        // Recursively search for .gguf files
-        fn scan_dir(dir: &Path, models: &mut Vec<PathBuf>) -> Result<()> {
-            for entry in fs::read_dir(dir)? {
-                let entry = entry?;
-                let path = entry.path();
-
-                if path.is_dir() {
-                    scan_dir(&path, models)?;
-                } else if path.extension().and_then(|s| s.to_str()) == Some("gguf") {
-                    models.push(path);
-                }
-            }
-            Ok(())
-        }
-
-        scan_dir(path, &mut models)?;
-        Ok(models)
+    /// Every .gguf file under the models directory, from the last scan
+    /// while the tree is unchanged
+    pub fn scan_models_dir(&self) -> Result<Vec<PathBuf>> {
+        super::model_scan::models_in(&self.models_dir())
     }
 
-    fn get_model_alias(&self, model_path: &Path) -> String {
+    pub fn get_model_alias(&self, model_path: &Path) -> String {
         // Extract a clean alias from the model path
         model_path
             .file_stem()
@@ -79,6 +56,63 @@ impl LlamaCppBackend {
             .replace(['_', '-', '.'], "-")
     }
 
+    pub fn models_dir(&self) -> PathBuf {
+        PathBuf::from(shellexpand::tilde(&self.config.llamacpp.models_dir).to_string())
+    }
+
+    /// GGUF file for a model given by alias, file name or path. Exact matches
+    /// win; otherwise a partial match must be unambiguous.
+    pub fn find_model(&self, name: &str) -> Result<PathBuf> {
+        let path = Path::new(name);
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+
+        let models = self.scan_models_dir()?;
+        let file_name = |p: &Path| p.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+        if let Some(exact) = models
+            .iter()
+            .find(|p| self.get_model_alias(p) == name || file_name(p) == name)
+        {
+            return Ok(exact.clone());
+        }
+
+        let needle = name.to_lowercase();
+        let partial: Vec<&PathBuf> = models
+            .iter()
+            .filter(|p| self.get_model_alias(p).contains(&needle) || file_name(p).to_lowercase().contains(&needle))
+            .collect();
+        match partial.as_slice() {
+            [one] => Ok((*one).clone()),
+            [] => anyhow::bail!("Model '{}' not found in {}", name, self.config.llamacpp.models_dir),
+            many => anyhow::bail!(
+                "'{}' matches several models: {}",
+                name,
+                many.iter().map(|p| file_name(p)).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    /// The file `start_server` loads for a configured model name: the first
+    /// one whose alias or file name contains it
+    pub fn served_file<'a>(&self, models: &'a [PathBuf], model: &str) -> Option<&'a PathBuf> {
+        models.iter().find(|p| {
+            self.get_model_alias(p).contains(model)
+                || p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.contains(model))
+        })
+    }
+
+    /// Another llama.cpp tool (llama-quantize, ...): next to the configured
+    /// server binary first, then on PATH
+    pub fn find_tool(&self, name: &str) -> Option<PathBuf> {
+        let beside_server = self
+            .find_binary()
+            .and_then(|server| Path::new(&server).parent().map(|dir| dir.join(name)))
+            .filter(|p| p.is_file());
+        beside_server.or_else(|| crate::config::find_in_path(name))
+    }
+
+    /// Launch llama-server for the configured model and record it for reuse
     pub fn start_server(port: u16) -> Result<()> {
         // Load fresh config
         let config = Config::load()?;
@@ -93,9 +127,8 @@ impl LlamaCppBackend {
 
         // Find the model file
         let models = backend.scan_models_dir()?;
-        let model_path = models
-            .iter()
-            .find(|p| backend.get_model_alias(p).contains(model) || p.file_name().and_then(|n| n.to_str()).map(|n| n.contains(model)).unwrap_or(false))
+        let model_path = backend
+            .served_file(&models, model)
             .ok_or_else(|| anyhow::anyhow!("Model '{}' not found", model))?;
 
         let cfg = &backend.config.llamacpp;
@@ -139,8 +172,10 @@ impl LlamaCppBackend {
             .stdin(Stdio::null());
 
         // Spawn in background
-        cmd.spawn()
+        let child = cmd.spawn()
             .context("Failed to spawn llama-server")?;
+        // A server that cannot be recorded still works, it is just not reused
+        let _ = crate::llm::server::RunningServer::record(port, child.id(), &backend.config);
 
         Ok(())
     }
@@ -158,10 +193,9 @@ impl Backend for LlamaCppBackend {
         Ok(models
             .into_iter()
             .map(|path| {
-                let size = fs::metadata(&path)
-                    .ok()
-                    .map(|m| {
-                        let bytes = m.len();
+                let bytes = fs::metadata(&path).ok().map(|m| m.len());
+                let size = bytes
+                    .map(|bytes| {
                         if bytes >= 1_073_741_824 {
                             format!("{:.1} GB", bytes as f64 / 1_073_741_824.0)
                         } else if bytes >= 1_048_576 {
@@ -174,6 +208,7 @@ impl Backend for LlamaCppBackend {
                 ModelInfo {
                     name: self.get_model_alias(&path),
                     size,
+                    bytes,
                     modified: None,
                     backend: "llama.cpp".to_string(),
                 }
@@ -181,12 +216,68 @@ impl Backend for LlamaCppBackend {
             .collect())
     }
 
-    async fn install_model(&self, _model: &str) -> Result<()> {
-        anyhow::bail!("llama.cpp backend does not support automatic model installation. Please download GGUF models manually to: {}", self.config.llamacpp.models_dir);
+    async fn install_model(&self, model: &str) -> Result<()> {
+        use colored::Colorize;
+
+        // hf:<owner>/<repo>/<file>.gguf, as printed by `vork models search`
+        let Some((repo, file)) = model.strip_prefix("hf:").and_then(split_hf_spec) else {
+            anyhow::bail!(
+                "llama.cpp models are installed from HuggingFace as hf:<owner>/<repo>/<file>.gguf \
+                (see `vork models search`), or download GGUF files manually to: {}",
+                self.config.llamacpp.models_dir
+            );
+        };
+
+        let models_dir = PathBuf::from(shellexpand::tilde(&self.config.llamacpp.models_dir).to_string());
+        fs::create_dir_all(&models_dir)
+            .with_context(|| format!("Failed to create {}", models_dir.display()))?;
+
+        // Split models (name-00001-of-00003.gguf) need every part next to the first
+        let files = split_parts(file);
+        for part in &files {
+            let name = Path::new(part).file_name().and_then(|n| n.to_str()).unwrap_or(part);
+            let dest = models_dir.join(name);
+            if dest.exists() {
+                println!("{} {} (already downloaded)", "✓".green(), name);
+                continue;
+            }
+            println!("{} {}", "Downloading:".green().bold(), name);
+            download_hf_file(repo, part, &dest).await?;
+        }
+
+        println!(
+            "{} {} → {}",
+            "Successfully installed:".green().bold(),
+            model,
+            models_dir.display()
+        );
+        Ok(())
     }
 
-    async fn remove_model(&self, _model: &str) -> Result<()> {
-        anyhow::bail!("llama.cpp backend does not support model removal through vork");
+    async fn remove_model(&self, model: &str) -> Result<()> {
+        use colored::Colorize;
+
+        // Only exact names here: a partial match must never delete a file
+        let path = self
+            .scan_models_dir()?
+            .into_iter()
+            .find(|p| {
+                self.get_model_alias(p) == model
+                    || p.file_name().and_then(|n| n.to_str()) == Some(model)
+            })
+            .ok_or_else(|| anyhow::anyhow!("Model '{}' not found in {}", model, self.config.llamacpp.models_dir))?;
+
+        let file = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        for part in split_parts(&file) {
+            let part_path = path.with_file_name(part);
+            if part_path.exists() {
+                fs::remove_file(&part_path)
+                    .with_context(|| format!("Failed to delete {}", part_path.display()))?;
+            }
+        }
+
+        println!("{} {}", "Removed model:".green().bold(), path.display());
+        Ok(())
     }
 
     async fn run_model(&self, model: &str, port: u16) -> Result<()> {
@@ -260,3 +351,69 @@ impl Backend for LlamaCppBackend {
         Ok(())
     }
 }
+
+/// Split `owner/repo/path/to/file.gguf` into the repo id and file path
+fn split_hf_spec(spec: &str) -> Option<(&str, &str)> {
+    let (owner, rest) = spec.split_once('/')?;
+    let (name, file) = rest.split_once('/')?;
+    let repo = &spec[..owner.len() + 1 + name.len()];
+    file.ends_with(".gguf").then_some((repo, file))
+}
+
+/// Every part of a split GGUF given its first part, or just the file itself
+fn split_parts(file: &str) -> Vec<String> {
+    // name-00001-of-00003.gguf
+    let Some(stem) = file.strip_suffix(".gguf") else {
+        return vec![file.to_string()];
+    };
+    let Some((prefix, total)) = stem.rsplit_once("-of-") else {
+        return vec![file.to_string()];
+    };
+    let (Some(base), Ok(count)) = (prefix.strip_suffix("-00001"), total.parse::<usize>()) else {
+        return vec![file.to_string()];
+    };
+    (1..=count)
+        .map(|i| format!("{}-{:05}-of-{}.gguf", base, i, total))
+        .collect()
+}
+
+/// Stream a file from the HuggingFace hub to `dest`, via a .part file so an
+/// interrupted download never looks like a finished model
+async fn download_hf_file(repo: &str, file: &str, dest: &Path) -> Result<()> {
+    use indicatif::{ProgressBar, ProgressStyle};
+    use std::io::Write;
+
+    let url = format!("https://huggingface.co/{}/resolve/main/{}", repo, file);
+    let mut request = reqwest::Client::new().get(&url);
+    // Gated repos need a token
+    if let Ok(token) = std::env::var("HF_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+    let mut response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", url))?;
+    if !response.status().is_success() {
+        anyhow::bail!("Download of {} failed: {}", url, response.status());
+    }
+
+    let progress = ProgressBar::new(response.content_length().unwrap_or(0));
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template("{bar:40.green} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+            .unwrap(),
+    );
+
+    let partial = dest.with_extension("gguf.part");
+    let mut out = fs::File::create(&partial)
+        .with_context(|| format!("Failed to create {}", partial.display()))?;
+    while let Some(chunk) = response.chunk().await.context("Download interrupted")? {
+        out.write_all(&chunk)?;
+        progress.inc(chunk.len() as u64);
+    }
+    out.flush()?;
+    progress.finish_and_clear();
+
+    fs::rename(&partial, dest)?;
+    Ok(())
+}
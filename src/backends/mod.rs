@@ -1,5 +1,7 @@
 pub mod ollama;
 pub mod llamacpp;
+pub mod model_scan;
+pub mod openai_compat;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -18,6 +20,7 @@ pub trait Backend {
 pub struct ModelInfo {
     pub name: String,
     pub size: Option<String>,
+    pub bytes: Option<u64>,
     pub modified: Option<String>,
     pub backend: String,
 }
@@ -26,6 +29,8 @@ pub fn get_backend(name: &str) -> Result<Box<dyn Backend>> {
     match name.to_lowercase().as_str() {
         "ollama" => Ok(Box::new(ollama::OllamaBackend::new())),
         "llamacpp" | "llama.cpp" => Ok(Box::new(llamacpp::LlamaCppBackend::new())),
+        "lmstudio" | "lm-studio" => Ok(Box::new(openai_compat::LocalServerBackend::lmstudio())),
+        "jan" => Ok(Box::new(openai_compat::LocalServerBackend::jan())),
         _ => anyhow::bail!("Unknown backend: {}", name),
     }
 }
@@ -39,5 +44,10 @@ pub async fn detect_backends() -> Vec<(String, bool)> {
     let llamacpp = llamacpp::LlamaCppBackend::new();
     backends.push(("llama.cpp".to_string(), llamacpp.is_available().await));
 
+    for server in [openai_compat::LocalServerBackend::lmstudio(), openai_compat::LocalServerBackend::jan()] {
+        let available = server.is_available().await;
+        backends.push((server.name().to_string(), available));
+    }
+
     backends
 }
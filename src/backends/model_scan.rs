@@ -0,0 +1,105 @@
+//! The GGUF files under the models directory, remembered between runs.
+//! Listing models, starting a server and the TUI selector all need them,
+//! and the tree can hold hundreds of GB across many folders. Adding,
+//! removing or renaming a file changes its directory's mtime, so a scan
+//! stays good while one stat per directory still matches.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+
+/// A directory changed this recently may still be changing within the same
+/// mtime tick (a download being renamed into place), so its scan is not kept
+const SETTLE: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize)]
+struct Scan {
+    root: PathBuf,
+    /// Every directory walked, with its modification time
+    dirs: Vec<(PathBuf, SystemTime)>,
+    models: Vec<PathBuf>,
+}
+
+/// The last scan, so one process stats the tree instead of reading the file
+static LAST: Mutex<Option<Scan>> = Mutex::new(None);
+
+impl Scan {
+    fn walk(root: &Path) -> Result<Self> {
+        let mut scan = Self { root: root.to_path_buf(), dirs: vec![], models: vec![] };
+        scan.walk_dir(root)?;
+        Ok(scan)
+    }
+
+    fn walk_dir(&mut self, dir: &Path) -> Result<()> {
+        self.dirs.push((dir.to_path_buf(), fs::metadata(dir)?.modified()?));
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.walk_dir(&path)?;
+            } else if path.extension().and_then(|s| s.to_str()) == Some("gguf") {
+                self.models.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Nothing was added, removed or renamed since the walk
+    fn is_current(&self) -> bool {
+        self.dirs
+            .iter()
+            .all(|(dir, modified)| fs::metadata(dir).and_then(|m| m.modified()).ok() == Some(*modified))
+    }
+
+    fn is_settled(&self) -> bool {
+        let now = SystemTime::now();
+        self.dirs
+            .iter()
+            .all(|(_, modified)| now.duration_since(*modified).is_ok_and(|age| age >= SETTLE))
+    }
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("model_scan.json"))
+}
+
+fn load(root: &Path) -> Option<Scan> {
+    let json = fs::read_to_string(cache_path().ok()?).ok()?;
+    serde_json::from_str::<Scan>(&json).ok().filter(|scan| scan.root == root)
+}
+
+fn save(scan: &Scan) -> Result<()> {
+    fs::create_dir_all(Config::config_dir()?)?;
+    fs::write(cache_path()?, serde_json::to_string(scan)?)?;
+    Ok(())
+}
+
+/// Every .gguf file under `root`, walking the tree again only when a
+/// directory in it has changed
+pub fn models_in(root: &Path) -> Result<Vec<PathBuf>> {
+    if !root.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+    if last.as_ref().is_none_or(|scan| scan.root != root) {
+        *last = load(root);
+    }
+    if let Some(scan) = last.as_ref().filter(|scan| scan.is_current()) {
+        return Ok(scan.models.clone());
+    }
+
+    let scan = Scan::walk(root)?;
+    let models = scan.models.clone();
+    if scan.is_settled() {
+        let _ = save(&scan);
+        *last = Some(scan);
+    } else {
+        *last = None;
+    }
+    Ok(models)
+}
@@ -2,9 +2,18 @@ use super::{Backend, ModelInfo};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::config::Config;
+
+/// Fail fast on unreachable hosts instead of hanging `list`/`status`
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for quick metadata calls; pulls can take much longer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
 
 pub struct OllamaBackend {
     api_url: String,
+    api_key: Option<String>,
     client: reqwest::Client,
 }
 
@@ -27,9 +36,24 @@ struct PullRequest {
 
 impl OllamaBackend {
     pub fn new() -> Self {
+        let config = Config::load().unwrap_or_default();
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
         Self {
-            api_url: "http://localhost:11434".to_string(),
-            client: reqwest::Client::new(),
+            api_url: config.ollama.api_url.trim_end_matches('/').to_string(),
+            api_key: config.ollama.api_key,
+            client,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, format!("{}{}", self.api_url, path));
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
         }
     }
 
@@ -50,20 +74,24 @@ impl OllamaBackend {
 #[async_trait]
 impl Backend for OllamaBackend {
     async fn is_available(&self) -> bool {
-        self.client
-            .get(&format!("{}/api/tags", self.api_url))
+        self.request(reqwest::Method::GET, "/api/tags")
+            .timeout(REQUEST_TIMEOUT)
             .send()
             .await
-            .is_ok()
+            .is_ok_and(|r| r.status().is_success())
     }
 
     async fn list_models(&self) -> Result<Vec<ModelInfo>> {
         let response = self
-            .client
-            .get(&format!("{}/api/tags", self.api_url))
+            .request(reqwest::Method::GET, "/api/tags")
+            .timeout(REQUEST_TIMEOUT)
             .send()
             .await
-            .context("Failed to connect to Ollama API")?;
+            .with_context(|| format!("Failed to connect to Ollama API at {}", self.api_url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Ollama API at {} returned {}", self.api_url, response.status());
+        }
 
         let list: ListResponse = response
             .json()
@@ -76,6 +104,7 @@ impl Backend for OllamaBackend {
             .map(|m| ModelInfo {
                 name: m.name,
                 size: Some(Self::format_size(m.size)),
+                bytes: u64::try_from(m.size).ok(),
                 modified: Some(m.modified_at),
                 backend: "ollama".to_string(),
             })
@@ -97,8 +126,7 @@ impl Backend for OllamaBackend {
         spinner.set_message("Downloading...");
 
         let response = self
-            .client
-            .post(&format!("{}/api/pull", self.api_url))
+            .request(reqwest::Method::POST, "/api/pull")
             .json(&PullRequest {
                 name: model.to_string(),
             })
@@ -125,8 +153,7 @@ impl Backend for OllamaBackend {
         }
 
         let response = self
-            .client
-            .delete(&format!("{}/api/delete", self.api_url))
+            .request(reqwest::Method::DELETE, "/api/delete")
             .json(&DeleteRequest {
                 name: model.to_string(),
             })
@@ -153,8 +180,9 @@ impl Backend for OllamaBackend {
             "is managed by Ollama".cyan()
         );
         println!(
-            "{}",
-            "Ollama serves all models on http://localhost:11434".cyan()
+            "{} {}",
+            "Ollama serves all models on".cyan(),
+            self.api_url.cyan()
         );
         println!(
             "\n{} ollama run {}",
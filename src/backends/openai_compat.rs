@@ -0,0 +1,139 @@
+use super::{Backend, ModelInfo};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Local servers answer quickly; don't stall `status` when one isn't running
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A desktop app (LM Studio, Jan) that manages its own models and exposes an
+/// OpenAI-compatible server
+pub struct LocalServerBackend {
+    name: &'static str,
+    api_url: String,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+impl LocalServerBackend {
+    fn new(name: &'static str, api_url: &str) -> Self {
+        Self {
+            name,
+            api_url: api_url.to_string(),
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn lmstudio() -> Self {
+        Self::new("LM Studio", "http://localhost:1234")
+    }
+
+    pub fn jan() -> Self {
+        Self::new("Jan", "http://localhost:1337")
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+}
+
+/// Model ids served by an OpenAI-compatible server (`GET /v1/models`)
+pub async fn served_models(base_url: &str) -> Result<Vec<String>> {
+    let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+    fetch_models(&client, base_url).await
+}
+
+/// Pick the model to use with an explicitly given server: the configured model
+/// if the server has it, otherwise the first one it serves
+pub async fn model_for_server(base_url: &str, configured: &str) -> String {
+    match served_models(base_url).await {
+        Ok(models) if !models.iter().any(|m| m == configured) => {
+            models.into_iter().next().unwrap_or_else(|| configured.to_string())
+        }
+        _ => configured.to_string(),
+    }
+}
+
+async fn fetch_models(client: &reqwest::Client, base_url: &str) -> Result<Vec<String>> {
+    let response = client
+        .get(format!("{}/v1/models", base_url.trim_end_matches('/')))
+        .send()
+        .await
+        .with_context(|| format!("Failed to connect to {}", base_url))?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("{} returned {}", base_url, response.status());
+    }
+
+    let models: ModelsResponse = response
+        .json()
+        .await
+        .context("Failed to parse model list")?;
+    Ok(models.data.into_iter().map(|m| m.id).collect())
+}
+
+#[async_trait]
+impl Backend for LocalServerBackend {
+    async fn is_available(&self) -> bool {
+        fetch_models(&self.client, &self.api_url).await.is_ok()
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        Ok(fetch_models(&self.client, &self.api_url)
+            .await?
+            .into_iter()
+            .map(|name| ModelInfo {
+                name,
+                size: None,
+                bytes: None,
+                modified: None,
+                backend: self.name.to_string(),
+            })
+            .collect())
+    }
+
+    async fn install_model(&self, model: &str) -> Result<()> {
+        anyhow::bail!("{} manages its own models; download '{}' in the {} app", self.name, model, self.name)
+    }
+
+    async fn remove_model(&self, model: &str) -> Result<()> {
+        anyhow::bail!("{} manages its own models; delete '{}' in the {} app", self.name, model, self.name)
+    }
+
+    async fn run_model(&self, model: &str, _port: u16) -> Result<()> {
+        use colored::Colorize;
+
+        println!(
+            "{} {} {} {}",
+            "Model".cyan(),
+            model.yellow().bold(),
+            "is served by".cyan(),
+            self.name
+        );
+        println!(
+            "\n{} vork chat -s {} -m {}",
+            "To chat with this model, use:".green(),
+            self.api_url,
+            model
+        );
+
+        Ok(())
+    }
+}
@@ -1,10 +1,21 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
+use std::fs;
 use std::io::{self, Write};
+use std::path::Path;
 
+use super::presets::open_editor;
 use crate::agents::Agent;
 
-pub fn execute(list: bool, create: bool, agent_name: Option<String>) -> Result<()> {
+pub fn execute(list: bool, create: bool, agent_name: Option<String>, force_recreate_defaults: bool) -> Result<()> {
+    if force_recreate_defaults {
+        recreate_defaults()?;
+        if !list && !create && agent_name.is_none() {
+            return Ok(());
+        }
+        println!();
+    }
+
     // Initialize default agents if agents dir doesn't exist
     let agents_dir = Agent::agents_dir()?;
     if !agents_dir.exists() {
@@ -38,6 +49,9 @@ pub fn execute(list: bool, create: bool, agent_name: Option<String>) -> Result<(
         println!("  {} --agent <name>    Start vork with a specific agent", "vork".green());
         println!("  {} agents <name>         Show details for an agent", "vork".green());
         println!("  {} agents --create       Create a new custom agent", "vork".green());
+        println!("  {} agents edit <name>    Edit an agent in $EDITOR", "vork".green());
+        println!("  {} agents reset <name>   Restore a built-in agent", "vork".green());
+        println!("  {} agents history <name> Show previous versions", "vork".green());
         println!();
         println!("{}", "Agents directory:".cyan());
         println!("  {}", agents_dir.display().to_string().yellow());
@@ -122,6 +136,8 @@ pub fn execute(list: bool, create: bool, agent_name: Option<String>) -> Result<(
             color,
             title,
             preferred_preset: None,
+            max_sandbox_mode: None,
+            max_approval_policy: None,
         };
 
         agent.save()?;
@@ -140,6 +156,12 @@ pub fn execute(list: bool, create: bool, agent_name: Option<String>) -> Result<(
         println!("{} {}", "Description:".cyan().bold(), agent.description);
         println!("{} {}", "Temperature:".cyan().bold(), agent.temperature);
         println!("{} {}", "Tools Enabled:".cyan().bold(), agent.tools_enabled);
+        if let Some(ref mode) = agent.max_sandbox_mode {
+            println!("{} {:?}", "Max Sandbox Mode:".cyan().bold(), mode);
+        }
+        if let Some(ref policy) = agent.max_approval_policy {
+            println!("{} {:?}", "Max Approval Policy:".cyan().bold(), policy);
+        }
         println!();
         println!("{}", "System Prompt:".cyan().bold());
         println!("{}", "─".repeat(60).cyan());
@@ -166,3 +188,218 @@ fn display_agent(name: &str) -> Result<()> {
     }
     Ok(())
 }
+
+/// Edit an agent's file in $EDITOR, saving it only once it parses
+pub fn edit(name: &str) -> Result<()> {
+    let path = match Agent::file_path(name)? {
+        Some(path) => path,
+        // Built-in agents not yet installed are written out first
+        None => {
+            Agent::builtin(name).with_context(|| format!("Agent '{}' not found", name))?.save()?;
+            Agent::file_path(name)?.context("Failed to install the built-in agent")?
+        }
+    };
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("json");
+    // Keep the extension last so editors still highlight the file
+    let scratch = path.with_file_name(format!(".{}.edit.{}", name, extension));
+    fs::copy(&path, &scratch)?;
+
+    let result = (|| loop {
+        open_editor(&scratch)?;
+        let edited = fs::read_to_string(&scratch)?;
+
+        match validate(name, &edited, &path) {
+            Ok(()) => {
+                if fs::read_to_string(&path)? != edited {
+                    Agent::archive(name)?;
+                }
+                fs::write(&path, edited)?;
+                println!("{} Saved agent {}", "✓".green(), name.green());
+                return Ok(());
+            }
+            Err(e) => {
+                println!("{} {:#}", "✗".red(), e);
+                if !confirm("Re-open the editor? [Y/n] ", true)? {
+                    anyhow::bail!("Agent not saved");
+                }
+            }
+        }
+    })();
+
+    let _ = fs::remove_file(&scratch);
+    result
+}
+
+fn validate(name: &str, content: &str, path: &Path) -> Result<()> {
+    let agent = Agent::parse(content, path).context("Agent definition is not valid")?;
+    if agent.name != name {
+        anyhow::bail!("The agent's name must stay '{}' (copy the file to create a new agent)", name);
+    }
+    if !(0.0..=2.0).contains(&agent.temperature) {
+        anyhow::bail!("Temperature {} is outside 0.0-2.0", agent.temperature);
+    }
+    Ok(())
+}
+
+pub fn delete(name: &str, yes: bool) -> Result<()> {
+    let files = agent_files(name)?;
+    if files.is_empty() {
+        anyhow::bail!("Agent '{}' not found", name);
+    }
+
+    if !yes && !confirm(&format!("Delete agent '{}'? [y/N] ", name), false)? {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    Agent::archive(name)?;
+    for path in files {
+        fs::remove_file(&path)?;
+    }
+    println!("{} Deleted agent {}", "✓".green(), name);
+    if Agent::builtin(name).is_some() {
+        println!("  Built-in agents are reinstalled the next time they are used.");
+    }
+    Ok(())
+}
+
+/// Replace an agent with its bundled definition, dropping local edits
+pub fn reset(name: &str, yes: bool) -> Result<()> {
+    let builtin = Agent::builtin(name).with_context(|| format!("'{}' is not a built-in agent", name))?;
+
+    if !agent_files(name)?.is_empty()
+        && !yes
+        && !confirm(&format!("Discard your changes to '{}'? [y/N] ", name), false)?
+    {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    Agent::archive(name)?;
+    for path in agent_files(name)? {
+        fs::remove_file(&path)?;
+    }
+    builtin.save()?;
+    println!("{} Restored built-in agent {}", "✓".green(), name.green());
+    Ok(())
+}
+
+/// Rewrite the bundled agents from this version. Agents with other names are
+/// left alone, and an edited built-in is kept in the agent's history.
+fn recreate_defaults() -> Result<()> {
+    let dir = Agent::agents_dir()?;
+    fs::create_dir_all(&dir)?;
+
+    let mut updated = 0;
+    for agent in Agent::builtin_agents() {
+        let path = dir.join(format!("{}.json", agent.name));
+        let fresh = serde_json::to_string_pretty(&agent)?;
+        match fs::read_to_string(&path) {
+            Ok(current) if current == fresh => continue,
+            Ok(_) => println!("  {} {} (previous version kept in its history)", "↻".cyan(), agent.name),
+            Err(_) => println!("  {} {}", "+".green(), agent.name),
+        }
+        agent.save()?;
+        updated += 1;
+        if dir.join(format!("{}.md", agent.name)).exists() {
+            println!("    {} {}.md still overrides it", "⚠️".yellow(), agent.name);
+        }
+    }
+
+    if updated == 0 {
+        println!("{} Built-in agents are up to date", "✓".green());
+    } else {
+        println!("{} Refreshed {} built-in agent(s)", "✓".green(), updated);
+    }
+    Ok(())
+}
+
+/// List an agent's previous versions, newest first
+pub fn history(name: &str) -> Result<()> {
+    let versions = Agent::history(name)?;
+    if versions.is_empty() {
+        println!("No previous versions of '{}'", name);
+        return Ok(());
+    }
+
+    println!("{}", format!("=== History: {} ===", name).green().bold());
+    println!();
+    for (index, path) in versions.iter().enumerate() {
+        let version = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        let summary = fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| Agent::parse(&text, &path.with_file_name(format!("{}.{}", name, extension(path)))))
+            .map(|agent| {
+                format!(
+                    "temp {}, {} chars of prompt{}",
+                    agent.temperature,
+                    agent.system_prompt.len(),
+                    if extension(path) == "md" { ", markdown" } else { "" }
+                )
+            })
+            .unwrap_or_else(|_| "invalid".to_string());
+        println!("  {:>3}  {}  {}", (index + 1).to_string().cyan(), version, summary.dimmed());
+    }
+    println!();
+    println!("Roll back with: {} agents rollback {} [number or version]", "vork".green(), name);
+    Ok(())
+}
+
+/// Restore a previous version (the newest by default). The current file goes
+/// into the history too, so a rollback can itself be undone.
+pub fn rollback(name: &str, version: Option<String>) -> Result<()> {
+    let versions = Agent::history(name)?;
+    let chosen = match version.as_deref() {
+        None => versions.first(),
+        Some(v) => match v.parse::<usize>() {
+            Ok(n) if (1..=versions.len()).contains(&n) => versions.get(n - 1),
+            _ => versions.iter().find(|path| path.file_stem().and_then(|s| s.to_str()) == Some(v)),
+        },
+    }
+    .with_context(|| match version {
+        Some(ref v) => format!("No version '{}' of agent '{}' (see `vork agents history {}`)", v, name, name),
+        None => format!("No previous versions of agent '{}'", name),
+    })?;
+
+    let content = fs::read_to_string(chosen)?;
+    let target = Agent::agents_dir()?.join(format!("{}.{}", name, extension(chosen)));
+    validate(name, &content, &target).context("That version is not a valid agent")?;
+
+    Agent::archive(name)?;
+    for path in agent_files(name)? {
+        fs::remove_file(&path)?;
+    }
+    fs::write(&target, content)?;
+    println!(
+        "{} Rolled {} back to {}",
+        "✓".green(),
+        name.green(),
+        chosen.file_stem().and_then(|s| s.to_str()).unwrap_or_default()
+    );
+    Ok(())
+}
+
+fn extension(path: &Path) -> &str {
+    path.extension().and_then(|s| s.to_str()).unwrap_or("json")
+}
+
+fn agent_files(name: &str) -> Result<Vec<std::path::PathBuf>> {
+    let dir = Agent::agents_dir()?;
+    Ok(["md", "json"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .filter(|path| path.exists())
+        .collect())
+}
+
+fn confirm(question: &str, default: bool) -> Result<bool> {
+    print!("{}", question);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
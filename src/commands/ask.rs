@@ -2,21 +2,50 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 
 use crate::config::Config;
-use crate::llm::{LlamaClient, Conversation, ApprovalSystem};
+use crate::llm::{LlamaClient, Conversation, ApprovalSystem, UsageTracker};
+use crate::llm::client::Message;
+use crate::llm::arguments;
+use crate::llm::templates::{self, Template};
 use crate::llm::tools::{get_available_tools, execute_tool};
 
+/// The question from a prompt template, with `--var` values and the
+/// question text as its input
+pub fn from_template(name: &str, vars: &[String], input: Option<&str>) -> Result<String> {
+    let vars = vars.iter().map(|var| templates::parse_var(var)).collect::<Result<_>>()?;
+    Template::load(name)?.render(&vars, input.unwrap_or(""))
+}
+
 pub async fn execute(
     question: &str,
     server_url: Option<String>,
     model: Option<String>,
     no_tools: bool,
+    raw: bool,
 ) -> Result<()> {
     let config = Config::load()?;
-    let server_url = server_url.unwrap_or_else(|| config.assistant.server_url.clone());
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
     let model = model.unwrap_or_else(|| config.assistant.model.clone());
 
-    let client = LlamaClient::new(server_url, model);
+    let client = LlamaClient::for_config(&config, server_url, model.clone());
+    let mut usage = UsageTracker::new("ask", "", &model);
+
+    // Plain LLM: the question alone, no system prompt and no tools
+    if raw {
+        usage.check_budget()?;
+        let question = Message { role: "user".to_string(), content: question.to_string() };
+        let response = client
+            .chat_completion(&[question], None)
+            .await
+            .context("Failed to get response from LLM")?;
+        usage.record_response(response.usage.as_ref());
+        if let Some(content) = response.choices.into_iter().next().and_then(|choice| choice.message.content) {
+            println!("{}", content);
+        }
+        return Ok(());
+    }
+
     let mut conversation = Conversation::new();
+    let working_dir = std::env::current_dir()?;
     let approval_system = ApprovalSystem::new(
         config.assistant.approval_policy.clone(),
         config.assistant.sandbox_mode.clone(),
@@ -26,6 +55,8 @@ pub async fn execute(
 
     // Main loop: keep calling LLM until it stops requesting tool calls
     loop {
+        usage.check_budget()?;
+
         let tools = if no_tools {
             None
         } else {
@@ -33,9 +64,10 @@ pub async fn execute(
         };
 
         let response = client
-            .chat_completion(conversation.get_messages(), tools)
+            .chat_completion(conversation.get_messages(), tools.as_deref())
             .await
             .context("Failed to get response from LLM")?;
+        usage.record_response(response.usage.as_ref());
 
         let choice = response
             .choices
@@ -46,10 +78,15 @@ pub async fn execute(
         if let Some(tool_calls) = &choice.message.tool_calls {
             // Execute each tool call
             for tool_call in tool_calls {
+                usage.record_tool_call();
                 let tool_name = &tool_call.function.name;
-                let arguments: serde_json::Value =
-                    serde_json::from_str(&tool_call.function.arguments)
-                        .context("Failed to parse tool arguments")?;
+                let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                    Ok(arguments) => arguments,
+                    Err(e) => {
+                        conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                        continue;
+                    }
+                };
 
                 println!(
                     "{} {} {}",
@@ -58,7 +95,7 @@ pub async fn execute(
                     tool_name.yellow().bold()
                 );
 
-                match execute_tool(tool_name, arguments, Some(&approval_system)).await {
+                match execute_tool(tool_name, arguments, Some(&approval_system), &working_dir).await {
                     Ok(result) => {
                         conversation.add_tool_result(tool_name, &result);
                     }
@@ -0,0 +1,479 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::llm::client::Message;
+use crate::llm::findings::{Added, Findings, Report, FINDINGS_FILE};
+use crate::llm::{processes, LlamaClient};
+
+/// Numbered source sent to the model per request
+const MAX_BATCH_BYTES: usize = 24_000;
+
+/// Files larger than this are generated or vendored more often than not
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+
+/// Directories never worth auditing when walking outside git
+const SKIP_DIRS: &[&str] = &["target", "node_modules", "vendor", "dist", "build", "__pycache__"];
+
+/// Rule id, short description and default level. Findings the model files
+/// under any other rule land in `quality`.
+const RULES: &[(&str, &str, &str)] = &[
+    ("stub", "Stub or placeholder implementation", "warning"),
+    ("unwrap", "unwrap()/expect() that can panic on bad input", "warning"),
+    ("panic", "Explicit panic!, unimplemented! or unreachable!", "warning"),
+    ("error-handling", "Ignored or incomplete error handling", "warning"),
+    ("todo", "TODO/FIXME left in code", "note"),
+    ("security", "Security issue", "error"),
+    ("performance", "Performance issue", "note"),
+    ("quality", "Other code quality issue", "note"),
+];
+
+const LEVELS: &[&str] = &["note", "warning", "error"];
+
+pub struct AuditOptions {
+    pub paths: Vec<PathBuf>,
+    pub output: PathBuf,
+    /// Exit with status 1 when a finding at or above this level is reported
+    pub fail_on: Option<String>,
+}
+
+/// Part of a file, small enough to share a request with others
+struct Chunk {
+    path: String,
+    first_line: usize,
+    text: String,
+}
+
+impl Chunk {
+    fn last_line(&self) -> usize {
+        self.first_line + self.text.lines().count().saturating_sub(1)
+    }
+
+    fn numbered(&self) -> String {
+        self.text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{:>5} | {}", self.first_line + i, line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFinding {
+    file: String,
+    #[serde(default)]
+    line: Option<usize>,
+    #[serde(default)]
+    end_line: Option<usize>,
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    rule: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+struct Finding {
+    file: String,
+    line: usize,
+    end_line: Option<usize>,
+    level: &'static str,
+    rule: &'static str,
+    message: String,
+}
+
+pub async fn execute(
+    options: AuditOptions,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent_name: Option<String>,
+) -> Result<i32> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    if let Some(ref level) = options.fail_on {
+        if !LEVELS.contains(&level.as_str()) {
+            anyhow::bail!("Unknown level '{}' for --fail-on (use note, warning or error)", level);
+        }
+    }
+
+    let markdown_path = options.output.with_extension("md");
+    // Listing and reading the files walks the disk
+    let paths = options.paths.clone();
+    let mut files = processes::blocking(move || collect_files(&paths)).await?;
+    // Reports and findings from earlier runs are not source
+    files.retain(|f| *f != options.output && *f != markdown_path && !f.starts_with(".vork"));
+    if files.is_empty() {
+        anyhow::bail!("No source files to audit");
+    }
+    let batches = {
+        let files = files.clone();
+        processes::blocking(move || batch(&files)).await
+    };
+
+    let system_prompt = Agent::load_or_create_defaults(agent_name.as_deref().unwrap_or("code-auditor"))
+        .map(|a| a.system_prompt)
+        .unwrap_or_else(|_| "You are a meticulous code auditor.".to_string());
+    let client = LlamaClient::for_config(&config, server_url, model);
+
+    eprintln!(
+        "{} {} files in {} batches",
+        "🔎 Auditing".cyan().bold(),
+        files.len(),
+        batches.len()
+    );
+
+    let mut findings: Vec<Finding> = vec![];
+    let mut seen = HashSet::new();
+    for (i, chunks) in batches.iter().enumerate() {
+        let names: Vec<&str> = chunks.iter().map(|c| c.path.as_str()).collect();
+        eprintln!("  [{}/{}] {}", i + 1, batches.len(), names.join(", ").dimmed());
+
+        let raw = match audit_batch(&client, &system_prompt, chunks).await {
+            Ok(raw) => raw,
+            Err(e) => {
+                eprintln!("  {} {:#}", "⚠️  Skipped batch:".yellow(), e);
+                continue;
+            }
+        };
+        for finding in raw.into_iter().filter_map(|f| validate(f, chunks)) {
+            if seen.insert((finding.file.clone(), finding.line, finding.rule)) {
+                findings.push(finding);
+            }
+        }
+    }
+    findings.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+
+    let sarif = sarif_report(&findings);
+    tokio::fs::write(&options.output, serde_json::to_string_pretty(&sarif)?)
+        .await
+        .with_context(|| format!("Failed to write {}", options.output.display()))?;
+    tokio::fs::write(&markdown_path, markdown_report(&findings, files.len()))
+        .await
+        .with_context(|| format!("Failed to write {}", markdown_path.display()))?;
+
+    println!();
+    for level in LEVELS.iter().rev() {
+        let count = findings.iter().filter(|f| f.level == *level).count();
+        let label = format!("{:>8}", level);
+        let label = match *level {
+            "error" => label.red(),
+            "warning" => label.yellow(),
+            _ => label.normal(),
+        };
+        println!("{} {}", label, count);
+    }
+    let (new, known) = record(&findings)?;
+    println!();
+    println!("{} {}", "SARIF:".cyan(), options.output.display());
+    println!("{} {}", "Report:".cyan(), markdown_path.display());
+    println!("{} {} ({} new, {} already known)", "Findings:".cyan(), FINDINGS_FILE, new, known);
+
+    let failed = options.fail_on.as_deref().is_some_and(|threshold| {
+        let threshold = level_rank(threshold);
+        findings.iter().any(|f| level_rank(f.level) >= threshold)
+    });
+    Ok(if failed { 1 } else { 0 })
+}
+
+/// Workspace files to audit: tracked and untracked-but-not-ignored files in
+/// a git repository, otherwise a directory walk
+fn collect_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut args = vec!["ls-files", "-co", "--exclude-standard", "--"];
+    let path_args: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    args.extend(path_args.iter().map(String::as_str));
+
+    let mut files = match Command::new("git").args(&args).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(PathBuf::from)
+            .collect(),
+        _ => {
+            let mut files = vec![];
+            let roots = if paths.is_empty() { vec![PathBuf::from(".")] } else { paths.to_vec() };
+            for root in roots {
+                walk(&root, &mut files)?;
+            }
+            files
+        }
+    };
+
+    files.retain(|path| is_auditable(path));
+    files.sort();
+    Ok(files)
+}
+
+fn walk(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_file() {
+        files.push(path.strip_prefix("./").unwrap_or(path).to_path_buf());
+        return Ok(());
+    }
+    for entry in fs::read_dir(path).with_context(|| format!("Failed to read {}", path.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || (entry.path().is_dir() && SKIP_DIRS.contains(&name.as_str())) {
+            continue;
+        }
+        walk(&entry.path(), files)?;
+    }
+    Ok(())
+}
+
+/// Text files of a reasonable size
+fn is_auditable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() || metadata.len() == 0 || metadata.len() > MAX_FILE_BYTES {
+        return false;
+    }
+    let lock_file = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(".lock") || n == "package-lock.json");
+    !lock_file && fs::read(path).is_ok_and(|bytes| !bytes.iter().take(8192).any(|b| *b == 0))
+}
+
+/// Group files into requests, splitting files that do not fit in one
+fn batch(files: &[PathBuf]) -> Vec<Vec<Chunk>> {
+    let mut batches = vec![];
+    let mut current: Vec<Chunk> = vec![];
+    let mut size = 0;
+
+    for path in files {
+        let Ok(text) = fs::read_to_string(path) else {
+            continue;
+        };
+        let mut first_line = 1;
+        let mut chunk = String::new();
+        let mut pieces = vec![];
+        for (i, line) in text.lines().enumerate() {
+            if !chunk.is_empty() && chunk.len() + line.len() > MAX_BATCH_BYTES {
+                pieces.push((first_line, std::mem::take(&mut chunk)));
+                first_line = i + 1;
+            }
+            chunk.push_str(line);
+            chunk.push('\n');
+        }
+        if !chunk.is_empty() {
+            pieces.push((first_line, chunk));
+        }
+
+        for (first_line, text) in pieces {
+            if size + text.len() > MAX_BATCH_BYTES && !current.is_empty() {
+                batches.push(std::mem::take(&mut current));
+                size = 0;
+            }
+            size += text.len();
+            current.push(Chunk {
+                path: path.to_string_lossy().to_string(),
+                first_line,
+                text,
+            });
+        }
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+async fn audit_batch(client: &LlamaClient, system_prompt: &str, chunks: &[Chunk]) -> Result<Vec<RawFinding>> {
+    let rules: Vec<String> = RULES
+        .iter()
+        .map(|(id, description, _)| format!("- {}: {}", id, description))
+        .collect();
+    let sources: Vec<String> = chunks
+        .iter()
+        .map(|c| format!("File: {} (lines {}-{})\n```\n{}\n```", c.path, c.first_line, c.last_line(), c.numbered()))
+        .collect();
+
+    let prompt = format!(
+        "Audit the source below. Each line is prefixed with its line number.\n\n\
+        Report every real issue as a JSON array and output nothing else. Each element:\n\
+        {{\"file\": \"<path as given>\", \"line\": <number>, \"end_line\": <number or null>, \
+        \"severity\": \"error\" | \"warning\" | \"note\", \"rule\": \"<rule id>\", \"message\": \"<one or two sentences>\"}}\n\n\
+        Rule ids:\n{}\n\n\
+        Use `error` only for bugs and security problems. Output [] when there is nothing to report. \
+        Do not write files or reports; the JSON is the report.\n\n{}",
+        rules.join("\n"),
+        sources.join("\n\n")
+    );
+
+    let response = client
+        .chat_completion(
+            &[
+                Message {
+                    role: "system".to_string(),
+                    content: system_prompt.to_string(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt,
+                },
+            ],
+            None,
+        )
+        .await
+        .context("Failed to get response from LLM")?;
+
+    let content = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+    let json = match (content.find('['), content.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => anyhow::bail!("The model did not return a JSON array"),
+    };
+    serde_json::from_str(json).context("The model returned malformed findings")
+}
+
+/// Keep findings that point into the batch, normalizing rule and level
+fn validate(raw: RawFinding, chunks: &[Chunk]) -> Option<Finding> {
+    let file = raw.file.trim_start_matches("./");
+    let mut file_chunks = chunks.iter().filter(|c| c.path == file).peekable();
+    let first = file_chunks.peek()?.first_line;
+    let line = raw
+        .line
+        .filter(|line| file_chunks.any(|c| (c.first_line..=c.last_line()).contains(line)))
+        .unwrap_or(first);
+
+    let (rule, _, default_level) = RULES
+        .iter()
+        .find(|(id, _, _)| raw.rule.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(id)))
+        .unwrap_or(&RULES[RULES.len() - 1]);
+    let level = match raw.severity.as_deref().map(str::to_lowercase).as_deref() {
+        Some("error" | "critical" | "high") => "error",
+        Some("warning" | "medium") => "warning",
+        Some("note" | "low" | "info") => "note",
+        _ => default_level,
+    };
+
+    Some(Finding {
+        file: file.to_string(),
+        line,
+        end_line: raw.end_line.filter(|end| *end > line),
+        level,
+        rule,
+        message: raw.message.trim().to_string(),
+    })
+}
+
+/// Add the run's findings to the workspace's findings store, so they
+/// accumulate across audits. Returns how many were new and how many known.
+fn record(findings: &[Finding]) -> Result<(usize, usize)> {
+    let mut store = Findings::load(&std::env::current_dir()?)?;
+    let mut new = 0;
+    for finding in findings {
+        let report = Report {
+            file: &finding.file,
+            line: Some(finding.line),
+            severity: finding.level,
+            rule: finding.rule,
+            message: &finding.message,
+        };
+        if matches!(store.add(report).1, Added::New) {
+            new += 1;
+        }
+    }
+    store.save()?;
+    Ok((new, findings.len() - new))
+}
+
+fn level_rank(level: &str) -> usize {
+    LEVELS.iter().position(|l| *l == level).unwrap_or(0)
+}
+
+/// SARIF 2.1.0, the format GitHub code scanning accepts
+fn sarif_report(findings: &[Finding]) -> serde_json::Value {
+    let rules: Vec<serde_json::Value> = RULES
+        .iter()
+        .map(|(id, description, level)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": level },
+            })
+        })
+        .collect();
+
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|f| {
+            let mut region = json!({ "startLine": f.line });
+            if let Some(end) = f.end_line {
+                region["endLine"] = json!(end);
+            }
+            json!({
+                "ruleId": f.rule,
+                "ruleIndex": RULES.iter().position(|(id, _, _)| *id == f.rule),
+                "level": f.level,
+                "message": { "text": f.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": f.file, "uriBaseId": "%SRCROOT%" },
+                        "region": region,
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "vork",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/Kelsidavis/vork",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn markdown_report(findings: &[Finding], file_count: usize) -> String {
+    let mut out = format!(
+        "# Code Audit\n\nGenerated by `vork audit` on {} over {} files.\n\n| Level | Findings |\n|-------|----------|\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        file_count
+    );
+    for level in LEVELS.iter().rev() {
+        out.push_str(&format!("| {} | {} |\n", level, findings.iter().filter(|f| f.level == *level).count()));
+    }
+
+    let mut current_file = None;
+    for finding in findings {
+        if current_file != Some(&finding.file) {
+            out.push_str(&format!("\n## `{}`\n\n", finding.file));
+            current_file = Some(&finding.file);
+        }
+        let lines = match finding.end_line {
+            Some(end) => format!("{}-{}", finding.line, end),
+            None => finding.line.to_string(),
+        };
+        out.push_str(&format!(
+            "- **{}** `{}` line {}: {}\n",
+            finding.level, finding.rule, lines, finding.message
+        ));
+    }
+    if findings.is_empty() {
+        out.push_str("\nNo issues found.\n");
+    }
+    out
+}
@@ -1,6 +1,9 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
@@ -8,10 +11,47 @@ use crate::config::Config;
 use crate::llm::LlamaClient;
 use crate::llm::client::Message;
 
-pub async fn execute() -> Result<()> {
+/// Prompt sizes, in tokens, for the `--context` needle-in-a-haystack test
+const CONTEXT_SIZES: &[u32] = &[4096, 16384, 32768, 65536];
+
+/// Options for `vork benchmark`
+pub struct BenchmarkOptions {
+    /// Score each answer with a judge prompt and a compile check
+    pub quality: bool,
+    /// Server that judges answers; defaults to the preset under test
+    pub judge_server: Option<String>,
+    pub judge_model: Option<String>,
+    /// Only benchmark these presets (all when empty)
+    pub presets: Vec<String>,
+    /// Only run these tests, by id (all when empty)
+    pub tests: Vec<String>,
+    /// Continue from the results saved by an interrupted run
+    pub resume: bool,
+    /// Directory for the JSON/CSV/HTML reports (default ~/.vork/benchmarks)
+    pub report_dir: Option<PathBuf>,
+    /// Run the long-context retrieval test instead of the generation tests
+    pub context: bool,
+    /// Sampling overrides so runs can be compared answer for answer
+    pub temperature: Option<f32>,
+    pub seed: Option<u64>,
+}
+
+pub async fn execute(options: BenchmarkOptions) -> Result<()> {
     println!("{}", "=== Vork Model Benchmark ===" .green().bold());
     println!();
 
+    // A dedicated judge stays the same across presets, so scores compare fairly
+    let dedicated_judge = match (&options.judge_server, &options.judge_model) {
+        (None, None) => None,
+        (server, model) => {
+            let config = Config::load()?;
+            Some(LlamaClient::new(
+                server.clone().unwrap_or_else(|| config.chat_server_url()),
+                model.clone().unwrap_or_else(|| config.assistant.model.clone()),
+            ).with_sampling(options.temperature, options.seed))
+        }
+    };
+
     // Get all available presets
     let config_dir = Config::config_dir()?;
     let presets_dir = config_dir.join("presets");
@@ -31,6 +71,13 @@ pub async fn execute() -> Result<()> {
     }
     presets.sort();
 
+    if let Some(unknown) = options.presets.iter().find(|p| !presets.contains(p)) {
+        anyhow::bail!("Unknown preset '{}'. Available: {}", unknown, presets.join(", "));
+    }
+    if !options.presets.is_empty() {
+        presets.retain(|p| options.presets.contains(p));
+    }
+
     if presets.is_empty() {
         println!("{}", "No presets found in presets directory".yellow());
         return Ok(());
@@ -43,8 +90,9 @@ pub async fn execute() -> Result<()> {
     println!();
 
     // Test prompts - Complex real-world tasks with specified length for fair comparison
-    let test_cases = vec![
+    let mut test_cases = vec![
         (
+            "codegen",
             "Code Generation",
             "Write a complete Rust function that implements a thread-safe LRU cache with generic key/value types. Include:\n\
             - Proper struct definition with HashMap and linked list\n\
@@ -54,6 +102,7 @@ pub async fn execute() -> Result<()> {
             Target: 300-400 words including code and explanations.",
         ),
         (
+            "bugfix",
             "Bug Analysis & Fix",
             "Analyze and fix this Rust code:\n\
             ```rust\n\
@@ -74,6 +123,7 @@ pub async fn execute() -> Result<()> {
             Target: 350-450 words.",
         ),
         (
+            "design",
             "System Design & Implementation",
             "Design and implement a CLI tool in Rust that monitors system resources (CPU, memory, disk) and logs to a file when thresholds are exceeded. Include:\n\
             - Architecture overview with component breakdown\n\
@@ -84,6 +134,7 @@ pub async fn execute() -> Result<()> {
             Target: 400-500 words with code snippets and technical reasoning.",
         ),
         (
+            "algorithm",
             "Algorithm Optimization",
             "Given this naive string matching implementation:\n\
             ```rust\n\
@@ -105,6 +156,7 @@ pub async fn execute() -> Result<()> {
             Target: 350-450 words with detailed explanations.",
         ),
         (
+            "refactor",
             "Refactoring & Architecture",
             "Refactor this monolithic function into clean, testable components:\n\
             ```rust\n\
@@ -132,37 +184,85 @@ pub async fn execute() -> Result<()> {
         ),
     ];
 
-    let mut results = Vec::new();
+    if let Some(unknown) = options.tests.iter().find(|t| !test_cases.iter().any(|(id, _, _)| id == t)) {
+        let ids: Vec<&str> = test_cases.iter().map(|(id, _, _)| *id).collect();
+        anyhow::bail!("Unknown test '{}'. Available: {}", unknown, ids.join(", "));
+    }
+    if !options.tests.is_empty() {
+        test_cases.retain(|(id, _, _)| options.tests.iter().any(|t| t == id));
+    }
+    // --context replaces the generation tests
+    if options.context {
+        test_cases.clear();
+    }
+
+    // Results are saved after every test so an interrupted run can resume
+    let partial_path = config_dir.join("benchmark_partial.json");
+    let saved: Vec<PresetBenchmark> = match std::fs::read_to_string(&partial_path) {
+        Ok(json) if options.resume => {
+            let saved: Vec<PresetBenchmark> = serde_json::from_str(&json)
+                .context("Failed to parse saved benchmark progress")?;
+            println!("{} Resuming: {} preset(s) have saved results", "↻".cyan(), saved.len());
+            println!();
+            saved
+        }
+        Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
+        Err(_) => {
+            if options.resume {
+                println!("{}", "No interrupted benchmark to resume, starting fresh".yellow());
+                println!();
+            }
+            Vec::new()
+        }
+    };
+    // Presets outside this run's selection stay in the progress file
+    // untouched; the selected ones start over unless resuming
+    let (selected, unselected): (Vec<_>, Vec<_>) = saved.into_iter().partition(|r| presets.contains(&r.name));
+    let mut results = if options.resume { selected } else { Vec::new() };
 
     for preset_name in &presets {
+        let done = |r: &PresetBenchmark| {
+            test_cases.iter().all(|(_, name, _)| r.test_results.iter().any(|t| t.test_name == *name))
+                && (!options.context
+                    || CONTEXT_SIZES.iter()
+                        .filter(|size| **size <= r.context_size)
+                        .all(|size| r.context_results.iter().any(|c| c.prompt_size == *size)))
+        };
+        if results.iter().any(|r| r.name == *preset_name && done(r)) {
+            println!("{} {} already benchmarked, skipping", "✓".green(), preset_name.green());
+            continue;
+        }
+
         println!("{}", format!("=== Testing: {} ===", preset_name).cyan().bold());
 
         // Load preset config
         let preset_path = presets_dir.join(format!("{}.toml", preset_name));
-        let preset_content = std::fs::read_to_string(&preset_path)?;
+        let preset_content = tokio::fs::read_to_string(&preset_path).await?;
         let preset_config: Config = toml::from_str(&preset_content)?;
 
         // Copy to main config
-        let config_path = Config::config_path()?;
-        std::fs::copy(&preset_path, &config_path)?;
+        let name = preset_name.clone();
+        crate::llm::processes::blocking(move || Config::apply_preset(&name)).await?;
 
         println!("{}", "  Restarting server with preset...".yellow());
 
         // Kill existing server
-        let _ = std::process::Command::new("pkill")
+        let _ = tokio::process::Command::new("pkill")
             .arg("llama-server")
-            .output();
+            .output()
+            .await;
         sleep(Duration::from_secs(2)).await;
 
         // Start new server
-        crate::backends::llamacpp::LlamaCppBackend::start_server(8080)?;
+        crate::llm::processes::blocking(|| crate::backends::llamacpp::LlamaCppBackend::start_server(8080)).await?;
         sleep(Duration::from_secs(5)).await; // Give more time for server to fully initialize
 
         // Create client
         let client = LlamaClient::new(
             preset_config.assistant.server_url.clone(),
             preset_config.assistant.model.clone(),
-        );
+        )
+        .with_sampling(options.temperature, options.seed);
 
         // Wait for server to be ready - try a simple test message
         println!("{}", "  Waiting for server to be ready...".yellow());
@@ -174,7 +274,7 @@ pub async fn execute() -> Result<()> {
                     content: "Hi".to_string(),
                 },
             ];
-            if client.chat_completion(test_messages, None).await.is_ok() {
+            if client.chat_completion(&test_messages, None).await.is_ok() {
                 ready = true;
                 break;
             }
@@ -189,15 +289,57 @@ pub async fn execute() -> Result<()> {
         println!("{}", "  ✓ Server ready".green());
         println!();
 
-        let mut preset_results = PresetBenchmark {
-            name: preset_name.clone(),
-            model: preset_config.assistant.model.clone(),
-            context_size: preset_config.llamacpp.context_size,
-            test_results: Vec::new(),
+        let index = match results.iter().position(|r| r.name == *preset_name) {
+            Some(index) => index,
+            None => {
+                results.push(PresetBenchmark {
+                    name: preset_name.clone(),
+                    model: preset_config.assistant.model.clone(),
+                    context_size: preset_config.llamacpp.context_size,
+                    test_results: Vec::new(),
+                    context_results: Vec::new(),
+                });
+                results.len() - 1
+            }
         };
 
+        // Long-context retrieval at each prompt size the preset can hold
+        if options.context {
+            let context_size = preset_config.llamacpp.context_size;
+            for &size in CONTEXT_SIZES {
+                let label = format!("{}k", size / 1024);
+                if size > context_size {
+                    println!("    {} {} skipped (context is {}k)", "-".yellow(), label, context_size / 1024);
+                    continue;
+                }
+                if results[index].context_results.iter().any(|c| c.prompt_size == size) {
+                    println!("    {} {} (saved)", "✓".green(), label.cyan());
+                    continue;
+                }
+                println!("    Needle in {} tokens", label.cyan());
+
+                let result = needle_test(&client, size).await;
+                match (&result.error, result.found) {
+                    (Some(e), _) => println!("      {} Failed: {}", "✗".red(), e),
+                    (None, found) => println!("      {} {} ({:.0} prompt tok/s, {:.1}s)",
+                        if found { "✓".green() } else { "✗".red() },
+                        if found { "found the needle" } else { "missed the needle" },
+                        result.prompt_tokens_per_second,
+                        result.duration_secs
+                    ),
+                }
+                results[index].context_results.push(result);
+
+                save_progress(&partial_path, &results, &unselected).await?;
+            }
+        }
+
         // Run each test case
-        for (test_name, prompt) in &test_cases {
+        for (_, test_name, prompt) in &test_cases {
+            if results[index].test_results.iter().any(|t| t.test_name == *test_name) {
+                println!("    {} {} (saved)", "✓".green(), test_name.cyan());
+                continue;
+            }
             println!("    Testing: {}", test_name.cyan());
 
             let messages = vec![
@@ -213,7 +355,7 @@ pub async fn execute() -> Result<()> {
 
             let start = Instant::now();
 
-            match client.chat_completion(messages, None).await {
+            match client.chat_completion(&messages, None).await {
                 Ok(response) => {
                     let duration = start.elapsed();
 
@@ -223,82 +365,368 @@ pub async fn execute() -> Result<()> {
                         .map(|s| s.as_str())
                         .unwrap_or("");
 
-                    // Estimate tokens (rough approximation: ~4 chars per token)
-                    let estimated_tokens = content.len() / 4;
-                    let tokens_per_sec = if estimated_tokens > 0 {
-                        estimated_tokens as f64 / duration.as_secs_f64()
+                    // Tokens and generation time as reported by the server
+                    let (generated_tokens, generation_secs) = response.generation(duration);
+                    let tokens_per_sec = if generated_tokens > 0 && generation_secs > 0.0 {
+                        generated_tokens as f64 / generation_secs
                     } else {
                         0.0
                     };
 
                     // Latency score: ms per token (lower is better)
-                    let latency_score = if estimated_tokens > 0 {
-                        duration.as_millis() as f64 / estimated_tokens as f64
+                    let latency_score = if generated_tokens > 0 {
+                        generation_secs * 1000.0 / generated_tokens as f64
                     } else {
                         999999.0
                     };
 
-                    println!("      {} {:.1} tok/s (~{} tokens, {:.1}s, {:.1}ms/tok)",
+                    println!("      {} {:.1} tok/s ({} tokens, {:.1}s, {:.1}ms/tok)",
                         "✓".green(),
                         tokens_per_sec,
-                        estimated_tokens,
+                        generated_tokens,
                         duration.as_secs_f64(),
                         latency_score
                     );
 
-                    preset_results.test_results.push(TestResult {
+                    let (quality, compiles) = if options.quality {
+                        let judge = dedicated_judge.as_ref().unwrap_or(&client);
+                        let (score, compiles) = score_quality(judge, prompt, content).await;
+                        match score {
+                            Some(score) => println!("      {} quality {:.1}/10", "★".yellow(), score),
+                            None => println!("      {} quality could not be scored", "?".yellow()),
+                        }
+                        match compiles {
+                            Some(true) => println!("      {} the Rust code compiles", "✓".green()),
+                            Some(false) => println!("      {} the Rust code does not compile", "✗".red()),
+                            None => {}
+                        }
+                        (score, compiles)
+                    } else {
+                        (None, None)
+                    };
+
+                    results[index].test_results.push(TestResult {
                         test_name: test_name.to_string(),
                         tokens_per_second: tokens_per_sec,
-                        total_tokens: estimated_tokens,
-                        duration,
+                        total_tokens: generated_tokens,
+                        duration_secs: duration.as_secs_f64(),
                         response_preview: truncate_string(content, 100),
                         latency_score,
+                        quality,
+                        compiles,
                     });
                 }
                 Err(e) => {
                     println!("      {} Failed: {}", "✗".red(), e);
-                    preset_results.test_results.push(TestResult {
+                    results[index].test_results.push(TestResult {
                         test_name: test_name.to_string(),
                         tokens_per_second: 0.0,
                         total_tokens: 0,
-                        duration: Duration::from_secs(0),
+                        duration_secs: 0.0,
                         response_preview: format!("Error: {}", e),
                         latency_score: 999999.0,
+                        quality: None,
+                        compiles: None,
                     });
                 }
             }
 
+            save_progress(&partial_path, &results, &unselected).await?;
+
             // Small delay between tests
             sleep(Duration::from_millis(500)).await;
         }
 
-        results.push(preset_results);
         println!();
     }
 
-    // Print summary report
-    print_summary(&results);
+    // Presets whose server never came up have no results to report
+    results.retain(|r| !r.test_results.is_empty() || !r.context_results.is_empty());
+    if results.is_empty() {
+        println!("{}", "No presets produced results".yellow());
+        return Ok(());
+    }
+
+    if options.context {
+        // Speed stats and agent assignments come from the generation tests only
+        print_context_summary(&results);
+    } else {
+        // Print summary report
+        print_summary(&results);
+
+        // Save benchmark results for agent allocation
+        save_benchmark_results(&results)?;
+    }
+
+    let report_dir = match options.report_dir {
+        Some(dir) => dir,
+        None => config_dir.join("benchmarks"),
+    };
+    write_reports(&results, &report_dir)?;
 
-    // Save benchmark results for agent allocation
-    save_benchmark_results(&results)?;
+    // The run is complete, so only other presets' progress is left to resume
+    if unselected.is_empty() {
+        let _ = std::fs::remove_file(&partial_path);
+    } else {
+        save_progress(&partial_path, &[], &unselected).await?;
+    }
 
     Ok(())
 }
 
+/// Write this run's results and the saved ones of presets it skipped
+async fn save_progress(path: &Path, results: &[PresetBenchmark], unselected: &[PresetBenchmark]) -> Result<()> {
+    let all: Vec<&PresetBenchmark> = results.iter().chain(unselected).collect();
+    tokio::fs::write(path, serde_json::to_string_pretty(&all)?)
+        .await
+        .context("Failed to save benchmark progress")
+}
+
+#[derive(Serialize, Deserialize)]
 struct PresetBenchmark {
     name: String,
     model: String,
     context_size: u32,
     test_results: Vec<TestResult>,
+    #[serde(default)]
+    context_results: Vec<ContextResult>,
 }
 
+#[derive(Serialize, Deserialize)]
 struct TestResult {
     test_name: String,
     tokens_per_second: f64,
     total_tokens: usize,
-    duration: Duration,
+    duration_secs: f64,
     response_preview: String,
     latency_score: f64,  // Lower is better: duration_ms / tokens
+    #[serde(default)]
+    quality: Option<f64>,  // 0-10, only with --quality
+    #[serde(default)]
+    compiles: Option<bool>,  // Whether the answer's Rust code compiled, only with --quality
+}
+
+/// One needle-in-a-haystack run at a given prompt size
+#[derive(Serialize, Deserialize)]
+struct ContextResult {
+    prompt_size: u32,
+    prompt_tokens: usize,
+    duration_secs: f64,
+    prompt_tokens_per_second: f64,
+    found: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Hide a code in the middle of roughly `size` tokens of filler and ask for
+/// it back. Measures prompt processing speed and long-context recall.
+async fn needle_test(client: &LlamaClient, size: u32) -> ContextResult {
+    let filler = [
+        "The scheduler module keeps a queue of pending jobs and wakes a worker when one arrives.",
+        "Configuration is read once at startup and cached for the lifetime of the process.",
+        "Each request handler validates its input before touching the database layer.",
+        "Log rotation happens nightly, and old archives are compressed after seven days.",
+        "The cache layer evicts the least recently used entries when memory runs low.",
+        "Integration tests spin up a temporary database and tear it down afterwards.",
+        "Metrics are exported in a plain text format that the monitoring system scrapes.",
+        "Retries use exponential backoff with jitter to avoid overwhelming the upstream.",
+    ];
+    // A fixed seed hides the same code every run
+    let seed = client.seed().unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(0)
+    });
+    let code = format!("VORK{:06}", seed % 1_000_000);
+
+    // ~4 chars per token; leave a tenth of the window for the question and answer
+    let target_chars = size as usize * 9 / 10 * 4;
+    let mut sentences = Vec::new();
+    let mut chars = 0;
+    let mut i = 0;
+    while chars < target_chars {
+        let sentence = format!("Note {}: {}", i + 1, filler[i % filler.len()]);
+        chars += sentence.len() + 1;
+        sentences.push(sentence);
+        i += 1;
+    }
+    sentences.insert(sentences.len() / 2, format!("Note: the secret code is {}.", code));
+
+    let prompt = format!(
+        "{}\n\nWhat is the secret code mentioned in the notes above? Reply with only the code.",
+        sentences.join(" ")
+    );
+    let estimated_tokens = prompt.len() / 4;
+    let messages = vec![Message {
+        role: "user".to_string(),
+        content: prompt,
+    }];
+
+    let start = Instant::now();
+    let response = client.chat_completion(&messages, None).await;
+    let duration = start.elapsed().as_secs_f64();
+
+    match response {
+        Ok(response) => {
+            let answer = response.choices.first()
+                .and_then(|c| c.message.content.as_deref())
+                .unwrap_or("");
+            let prompt_tokens = response.usage.as_ref()
+                .map(|u| u.prompt_tokens)
+                .filter(|n| *n > 0)
+                .unwrap_or(estimated_tokens);
+            // Server timings exclude cached tokens and the generated answer
+            let prompt_tokens_per_second = match response.prompt_processing() {
+                Some((evaluated, secs)) => evaluated as f64 / secs,
+                None => prompt_tokens as f64 / duration,
+            };
+            ContextResult {
+                prompt_size: size,
+                prompt_tokens,
+                duration_secs: duration,
+                prompt_tokens_per_second,
+                found: answer.contains(&code),
+                error: None,
+            }
+        }
+        Err(e) => ContextResult {
+            prompt_size: size,
+            prompt_tokens: estimated_tokens,
+            duration_secs: duration,
+            prompt_tokens_per_second: 0.0,
+            found: false,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+fn print_context_summary(results: &[PresetBenchmark]) {
+    println!();
+    println!("{}", "=== LONG CONTEXT SUMMARY ===".green().bold());
+    println!();
+
+    print!("{:<25}", "Preset".cyan().bold());
+    for size in CONTEXT_SIZES {
+        print!(" {:<18}", format!("{}k", size / 1024).cyan().bold());
+    }
+    println!();
+    println!("{}", "─".repeat(25 + CONTEXT_SIZES.len() * 19).cyan());
+
+    for preset in results {
+        print!("{:<25}", preset.name.green());
+        for size in CONTEXT_SIZES {
+            let cell = match preset.context_results.iter().find(|c| c.prompt_size == *size) {
+                Some(c) if c.error.is_some() => "error".red(),
+                Some(c) if c.found => format!("✓ {:.0} tok/s", c.prompt_tokens_per_second).green(),
+                Some(c) => format!("✗ {:.0} tok/s", c.prompt_tokens_per_second).red(),
+                None => "-".normal(),
+            };
+            print!(" {:<18}", cell);
+        }
+        println!();
+    }
+    println!();
+    println!("✓/✗ = needle found/missed; speed is prompt processing");
+    println!();
+}
+
+impl PresetBenchmark {
+    fn avg_speed(&self) -> f64 {
+        self.test_results.iter().map(|r| r.tokens_per_second).sum::<f64>() / self.test_results.len() as f64
+    }
+
+    /// Mean quality over the scored tests, None when nothing was scored
+    fn avg_quality(&self) -> Option<f64> {
+        let scores: Vec<f64> = self.test_results.iter().filter_map(|r| r.quality).collect();
+        if scores.is_empty() {
+            None
+        } else {
+            Some(scores.iter().sum::<f64>() / scores.len() as f64)
+        }
+    }
+}
+
+/// Score an answer from 0 to 10, and whether its Rust code compiles when it
+/// has any and rustc is installed. The judge's rating is blended with the
+/// compile result; without a rating there is no score.
+async fn score_quality(judge: &LlamaClient, task: &str, answer: &str) -> (Option<f64>, Option<bool>) {
+    if answer.trim().is_empty() {
+        return (Some(0.0), None);
+    }
+
+    let prompt = format!(
+        "Rate how well the answer below completes the task, from 0 to 10. \
+        Judge correctness first, then completeness and code quality. \
+        Reply with only the number.\n\n## Task\n{}\n\n## Answer\n{}",
+        task, answer
+    );
+    let messages = vec![
+        Message {
+            role: "system".to_string(),
+            content: "You are a strict reviewer grading answers to programming tasks.".to_string(),
+        },
+        Message {
+            role: "user".to_string(),
+            content: prompt,
+        },
+    ];
+    let judged = judge
+        .chat_completion(&messages, None)
+        .await
+        .ok()
+        .and_then(|r| r.choices.into_iter().next())
+        .and_then(|c| c.message.content)
+        .and_then(|text| parse_score(&text));
+
+    // rustc takes a while; keep it off the runtime's threads
+    let code = answer.to_string();
+    let compiles = crate::llm::processes::blocking(move || rust_compiles(&code)).await;
+    let score = judged.map(|score| match compiles {
+        Some(compiles) => score * 0.7 + if compiles { 3.0 } else { 0.0 },
+        None => score,
+    });
+    (score, compiles)
+}
+
+/// First number in the judge's reply, clamped to 0-10
+fn parse_score(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let number: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.trim_end_matches('.').parse::<f64>().ok().map(|n| n.clamp(0.0, 10.0))
+}
+
+/// Whether the longest ```rust block in the answer compiles as a library.
+/// None when there is no Rust code or rustc is not available.
+fn rust_compiles(answer: &str) -> Option<bool> {
+    let code = answer
+        .split("```")
+        .skip(1)
+        .step_by(2)
+        .filter_map(|block| block.strip_prefix("rust"))
+        .max_by_key(|block| block.len())?;
+
+    let rustc_works = Command::new("rustc").arg("--version").output().is_ok_and(|o| o.status.success());
+    if !rustc_works {
+        return None;
+    }
+
+    let dir = std::env::temp_dir().join(format!("vork-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).ok()?;
+    let source = dir.join("answer.rs");
+    std::fs::write(&source, code).ok()?;
+
+    let output = Command::new("rustc")
+        .args(["--edition", "2021", "--crate-type", "lib", "--emit", "metadata", "--out-dir"])
+        .arg(&dir)
+        .arg(&source)
+        .output()
+        .ok()?;
+    let _ = std::fs::remove_dir_all(&dir);
+    Some(output.status.success())
 }
 
 fn print_summary(results: &[PresetBenchmark]) {
@@ -316,10 +744,10 @@ fn print_summary(results: &[PresetBenchmark]) {
     );
     println!("{}", "─".repeat(95).cyan());
 
+    let show_quality = results.iter().any(|p| p.avg_quality().is_some());
+
     for preset in results {
-        let avg_speed: f64 = preset.test_results.iter()
-            .map(|r| r.tokens_per_second)
-            .sum::<f64>() / preset.test_results.len() as f64;
+        let avg_speed = preset.avg_speed();
 
         let avg_latency: f64 = preset.test_results.iter()
             .filter(|r| r.latency_score < 999999.0)
@@ -344,6 +772,11 @@ fn print_summary(results: &[PresetBenchmark]) {
             format!("{:.1} ms/tok", avg_latency).magenta(),
             use_case
         );
+        if show_quality {
+            if let Some(quality) = preset.avg_quality() {
+                println!("{:<25} quality {:.1}/10", "", quality);
+            }
+        }
     }
 
     println!();
@@ -362,7 +795,13 @@ fn print_summary(results: &[PresetBenchmark]) {
                 println!("     Speed: {:.1} tok/s ({:.1} ms/tok)",
                     test.tokens_per_second, test.latency_score);
                 println!("     Time: {:.2}s for {} tokens",
-                    test.duration.as_secs_f64(), test.total_tokens);
+                    test.duration_secs, test.total_tokens);
+                if let Some(quality) = test.quality {
+                    println!("     Quality: {:.1}/10", quality);
+                }
+                if let Some(compiles) = test.compiles {
+                    println!("     Compiles: {}", if compiles { "yes" } else { "no" });
+                }
                 println!("     Preview: {}",
                     test.response_preview.trim().replace('\n', " "));
             } else {
@@ -376,11 +815,7 @@ fn print_summary(results: &[PresetBenchmark]) {
     println!();
 
     // Find fastest and best for different use cases
-    if let Some(fastest) = results.iter().max_by(|a, b| {
-        let avg_a = a.test_results.iter().map(|r| r.tokens_per_second).sum::<f64>() / a.test_results.len() as f64;
-        let avg_b = b.test_results.iter().map(|r| r.tokens_per_second).sum::<f64>() / b.test_results.len() as f64;
-        avg_a.partial_cmp(&avg_b).unwrap()
-    }) {
+    if let Some(fastest) = results.iter().max_by(|a, b| a.avg_speed().total_cmp(&b.avg_speed())) {
         println!("⚡ {} for quick responses and simple tasks", fastest.name.green().bold());
     }
 
@@ -389,7 +824,10 @@ fn print_summary(results: &[PresetBenchmark]) {
             largest_ctx.name.green().bold(), largest_ctx.context_size / 1024);
     }
 
-    if let Some(largest_model) = results.iter().max_by(|a, b| {
+    if let Some(best) = best_quality(results) {
+        println!("🧠 {} for complex reasoning and research (quality {:.1}/10)",
+            best.name.green().bold(), best.avg_quality().unwrap_or_default());
+    } else if let Some(largest_model) = results.iter().max_by(|a, b| {
         let size_a = if a.name.contains("30") { 30 } else { 14 };
         let size_b = if b.name.contains("30") { 30 } else { 14 };
         size_a.cmp(&size_b)
@@ -403,6 +841,15 @@ fn print_summary(results: &[PresetBenchmark]) {
     println!();
 }
 
+/// Preset with the highest average quality score, if any were scored
+fn best_quality(results: &[PresetBenchmark]) -> Option<&PresetBenchmark> {
+    results
+        .iter()
+        .filter_map(|p| p.avg_quality().map(|q| (p, q)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(p, _)| p)
+}
+
 fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -425,6 +872,8 @@ struct PresetStats {
     name: String,
     avg_tokens_per_second: f64,
     context_size: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    avg_quality: Option<f64>,
 }
 
 fn save_benchmark_results(results: &[PresetBenchmark]) -> Result<()> {
@@ -452,20 +901,20 @@ fn save_benchmark_results(results: &[PresetBenchmark]) -> Result<()> {
         .map(|p| p.name.clone())
         .unwrap_or_else(|| "qwen3-30b-extended".to_string());
 
-    // Find best for reasoning (30B model or fallback to fastest)
-    let best_reasoning = results.iter()
-        .find(|r| r.name.contains("30b"))
+    // Find best for reasoning: highest quality score when --quality ran,
+    // otherwise the 30B model, falling back to fastest
+    let best_reasoning = best_quality(results)
+        .or_else(|| results.iter().find(|r| r.name.contains("30b")))
         .map(|p| p.name.clone())
         .unwrap_or_else(|| fastest.clone());
 
     let preset_stats: Vec<PresetStats> = results.iter()
         .map(|p| {
-            let avg_speed = p.test_results.iter().map(|r| r.tokens_per_second).sum::<f64>()
-                / p.test_results.len() as f64;
             PresetStats {
                 name: p.name.clone(),
-                avg_tokens_per_second: avg_speed,
+                avg_tokens_per_second: p.avg_speed(),
                 context_size: p.context_size,
+                avg_quality: p.avg_quality(),
             }
         })
         .collect();
@@ -537,3 +986,180 @@ fn update_agent_presets(results: &BenchmarkResults) -> Result<()> {
 
     Ok(())
 }
+
+/// Write JSON, CSV and HTML reports for this run into `dir`, named by
+/// timestamp so earlier reports are kept
+fn write_reports(results: &[PresetBenchmark], dir: &Path) -> Result<()> {
+    use chrono::Local;
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let now = Local::now();
+    let stem = dir.join(format!("benchmark-{}", now.format("%Y%m%d-%H%M%S")));
+
+    let json = serde_json::json!({
+        "timestamp": now.to_rfc3339(),
+        "presets": results,
+    });
+    std::fs::write(stem.with_extension("json"), serde_json::to_string_pretty(&json)?)?;
+
+    let mut written = vec![stem.with_extension("json")];
+
+    let mut csv = String::from("preset,model,context_size,test,tokens_per_second,total_tokens,duration_secs,ms_per_token,quality,compiles\n");
+    for preset in results {
+        for test in &preset.test_results {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.2},{},{:.2},{:.2},{},{}",
+                csv_field(&preset.name),
+                csv_field(&preset.model),
+                preset.context_size,
+                csv_field(&test.test_name),
+                test.tokens_per_second,
+                test.total_tokens,
+                test.duration_secs,
+                test.latency_score,
+                test.quality.map(|q| format!("{:.1}", q)).unwrap_or_default(),
+                test.compiles.map(|c| c.to_string()).unwrap_or_default()
+            );
+        }
+    }
+    if results.iter().any(|p| !p.test_results.is_empty()) {
+        std::fs::write(stem.with_extension("csv"), csv)?;
+        written.push(stem.with_extension("csv"));
+    }
+
+    if results.iter().any(|p| !p.context_results.is_empty()) {
+        let mut csv = String::from("preset,model,context_size,prompt_size,prompt_tokens,duration_secs,prompt_tokens_per_second,found,error\n");
+        for preset in results {
+            for c in &preset.context_results {
+                let _ = writeln!(
+                    csv,
+                    "{},{},{},{},{},{:.2},{:.1},{},{}",
+                    csv_field(&preset.name),
+                    csv_field(&preset.model),
+                    preset.context_size,
+                    c.prompt_size,
+                    c.prompt_tokens,
+                    c.duration_secs,
+                    c.prompt_tokens_per_second,
+                    c.found,
+                    csv_field(c.error.as_deref().unwrap_or(""))
+                );
+            }
+        }
+        let path = dir.join(format!("benchmark-{}-context.csv", now.format("%Y%m%d-%H%M%S")));
+        std::fs::write(&path, csv)?;
+        written.push(path);
+    }
+
+    std::fs::write(stem.with_extension("html"), html_report(results, &now.to_rfc2822()))?;
+    written.push(stem.with_extension("html"));
+
+    println!("{}", "📑 Reports written:".cyan().bold());
+    for path in written {
+        println!("   {}", path.display().to_string().yellow());
+    }
+    println!();
+
+    Ok(())
+}
+
+fn html_report(results: &[PresetBenchmark], timestamp: &str) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Vork benchmark</title>\n\
+        <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse;margin-bottom:2em}}\
+        th,td{{border:1px solid #ccc;padding:4px 10px;text-align:right}}th:first-child,td:first-child{{text-align:left}}\
+        th{{background:#eee}}</style></head><body>\n<h1>Vork benchmark</h1>\n<p>{}</p>\n",
+        escape_html(timestamp)
+    );
+
+    let generation: Vec<&PresetBenchmark> = results.iter().filter(|p| !p.test_results.is_empty()).collect();
+    let long_context: Vec<&PresetBenchmark> = results.iter().filter(|p| !p.context_results.is_empty()).collect();
+
+    if !generation.is_empty() {
+        html.push_str("<h2>Summary</h2>\n<table><tr><th>Preset</th><th>Model</th><th>Context</th><th>Avg tok/s</th><th>Quality</th></tr>\n");
+    }
+    for preset in &generation {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}k</td><td>{:.1}</td><td>{}</td></tr>",
+            escape_html(&preset.name),
+            escape_html(&preset.model),
+            preset.context_size / 1024,
+            preset.avg_speed(),
+            preset.avg_quality().map(|q| format!("{:.1}", q)).unwrap_or_else(|| "-".to_string())
+        );
+    }
+    if !generation.is_empty() {
+        html.push_str("</table>\n");
+    }
+
+    for preset in &generation {
+        let _ = writeln!(
+            html,
+            "<h2>{}</h2>\n<table><tr><th>Test</th><th>tok/s</th><th>Tokens</th><th>Time (s)</th><th>ms/tok</th><th>Quality</th><th>Compiles</th></tr>",
+            escape_html(&preset.name)
+        );
+        for test in &preset.test_results {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{:.1}</td><td>{}</td><td>{:.2}</td><td>{:.1}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&test.test_name),
+                test.tokens_per_second,
+                test.total_tokens,
+                test.duration_secs,
+                test.latency_score,
+                test.quality.map(|q| format!("{:.1}", q)).unwrap_or_else(|| "-".to_string()),
+                match test.compiles {
+                    Some(true) => "yes",
+                    Some(false) => "no",
+                    None => "-",
+                }
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !long_context.is_empty() {
+        html.push_str("<h2>Long context (needle in a haystack)</h2>\n<table><tr><th>Preset</th>");
+        for size in CONTEXT_SIZES {
+            let _ = write!(html, "<th>{}k</th>", size / 1024);
+        }
+        html.push_str("</tr>\n");
+        for preset in &long_context {
+            let _ = write!(html, "<tr><td>{}</td>", escape_html(&preset.name));
+            for size in CONTEXT_SIZES {
+                let cell = match preset.context_results.iter().find(|c| c.prompt_size == *size) {
+                    Some(c) if c.error.is_some() => "error".to_string(),
+                    Some(c) => format!(
+                        "{} {:.0} tok/s",
+                        if c.found { "&#10003;" } else { "&#10007;" },
+                        c.prompt_tokens_per_second
+                    ),
+                    None => "-".to_string(),
+                };
+                let _ = write!(html, "<td>{}</td>", cell);
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
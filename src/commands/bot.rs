@@ -0,0 +1,582 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::exec::{self, ExecOptions, ExecReport};
+use crate::agents::Agent;
+use crate::config::{BotConfig, Config};
+use crate::llm::{ApprovalSystem, LlamaClient, Session, UsageTracker};
+
+/// Threads still polled for replies; the oldest are dropped beyond this
+const MAX_THREADS: usize = 50;
+
+/// Diff text included in a reply
+const MAX_DIFF_CHARS: usize = 2500;
+
+/// A message the bot should act on
+struct Incoming {
+    /// Slack thread timestamp or Discord thread channel id
+    thread: String,
+    /// Message id, used as the thread's read cursor
+    id: String,
+    text: String,
+}
+
+/// Progress through the channel, kept across restarts so nothing is
+/// answered twice
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BotState {
+    /// Newest top-level message already seen
+    cursor: Option<String>,
+    threads: BTreeMap<String, ThreadState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ThreadState {
+    session_id: String,
+    cursor: String,
+}
+
+impl BotState {
+    fn path(platform: &str, channel: &str) -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join(format!("bot-{}-{}.json", platform, channel)))
+    }
+
+    fn load(path: &PathBuf) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &PathBuf) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?).context("Failed to save bot state")
+    }
+}
+
+#[async_trait]
+trait Platform: Send + Sync {
+    /// Look up the bot's own user id
+    async fn connect(&mut self) -> Result<()>;
+    /// Top-level messages after `cursor` that mention the bot, each opening a
+    /// thread, and the new cursor. The first poll only records the cursor.
+    async fn new_requests(&self, cursor: Option<&str>) -> Result<(Vec<Incoming>, Option<String>)>;
+    /// Messages from people in `thread` after `cursor`
+    async fn thread_replies(&self, thread: &str, cursor: &str) -> Result<Vec<Incoming>>;
+    async fn post(&self, thread: &str, text: &str) -> Result<()>;
+}
+
+pub async fn execute(
+    platform: Option<String>,
+    channel: Option<String>,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent: Option<String>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let mut bot = config.bot.clone();
+    if let Some(platform) = platform {
+        bot.platform = platform.to_lowercase();
+    }
+    if let Some(channel) = channel {
+        bot.channel = channel;
+    }
+    if bot.channel.is_empty() {
+        anyhow::bail!("No channel configured. Set bot.channel or pass --channel <id>");
+    }
+    let token = bot.token().ok_or_else(|| {
+        anyhow::anyhow!("No bot token. Set bot.token, bot.token_env or the platform's *_BOT_TOKEN variable")
+    })?;
+
+    if let Some(ref workspace) = bot.workspace {
+        let workspace = shellexpand::tilde(workspace).to_string();
+        env::set_current_dir(&workspace).with_context(|| format!("Failed to enter workspace {}", workspace))?;
+    }
+
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+    let agent = agent.or_else(|| bot.agent.clone());
+    let client = LlamaClient::for_config(&config, server_url, model.clone());
+
+    // Nobody is at a terminal to approve anything; refusals are reported in the thread
+    let agent_limits = agent.as_deref().map(Agent::load_or_create_defaults).transpose()?;
+    let approval = Arc::new(ApprovalSystem::unattended(bot.full_auto, &config.remote_approval)?.with_agent(agent_limits.as_ref()));
+
+    let mut platform: Box<dyn Platform> = match bot.platform.as_str() {
+        "slack" => Box::new(Slack::new(&bot, token)),
+        "discord" => Box::new(Discord::new(&bot, token)),
+        other => anyhow::bail!("Unknown bot platform '{}' (use slack or discord)", other),
+    };
+    platform.connect().await?;
+
+    let state_path = BotState::path(&bot.platform, &bot.channel)?;
+    fs::create_dir_all(Config::config_dir()?)?;
+    let mut state = BotState::load(&state_path);
+
+    eprintln!(
+        "{} Listening in {} channel {} (workspace {})",
+        "🤖".cyan(),
+        bot.platform,
+        bot.channel.yellow(),
+        env::current_dir()?.display()
+    );
+
+    loop {
+        let mut work = vec![];
+        match platform.new_requests(state.cursor.as_deref()).await {
+            Ok((requests, cursor)) => {
+                for request in &requests {
+                    state.threads.insert(
+                        request.thread.clone(),
+                        ThreadState { session_id: String::new(), cursor: request.id.clone() },
+                    );
+                }
+                work.extend(requests);
+                state.cursor = cursor.or(state.cursor.take());
+            }
+            Err(e) => eprintln!("{} {:#}", "⚠️  Poll failed:".yellow(), e),
+        }
+        for (thread, thread_state) in state.threads.clone() {
+            if work.iter().any(|w| w.thread == thread) {
+                continue;
+            }
+            match platform.thread_replies(&thread, &thread_state.cursor).await {
+                Ok(replies) => work.extend(replies),
+                Err(e) => eprintln!("{} {:#}", "⚠️  Poll failed:".yellow(), e),
+            }
+        }
+
+        for incoming in work {
+            let thread_state = state.threads.get(&incoming.thread).cloned();
+            let session_id = thread_state.map(|t| t.session_id).filter(|id| !id.is_empty());
+            eprintln!("{} [{}] {}", "💬".cyan(), incoming.thread, incoming.text);
+
+            let reply = match handle(&incoming, session_id.as_deref(), &client, &approval, agent.as_deref(), &model).await {
+                Ok((session_id, reply)) => {
+                    state.threads.insert(
+                        incoming.thread.clone(),
+                        ThreadState { session_id, cursor: incoming.id.clone() },
+                    );
+                    reply
+                }
+                Err(e) => {
+                    if let Some(thread) = state.threads.get_mut(&incoming.thread) {
+                        thread.cursor = incoming.id.clone();
+                    }
+                    format!("⚠️ {:#}", e)
+                }
+            };
+            if let Err(e) = platform.post(&incoming.thread, &reply).await {
+                eprintln!("{} {:#}", "⚠️  Reply failed:".yellow(), e);
+            }
+        }
+
+        while state.threads.len() > MAX_THREADS {
+            let oldest = state
+                .threads
+                .iter()
+                .min_by(|a, b| compare_ids(&a.1.cursor, &b.1.cursor))
+                .map(|(thread, _)| thread.clone());
+            if let Some(oldest) = oldest {
+                state.threads.remove(&oldest);
+            }
+        }
+        state.save(&state_path)?;
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(bot.poll_secs.max(1))) => {}
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("{} Stopped", "🤖".cyan());
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Run one message through the exec tool loop in the thread's session.
+/// Returns the session id and the reply to post.
+async fn handle(
+    incoming: &Incoming,
+    session_id: Option<&str>,
+    client: &LlamaClient,
+    approval: &Arc<ApprovalSystem>,
+    agent: Option<&str>,
+    model: &str,
+) -> Result<(String, String)> {
+    let mut session = match session_id {
+        Some(id) => {
+            let mut session = Session::load(id).with_context(|| format!("Failed to load session {}", id))?;
+            session.resume_transcript();
+            session
+        }
+        None => {
+            let mut session = Session::new(env::current_dir()?);
+            if let Some(name) = agent {
+                session.conversation.set_system_prompt(Agent::load_or_create_defaults(name)?.system_prompt);
+            }
+            session
+        }
+    };
+
+    let mut usage = UsageTracker::new("bot", &session.id, model);
+    usage.set_agent(agent);
+    let options = ExecOptions {
+        agent: agent.map(String::from),
+        ..Default::default()
+    };
+    let denied_before = approval.denied_count();
+    let report = exec::run(&incoming.text, &mut session, client, approval, &mut usage, &options).await?;
+    session.save()?;
+
+    Ok((session.id.clone(), summary(&report, approval.denied_count() - denied_before).await))
+}
+
+/// The agent's answer followed by what it changed and ran
+async fn summary(report: &ExecReport, denied: usize) -> String {
+    let mut out = report.message.clone().unwrap_or_else(|| "(no answer)".to_string());
+
+    if !report.files_changed.is_empty() {
+        let files: Vec<String> = report.files_changed.iter().map(|f| format!("`{}`", f)).collect();
+        out.push_str(&format!("\n\n*Changed:* {}", files.join(", ")));
+        let mut diff = tokio::process::Command::new("git")
+            .args(["--no-pager", "diff", "--"])
+            .args(&report.files_changed)
+            .output()
+            .await
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap_or_default();
+        if !diff.trim().is_empty() {
+            if diff.len() > MAX_DIFF_CHARS {
+                let mut cut = MAX_DIFF_CHARS;
+                while !diff.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                diff.truncate(cut);
+                diff.push_str("\n... (diff truncated)");
+            }
+            out.push_str(&format!("\n```diff\n{}\n```", diff.trim_end()));
+        }
+    }
+    if !report.commands_run.is_empty() {
+        let commands: Vec<String> = report.commands_run.iter().map(|c| format!("`{}`", c)).collect();
+        out.push_str(&format!("\n\n*Ran:* {}", commands.join(", ")));
+    }
+    if denied > 0 {
+        out.push_str(&format!(
+            "\n\n⚠️ {} operation(s) needed approval and were skipped (set bot.full_auto to allow them)",
+            denied
+        ));
+    }
+    if let Some(ref error) = report.error {
+        out.push_str(&format!("\n\n⚠️ {}", error));
+    }
+    out
+}
+
+/// Order message ids: Slack timestamps ("1712345678.123456") and Discord
+/// snowflakes both grow over time, but snowflakes do not fit in an f64
+fn compare_ids(a: &str, b: &str) -> std::cmp::Ordering {
+    let key = |id: &str| {
+        let (whole, fraction) = id.split_once('.').unwrap_or((id, ""));
+        (whole.parse::<u128>().unwrap_or(0), fraction.to_string())
+    };
+    key(a).cmp(&key(b))
+}
+
+fn is_after(id: &str, cursor: &str) -> bool {
+    compare_ids(id, cursor) == std::cmp::Ordering::Greater
+}
+
+/// Split a reply into pieces under the platform's message size limit,
+/// preferring line breaks
+fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    for line in text.lines() {
+        if !current.is_empty() && current.len() + line.len() + 1 > limit {
+            parts.push(std::mem::take(&mut current));
+        }
+        let mut line = line;
+        while line.len() > limit {
+            let mut cut = limit;
+            while !line.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            parts.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Slack Web API, polled with conversations.history and conversations.replies
+struct Slack {
+    base: String,
+    token: String,
+    channel: String,
+    user_id: String,
+    client: reqwest::Client,
+}
+
+impl Slack {
+    fn new(config: &BotConfig, token: String) -> Self {
+        Self {
+            base: config.api_url.clone().unwrap_or_else(|| "https://slack.com/api".to_string()),
+            token,
+            channel: config.channel.clone(),
+            user_id: String::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(&self, method: &str, query: &[(&str, &str)]) -> Result<Value> {
+        let response: Value = self
+            .client
+            .get(format!("{}/{}", self.base.trim_end_matches('/'), method))
+            .bearer_auth(&self.token)
+            .query(query)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach Slack ({})", method))?
+            .json()
+            .await?;
+        if response["ok"].as_bool() != Some(true) {
+            anyhow::bail!("Slack {} failed: {}", method, response["error"].as_str().unwrap_or("unknown error"));
+        }
+        Ok(response)
+    }
+
+    /// People's messages, oldest first
+    fn human_messages(&self, response: &Value) -> Vec<Value> {
+        let mut messages: Vec<Value> = response["messages"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|m| m["bot_id"].is_null() && m["subtype"].is_null() && m["user"].as_str() != Some(&self.user_id))
+            .collect();
+        messages.sort_by(|a, b| compare_ids(a["ts"].as_str().unwrap_or("0"), b["ts"].as_str().unwrap_or("0")));
+        messages
+    }
+}
+
+#[async_trait]
+impl Platform for Slack {
+    async fn connect(&mut self) -> Result<()> {
+        let response = self.call("auth.test", &[]).await?;
+        self.user_id = response["user_id"].as_str().unwrap_or_default().to_string();
+        Ok(())
+    }
+
+    async fn new_requests(&self, cursor: Option<&str>) -> Result<(Vec<Incoming>, Option<String>)> {
+        let Some(cursor) = cursor else {
+            let response = self.call("conversations.history", &[("channel", &self.channel), ("limit", "1")]).await?;
+            let latest = response["messages"][0]["ts"].as_str().unwrap_or("0").to_string();
+            return Ok((vec![], Some(latest)));
+        };
+
+        let response = self
+            .call("conversations.history", &[("channel", &self.channel), ("oldest", cursor), ("limit", "100")])
+            .await?;
+        let newest = response["messages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["ts"].as_str())
+            .max_by(|a, b| compare_ids(a, b))
+            .map(String::from);
+
+        let mention = format!("<@{}>", self.user_id);
+        let requests = self
+            .human_messages(&response)
+            .into_iter()
+            .filter(|m| m["text"].as_str().is_some_and(|t| t.contains(&mention)))
+            .filter_map(|m| {
+                let ts = m["ts"].as_str()?.to_string();
+                let text = m["text"].as_str()?.replace(&mention, "").trim().to_string();
+                Some(Incoming { thread: ts.clone(), id: ts, text })
+            })
+            .collect();
+        Ok((requests, newest))
+    }
+
+    async fn thread_replies(&self, thread: &str, cursor: &str) -> Result<Vec<Incoming>> {
+        let response = self
+            .call("conversations.replies", &[("channel", &self.channel), ("ts", thread), ("oldest", cursor)])
+            .await?;
+        let mention = format!("<@{}>", self.user_id);
+        Ok(self
+            .human_messages(&response)
+            .into_iter()
+            .filter_map(|m| {
+                let ts = m["ts"].as_str()?.to_string();
+                is_after(&ts, cursor).then(|| Incoming {
+                    thread: thread.to_string(),
+                    id: ts,
+                    text: m["text"].as_str().unwrap_or_default().replace(&mention, "").trim().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn post(&self, thread: &str, text: &str) -> Result<()> {
+        for part in split_message(text, 3900) {
+            let response: Value = self
+                .client
+                .post(format!("{}/chat.postMessage", self.base.trim_end_matches('/')))
+                .bearer_auth(&self.token)
+                .json(&json!({ "channel": self.channel, "thread_ts": thread, "text": part }))
+                .send()
+                .await
+                .context("Failed to reach Slack (chat.postMessage)")?
+                .json()
+                .await?;
+            if response["ok"].as_bool() != Some(true) {
+                anyhow::bail!("Slack chat.postMessage failed: {}", response["error"].as_str().unwrap_or("unknown error"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Discord REST API; each request gets its own thread channel
+struct Discord {
+    base: String,
+    token: String,
+    channel: String,
+    user_id: String,
+    client: reqwest::Client,
+}
+
+impl Discord {
+    fn new(config: &BotConfig, token: String) -> Self {
+        Self {
+            base: config.api_url.clone().unwrap_or_else(|| "https://discord.com/api/v10".to_string()),
+            token,
+            channel: config.channel.clone(),
+            user_id: String::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value> {
+        let response = request
+            .header("Authorization", format!("Bot {}", self.token))
+            .send()
+            .await
+            .context("Failed to reach Discord")?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            anyhow::bail!("Discord error {}: {}", status, body["message"].as_str().unwrap_or_default());
+        }
+        Ok(body)
+    }
+
+    /// People's messages in a channel after `cursor`, oldest first
+    async fn messages(&self, channel: &str, cursor: &str) -> Result<Vec<Value>> {
+        let url = format!("{}/channels/{}/messages", self.base.trim_end_matches('/'), channel);
+        let body = self
+            .send(self.client.get(url).query(&[("after", cursor), ("limit", "50")]))
+            .await?;
+        let mut messages: Vec<Value> = body.as_array().cloned().unwrap_or_default();
+        messages.sort_by(|a, b| compare_ids(a["id"].as_str().unwrap_or("0"), b["id"].as_str().unwrap_or("0")));
+        Ok(messages)
+    }
+
+    fn is_human(&self, message: &Value) -> bool {
+        message["author"]["bot"].as_bool() != Some(true) && message["author"]["id"].as_str() != Some(&self.user_id)
+    }
+
+    fn strip_mention(&self, text: &str) -> String {
+        text.replace(&format!("<@{}>", self.user_id), "")
+            .replace(&format!("<@!{}>", self.user_id), "")
+            .trim()
+            .to_string()
+    }
+}
+
+#[async_trait]
+impl Platform for Discord {
+    async fn connect(&mut self) -> Result<()> {
+        let url = format!("{}/users/@me", self.base.trim_end_matches('/'));
+        let me = self.send(self.client.get(url)).await?;
+        self.user_id = me["id"].as_str().unwrap_or_default().to_string();
+        Ok(())
+    }
+
+    async fn new_requests(&self, cursor: Option<&str>) -> Result<(Vec<Incoming>, Option<String>)> {
+        let Some(cursor) = cursor else {
+            let url = format!("{}/channels/{}/messages", self.base.trim_end_matches('/'), self.channel);
+            let body = self.send(self.client.get(url).query(&[("limit", "1")])).await?;
+            let latest = body[0]["id"].as_str().unwrap_or("0").to_string();
+            return Ok((vec![], Some(latest)));
+        };
+
+        let messages = self.messages(&self.channel, cursor).await?;
+        let newest = messages.last().and_then(|m| m["id"].as_str()).map(String::from);
+
+        let mut requests = vec![];
+        for message in messages.iter().filter(|m| self.is_human(m)) {
+            let mentioned = message["mentions"]
+                .as_array()
+                .is_some_and(|mentions| mentions.iter().any(|u| u["id"].as_str() == Some(&self.user_id)));
+            let (Some(id), Some(text)) = (message["id"].as_str(), message["content"].as_str()) else {
+                continue;
+            };
+            if !mentioned {
+                continue;
+            }
+            let text = self.strip_mention(text);
+            let name: String = text.chars().take(90).collect();
+            let url = format!("{}/channels/{}/messages/{}/threads", self.base.trim_end_matches('/'), self.channel, id);
+            let thread = self
+                .send(self.client.post(url).json(&json!({ "name": if name.is_empty() { "vork".to_string() } else { name } })))
+                .await?;
+            let Some(thread) = thread["id"].as_str() else {
+                continue;
+            };
+            requests.push(Incoming { thread: thread.to_string(), id: id.to_string(), text });
+        }
+        Ok((requests, newest))
+    }
+
+    async fn thread_replies(&self, thread: &str, cursor: &str) -> Result<Vec<Incoming>> {
+        Ok(self
+            .messages(thread, cursor)
+            .await?
+            .iter()
+            .filter(|m| self.is_human(m))
+            .filter_map(|m| {
+                Some(Incoming {
+                    thread: thread.to_string(),
+                    id: m["id"].as_str()?.to_string(),
+                    text: self.strip_mention(m["content"].as_str()?),
+                })
+            })
+            .collect())
+    }
+
+    async fn post(&self, thread: &str, text: &str) -> Result<()> {
+        let url = format!("{}/channels/{}/messages", self.base.trim_end_matches('/'), thread);
+        for part in split_message(text, 1900) {
+            self.send(self.client.post(&url).json(&json!({ "content": part }))).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::llm::LlamaClient;
+use crate::llm::client::Message;
+
+/// Keep a Changelog categories, in the order they appear in a release section
+const CATEGORIES: &[&str] = &["Added", "Changed", "Deprecated", "Removed", "Fixed", "Security"];
+
+const HEADER: &str = "# Changelog\n\n\
+All notable changes to this project will be documented in this file.\n\n\
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.0.0/),\n\
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).\n";
+
+pub async fn execute(
+    since: Option<String>,
+    file: String,
+    yes: bool,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent_name: Option<String>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    let since = match since {
+        Some(since) => Some(since),
+        None => last_tag().await,
+    };
+    let range = since.as_ref().map(|tag| format!("{}..HEAD", tag));
+
+    let mut log_args = vec!["log", "--no-merges", "--format=- %h %s%n%b"];
+    if let Some(ref range) = range {
+        log_args.push(range);
+    }
+    let commits = git(&log_args).await?;
+    if commits.trim().is_empty() {
+        println!("No commits since {}", since.as_deref().unwrap_or("the beginning of history"));
+        return Ok(());
+    }
+
+    let system_prompt = Agent::load_or_create_defaults(agent_name.as_deref().unwrap_or("release-manager"))
+        .map(|a| a.system_prompt)
+        .unwrap_or_else(|_| "You are a release engineering specialist.".to_string());
+
+    let prompt = format!(
+        "Group these git commits into Keep a Changelog entries.\n\n\
+        Use only these headings, each as `### <Category>`: {}.\n\
+        Under each heading write one `- ` bullet per user-visible change, in plain language.\n\
+        Merge related commits, and skip pure refactors, formatting, CI and merge noise.\n\
+        Output only the headings and bullets, nothing else.\n\nCommits:\n{}",
+        CATEGORIES.join(", "),
+        commits.trim()
+    );
+
+    eprintln!(
+        "{} {}",
+        "📜 Summarizing commits since".cyan().bold(),
+        since.as_deref().unwrap_or("the first commit").yellow()
+    );
+
+    let client = LlamaClient::for_config(&config, server_url, model);
+    let response = client
+        .chat_completion(
+            &[
+                Message {
+                    role: "system".to_string(),
+                    content: system_prompt,
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt,
+                },
+            ],
+            None,
+        )
+        .await
+        .context("Failed to get response from LLM")?;
+
+    let content = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+    let entries = parse_entries(&content);
+    if entries.iter().all(|(_, bullets)| bullets.is_empty()) {
+        anyhow::bail!("The model did not return any changelog entries");
+    }
+
+    // Preview
+    println!();
+    println!("{}", "## [Unreleased]".bold());
+    for (category, bullets) in &entries {
+        if bullets.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", format!("### {}", category).cyan());
+        for bullet in bullets {
+            println!("{}", bullet.green());
+        }
+    }
+    println!();
+
+    if !yes {
+        print!("{} ", format!("Update {}? [y/N]", file).cyan());
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Aborted, {} unchanged", file);
+            return Ok(());
+        }
+    }
+
+    let existing = if Path::new(&file).exists() {
+        fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file))?
+    } else {
+        HEADER.to_string()
+    };
+
+    fs::write(&file, merge_unreleased(&existing, &entries))
+        .with_context(|| format!("Failed to write {}", file))?;
+    println!("{} Updated {}", "✓".green(), file);
+
+    Ok(())
+}
+
+async fn last_tag() -> Option<String> {
+    git(&["describe", "--tags", "--abbrev=0"]).await.ok().filter(|t| !t.is_empty())
+}
+
+/// Parse `### Category` headings and their `- ` bullets from the model output
+fn parse_entries(content: &str) -> Vec<(&'static str, Vec<String>)> {
+    let mut entries: Vec<(&'static str, Vec<String>)> =
+        CATEGORIES.iter().map(|c| (*c, Vec::new())).collect();
+    let mut current: Option<usize> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            let heading = heading.trim_start_matches('#').trim();
+            current = CATEGORIES.iter().position(|c| c.eq_ignore_ascii_case(heading));
+        } else if let Some(bullet) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            if let Some(index) = current {
+                entries[index].1.push(format!("- {}", bullet.trim()));
+            }
+        }
+    }
+
+    entries
+}
+
+/// Insert entries into the `## [Unreleased]` section, creating it (and any
+/// missing category headings) when needed. Existing bullets are kept.
+fn merge_unreleased(existing: &str, entries: &[(&'static str, Vec<String>)]) -> String {
+    let mut lines: Vec<String> = existing.lines().map(String::from).collect();
+
+    let is_release = |l: &str| l.starts_with("## ");
+    let unreleased = lines
+        .iter()
+        .position(|l| l.trim().eq_ignore_ascii_case("## [unreleased]"));
+
+    let start = match unreleased {
+        Some(i) => i,
+        None => {
+            // Put a fresh section before the first release (or at the end)
+            let at = lines.iter().position(|l| is_release(l)).unwrap_or(lines.len());
+            lines.insert(at, "## [Unreleased]".to_string());
+            lines.insert(at + 1, String::new());
+            at
+        }
+    };
+
+    for (category, bullets) in entries {
+        if bullets.is_empty() {
+            continue;
+        }
+        let end = lines
+            .iter()
+            .skip(start + 1)
+            .position(|l| is_release(l))
+            .map(|p| p + start + 1)
+            .unwrap_or(lines.len());
+        let heading = format!("### {}", category);
+
+        match lines[start..end].iter().position(|l| l.trim() == heading) {
+            Some(offset) => {
+                // Append after the last bullet of the existing category
+                let mut at = start + offset + 1;
+                while at < end && !lines[at].trim().is_empty() && !lines[at].starts_with('#') {
+                    at += 1;
+                }
+                for (i, bullet) in bullets.iter().enumerate() {
+                    lines.insert(at + i, bullet.clone());
+                }
+            }
+            None => {
+                // New category block at the end of the section
+                let mut at = end;
+                while at > start + 1 && lines[at - 1].trim().is_empty() {
+                    at -= 1;
+                }
+                let mut block = vec![String::new(), heading];
+                block.extend(bullets.iter().cloned());
+                if at < lines.len() && !lines[at].trim().is_empty() {
+                    block.push(String::new());
+                }
+                for (i, line) in block.into_iter().enumerate() {
+                    lines.insert(at + i, line);
+                }
+            }
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+async fn git(args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .output()
+        .await
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
@@ -3,14 +3,21 @@ use colored::Colorize;
 use std::io::{self, Write};
 use std::env;
 
+use crate::backends::openai_compat::model_for_server;
 use crate::config::Config;
-use crate::llm::{LlamaClient, Session, ApprovalSystem};
+use crate::llm::{LlamaClient, Session, ApprovalSystem, UsageTracker};
+use crate::llm::arguments;
 use crate::llm::tools::{get_available_tools, execute_tool};
 
 pub async fn execute(server_url: Option<String>, model: Option<String>, initial_prompt: Option<String>) -> Result<()> {
     let config = Config::load()?;
-    let server_url = server_url.unwrap_or_else(|| config.assistant.server_url.clone());
-    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+    // An explicit server may be LM Studio, Jan, etc.: use a model it serves
+    let model = match (model, &server_url) {
+        (Some(model), _) => model,
+        (None, Some(url)) => model_for_server(url, &config.assistant.model).await,
+        (None, None) => config.assistant.model.clone(),
+    };
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
 
     println!("{}", "=== Vork Chat - AI Coding Assistant ===".green().bold());
     println!("{} {}", "Server:".cyan(), server_url);
@@ -21,9 +28,13 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, initial_
     println!("{}", "Type 'clear' to start a new conversation".yellow());
     println!();
 
-    let client = LlamaClient::new(server_url, model);
+    let client = LlamaClient::for_config(&config, server_url, model.clone());
     let working_dir = env::current_dir()?;
-    let mut session = Session::new(working_dir);
+    let mut session = Session::open(working_dir)?;
+    if session.conversation.messages.len() > 1 {
+        println!("{} {}", "Continuing session:".cyan(), session.label());
+    }
+    let mut usage = UsageTracker::new("chat", &session.id, &model);
     let approval_system = ApprovalSystem::new(
         config.assistant.approval_policy.clone(),
         config.assistant.sandbox_mode.clone(),
@@ -36,13 +47,19 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, initial_
 
         // Process initial prompt
         loop {
+            usage.check_budget()?;
+            let tools = get_available_tools();
+            if let Some(notice) = session.conversation.fit_to_context(&client, Some(&tools)).await? {
+                println!("{}", notice.yellow());
+            }
             let response = client
                 .chat_completion(
                     session.conversation.get_messages(),
-                    Some(get_available_tools()),
+                    Some(&tools),
                 )
                 .await
                 .context("Failed to get response from LLM")?;
+            usage.record_response(response.usage.as_ref());
 
             let choice = response
                 .choices
@@ -51,13 +68,19 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, initial_
 
             if let Some(tool_calls) = &choice.message.tool_calls {
                 for tool_call in tool_calls {
+                    usage.record_tool_call();
                     let tool_name = &tool_call.function.name;
-                    let arguments: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)
-                        .context("Failed to parse tool arguments")?;
+                    let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                        Ok(arguments) => arguments,
+                        Err(e) => {
+                            session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                            continue;
+                        }
+                    };
 
                     println!("{} {} {}", "🔧".yellow(), "Executing:".yellow(), tool_name.yellow().bold());
 
-                    match execute_tool(tool_name, arguments, Some(&approval_system)).await {
+                    match execute_tool(tool_name, arguments, Some(&approval_system), &session.tool_dir()).await {
                         Ok(result) => {
                             session.conversation.add_tool_result(tool_name, &result);
                         }
@@ -104,6 +127,7 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, initial_
             "clear" => {
                 let working_dir = env::current_dir()?;
                 session = Session::new(working_dir);
+                usage = UsageTracker::new("chat", &session.id, &model);
                 println!("{}", "Conversation cleared".yellow());
                 continue;
             }
@@ -114,13 +138,19 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, initial_
 
         // Main loop: keep calling LLM until it stops requesting tool calls
         loop {
+            usage.check_budget()?;
+            let tools = get_available_tools();
+            if let Some(notice) = session.conversation.fit_to_context(&client, Some(&tools)).await? {
+                println!("{}", notice.yellow());
+            }
             let response = client
                 .chat_completion(
                     session.conversation.get_messages(),
-                    Some(get_available_tools()),
+                    Some(&tools),
                 )
                 .await
                 .context("Failed to get response from LLM")?;
+            usage.record_response(response.usage.as_ref());
 
             let choice = response
                 .choices
@@ -131,13 +161,19 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, initial_
             if let Some(tool_calls) = &choice.message.tool_calls {
                 // Execute each tool call
                 for tool_call in tool_calls {
+                    usage.record_tool_call();
                     let tool_name = &tool_call.function.name;
-                    let arguments: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)
-                        .context("Failed to parse tool arguments")?;
+                    let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                        Ok(arguments) => arguments,
+                        Err(e) => {
+                            session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                            continue;
+                        }
+                    };
 
                     println!("{} {} {}", "🔧".yellow(), "Executing:".yellow(), tool_name.yellow().bold());
 
-                    match execute_tool(tool_name, arguments, Some(&approval_system)).await {
+                    match execute_tool(tool_name, arguments, Some(&approval_system), &session.tool_dir()).await {
                         Ok(result) => {
                             session.conversation.add_tool_result(tool_name, &result);
                         }
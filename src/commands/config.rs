@@ -15,7 +15,50 @@ pub fn execute(show_path: bool) -> Result<()> {
         println!("{}", config_str);
         println!();
         println!("{} {}", "Config file:".cyan(), Config::config_path()?.display());
+        if let Some(profile) = Config::active_profile() {
+            println!("{} {}", "Active profile:".cyan(), profile);
+        }
     }
 
     Ok(())
 }
+
+/// Print the value at a dotted key such as `llamacpp.context_size`
+pub fn get(key: &str) -> Result<()> {
+    let value = Config::load()?.get_key(key)?;
+
+    match value {
+        // Plain strings print unquoted so scripts can use them directly
+        toml::Value::String(s) => println!("{}", s),
+        toml::Value::Table(_) => print!("{}", toml::to_string_pretty(&value)?),
+        other => println!("{}", other),
+    }
+
+    Ok(())
+}
+
+/// Set a dotted key in the config file, validating the value against the schema
+pub fn set(key: &str, value: &str) -> Result<()> {
+    // Edit the file as written, so environment overrides are not persisted
+    let config = Config::load_file()?.set_key(key, value)?;
+    config.save()?;
+    println!("{} {} = {}", "✓".green(), key.cyan(), config.get_key(key)?);
+    Ok(())
+}
+
+/// Report every config problem; fails if there are any
+pub fn check() -> Result<()> {
+    let problems = Config::load_resolved()?.validate();
+
+    if problems.is_empty() {
+        println!("{} Config OK ({})", "✓".green(), Config::config_path()?.display());
+        return Ok(());
+    }
+
+    println!("{}", format!("Found {} problem(s):", problems.len()).yellow().bold());
+    for problem in &problems {
+        println!("  {} {}: {}", "✗".red(), problem.key.cyan(), problem.message);
+        println!("      {} {}", "→".green(), problem.fix);
+    }
+    anyhow::bail!("Config check failed");
+}
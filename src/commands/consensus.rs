@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::env;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+use super::exec::{self, Candidate, ExecOptions, ExecReport};
+use crate::agents::Agent;
+use crate::config::{ApprovalPolicy, Config, SandboxMode};
+use crate::llm::{ApprovalSystem, LlamaClient, Session, UsageTracker};
+
+/// Candidates only investigate; the judge is the one that may change files
+const CANDIDATE_BRIEF: &str = "You are one of several agents working on this task independently. \
+You cannot change files: investigate with the read-only tools, then answer with your proposed \
+solution as concrete code or a unified diff and a short explanation of why it works.";
+
+/// Send the task to several agents in parallel, show what each proposed and
+/// let a judge (the global --agent, default "default") pick or merge the best.
+/// Each entry is an agent name, optionally with a model: `debugger@qwen2.5-coder:32b`.
+pub async fn execute(
+    prompt: &str,
+    agents: &[String],
+    server_url: Option<String>,
+    model: Option<String>,
+    options: ExecOptions,
+) -> Result<i32> {
+    if options.resume.is_some() {
+        anyhow::bail!("--resume cannot be combined with --agents");
+    }
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+    let json_output = options.json_output;
+    // Only the judge plans; candidates cannot change anything anyway
+    let options = ExecOptions {
+        require_plan_approval: options.require_plan_approval || config.assistant.require_plan_approval,
+        ..options
+    };
+
+    // The judge's session gets the usual id; candidates are numbered after it
+    let mut session = Session::new(env::current_dir()?);
+    let base_id = session.id.clone();
+    let mut n = 1;
+    while session.session_path()?.exists() {
+        n += 1;
+        session.id = format!("{}-{}", base_id, n);
+    }
+
+    let mut runs = JoinSet::new();
+    for (index, spec) in agents.iter().enumerate() {
+        let (name, candidate_model) = match spec.split_once('@') {
+            Some((name, model)) => (name.trim().to_string(), model.trim().to_string()),
+            None => (spec.trim().to_string(), model.clone()),
+        };
+        let agent = Agent::load_or_create_defaults(&name)?;
+        let client = LlamaClient::for_config(&config, server_url.clone(), candidate_model.clone())
+            .with_sampling(options.temperature, options.seed);
+        let candidate_options = ExecOptions {
+            // Parallel tool logs would interleave; progress is reported per candidate
+            json_output: true,
+            timeout: options.timeout,
+            max_tokens: options.max_tokens,
+            max_tool_calls: options.max_tool_calls,
+            temperature: options.temperature,
+            seed: options.seed,
+            ..Default::default()
+        };
+        let task = format!("{}\n\n{}", prompt, CANDIDATE_BRIEF);
+        let session_id = format!("{}-c{}", session.id, index + 1);
+
+        runs.spawn(async move {
+            let mut session = Session::new(env::current_dir()?);
+            session.id = session_id;
+            session.conversation.set_system_prompt(agent.system_prompt.clone());
+            let mut usage = UsageTracker::new("exec", &session.id, &candidate_model);
+            usage.set_agent(Some(&agent.name));
+            let approval = Arc::new(ApprovalSystem::new(ApprovalPolicy::Auto, SandboxMode::ReadOnly).with_agent(Some(&agent)));
+            let report = exec::run(&task, &mut session, &client, &approval, &mut usage, &candidate_options).await?;
+            session.save()?;
+            anyhow::Ok((index, Candidate { agent: name, model: candidate_model, report }))
+        });
+    }
+
+    if !json_output {
+        eprintln!("{} Asking {} agents: {}", "🗳️".cyan(), agents.len(), agents.join(", "));
+    }
+    let mut candidates = vec![];
+    while let Some(joined) = runs.join_next().await {
+        let (index, candidate) = joined.context("Candidate run panicked")??;
+        if !json_output {
+            eprintln!("  {} {} finished ({:?})", "✓".green(), candidate.agent, candidate.report.status);
+        }
+        candidates.push((index, candidate));
+    }
+    candidates.sort_by_key(|(index, _)| *index);
+    let candidates: Vec<Candidate> = candidates.into_iter().map(|(_, candidate)| candidate).collect();
+
+    if !json_output {
+        for (number, candidate) in candidates.iter().enumerate() {
+            println!();
+            println!(
+                "{}",
+                format!("=== Candidate {}: {} ({}) ===", number + 1, candidate.agent, candidate.model).cyan().bold()
+            );
+            println!("{}", proposal(&candidate.report));
+        }
+        println!();
+        println!("{}", "=== Verdict ===".green().bold());
+    }
+
+    let judge = Agent::load_or_create_defaults(options.agent.as_deref().unwrap_or("default"))?;
+    let client = LlamaClient::for_config(&config, server_url, model.clone()).with_sampling(options.temperature, options.seed);
+    session.conversation.set_system_prompt(judge.system_prompt.clone());
+    let mut usage = UsageTracker::new("exec", &session.id, &model);
+    usage.set_agent(Some(&judge.name));
+    let approval_system = Arc::new(exec::approval_system(&config, &options, Some(&judge))?);
+
+    let mut report = exec::run(
+        &judge_prompt(prompt, &candidates, options.full_auto || options.approve_remotely),
+        &mut session,
+        &client,
+        &approval_system,
+        &mut usage,
+        &options,
+    )
+    .await?;
+    session.save()?;
+
+    // The run's token usage covers every agent, not just the judge
+    for candidate in &candidates {
+        report.token_usage.prompt_tokens += candidate.report.token_usage.prompt_tokens;
+        report.token_usage.completion_tokens += candidate.report.token_usage.completion_tokens;
+        report.token_usage.total_tokens += candidate.report.token_usage.total_tokens;
+    }
+    report.candidates = candidates;
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        if let Some(message) = &report.message {
+            println!("{}", message);
+        }
+        if let Some(error) = &report.error {
+            eprintln!("{} {}", "✗".red(), error);
+        }
+        eprintln!("{} Session saved as {}", "✓".green(), session.id);
+    }
+
+    Ok(report.status.exit_code())
+}
+
+/// What a candidate proposed, or why it has nothing to show
+fn proposal(report: &ExecReport) -> String {
+    match (&report.message, &report.error) {
+        (Some(message), _) if !message.trim().is_empty() => message.trim().to_string(),
+        (_, Some(error)) => format!("(no proposal: {})", error),
+        _ => format!("(no proposal: {:?})", report.status),
+    }
+}
+
+fn judge_prompt(task: &str, candidates: &[Candidate], can_apply: bool) -> String {
+    let mut prompt = format!(
+        "Task:\n{}\n\n{} agents proposed solutions independently. Compare them, check the \
+         relevant code yourself, and pick the best proposal or merge the strengths of several. \
+         Say which candidates you used and why.",
+        task,
+        candidates.len()
+    );
+    if can_apply {
+        prompt.push_str(" Then apply the final solution to the workspace.");
+    } else {
+        prompt.push_str(" Then give the final solution as concrete code or a unified diff.");
+    }
+    for (number, candidate) in candidates.iter().enumerate() {
+        prompt.push_str(&format!(
+            "\n\n## Candidate {} ({})\n{}",
+            number + 1,
+            candidate.agent,
+            proposal(&candidate.report)
+        ));
+    }
+    prompt
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::io;
+
+use crate::config::Config;
+use crate::voice::{self, Recording};
+
+/// Record until Enter, then print the transcript to stdout so it can be piped,
+/// e.g. `vork exec "$(vork dictate)"`
+pub async fn execute(copy: bool) -> Result<()> {
+    let config = Config::load()?;
+
+    let recording = Recording::start(&config.voice)?;
+    eprintln!("{}", "🎙️  Recording... press Enter to stop".cyan().bold());
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let audio = recording.stop()?;
+
+    eprintln!("{}", "📝 Transcribing...".cyan());
+    let transcript = voice::transcribe(&config.voice, &audio).await;
+    let _ = std::fs::remove_file(&audio);
+    let transcript = transcript?;
+
+    if transcript.is_empty() {
+        anyhow::bail!("No speech recognized");
+    }
+
+    println!("{}", transcript);
+
+    if copy {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(transcript))
+            .map_err(|e| anyhow::anyhow!("Failed to copy to clipboard: {}", e))?;
+        eprintln!("{} Copied to clipboard", "✓".green());
+    }
+
+    Ok(())
+}
@@ -1,63 +1,342 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
+use serde::Serialize;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::time::{timeout_at, Instant};
 
+use crate::agents::Agent;
 use crate::config::{Config, ApprovalPolicy, SandboxMode};
-use crate::llm::{LlamaClient, Session, ApprovalSystem};
+use crate::error::VorkError;
+use crate::llm::{LlamaClient, Session, ApprovalSystem, UsageTracker};
+use crate::llm::client::Usage;
+use crate::llm::{arguments, planner, processes, remote_approval};
 use crate::llm::tools::{get_available_tools, execute_tool};
 
+/// Maximum number of LLM round-trips before exec gives up
+const MAX_ITERATIONS: usize = 50;
+
+/// Outcome of an exec run, mapped to the process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecStatus {
+    Success,
+    Denied,
+    IterationLimit,
+    ServerError,
+    Timeout,
+    BudgetExceeded,
+    ServerUnavailable,
+    ModelNotFound,
+    ContextOverflow,
+}
+
+impl ExecStatus {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ExecStatus::Success => 0,
+            ExecStatus::Denied => 2,
+            ExecStatus::IterationLimit => 3,
+            ExecStatus::ServerError => 4,
+            ExecStatus::Timeout => 5,
+            ExecStatus::BudgetExceeded => 6,
+            ExecStatus::ServerUnavailable => 7,
+            ExecStatus::ModelNotFound => 8,
+            ExecStatus::ContextOverflow => 9,
+        }
+    }
+
+    /// Status for a run that stopped on `error`
+    fn for_error(error: &anyhow::Error) -> Self {
+        match VorkError::find(error) {
+            Some(VorkError::ServerUnavailable { .. }) => ExecStatus::ServerUnavailable,
+            Some(VorkError::ModelNotFound { .. }) => ExecStatus::ModelNotFound,
+            Some(VorkError::ContextOverflow { .. } | VorkError::OutOfMemory { .. }) => ExecStatus::ContextOverflow,
+            Some(VorkError::ApprovalDenied { .. }) => ExecStatus::Denied,
+            Some(VorkError::ToolFailed { .. }) | None => ExecStatus::ServerError,
+        }
+    }
+}
+
+/// Stable JSON schema printed by `vork exec --json`
+#[derive(Debug, Serialize)]
+pub struct ExecReport {
+    pub status: ExecStatus,
+    pub session_id: String,
+    pub message: Option<String>,
+    pub files_changed: Vec<String>,
+    pub commands_run: Vec<String>,
+    pub token_usage: Usage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Sampling seed the run used, to reproduce it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// What each agent proposed in a `--agents` consensus run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<Candidate>,
+}
+
+/// One agent's proposal in a consensus run
+#[derive(Debug, Serialize)]
+pub struct Candidate {
+    pub agent: String,
+    pub model: String,
+    #[serde(flatten)]
+    pub report: ExecReport,
+}
+
+/// Flags controlling a single exec run
+#[derive(Debug, Default)]
+pub struct ExecOptions {
+    pub full_auto: bool,
+    pub json_output: bool,
+    pub resume: Option<String>,
+    /// Agent whose system prompt drives the run
+    pub agent: Option<String>,
+    /// Wall-clock limit for the whole run
+    pub timeout: Option<Duration>,
+    /// Total token budget (prompt + completion) across all requests
+    pub max_tokens: Option<usize>,
+    /// Maximum number of tool calls to execute
+    pub max_tool_calls: Option<usize>,
+    /// Ask a person through the remote approval page instead of refusing
+    pub approve_remotely: bool,
+    /// Show a plan and wait for approval before any tool runs
+    pub require_plan_approval: bool,
+    /// Sampling overrides for reproducible runs
+    pub temperature: Option<f32>,
+    pub seed: Option<u64>,
+}
+
+/// Run a non-interactive task and return the process exit code
 pub async fn execute(
     prompt: &str,
     server_url: Option<String>,
     model: Option<String>,
-    full_auto: bool,
-    json_output: bool,
-) -> Result<()> {
-    let mut config = Config::load()?;
-    let server_url = server_url.unwrap_or_else(|| config.assistant.server_url.clone());
+    options: ExecOptions,
+) -> Result<i32> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
     let model = model.unwrap_or_else(|| config.assistant.model.clone());
+    let json_output = options.json_output;
+    let options = ExecOptions {
+        require_plan_approval: options.require_plan_approval || config.assistant.require_plan_approval,
+        ..options
+    };
+
+    let client = LlamaClient::for_config(&config, server_url, model.clone()).with_sampling(options.temperature, options.seed);
+    let mut session = match options.resume.clone() {
+        Some(id) => {
+            let mut session = Session::load(&id).with_context(|| format!("Failed to load session: {}", id))?;
+            session.resume_transcript();
+            session
+        }
+        None => Session::open(env::current_dir()?)?,
+    };
+    if let Some(notice) = session.working_dir_notice().filter(|_| !json_output) {
+        eprintln!("{}", notice.yellow());
+    }
+    let agent = options.agent.as_deref().map(Agent::load_or_create_defaults).transpose()?;
+    if let Some(ref agent) = agent {
+        session.conversation.set_system_prompt(agent.system_prompt.clone());
+    }
+    let mut usage = UsageTracker::new("exec", &session.id, &model);
+    usage.set_agent(options.agent.as_deref());
+    let approval_system = Arc::new(approval_system(&config, &options, agent.as_ref())?);
 
-    // In exec mode, default to read-only unless --full-auto is specified
-    if full_auto {
-        config.assistant.sandbox_mode = SandboxMode::DangerFullAccess;
-        config.assistant.approval_policy = ApprovalPolicy::Never;
+    let report = run(prompt, &mut session, &client, &approval_system, &mut usage, &options).await?;
+
+    // Save session for potential resume
+    session.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        if let Some(message) = &report.message {
+            println!("{}", message);
+        }
+        if let Some(error) = &report.error {
+            eprintln!("{} {}", "✗".red(), error);
+        }
+        eprintln!("{} Session saved as {}", "✓".green(), session.id);
+    }
+
+    Ok(report.status.exit_code())
+}
+
+/// Exec runs are read-only unless --full-auto is specified; with a remote
+/// approver they work like the TUI's auto mode
+pub fn approval_system(config: &Config, options: &ExecOptions, agent: Option<&Agent>) -> Result<ApprovalSystem> {
+    let (policy, sandbox_mode) = if options.full_auto {
+        (ApprovalPolicy::Never, SandboxMode::DangerFullAccess)
+    } else if options.approve_remotely {
+        (ApprovalPolicy::Auto, SandboxMode::WorkspaceWrite)
     } else {
-        config.assistant.sandbox_mode = SandboxMode::ReadOnly;
+        (config.assistant.approval_policy.clone(), SandboxMode::ReadOnly)
+    };
+
+    let approval_system = ApprovalSystem::new(policy, sandbox_mode).with_agent(agent).with_quiet_stdout();
+    if options.approve_remotely {
+        return Ok(approval_system.with_prompter(remote_approval::prompter(&config.remote_approval)?));
     }
+    Ok(approval_system)
+}
 
-    let client = LlamaClient::new(server_url, model);
-    let working_dir = env::current_dir()?;
-    let mut session = Session::new(working_dir);
-    let approval_system = ApprovalSystem::new(
-        config.assistant.approval_policy.clone(),
-        config.assistant.sandbox_mode.clone(),
-    );
+/// The exec tool loop: send `prompt` and run tool calls until the model
+/// answers without any, a limit is hit or the server fails
+pub async fn run(
+    prompt: &str,
+    session: &mut Session,
+    client: &LlamaClient,
+    approval_system: &Arc<ApprovalSystem>,
+    usage: &mut UsageTracker,
+    options: &ExecOptions,
+) -> Result<ExecReport> {
+    let json_output = options.json_output;
+    let deadline = options.timeout.map(|t| Instant::now() + t);
 
     session.conversation.add_user_message(prompt.to_string());
 
+    let mut report = ExecReport {
+        status: ExecStatus::IterationLimit,
+        session_id: session.id.clone(),
+        message: None,
+        files_changed: vec![],
+        commands_run: vec![],
+        token_usage: Usage::default(),
+        error: None,
+        seed: client.seed(),
+        candidates: vec![],
+    };
+
+    let mut tool_calls_made = 0usize;
+
+    if options.require_plan_approval {
+        if !json_output {
+            eprintln!("{}", "📋 Planning...".cyan());
+        }
+        let (plan, response) = match planner::plan(client, session.conversation.get_messages()).await {
+            Ok(planned) => planned,
+            Err(e) => {
+                report.status = ExecStatus::for_error(&e);
+                report.error = Some(format!("{:#}", e));
+                return Ok(report);
+            }
+        };
+        if let Some(used) = &response.usage {
+            report.token_usage.prompt_tokens += used.prompt_tokens;
+            report.token_usage.completion_tokens += used.completion_tokens;
+            report.token_usage.total_tokens += used.total_tokens;
+        }
+        usage.record_response(response.usage.as_ref());
+        session.conversation.add_assistant_message(plan.clone());
+
+        if !approval_system.approve_plan(&plan)? {
+            report.status = ExecStatus::Denied;
+            report.message = Some(plan);
+            report.error = Some("The plan was not approved".to_string());
+            return Ok(report);
+        }
+        session.conversation.add_user_message(planner::PLAN_APPROVED.to_string());
+    }
+
     // Main loop: keep calling LLM until it stops requesting tool calls
-    loop {
-        let response = client
-            .chat_completion(
-                session.conversation.get_messages(),
-                Some(get_available_tools()),
-            )
-            .await
-            .context("Failed to get response from LLM")?;
-
-        let choice = response
-            .choices
-            .first()
-            .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+    'outer: for _ in 0..MAX_ITERATIONS {
+        if let Err(e) = usage.check_budget() {
+            report.status = ExecStatus::BudgetExceeded;
+            report.error = Some(e.to_string());
+            break;
+        }
+
+        let tools = get_available_tools();
+        match session.conversation.fit_to_context(client, Some(&tools)).await {
+            Ok(Some(notice)) if !json_output => eprintln!("{}", notice.yellow()),
+            Ok(_) => {}
+            Err(e) => {
+                report.status = ExecStatus::for_error(&e);
+                report.error = Some(format!("Failed to fit the conversation into the context window: {:#}", e));
+                break;
+            }
+        }
+        let request = client.chat_completion(
+            session.conversation.get_messages(),
+            Some(&tools),
+        );
+        let result = match deadline {
+            Some(deadline) => match timeout_at(deadline, request).await {
+                Ok(result) => result,
+                Err(_) => {
+                    report.status = ExecStatus::Timeout;
+                    break;
+                }
+            },
+            None => request.await,
+        };
+        if let Some(notice) = client.take_failover_notice() {
+            eprintln!("{}", notice.yellow());
+        }
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                report.status = ExecStatus::for_error(&e);
+                report.error = Some(format!("{:#}", e));
+                break;
+            }
+        };
+
+        if let Some(usage) = &response.usage {
+            report.token_usage.prompt_tokens += usage.prompt_tokens;
+            report.token_usage.completion_tokens += usage.completion_tokens;
+            report.token_usage.total_tokens += usage.total_tokens;
+        }
+        usage.record_response(response.usage.as_ref());
+
+        let Some(choice) = response.choices.first() else {
+            report.status = ExecStatus::ServerError;
+            report.error = Some("No response from LLM".to_string());
+            break;
+        };
 
         // Check if there are tool calls
         if let Some(tool_calls) = &choice.message.tool_calls {
+            // Stop here if the token budget is spent; the pending calls are not run
+            if exceeds(report.token_usage.total_tokens, options.max_tokens) {
+                report.status = ExecStatus::BudgetExceeded;
+                report.error = Some(format!(
+                    "Token budget of {} exhausted",
+                    options.max_tokens.unwrap_or_default()
+                ));
+                break;
+            }
+
             // Execute each tool call
             for tool_call in tool_calls {
+                if deadline.is_some_and(|d| Instant::now() >= d) {
+                    report.status = ExecStatus::Timeout;
+                    break 'outer;
+                }
+                if exceeds(tool_calls_made + 1, options.max_tool_calls) {
+                    report.status = ExecStatus::BudgetExceeded;
+                    report.error = Some(format!(
+                        "Tool call budget of {} exhausted",
+                        options.max_tool_calls.unwrap_or_default()
+                    ));
+                    break 'outer;
+                }
+                tool_calls_made += 1;
+                usage.record_tool_call();
+
                 let tool_name = &tool_call.function.name;
-                let arguments: serde_json::Value =
-                    serde_json::from_str(&tool_call.function.arguments)
-                        .context("Failed to parse tool arguments")?;
+                let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                    Ok(arguments) => arguments,
+                    Err(e) => {
+                        session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                        continue;
+                    }
+                };
 
                 if !json_output {
                     eprintln!(
@@ -68,8 +347,31 @@ pub async fn execute(
                     );
                 }
 
-                match execute_tool(tool_name, arguments, Some(&approval_system)).await {
+                let denied_before = approval_system.denied_count();
+                // On the blocking pool, so the deadline can pass while a tool hangs
+                let tool = {
+                    let (name, arguments, approval_system, dir) =
+                        (tool_name.clone(), arguments.clone(), approval_system.clone(), session.tool_dir());
+                    processes::blocking(move || {
+                        Handle::current().block_on(execute_tool(&name, arguments, Some(&approval_system), &dir))
+                    })
+                };
+                let result = match deadline {
+                    Some(deadline) => match timeout_at(deadline, tool).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            session.conversation.add_tool_result(tool_name, "Error: stopped by the exec timeout");
+                            report.status = ExecStatus::Timeout;
+                            break 'outer;
+                        }
+                    },
+                    None => tool.await,
+                };
+                match result {
                     Ok(result) => {
+                        if approval_system.denied_count() == denied_before {
+                            record_side_effect(&mut report, tool_name, &arguments);
+                        }
                         session.conversation.add_tool_result(tool_name, &result);
                     }
                     Err(e) => {
@@ -83,30 +385,62 @@ pub async fn execute(
             continue;
         }
 
-        // If no tool calls, output the assistant's message and exit
+        // If no tool calls, record the assistant's message and finish
         if let Some(content) = &choice.message.content {
-            if json_output {
-                let output = serde_json::json!({
-                    "session_id": session.id,
-                    "message": content,
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
-            } else {
-                println!("{}", content);
-            }
-
+            report.message = Some(content.clone());
             session.conversation.add_assistant_message(content.clone());
         }
 
+        report.status = if approval_system.denied_count() > 0 {
+            ExecStatus::Denied
+        } else {
+            ExecStatus::Success
+        };
         break;
     }
 
-    // Save session for potential resume
-    session.save()?;
+    match report.status {
+        ExecStatus::IterationLimit => {
+            report.error = Some(format!("Stopped after {} iterations", MAX_ITERATIONS));
+        }
+        ExecStatus::Timeout => {
+            report.error = Some(format!(
+                "Timed out after {}s",
+                options.timeout.unwrap_or_default().as_secs()
+            ));
+        }
+        _ => {}
+    }
 
-    if !json_output {
-        eprintln!("{} Session saved as {}", "✓".green(), session.id);
+    // Partial results: fall back to the last thing the assistant said
+    if report.message.is_none() && report.status != ExecStatus::Success {
+        report.message = session.conversation.messages.iter()
+            .rev()
+            .find(|m| m.role == "assistant")
+            .map(|m| m.content.clone());
     }
 
-    Ok(())
+    Ok(report)
+}
+
+fn exceeds(used: usize, limit: Option<usize>) -> bool {
+    limit.is_some_and(|limit| used > limit)
+}
+
+fn record_side_effect(report: &mut ExecReport, tool_name: &str, arguments: &serde_json::Value) {
+    match tool_name {
+        "write_file" => {
+            if let Some(path) = arguments["path"].as_str() {
+                if !report.files_changed.iter().any(|p| p == path) {
+                    report.files_changed.push(path.to_string());
+                }
+            }
+        }
+        "bash_exec" => {
+            if let Some(command) = arguments["command"].as_str() {
+                report.commands_run.push(command.to_string());
+            }
+        }
+        _ => {}
+    }
 }
@@ -0,0 +1,387 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::llm::{outline, LlamaClient};
+use crate::llm::client::Message;
+
+/// Most of a whole file sent to the model, in bytes (about 6k tokens)
+const MAX_WHOLE_FILE: usize = 24_000;
+
+/// What part of the file to explain
+#[derive(Debug, PartialEq)]
+enum Selection {
+    Whole,
+    Lines(usize, usize),
+    Symbol(String),
+}
+
+pub async fn execute(target: &str, server_url: Option<String>, model: Option<String>) -> Result<()> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    let (path, selection) = parse_target(target)?;
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read file: {}", path))?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let (start, end, label) = match selection {
+        Selection::Whole => {
+            let mut size = 0;
+            let end = lines
+                .iter()
+                .take_while(|line| {
+                    size += line.len() + 1;
+                    size <= MAX_WHOLE_FILE
+                })
+                .count()
+                .clamp(lines.len().min(1), lines.len());
+            if end < lines.len() {
+                eprintln!(
+                    "{} {} is long; explaining lines 1-{} of {} (pick a range or symbol for the rest)",
+                    "✂️ ".yellow(),
+                    path,
+                    end,
+                    lines.len()
+                );
+                (1, end, format!("{} (lines 1-{} of {})", path, end, lines.len()))
+            } else {
+                (1, end, path.clone())
+            }
+        }
+        Selection::Lines(start, end) => {
+            let end = end.min(lines.len());
+            if start == 0 || start > end {
+                anyhow::bail!("Invalid line range {}-{} for {} ({} lines)", start, end, path, lines.len());
+            }
+            (start, end, format!("{}:{}-{}", path, start, end))
+        }
+        Selection::Symbol(symbol) => {
+            let found = match outline::outline(&path, &content) {
+                Some(symbols) => outline::find(&symbols, &symbol).map(|symbol| (symbol.start, symbol.end)),
+                // No parser for this language (or the file does not parse)
+                None => find_symbol(&path, &lines, &symbol),
+            };
+            let (start, end) = found
+                .ok_or_else(|| anyhow::anyhow!("Symbol '{}' not found in {}", symbol, path))?;
+            (start, end, format!("{}::{} (lines {}-{})", path, symbol, start, end))
+        }
+    };
+
+    let snippet = lines[start - 1..end]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:>5} | {}", start + i, line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Use the documenter agent's prompt when available
+    let system_prompt = Agent::load_or_create_defaults("documenter")
+        .map(|a| a.system_prompt)
+        .unwrap_or_else(|_| "You are a documentation specialist.".to_string());
+
+    let prompt = format!(
+        "Explain the following code from `{}` to a developer who is new to this codebase.\n\
+        Cover what it does, how it fits together, important inputs/outputs, and any gotchas.\n\
+        Do not rewrite the code.\n\n```\n{}\n```",
+        label, snippet
+    );
+
+    eprintln!("{} {}", "📖 Explaining".cyan().bold(), label.yellow());
+
+    let client = LlamaClient::for_config(&config, server_url, model);
+    let response = client
+        .chat_completion(
+            &[
+                Message {
+                    role: "system".to_string(),
+                    content: system_prompt,
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt,
+                },
+            ],
+            None,
+        )
+        .await
+        .context("Failed to get response from LLM")?;
+
+    let explanation = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+    println!("{}", explanation);
+
+    Ok(())
+}
+
+/// Split `path`, `path:10-20`, `path:42` or `path::symbol`
+fn parse_target(target: &str) -> Result<(String, Selection)> {
+    if let Some((path, symbol)) = target.split_once("::") {
+        if symbol.is_empty() {
+            anyhow::bail!("Empty symbol name in '{}'", target);
+        }
+        return Ok((path.to_string(), Selection::Symbol(symbol.to_string())));
+    }
+
+    if let Some((path, range)) = target.rsplit_once(':') {
+        let parsed = match range.split_once('-') {
+            Some((a, b)) => a.trim().parse::<usize>().ok().zip(b.trim().parse::<usize>().ok()),
+            None => range.trim().parse::<usize>().ok().map(|n| (n, n)),
+        };
+        if let Some((start, end)) = parsed {
+            return Ok((path.to_string(), Selection::Lines(start, end)));
+        }
+    }
+
+    Ok((target.to_string(), Selection::Whole))
+}
+
+/// Locate a symbol's definition and return its 1-based inclusive line range.
+///
+/// This is a lightweight declaration scan: it looks for common definition
+/// keywords followed by the name, then extends to the matching closing brace
+/// (or, for indentation-based languages, to the end of the indented block).
+/// Comments and string literals in `path`'s language are skipped. Used for
+/// files `outline` cannot parse.
+fn find_symbol(path: &str, lines: &[&str], symbol: &str) -> Option<(usize, usize)> {
+    // `Type::method` resolves to the last path segment
+    let name = symbol.rsplit("::").next().unwrap_or(symbol);
+    let code = code_only(path, lines);
+    let lines: Vec<&str> = code.iter().map(String::as_str).collect();
+    let keywords = [
+        "fn", "struct", "enum", "trait", "impl", "mod", "type", "const", "static",
+        "class", "def", "function", "interface", "func",
+    ];
+
+    let start = lines.iter().position(|line| {
+        // Generic parameters sit between `impl` and the type name
+        let mut depth = 0;
+        let line: String = line
+            .chars()
+            .filter(|&c| {
+                match c {
+                    '<' => depth += 1,
+                    '>' if depth > 0 => depth -= 1,
+                    _ => return depth == 0,
+                }
+                false
+            })
+            .collect();
+        let tokens: Vec<&str> = line
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|t| !t.is_empty())
+            .collect();
+        tokens
+            .windows(2)
+            .any(|w| keywords.contains(&w[0]) && w[1] == name)
+    })?;
+
+    // Brace-delimited block
+    let mut depth = 0i32;
+    let mut seen_open = false;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    seen_open = true;
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if seen_open && depth <= 0 {
+            return Some((start + 1, i + 1));
+        }
+        if !seen_open && line.trim_end().ends_with(';') {
+            return Some((start + 1, i + 1));
+        }
+        if !seen_open && line.trim_end().ends_with(':') {
+            break;
+        }
+    }
+
+    // Indentation-delimited block (Python and friends)
+    let indent = |l: &str| l.len() - l.trim_start().len();
+    let base = indent(lines[start]);
+    let mut end = start;
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= base {
+            break;
+        }
+        end = i;
+    }
+    Some((start + 1, end + 1))
+}
+
+/// The lines with comments and string literals blanked out, so names and
+/// braces inside them are not taken for code. The line count is kept.
+fn code_only(path: &str, lines: &[&str]) -> Vec<String> {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let hash_comments = matches!(extension, "py" | "sh" | "bash" | "rb" | "pl" | "r" | "toml" | "yaml" | "yml");
+    let rust = extension == "rs";
+    let python = extension == "py";
+    let backticks = matches!(extension, "js" | "jsx" | "ts" | "tsx" | "go");
+
+    let text: Vec<char> = lines.join("\n").chars().collect();
+    let find = |from: usize, pattern: &str| {
+        (from..text.len()).find(|&j| starts_with(&text[j..], pattern)).map(|j| j + pattern.chars().count())
+    };
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut code = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let starts = |pattern: &str| starts_with(rest, pattern);
+        let raw = (rust && rest[0] == 'r' && (i == 0 || !is_ident(text[i - 1])))
+            .then(|| raw_string_hashes(&rest[1..]))
+            .flatten();
+        // Where the comment or literal starting here ends
+        let end = if (hash_comments && rest[0] == '#') || (!hash_comments && starts("//")) {
+            Some(find(i, "\n").map_or(text.len(), |j| j - 1))
+        } else if !hash_comments && starts("/*") {
+            Some(find(i + 2, "*/").unwrap_or(text.len()))
+        } else if python && (starts("\"\"\"") || starts("'''")) {
+            Some(find(i + 3, &rest[..3].iter().collect::<String>()).unwrap_or(text.len()))
+        } else if let Some(hashes) = raw {
+            Some(find(i + 2 + hashes, &format!("\"{}", "#".repeat(hashes))).unwrap_or(text.len()))
+        } else if rest[0] == '"' || (rest[0] == '`' && backticks) || (rest[0] == '\'' && quotes_char(rust, rest)) {
+            Some(literal_end(&text, i, rust || rest[0] == '`'))
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                code.extend(text[i..end].iter().map(|&c| if c == '\n' { '\n' } else { ' ' }));
+                i = end;
+            }
+            None => {
+                code.push(rest[0]);
+                i += 1;
+            }
+        }
+    }
+    code.split('\n').map(str::to_string).collect()
+}
+
+fn starts_with(text: &[char], pattern: &str) -> bool {
+    pattern.chars().enumerate().all(|(k, c)| text.get(k) == Some(&c))
+}
+
+/// The number of `#`s when `rest`, just after an `r`, opens a raw string
+fn raw_string_hashes(rest: &[char]) -> Option<usize> {
+    let hashes = rest.iter().take_while(|&&c| c == '#').count();
+    (rest.get(hashes) == Some(&'"')).then_some(hashes)
+}
+
+/// Whether a `'` starts a literal: always outside Rust, where in Rust it
+/// may also start a lifetime or loop label
+fn quotes_char(rust: bool, rest: &[char]) -> bool {
+    !rust || rest.get(1) == Some(&'\\') || rest.get(2) == Some(&'\'')
+}
+
+/// Where the quoted literal starting at `start` ends, past its closing
+/// quote. Unless `multiline`, a line break ends it too.
+fn literal_end(text: &[char], start: usize, multiline: bool) -> usize {
+    let quote = text[start];
+    let mut j = start + 1;
+    while j < text.len() {
+        match text[j] {
+            '\\' => j += 1,
+            '\n' if !multiline => return j,
+            c if c == quote => return j + 1,
+            _ => {}
+        }
+        j += 1;
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<&str> {
+        text.lines().collect()
+    }
+
+    #[test]
+    fn targets() {
+        let target = |text| parse_target(text).unwrap();
+        assert_eq!(target("src/main.rs"), ("src/main.rs".to_string(), Selection::Whole));
+        assert_eq!(target("src/main.rs:10-20"), ("src/main.rs".to_string(), Selection::Lines(10, 20)));
+        assert_eq!(target("src/main.rs:42"), ("src/main.rs".to_string(), Selection::Lines(42, 42)));
+        assert_eq!(
+            target("src/llm/client.rs::LlamaClient::new"),
+            ("src/llm/client.rs".to_string(), Selection::Symbol("LlamaClient::new".to_string()))
+        );
+        // A colon that is not followed by line numbers is part of the path
+        assert_eq!(target("C:\\src\\main.rs"), ("C:\\src\\main.rs".to_string(), Selection::Whole));
+        assert!(parse_target("src/main.rs::").is_err());
+    }
+
+    #[test]
+    fn rust_items_span_their_braces() {
+        let source = lines(
+            "use std::fs;\n\
+             \n\
+             impl<'a> Parser<'a> {\n\
+             \x20   fn parse(&self) -> Option<char> {\n\
+             \x20       let open = '{';\n\
+             \x20       let text = \"}\";\n\
+             \x20       Some(open)\n\
+             \x20   }\n\
+             }\n\
+             const LIMIT: usize = 3;",
+        );
+        assert_eq!(find_symbol("lib.rs", &source, "Parser"), Some((3, 9)));
+        assert_eq!(find_symbol("lib.rs", &source, "Parser::parse"), Some((4, 8)));
+        assert_eq!(find_symbol("lib.rs", &source, "LIMIT"), Some((10, 10)));
+        assert_eq!(find_symbol("lib.rs", &source, "missing"), None);
+    }
+
+    #[test]
+    fn comments_and_strings_are_not_definitions() {
+        let source = lines(
+            "// fn run is below\n\
+             /* fn run\n\
+             \x20  fn run */\n\
+             const HELP: &str = \"fn run\";\n\
+             const RAW: &str = r#\"fn run \"quoted\"\"#;\n\
+             fn run() {\n\
+             }",
+        );
+        assert_eq!(find_symbol("main.rs", &source, "run"), Some((6, 7)));
+    }
+
+    #[test]
+    fn python_blocks_follow_indentation() {
+        let source = lines(
+            "# def load(): an old name\n\
+             USAGE = 'def load'\n\
+             \n\
+             def load(path):\n\
+             \x20   \"\"\"def load is documented here\n\
+             \n\
+             def save(): not code\"\"\"\n\
+             \x20   return open(path)\n\
+             \n\
+             def save():\n\
+             \x20   pass",
+        );
+        assert_eq!(find_symbol("store.py", &source, "load"), Some((4, 8)));
+        assert_eq!(find_symbol("store.py", &source, "save"), Some((10, 11)));
+    }
+}
@@ -0,0 +1,326 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::llm::findings::{Finding, Findings, Status, FINDINGS_FILE, SEVERITIES};
+
+pub struct ListOptions {
+    /// Open and resolved findings
+    pub all: bool,
+    /// Only resolved findings
+    pub resolved: bool,
+    pub file: Option<String>,
+    pub severity: Option<String>,
+}
+
+pub fn list(options: ListOptions) -> Result<()> {
+    if let Some(severity) = options.severity.as_deref() {
+        if !SEVERITIES.contains(&severity) {
+            anyhow::bail!("Unknown severity '{}' (use error, warning or note)", severity);
+        }
+    }
+    let store = Findings::load(&env::current_dir()?)?;
+    let status = match (options.all, options.resolved) {
+        (true, _) => None,
+        (false, true) => Some(Status::Resolved),
+        (false, false) => Some(Status::Open),
+    };
+    let list = store.list(status, options.file.as_deref(), options.severity.as_deref());
+    let scope = match status {
+        None => "",
+        Some(Status::Open) => "open ",
+        Some(Status::Resolved) => "resolved ",
+    };
+    if list.is_empty() {
+        println!("No {}findings in {}.", scope, FINDINGS_FILE);
+        if store.findings.is_empty() {
+            println!("Auditor agents record them with add_finding, and {} adds its results.", "vork audit".cyan());
+        }
+        return Ok(());
+    }
+
+    println!("{}", format!("{} {}finding{}", list.len(), scope, if list.len() == 1 { "" } else { "s" }).bold());
+    println!();
+    let mut current_file = None;
+    for finding in list {
+        if current_file != Some(&finding.file) {
+            println!("{}", finding.file.cyan());
+            current_file = Some(&finding.file);
+        }
+        let line = finding.line.map_or_else(|| "-".to_string(), |line| line.to_string());
+        let mut text = format!("{:>5} {:>5}  {} [{}] {}", format!("#{}", finding.id), line, colored_severity(finding), finding.rule, finding.message);
+        if finding.status == Status::Resolved {
+            text = text.dimmed().to_string();
+        }
+        println!("{}", text);
+    }
+    Ok(())
+}
+
+pub fn show(id: u64) -> Result<()> {
+    let store = Findings::load(&env::current_dir()?)?;
+    let finding = store.get(id).ok_or_else(|| anyhow::anyhow!("No finding #{}", id))?;
+    println!("{} {}", format!("#{}", finding.id).bold(), colored_severity(finding));
+    println!("{} {}", "Location:".cyan(), finding.location());
+    println!("{} {}", "Rule:".cyan(), finding.rule);
+    println!("{} {}", "Status:".cyan(), match finding.status {
+        Status::Open => "open".to_string(),
+        Status::Resolved => match finding.resolved_at {
+            Some(at) => format!("resolved {}", at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")),
+            None => "resolved".to_string(),
+        },
+    });
+    if let Some(resolution) = &finding.resolution {
+        println!("{} {}", "Resolution:".cyan(), resolution);
+    }
+    if let Some(issue) = &finding.issue {
+        println!("{} {}", "Issue:".cyan(), issue);
+    }
+    println!(
+        "{} first {}, last {} ({} time{})",
+        "Seen:".cyan(),
+        finding.first_seen.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        finding.last_seen.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        finding.times_seen,
+        if finding.times_seen == 1 { "" } else { "s" }
+    );
+    println!();
+    println!("{}", finding.message);
+    Ok(())
+}
+
+pub fn resolve(id: u64, note: Option<String>) -> Result<()> {
+    let mut store = Findings::load(&env::current_dir()?)?;
+    let summary = store.resolve(id, note.as_deref())?.summary();
+    store.save()?;
+    println!("{} {}", "✅ Resolved".green(), summary);
+    Ok(())
+}
+
+pub fn reopen(id: u64) -> Result<()> {
+    let mut store = Findings::load(&env::current_dir()?)?;
+    let summary = store.reopen(id)?.summary();
+    store.save()?;
+    println!("{} {}", "📌 Reopened".yellow(), summary);
+    Ok(())
+}
+
+pub struct ExportOptions {
+    /// github, gitlab or jsonl
+    pub to: String,
+    pub file: Option<String>,
+    pub severity: Option<String>,
+    /// Added to every issue along with vork, the severity and the rule
+    pub labels: Vec<String>,
+    /// Where jsonl goes (default: stdout)
+    pub output: Option<PathBuf>,
+    /// Export findings that already have an issue as well
+    pub again: bool,
+    /// Print the issues instead of creating them
+    pub dry_run: bool,
+}
+
+/// An issue ready to be filed for a finding
+struct Issue {
+    title: String,
+    body: String,
+    labels: Vec<String>,
+}
+
+/// File open findings as GitHub or GitLab issues through gh or glab, or
+/// write them as JSON lines for any other tracker
+pub fn export(options: ExportOptions) -> Result<()> {
+    if !matches!(options.to.as_str(), "github" | "gitlab" | "jsonl") {
+        anyhow::bail!("Unknown target '{}' (use github, gitlab or jsonl)", options.to);
+    }
+    if let Some(severity) = options.severity.as_deref() {
+        if !SEVERITIES.contains(&severity) {
+            anyhow::bail!("Unknown severity '{}' (use error, warning or note)", severity);
+        }
+    }
+    let mut store = Findings::load(&env::current_dir()?)?;
+    let list: Vec<Finding> = store
+        .list(Some(Status::Open), options.file.as_deref(), options.severity.as_deref())
+        .into_iter()
+        .filter(|finding| options.again || options.to == "jsonl" || finding.issue.is_none())
+        .cloned()
+        .collect();
+    let exported = store.findings.iter().filter(|f| f.status == Status::Open && f.issue.is_some()).count();
+    if list.is_empty() {
+        println!("No open findings to export.");
+        if exported > 0 && !options.again {
+            println!("{} already have issues; pass --again to export them anyway.", exported);
+        }
+        return Ok(());
+    }
+
+    let links = Links::detect(&options.to);
+    let issues: Vec<(u64, Issue)> = list.iter().map(|finding| (finding.id, issue(finding, &options.labels, links.as_ref()))).collect();
+
+    if options.to == "jsonl" {
+        let mut out: Box<dyn Write> = match &options.output {
+            Some(path) => Box::new(fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?),
+            None => Box::new(std::io::stdout()),
+        };
+        for (finding, (_, issue)) in list.iter().zip(&issues) {
+            let line = json!({
+                "id": finding.id,
+                "title": issue.title,
+                "body": issue.body,
+                "labels": issue.labels,
+                "file": finding.file,
+                "line": finding.line,
+                "severity": finding.severity,
+                "rule": finding.rule,
+                "url": links.as_ref().map(|links| links.file(finding)),
+            });
+            writeln!(out, "{}", line)?;
+        }
+        if let Some(path) = &options.output {
+            eprintln!("{} {} findings to {}", "📤 Exported".green(), list.len(), path.display());
+        }
+        return Ok(());
+    }
+
+    if options.dry_run {
+        for (id, issue) in &issues {
+            println!("{} {}", format!("#{}", id).bold(), issue.title);
+            println!("{} {}", "Labels:".cyan(), issue.labels.join(", "));
+            println!("{}\n", issue.body);
+        }
+        println!("{} issues would be created on {}", issues.len(), options.to);
+        return Ok(());
+    }
+
+    let (cli, label_args): (&str, &[&str]) = match options.to.as_str() {
+        "github" => ("gh", &["label", "create", "--force"]),
+        _ => ("glab", &[]),
+    };
+    // gh refuses labels that do not exist yet; GitLab creates them itself
+    if !label_args.is_empty() {
+        let mut labels: Vec<&String> = issues.iter().flat_map(|(_, issue)| &issue.labels).collect();
+        labels.sort();
+        labels.dedup();
+        for label in labels {
+            let _ = Command::new(cli).args(label_args).arg(label).output();
+        }
+    }
+
+    let mut created = 0;
+    for (id, issue) in issues {
+        let labels = issue.labels.join(",");
+        let mut command = Command::new(cli);
+        match cli {
+            "gh" => command.args(["issue", "create", "--title", &issue.title, "--body", &issue.body, "--label", &labels]),
+            _ => command.args(["issue", "create", "--title", &issue.title, "--description", &issue.body, "--label", &labels, "--yes"]),
+        };
+        let output = command.output().with_context(|| format!("Failed to run {} (is it installed and logged in?)", cli))?;
+        if !output.status.success() {
+            // Save what was filed before stopping
+            store.save()?;
+            anyhow::bail!("{} issue create failed for #{}: {}", cli, id, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let url = stdout.lines().rev().find(|line| line.starts_with("http")).unwrap_or(stdout.trim()).to_string();
+        println!("{} #{} → {}", "📤".green(), id, url);
+        if let Some(finding) = store.get_mut(id) {
+            finding.issue = Some(url);
+        }
+        created += 1;
+    }
+    store.save()?;
+    println!("{} {} issue{} on {}", "✅ Created".green(), created, if created == 1 { "" } else { "s" }, options.to);
+    Ok(())
+}
+
+fn issue(finding: &Finding, extra_labels: &[String], links: Option<&Links>) -> Issue {
+    let mut title: String = finding.message.lines().next().unwrap_or_default().trim_end_matches('.').to_string();
+    if title.chars().count() > 80 {
+        title = format!("{}…", title.chars().take(79).collect::<String>());
+    }
+    let location = match links {
+        Some(links) => format!("[`{}`]({})", finding.location(), links.file(finding)),
+        None => format!("`{}`", finding.location()),
+    };
+    let body = format!(
+        "{}\n\n| | |\n|---|---|\n| Location | {} |\n| Severity | {} |\n| Rule | `{}` |\n| First seen | {} |\n| Reported | {} time{} |\n\n\
+         _Exported from vork finding #{}. Resolve it with `vork findings resolve {}` once fixed._",
+        finding.message,
+        location,
+        finding.severity,
+        finding.rule,
+        finding.first_seen.format("%Y-%m-%d"),
+        finding.times_seen,
+        if finding.times_seen == 1 { "" } else { "s" },
+        finding.id,
+        finding.id
+    );
+    let mut labels = vec!["vork".to_string(), format!("severity:{}", finding.severity), finding.rule.clone()];
+    labels.extend(extra_labels.iter().cloned());
+    labels.dedup();
+    Issue { title: format!("{}: {}", finding.rule, title), body, labels }
+}
+
+/// Permalinks to files in the origin repository's web view, at HEAD
+struct Links {
+    base: String,
+    commit: String,
+    gitlab: bool,
+}
+
+impl Links {
+    fn detect(target: &str) -> Option<Self> {
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        let remote = git(&["remote", "get-url", "origin"])?;
+        let commit = git(&["rev-parse", "HEAD"])?;
+        let base = web_url(&remote)?;
+        let gitlab = target == "gitlab" || (target != "github" && base.contains("gitlab"));
+        Some(Self { base, commit, gitlab })
+    }
+
+    fn file(&self, finding: &Finding) -> String {
+        let separator = if self.gitlab { "/-/blob/" } else { "/blob/" };
+        let mut url = format!("{}{}{}/{}", self.base, separator, self.commit, finding.file);
+        if let Some(line) = finding.line {
+            url.push_str(&format!("#L{}", line));
+        }
+        url
+    }
+}
+
+/// https://host/owner/repo for git@host:owner/repo.git, ssh:// and https:// remotes
+fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/').trim_end_matches(".git");
+    let (host, path) = if let Some(rest) = remote.strip_prefix("https://").or_else(|| remote.strip_prefix("http://")) {
+        rest.split_once('/')?
+    } else if let Some(rest) = remote.strip_prefix("ssh://") {
+        let (host, path) = rest.split_once('/')?;
+        (host.split(':').next()?, path)
+    } else {
+        remote.split_once(':')?
+    };
+    // Drop user@ (and any credentials) and ports
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    Some(format!("https://{}/{}", host, path))
+}
+
+fn colored_severity(finding: &Finding) -> colored::ColoredString {
+    let label = format!("{:<7}", finding.severity);
+    match finding.severity.as_str() {
+        "error" => label.red(),
+        "warning" => label.yellow(),
+        _ => label.normal(),
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::env;
+use std::path::Path;
+
+use crate::agents::Agent;
+use crate::config::{Config, ApprovalPolicy, SandboxMode};
+use crate::llm::{LlamaClient, Session, ApprovalSystem, UsageTracker};
+use crate::llm::arguments;
+use crate::llm::tools::{get_available_tools, execute_tool};
+
+/// Maximum LLM round-trips within a single fix attempt
+const MAX_TOOL_ROUNDS: usize = 25;
+
+/// Lines of check output fed back to the model per attempt
+const OUTPUT_TAIL_LINES: usize = 200;
+
+pub async fn execute(
+    cmd: Option<String>,
+    max_iterations: usize,
+    auto: bool,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent_name: Option<String>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    let check_cmd = match cmd {
+        Some(cmd) => cmd,
+        None => detect_check_command(&env::current_dir()?).ok_or_else(|| {
+            anyhow::anyhow!("Could not detect a check command for this project; pass one with --cmd")
+        })?,
+    };
+
+    let agent = Agent::load_or_create_defaults(agent_name.as_deref().unwrap_or("debugger")).ok();
+
+    println!("{}", "=== Vork Fix ===".green().bold());
+    println!("{} {}", "Check:".cyan(), check_cmd.yellow());
+    println!("{} {}", "Max iterations:".cyan(), max_iterations);
+    if let Some(ref agent) = agent {
+        println!("{} {}", "Agent:".cyan(), agent.name);
+    }
+    println!();
+
+    let client = LlamaClient::for_config(&config, server_url, model);
+    let approval_system = approval_system(&config, auto, agent.as_ref());
+    let outcome = fix_loop(&client, &approval_system, &check_cmd, max_iterations, agent.as_ref()).await?;
+
+    println!();
+    println!("{}", "=== Summary ===".green().bold());
+    if outcome.passed {
+        println!("{} Check passed after {} fix attempt(s)", "✓".green(), outcome.attempts);
+    } else {
+        println!("{} Check still failing after {} fix attempt(s)", "✗".red(), outcome.attempts);
+    }
+    if outcome.files_changed.is_empty() {
+        println!("  No files changed");
+    } else {
+        println!("  Files changed:");
+        for file in &outcome.files_changed {
+            println!("    {} {}", "→".cyan(), file);
+        }
+    }
+    println!("  Session: {}", outcome.session_id);
+
+    if !outcome.passed {
+        anyhow::bail!("'{}' still failing", check_cmd);
+    }
+
+    Ok(())
+}
+
+/// Result of running the check/fix loop
+pub struct FixOutcome {
+    pub passed: bool,
+    pub attempts: usize,
+    pub files_changed: Vec<String>,
+    pub session_id: String,
+}
+
+/// The configured approval policy and sandbox; `--auto` auto-approves
+/// edits inside the workspace while dangerous commands still prompt
+pub fn approval_system(config: &Config, auto: bool, agent: Option<&Agent>) -> ApprovalSystem {
+    let (policy, sandbox_mode) = match auto {
+        true => (ApprovalPolicy::Auto, SandboxMode::WorkspaceWrite),
+        false => (config.assistant.approval_policy.clone(), config.assistant.sandbox_mode.clone()),
+    };
+    ApprovalSystem::new(policy, sandbox_mode).with_agent(agent)
+}
+
+/// Run `check_cmd`, hand failures to the model, and repeat until green or
+/// `max_iterations` fix attempts have been made.
+pub async fn fix_loop(
+    client: &LlamaClient,
+    approval_system: &ApprovalSystem,
+    check_cmd: &str,
+    max_iterations: usize,
+    agent: Option<&Agent>,
+) -> Result<FixOutcome> {
+    let mut session = Session::new(env::current_dir()?);
+    if let Some(agent) = agent {
+        session.conversation.set_system_prompt(agent.system_prompt.clone());
+    }
+    let mut usage = UsageTracker::new("fix", &session.id, client.model());
+    usage.set_agent(agent.map(|a| a.name.as_str()));
+
+    let mut outcome = FixOutcome {
+        passed: false,
+        attempts: 0,
+        files_changed: vec![],
+        session_id: session.id.clone(),
+    };
+
+    loop {
+        println!("{} {}", "▶ Running".cyan().bold(), check_cmd);
+        let (success, output) = run_check(check_cmd).await?;
+
+        if success {
+            println!("{}", "✓ Check passed".green().bold());
+            outcome.passed = true;
+            break;
+        }
+
+        if outcome.attempts >= max_iterations {
+            break;
+        }
+        outcome.attempts += 1;
+
+        println!(
+            "{}",
+            format!("✗ Check failed — fix attempt {}/{}", outcome.attempts, max_iterations).red()
+        );
+
+        session.conversation.add_user_message(format!(
+            "The check command `{}` is failing. Here is the end of its output:\n\n```\n{}\n```\n\n\
+            Find the root cause, read the relevant files, and edit them with write_file so the check passes. \
+            Do not disable or delete tests to make it pass. When you are done, briefly summarize what you changed.",
+            check_cmd,
+            tail(&output, OUTPUT_TAIL_LINES)
+        ));
+
+        for _ in 0..MAX_TOOL_ROUNDS {
+            usage.check_budget()?;
+            let tools = get_available_tools();
+            if let Some(notice) = session.conversation.fit_to_context(client, Some(&tools)).await? {
+                println!("{}", notice.yellow());
+            }
+            let response = client
+                .chat_completion(session.conversation.get_messages(), Some(&tools))
+                .await
+                .context("Failed to get response from LLM")?;
+            usage.record_response(response.usage.as_ref());
+
+            let choice = response
+                .choices
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+            if let Some(tool_calls) = &choice.message.tool_calls {
+                for tool_call in tool_calls {
+                    usage.record_tool_call();
+                    let tool_name = &tool_call.function.name;
+                    let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                        Ok(arguments) => arguments,
+                        Err(e) => {
+                            session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                            continue;
+                        }
+                    };
+
+                    println!("  {} {}", "🔧".yellow(), tool_name.yellow());
+
+                    let denied_before = approval_system.denied_count();
+                    match execute_tool(tool_name, arguments.clone(), Some(approval_system), &session.tool_dir()).await {
+                        Ok(result) => {
+                            if tool_name == "write_file" && approval_system.denied_count() == denied_before {
+                                if let Some(path) = arguments["path"].as_str() {
+                                    if !outcome.files_changed.iter().any(|p| p == path) {
+                                        outcome.files_changed.push(path.to_string());
+                                    }
+                                }
+                            }
+                            session.conversation.add_tool_result(tool_name, &result);
+                        }
+                        Err(e) => {
+                            session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if let Some(content) = &choice.message.content {
+                println!("  {} {}", "🐴".green(), content.lines().next().unwrap_or_default());
+                session.conversation.add_assistant_message(content.clone());
+            }
+            break;
+        }
+
+        session.conversation.compact_if_needed(client).await?;
+        session.save()?;
+    }
+
+    session.save()?;
+    Ok(outcome)
+}
+
+/// Guess the project's build/test command from marker files
+pub fn detect_check_command(dir: &Path) -> Option<String> {
+    let candidates = [
+        ("Cargo.toml", "cargo test"),
+        ("package.json", "npm test"),
+        ("go.mod", "go test ./..."),
+        ("pyproject.toml", "pytest"),
+        ("setup.py", "pytest"),
+        ("pom.xml", "mvn -q test"),
+        ("build.gradle", "./gradlew test"),
+        ("CMakeLists.txt", "cmake --build build"),
+        ("Makefile", "make"),
+    ];
+
+    candidates
+        .iter()
+        .find(|(marker, _)| dir.join(marker).exists())
+        .map(|(_, cmd)| cmd.to_string())
+}
+
+/// Run a check command, returning (success, combined output)
+pub async fn run_check(cmd: &str) -> Result<(bool, String)> {
+    let output = tokio::process::Command::new("bash")
+        .arg("-c")
+        .arg(cmd)
+        .output()
+        .await
+        .with_context(|| format!("Failed to execute check command: {}", cmd))?;
+
+    let mut combined = String::from_utf8_lossy(&output.stdout).to_string();
+    combined.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.success(), combined))
+}
+
+fn tail(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
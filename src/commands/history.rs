@@ -0,0 +1,73 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+use crate::llm::history::{past_prompts, search};
+
+/// What to do with a prompt picked from the history
+pub enum Choice {
+    /// Send the prompt again in a new session
+    Run(String),
+    /// Resume the session it came from
+    Resume(String),
+}
+
+pub fn execute(query: &str, project_only: bool, limit: usize) -> Result<Option<Choice>> {
+    let current = env::current_dir()?;
+    let prompts = past_prompts()?;
+    let matches: Vec<_> = search(&prompts, query)
+        .into_iter()
+        .filter(|prompt| !project_only || prompt.working_directory == current)
+        .take(limit)
+        .collect();
+    if matches.is_empty() {
+        println!("No past prompts{}.", if query.trim().is_empty() { String::new() } else { format!(" matching '{}'", query.trim()) });
+        return Ok(None);
+    }
+
+    for (i, prompt) in matches.iter().enumerate() {
+        println!(
+            "{:>3}. {}  {}  {}",
+            i + 1,
+            prompt.time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            prompt.session_id.yellow(),
+            prompt.working_directory.display().to_string().dimmed()
+        );
+        for line in prompt.text.lines().take(3) {
+            println!("     {}", line);
+        }
+        if prompt.text.lines().count() > 3 {
+            println!("     {}", "…".dimmed());
+        }
+    }
+
+    if !io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    print!("\n{} ", "Number to run again, r<number> to resume its session, Enter to quit:".cyan());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+
+    let (resume, number) = match input.strip_prefix('r') {
+        Some(number) => (true, number),
+        None => (false, input),
+    };
+    let prompt = number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| matches.get(i))
+        .ok_or_else(|| anyhow::anyhow!("Invalid choice '{}'", input))?;
+    Ok(Some(if resume {
+        Choice::Resume(prompt.session_id.clone())
+    } else {
+        Choice::Run(prompt.text.clone())
+    }))
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::agents::Agent;
+
+/// Marker line identifying hooks written by vork
+const HOOK_MARKER: &str = "# Installed by vork hooks install";
+
+const PREPARE_COMMIT_MSG: &str = r#"#!/bin/sh
+# Installed by vork hooks install
+# Generates a commit message from the staged diff when none was given.
+COMMIT_MSG_FILE="$1"
+COMMIT_SOURCE="$2"
+
+# Respect -m, -F, merges, squashes and amends
+[ -n "$COMMIT_SOURCE" ] && exit 0
+[ -n "$VORK_SKIP_HOOKS" ] && exit 0
+
+DIFF=$(git diff --cached --stat --patch | head -c 24000)
+[ -z "$DIFF" ] && exit 0
+
+MSG=$(vork --agent commit-writer exec "Write a commit message for this staged diff:
+
+$DIFF" 2>/dev/null) || exit 0
+[ -z "$MSG" ] && exit 0
+
+{
+    printf '%s\n' "$MSG"
+    cat "$COMMIT_MSG_FILE"
+} > "$COMMIT_MSG_FILE.vork" && mv "$COMMIT_MSG_FILE.vork" "$COMMIT_MSG_FILE"
+"#;
+
+const PRE_PUSH: &str = r#"#!/bin/sh
+# Installed by vork hooks install
+# Quick AI review of the outgoing diff, with a confirm prompt.
+[ -n "$VORK_SKIP_HOOKS" ] && exit 0
+
+BASE=$(git rev-parse --abbrev-ref --symbolic-full-name '@{upstream}' 2>/dev/null)
+[ -z "$BASE" ] && BASE=$(git rev-parse --abbrev-ref origin/HEAD 2>/dev/null)
+[ -z "$BASE" ] && exit 0
+
+DIFF=$(git diff "$BASE"...HEAD | head -c 48000)
+[ -z "$DIFF" ] && exit 0
+
+echo "🚦 vork: reviewing outgoing changes against $BASE..."
+REVIEW=$(vork --agent push-reviewer exec "Review this outgoing diff:
+
+$DIFF" 2>/dev/null) || exit 0
+
+echo "$REVIEW"
+[ "$REVIEW" = "LGTM" ] && exit 0
+
+# stdin is the ref list from git; ask on the terminal instead
+if [ -t 1 ] && [ -r /dev/tty ]; then
+    printf "Push anyway? [y/N] "
+    read -r answer < /dev/tty
+    case "$answer" in
+        y|Y|yes|YES) exit 0 ;;
+        *) echo "Push aborted."; exit 1 ;;
+    esac
+fi
+exit 0
+"#;
+
+pub fn install(pre_push: bool, force: bool) -> Result<()> {
+    let hooks_dir = hooks_dir()?;
+    fs::create_dir_all(&hooks_dir)?;
+
+    // Make sure the dedicated agents exist for the hook scripts
+    Agent::load_or_create_defaults("commit-writer")?;
+    if pre_push {
+        Agent::load_or_create_defaults("push-reviewer")?;
+    }
+
+    write_hook(&hooks_dir, "prepare-commit-msg", PREPARE_COMMIT_MSG, force)?;
+    if pre_push {
+        write_hook(&hooks_dir, "pre-push", PRE_PUSH, force)?;
+    }
+
+    println!();
+    println!("Set {} to bypass the hooks for a single command.", "VORK_SKIP_HOOKS=1".cyan());
+    Ok(())
+}
+
+pub fn uninstall() -> Result<()> {
+    let hooks_dir = hooks_dir()?;
+
+    for name in ["prepare-commit-msg", "pre-push"] {
+        let path = hooks_dir.join(name);
+        if is_vork_hook(&path) {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            println!("{} Removed {}", "✓".green(), name);
+        }
+    }
+
+    Ok(())
+}
+
+fn write_hook(hooks_dir: &std::path::Path, name: &str, script: &str, force: bool) -> Result<()> {
+    let path = hooks_dir.join(name);
+
+    if path.exists() && !is_vork_hook(&path) && !force {
+        println!(
+            "{} {} already exists and was not installed by vork (use --force to replace it)",
+            "⚠️".yellow(),
+            name
+        );
+        return Ok(());
+    }
+
+    fs::write(&path, script)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!("{} Installed {}", "✓".green(), name.green().bold());
+    Ok(())
+}
+
+fn is_vork_hook(path: &std::path::Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains(HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+fn hooks_dir() -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        anyhow::bail!("Not inside a git repository");
+    }
+
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
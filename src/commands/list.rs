@@ -25,7 +25,7 @@ pub async fn execute(installed: bool) -> Result<()> {
                         println!("  {} {}", "●".green(), "Ollama".bold());
                         for model in models {
                             let size_str = model.size.unwrap_or_else(|| "unknown".to_string());
-                            println!("    {} {}", "→".cyan(), format!("{} ({})", model.name, size_str));
+                            println!("    {} {} ({})", "→".cyan(), model.name, size_str);
                         }
                         println!();
                     } else if !installed {
@@ -58,7 +58,7 @@ pub async fn execute(installed: bool) -> Result<()> {
                         println!("  {} {}", "●".green(), "llama.cpp".bold());
                         for model in models {
                             let size_str = model.size.unwrap_or_else(|| "unknown".to_string());
-                            println!("    {} {}", "→".cyan(), format!("{} ({})", model.name, size_str));
+                            println!("    {} {} ({})", "→".cyan(), model.name, size_str);
                         }
                         println!();
                     } else if !installed {
@@ -80,5 +80,24 @@ pub async fn execute(installed: bool) -> Result<()> {
         }
     }
 
+    // LM Studio and Jan manage their own models; list what their servers offer
+    for server in [backends::openai_compat::LocalServerBackend::lmstudio(), backends::openai_compat::LocalServerBackend::jan()] {
+        match server.list_models().await {
+            Ok(models) => {
+                println!("  {} {} {}", "●".green(), server.name().bold(), server.api_url().dimmed());
+                for model in models {
+                    println!("    {} {}", "→".cyan(), model.name);
+                }
+                println!("    Use: vork chat -s {}", server.api_url());
+                println!();
+            }
+            Err(_) if !installed => {
+                println!("  {} {} {}", "○".yellow(), server.name().bold(), "(server not running)".dimmed());
+                println!();
+            }
+            Err(_) => {}
+        }
+    }
+
     Ok(())
 }
@@ -1,5 +1,7 @@
 pub mod list;
 pub mod install;
+pub mod models;
+pub mod quantize;
 pub mod run;
 pub mod remove;
 pub mod config;
@@ -8,8 +10,27 @@ pub mod chat;
 pub mod ask;
 pub mod resume;
 pub mod exec;
+pub mod consensus;
+pub mod explain;
+pub mod fix;
+pub mod watch;
+pub mod hooks;
+pub mod pr;
+pub mod changelog;
+pub mod stats;
+pub mod dictate;
 pub mod tui;
 pub mod setup;
 pub mod agents;
 pub mod benchmark;
 pub mod presets;
+pub mod rpc;
+pub mod audit;
+pub mod scan;
+pub mod findings;
+pub mod sessions;
+pub mod session_view;
+pub mod history;
+pub mod warm;
+pub mod bot;
+pub mod schedule;
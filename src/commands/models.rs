@@ -0,0 +1,555 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+use crate::backends::llamacpp::LlamaCppBackend;
+use crate::backends::ollama::OllamaBackend;
+use crate::backends::Backend;
+use crate::config::Config;
+use crate::llm::stats::UsageRecord;
+
+const OLLAMA_SEARCH_URL: &str = "https://ollama.com/search";
+const HF_API_URL: &str = "https://huggingface.co/api/models";
+
+/// Runtime overhead on top of the weights: KV cache at a modest context plus
+/// compute buffers. A rough rule of thumb, not a guarantee.
+const VRAM_OVERHEAD: f64 = 1.2;
+
+/// One installable variant of a search result
+struct Variant {
+    /// Short label shown to the user (8b, Q4_K_M)
+    label: String,
+    /// Name to pass to `vork install`
+    install_name: String,
+    /// Download size in bytes, when known
+    bytes: Option<u64>,
+}
+
+struct SearchResult {
+    name: String,
+    /// Backend that `vork install` should use for this result
+    backend: &'static str,
+    description: Option<String>,
+    popularity: Option<String>,
+    variants: Vec<Variant>,
+}
+
+pub async fn search(term: &str, source: &str, limit: usize) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .build()?;
+
+    let mut results = Vec::new();
+    if matches!(source, "all" | "ollama") {
+        match search_ollama(&client, term, limit).await {
+            Ok(found) => results.extend(found),
+            Err(e) => eprintln!("{} Ollama library: {}", "⚠️ ".yellow(), e),
+        }
+    }
+    if matches!(source, "all" | "hf" | "huggingface") {
+        match search_huggingface(&client, term, limit).await {
+            Ok(found) => results.extend(found),
+            Err(e) => eprintln!("{} HuggingFace: {}", "⚠️ ".yellow(), e),
+        }
+    }
+    if !matches!(source, "all" | "ollama" | "hf" | "huggingface") {
+        anyhow::bail!("Unknown source '{}'. Use all, ollama or hf", source);
+    }
+
+    if results.is_empty() {
+        println!("No models found for '{}'", term);
+        return Ok(());
+    }
+
+    let mut current_backend = "";
+    for (i, result) in results.iter().enumerate() {
+        if result.backend != current_backend {
+            current_backend = result.backend;
+            let heading = if current_backend == "ollama" { "Ollama library" } else { "HuggingFace (GGUF)" };
+            println!();
+            println!("{}", heading.green().bold());
+        }
+
+        let popularity = result.popularity.as_deref().map(|p| format!(" ({})", p)).unwrap_or_default();
+        println!("  {}. {}{}", i + 1, result.name.cyan().bold(), popularity.dimmed());
+        if let Some(ref description) = result.description {
+            println!("     {}", description);
+        }
+        for variant in &result.variants {
+            let size = match variant.bytes {
+                Some(bytes) => format!(
+                    "{} (~{} VRAM)",
+                    format_bytes(bytes),
+                    format_bytes((bytes as f64 * VRAM_OVERHEAD) as u64)
+                ),
+                None => "size unknown".to_string(),
+            };
+            println!("     {} {:<12} {}", "→".cyan(), variant.label, size.dimmed());
+        }
+    }
+    println!();
+    println!("VRAM figures are estimates: weights plus ~20% for the KV cache and buffers.");
+
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    // Hand off to `vork install`
+    print!("{} ", "Install? <number> <variant> (e.g. 1 8b), Enter to skip:".cyan());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let mut parts = input.split_whitespace();
+    let Some(number) = parts.next() else {
+        return Ok(());
+    };
+
+    let result = number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| results.get(i))
+        .ok_or_else(|| anyhow::anyhow!("No result numbered {}", number))?;
+    let variant = match parts.next() {
+        Some(label) => result
+            .variants
+            .iter()
+            .find(|v| v.label.eq_ignore_ascii_case(label))
+            .ok_or_else(|| anyhow::anyhow!("{} has no variant '{}'", result.name, label))?,
+        None if result.variants.len() == 1 => &result.variants[0],
+        None => anyhow::bail!("Pick a variant of {}, e.g. `{} {}`", result.name, number, result.variants[0].label),
+    };
+
+    println!();
+    super::install::execute(&variant.install_name, result.backend).await
+}
+
+/// An installed model as seen by `vork models prune`
+struct InstalledModel {
+    backend: &'static str,
+    /// Name `remove_model` takes: llama.cpp alias or Ollama tag
+    name: String,
+    bytes: u64,
+    last_used: Option<DateTime<Utc>>,
+    /// Config or presets that would load this model
+    used_by: Vec<String>,
+    note: Option<String>,
+}
+
+pub async fn prune(stale_days: u32, dry_run: bool) -> Result<()> {
+    let config = Config::load()?;
+
+    // Which configs point at which model names
+    let mut configured: Vec<(String, String)> = vec![("config".to_string(), config.assistant.model.clone())];
+    if let Ok(entries) = std::fs::read_dir(Config::config_dir()?.join("presets")) {
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+                continue;
+            }
+            let preset = std::fs::read_to_string(&path).ok().and_then(|c| toml::from_str::<Config>(&c).ok());
+            if let (Some(preset), Some(name)) = (preset, path.file_stem().and_then(|s| s.to_str())) {
+                configured.push((format!("preset {}", name), preset.assistant.model));
+            }
+        }
+    }
+
+    // Most recent use of each model name, from the usage log
+    let mut last_use: HashMap<String, DateTime<Utc>> = HashMap::new();
+    for record in UsageRecord::load_all().unwrap_or_default() {
+        let entry = last_use.entry(record.model).or_insert(record.timestamp);
+        *entry = (*entry).max(record.timestamp);
+    }
+
+    let mut models = Vec::new();
+
+    if config.llamacpp.enabled {
+        let llamacpp = LlamaCppBackend::new();
+        let files = llamacpp.scan_models_dir()?;
+        let quantized = super::quantize::QuantizedModel::load_all();
+
+        // A name loads the first file it matches, exactly like the server does
+        let resolves_to = |model: &str, path: &std::path::PathBuf| llamacpp.served_file(&files, model) == Some(path);
+
+        for path in &files {
+            let used_by = configured
+                .iter()
+                .filter(|(_, model)| resolves_to(model, path))
+                .map(|(who, _)| who.clone())
+                .collect();
+            let last_used = last_use
+                .iter()
+                .filter(|(model, _)| resolves_to(model, path))
+                .map(|(_, when)| *when)
+                .max();
+            let note = quantized
+                .iter()
+                .filter(|q| q.source == *path)
+                .map(|q| q.quant.as_str())
+                .collect::<Vec<_>>();
+            models.push(InstalledModel {
+                backend: "llama.cpp",
+                name: llamacpp.get_model_alias(path),
+                bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+                last_used,
+                used_by,
+                note: (!note.is_empty()).then(|| format!("quantized to {}", note.join(", "))),
+            });
+        }
+    }
+
+    if config.ollama.enabled {
+        let ollama = OllamaBackend::new();
+        if ollama.is_available().await {
+            let same = |a: &str, b: &str| {
+                a == b || a.strip_suffix(":latest") == Some(b) || b.strip_suffix(":latest") == Some(a)
+            };
+            for model in ollama.list_models().await.unwrap_or_default() {
+                models.push(InstalledModel {
+                    backend: "ollama",
+                    used_by: configured
+                        .iter()
+                        .filter(|(_, name)| same(name, &model.name))
+                        .map(|(who, _)| who.clone())
+                        .collect(),
+                    last_used: last_use.iter().filter(|(name, _)| same(name, &model.name)).map(|(_, when)| *when).max(),
+                    bytes: model.bytes.unwrap_or(0),
+                    name: model.name,
+                    note: None,
+                });
+            }
+        }
+    }
+
+    if models.is_empty() {
+        println!("No installed models found");
+        return Ok(());
+    }
+    models.sort_by_key(|m| std::cmp::Reverse(m.bytes));
+
+    let cutoff = Utc::now() - chrono::Duration::days(stale_days as i64);
+    let is_stale = |m: &InstalledModel| m.used_by.is_empty() && m.last_used.is_none_or(|t| t < cutoff);
+
+    println!("{}", "=== Installed Models ===".green().bold());
+    println!();
+    println!("  {:>3}  {:>9}  {:<10} {:<45} {:<14} kept by", "#", "size", "backend", "model", "last used");
+    for (i, model) in models.iter().enumerate() {
+        let last_used = match model.last_used {
+            Some(when) => match (Utc::now() - when).num_days() {
+                0 => "today".to_string(),
+                1 => "yesterday".to_string(),
+                days => format!("{} days ago", days),
+            },
+            None => "never".to_string(),
+        };
+        let line = format!(
+            "  {:>3}  {:>9}  {:<10} {:<45} {:<14} {}",
+            i + 1,
+            format_bytes(model.bytes),
+            model.backend,
+            model.name,
+            last_used,
+            model.used_by.join(", ")
+        );
+        if is_stale(model) {
+            println!("{}", line.yellow());
+        } else {
+            println!("{}", line);
+        }
+        if let Some(ref note) = model.note {
+            println!("  {:>3}  {:>9}  {}", "", "", note.dimmed());
+        }
+    }
+
+    println!();
+    for backend in ["llama.cpp", "ollama"] {
+        let total: u64 = models.iter().filter(|m| m.backend == backend).map(|m| m.bytes).sum();
+        if total > 0 {
+            println!("{} {}: {}", "Total".cyan(), backend, format_bytes(total));
+        }
+    }
+    let stale: Vec<usize> = (0..models.len()).filter(|&i| is_stale(&models[i])).collect();
+    let stale_bytes: u64 = stale.iter().map(|&i| models[i].bytes).sum();
+    println!(
+        "{} {} model(s), {} unused for {}+ days and not in any config or preset (highlighted)",
+        "Stale:".yellow(),
+        stale.len(),
+        format_bytes(stale_bytes),
+        stale_days
+    );
+    println!();
+
+    if dry_run || !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    print!("{} ", "Delete which? numbers (e.g. 3 5), 'stale', or Enter to keep everything:".cyan());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(());
+    }
+
+    let selected: Vec<usize> = if input.eq_ignore_ascii_case("stale") {
+        stale
+    } else {
+        input
+            .split([' ', ','])
+            .filter(|s| !s.is_empty())
+            .map(|n| {
+                n.parse::<usize>()
+                    .ok()
+                    .filter(|n| (1..=models.len()).contains(n))
+                    .map(|n| n - 1)
+                    .ok_or_else(|| anyhow::anyhow!("No model numbered {}", n))
+            })
+            .collect::<Result<_>>()?
+    };
+    if selected.is_empty() {
+        return Ok(());
+    }
+
+    let freed: u64 = selected.iter().map(|&i| models[i].bytes).sum();
+    for &i in &selected {
+        let model = &models[i];
+        let kept = if model.used_by.is_empty() {
+            String::new()
+        } else {
+            format!(" (used by {})", model.used_by.join(", ")).red().to_string()
+        };
+        println!("  {} {} [{}]{}", "✗".red(), model.name, model.backend, kept);
+    }
+    print!("{} ", format!("Delete {} model(s), freeing {}? [y/N]", selected.len(), format_bytes(freed)).red());
+    io::stdout().flush()?;
+    let mut confirm = String::new();
+    io::stdin().read_line(&mut confirm)?;
+    if !matches!(confirm.trim().to_lowercase().as_str(), "y" | "yes") {
+        println!("Nothing deleted");
+        return Ok(());
+    }
+
+    for &i in &selected {
+        let model = &models[i];
+        let result = match model.backend {
+            "ollama" => OllamaBackend::new().remove_model(&model.name).await,
+            _ => LlamaCppBackend::new().remove_model(&model.name).await,
+        };
+        if let Err(e) = result {
+            println!("{} {}: {}", "✗".red(), model.name, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Scrape ollama.com's search page; the library has no public search API
+async fn search_ollama(client: &reqwest::Client, term: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let html = client
+        .get(OLLAMA_SEARCH_URL)
+        .query(&[("q", term)])
+        .send()
+        .await
+        .context("Failed to reach ollama.com")?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    let mut results = Vec::new();
+    // Each result is an element tagged with a bare `x-test-model` attribute
+    for block in html.split("x-test-model ").skip(1) {
+        let Some(name) = between(block, "href=\"/library/", "\"") else {
+            continue;
+        };
+        let description = tagged_text(block, "<p").filter(|d| !d.is_empty());
+        let popularity = attribute_text(block, "x-test-pull-count").first().map(|p| format!("{} pulls", p));
+
+        let sizes = attribute_text(block, "x-test-size");
+        let variants = if sizes.is_empty() {
+            vec![Variant {
+                label: "latest".to_string(),
+                install_name: name.to_string(),
+                bytes: None,
+            }]
+        } else {
+            sizes
+                .into_iter()
+                .map(|size| Variant {
+                    bytes: ollama_size_bytes(&size),
+                    install_name: format!("{}:{}", name, size),
+                    label: size,
+                })
+                .collect()
+        };
+
+        results.push(SearchResult {
+            name: name.to_string(),
+            backend: "ollama",
+            description,
+            popularity,
+            variants,
+        });
+        if results.len() >= limit {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+#[derive(Deserialize)]
+struct HfModel {
+    id: String,
+    #[serde(default)]
+    downloads: u64,
+}
+
+#[derive(Deserialize)]
+struct HfFile {
+    #[serde(rename = "type")]
+    kind: String,
+    path: String,
+    #[serde(default)]
+    size: u64,
+}
+
+async fn search_huggingface(client: &reqwest::Client, term: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let limit_param = limit.to_string();
+    let models: Vec<HfModel> = client
+        .get(HF_API_URL)
+        .query(&[
+            ("search", term),
+            ("filter", "gguf"),
+            ("sort", "downloads"),
+            ("direction", "-1"),
+            ("limit", limit_param.as_str()),
+        ])
+        .send()
+        .await
+        .context("Failed to reach huggingface.co")?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse HuggingFace search results")?;
+
+    let mut results = Vec::new();
+    for model in models {
+        let files: Vec<HfFile> = match client
+            .get(format!("{}/{}/tree/main", HF_API_URL, model.id))
+            .query(&[("recursive", "true")])
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => response.json().await.unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        let mut variants: Vec<Variant> = Vec::new();
+        for file in files.iter().filter(|f| f.kind == "file" && f.path.ends_with(".gguf")) {
+            // Split models are listed once, by their first part, with the total size
+            if file.path.contains("-of-") && !file.path.contains("-00001-of-") {
+                continue;
+            }
+            let bytes = if let Some((prefix, _)) = file.path.split_once("-00001-of-") {
+                files.iter().filter(|f| f.path.starts_with(prefix) && f.path.contains("-of-")).map(|f| f.size).sum()
+            } else {
+                file.size
+            };
+            variants.push(Variant {
+                label: quant_label(&file.path),
+                install_name: format!("hf:{}/{}", model.id, file.path),
+                bytes: Some(bytes),
+            });
+        }
+        if variants.is_empty() {
+            continue;
+        }
+        variants.sort_by_key(|v| v.bytes);
+
+        results.push(SearchResult {
+            name: model.id,
+            backend: "llamacpp",
+            description: None,
+            popularity: Some(format!("{} downloads", model.downloads)),
+            variants,
+        });
+    }
+    Ok(results)
+}
+
+/// Quantization named in a GGUF file name (Q4_K_M, IQ3_XS, F16, ...),
+/// falling back to the file name itself
+fn quant_label(path: &str) -> String {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let stem = file.trim_end_matches(".gguf");
+    let stem = stem.split("-00001-of-").next().unwrap_or(stem);
+    quant_segment(stem)
+        .map(str::to_uppercase)
+        .unwrap_or_else(|| file.to_string())
+}
+
+/// The part of a GGUF file stem that names its quantization, as written
+pub fn quant_segment(stem: &str) -> Option<&str> {
+    stem.split(['-', '.']).find(|part| {
+        let part = part.to_uppercase();
+        let digits_after = |prefix: &str| {
+            part.strip_prefix(prefix)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| c.is_ascii_digit())
+        };
+        digits_after("Q") || digits_after("IQ") || digits_after("TQ") || matches!(part.as_str(), "F16" | "BF16" | "F32")
+    })
+}
+
+/// Approximate download size for an Ollama tag like `8b`, `270m` or `8x7b`,
+/// assuming the default ~4.5-bit quantization
+fn ollama_size_bytes(tag: &str) -> Option<u64> {
+    let tag = tag.to_lowercase();
+    let (count, tag) = match tag.split_once('x') {
+        Some((experts, rest)) => (experts.parse::<f64>().ok()?, rest.to_string()),
+        None => (1.0, tag),
+    };
+    let params = if let Some(b) = tag.strip_suffix('b') {
+        b.trim_start_matches('e').parse::<f64>().ok()? * 1e9
+    } else if let Some(m) = tag.strip_suffix('m') {
+        m.parse::<f64>().ok()? * 1e6
+    } else {
+        return None;
+    };
+    Some((count * params * 0.6) as u64)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let gb = bytes as f64 / 1_000_000_000.0;
+    if gb >= 1.0 {
+        format!("{:.1} GB", gb)
+    } else {
+        format!("{:.0} MB", bytes as f64 / 1_000_000.0)
+    }
+}
+
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let from = text.find(start)? + start.len();
+    let len = text[from..].find(end)?;
+    Some(&text[from..from + len])
+}
+
+/// Inner text of the first element opening with `tag` (e.g. "<p")
+fn tagged_text(block: &str, tag: &str) -> Option<String> {
+    let start = block.find(tag)?;
+    let text = between(&block[start..], ">", "<")?;
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Inner text of every element carrying the given attribute
+fn attribute_text(block: &str, attribute: &str) -> Vec<String> {
+    block
+        .match_indices(attribute)
+        .filter_map(|(i, _)| between(&block[i..], ">", "<"))
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect()
+}
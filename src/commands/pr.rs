@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::llm::LlamaClient;
+use crate::llm::client::Message;
+
+/// Maximum diff bytes sent to the model
+const MAX_DIFF_BYTES: usize = 40_000;
+
+/// Repository-local PR templates, checked in order
+const TEMPLATE_PATHS: &[&str] = &[
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+];
+
+const DEFAULT_TEMPLATE: &str = "## Summary\n\n## Changes\n\n## Testing\n";
+
+pub async fn execute(
+    base: Option<String>,
+    create: bool,
+    draft: bool,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent_name: Option<String>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    let base = match base.or_else(|| config.pr.base.clone()) {
+        Some(base) => base,
+        None => detect_base()?,
+    };
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+
+    let commits = git(&["log", "--no-merges", "--format=- %s%n%b", &format!("{}..HEAD", base)])?;
+    if commits.trim().is_empty() {
+        anyhow::bail!("No commits between {} and {}", base, branch);
+    }
+    let stat = git(&["diff", "--stat", &format!("{}...HEAD", base)])?;
+    let mut diff = git(&["diff", &format!("{}...HEAD", base)])?;
+    if diff.len() > MAX_DIFF_BYTES {
+        let mut cut = MAX_DIFF_BYTES;
+        while !diff.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        diff.truncate(cut);
+        diff.push_str("\n... [diff truncated]");
+    }
+
+    let template = load_template(config.pr.template.as_deref())?;
+
+    // Complements the release-manager agent, which owns changelog conventions
+    let system_prompt = Agent::load_or_create_defaults(agent_name.as_deref().unwrap_or("release-manager"))
+        .map(|a| a.system_prompt)
+        .unwrap_or_else(|_| "You are a release engineering specialist.".to_string());
+
+    let prompt = format!(
+        "Write a pull request for branch `{}` (base `{}`).\n\n\
+        Reply with the PR title on the first line (imperative mood, under 72 characters, no prefix), \
+        then a blank line, then the PR body in Markdown following this template:\n\n\
+        ```markdown\n{}\n```\n\n\
+        Describe only what the changes do. Do not invent testing that is not evident from the commits.\n\n\
+        Commits:\n{}\n\nDiff stat:\n```\n{}\n```\n\nDiff:\n```diff\n{}\n```",
+        branch, base, template.trim(), commits.trim(), stat.trim(), diff
+    );
+
+    eprintln!("{} {} → {}", "📝 Drafting PR".cyan().bold(), branch.yellow(), base.yellow());
+
+    let client = LlamaClient::for_config(&config, server_url, model);
+    let response = client
+        .chat_completion(
+            &[
+                Message {
+                    role: "system".to_string(),
+                    content: system_prompt,
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt,
+                },
+            ],
+            None,
+        )
+        .await
+        .context("Failed to get response from LLM")?;
+
+    let content = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+    let (title, body) = split_title_body(&content);
+
+    println!("{}", title.bold());
+    println!();
+    println!("{}", body);
+
+    if create {
+        let base_branch = base.split_once('/').map(|(_, b)| b).unwrap_or(&base);
+        let mut cmd = Command::new("gh");
+        cmd.args(["pr", "create", "--title", &title, "--body", &body, "--base", base_branch]);
+        if draft {
+            cmd.arg("--draft");
+        }
+
+        let status = cmd.status().context("Failed to run gh (is the GitHub CLI installed?)")?;
+        if !status.success() {
+            anyhow::bail!("gh pr create failed");
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the comparison base: the upstream branch, else the remote default branch
+fn detect_base() -> Result<String> {
+    if let Ok(upstream) = git(&["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{upstream}"]) {
+        // A pushed feature branch tracks itself; compare against the default branch instead
+        let head = git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+        if !upstream.ends_with(&format!("/{}", head)) {
+            return Ok(upstream);
+        }
+    }
+
+    if let Ok(default) = git(&["rev-parse", "--abbrev-ref", "origin/HEAD"]) {
+        return Ok(default);
+    }
+
+    for candidate in ["origin/main", "origin/master", "main", "master"] {
+        if git(&["rev-parse", "--verify", "--quiet", candidate]).is_ok() {
+            return Ok(candidate.to_string());
+        }
+    }
+
+    anyhow::bail!("Could not determine a base branch; pass one with --base")
+}
+
+fn load_template(configured: Option<&str>) -> Result<String> {
+    if let Some(path) = configured {
+        let path = shellexpand::tilde(path).to_string();
+        return fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read PR template: {}", path));
+    }
+
+    for path in TEMPLATE_PATHS {
+        if let Ok(content) = fs::read_to_string(Path::new(path)) {
+            return Ok(content);
+        }
+    }
+
+    Ok(DEFAULT_TEMPLATE.to_string())
+}
+
+/// First non-empty line is the title, the rest is the body
+fn split_title_body(content: &str) -> (String, String) {
+    let content = content.trim();
+    let (title, body) = content.split_once('\n').unwrap_or((content, ""));
+    let title = title.trim().trim_start_matches('#').trim().trim_matches('*').trim();
+    (title.to_string(), body.trim().to_string())
+}
+
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
@@ -1,6 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
 
 use crate::config::Config;
 use crate::agents::Agent;
@@ -19,6 +24,8 @@ struct PresetStats {
     name: String,
     avg_tokens_per_second: f64,
     context_size: u32,
+    #[serde(default)]
+    avg_quality: Option<f64>,
 }
 
 pub fn execute() -> Result<()> {
@@ -56,18 +63,28 @@ pub fn execute() -> Result<()> {
             .map(|p| p.context_size / 1024)
             .unwrap_or(0)
     );
-    println!("   🧠 Best Reasoning: {}",
-        benchmark.best_reasoning_preset.green().bold()
-    );
+    match benchmark.presets.iter()
+        .find(|p| p.name == benchmark.best_reasoning_preset)
+        .and_then(|p| p.avg_quality)
+    {
+        Some(quality) => println!("   🧠 Best Reasoning: {} (quality {:.1}/10)",
+            benchmark.best_reasoning_preset.green().bold(), quality),
+        None => println!("   🧠 Best Reasoning: {}",
+            benchmark.best_reasoning_preset.green().bold()),
+    }
     println!();
 
     // Show all presets with stats
     println!("{}", "Available Presets:".cyan().bold());
     for preset in &benchmark.presets {
-        println!("   • {} - {:.1} tok/s, {}k context",
+        let quality = preset.avg_quality
+            .map(|q| format!(", quality {:.1}/10", q))
+            .unwrap_or_default();
+        println!("   • {} - {:.1} tok/s, {}k context{}",
             preset.name.green(),
             preset.avg_tokens_per_second,
-            preset.context_size / 1024
+            preset.context_size / 1024,
+            quality
         );
     }
     println!();
@@ -89,7 +106,7 @@ pub fn execute() -> Result<()> {
         if let Ok(agent) = Agent::load(agent_name) {
             if let Some(preset) = agent.preferred_preset {
                 preset_to_agents.entry(preset)
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push(agent_name.clone());
             }
         }
@@ -117,3 +134,259 @@ pub fn execute() -> Result<()> {
 
     Ok(())
 }
+
+fn presets_dir() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("presets"))
+}
+
+fn preset_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name == "auto" || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("Invalid preset name: '{}'", name);
+    }
+    Ok(presets_dir()?.join(format!("{}.toml", name)))
+}
+
+fn existing_preset(name: &str) -> Result<PathBuf> {
+    let path = preset_path(name)?;
+    if !path.exists() {
+        anyhow::bail!("Preset '{}' not found (see `vork presets list`)", name);
+    }
+    Ok(path)
+}
+
+/// List preset files, marking the one last switched to
+pub fn list() -> Result<()> {
+    let dir = presets_dir()?;
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    let path = e.path();
+                    if path.extension()? != "toml" {
+                        return None;
+                    }
+                    Some(path.file_stem()?.to_string_lossy().to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    if names.is_empty() {
+        println!("No presets in {}", dir.display());
+        println!("Create one from your current config with {}", "vork presets new <name>".cyan());
+        return Ok(());
+    }
+
+    let active = Config::active_preset();
+
+    println!("{}", "Presets:".cyan().bold());
+    for name in names {
+        let path = dir.join(format!("{}.toml", name));
+        match fs::read_to_string(&path).map(|c| toml::from_str::<Config>(&c)) {
+            Ok(Ok(preset)) => {
+                let active = active.as_ref().filter(|active| active.name == name);
+                println!(
+                    "  {} {} - {} ({}k ctx){}",
+                    if active.is_some() { "●".green() } else { "•".normal() },
+                    name.green(),
+                    preset.assistant.model,
+                    preset.llamacpp.context_size / 1024,
+                    if active.is_some_and(|active| active.modified) {
+                        " — config.toml edited since".yellow().to_string()
+                    } else {
+                        String::new()
+                    }
+                );
+            }
+            _ => println!("  {} {} - {}", "✗".red(), name.red(), "invalid preset file".red()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a preset from the current config (or another preset) and open it in $EDITOR
+pub fn new(name: &str, from: Option<String>, edit: bool) -> Result<()> {
+    let path = preset_path(name)?;
+    if path.exists() {
+        anyhow::bail!("Preset '{}' already exists; use `vork presets edit {}`", name, name);
+    }
+
+    let content = match from {
+        Some(source) => fs::read_to_string(existing_preset(&source)?)?,
+        None => format!(
+            "# Preset '{}' - created from the current config\n\n{}",
+            name,
+            toml::to_string_pretty(&Config::load_file()?)?
+        ),
+    };
+
+    fs::create_dir_all(presets_dir()?)?;
+    if edit {
+        edit_and_save(&path, &content)?;
+    } else {
+        save_validated(&path, &content)?;
+    }
+    Ok(())
+}
+
+pub fn edit(name: &str) -> Result<()> {
+    let path = existing_preset(name)?;
+    let content = fs::read_to_string(&path)?;
+    edit_and_save(&path, &content)
+}
+
+pub fn delete(name: &str, yes: bool) -> Result<()> {
+    let path = existing_preset(name)?;
+
+    if !yes {
+        print!("Delete preset '{}'? [y/N] ", name);
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    fs::remove_file(&path)?;
+    println!("{} Deleted preset {}", "✓".green(), name);
+    Ok(())
+}
+
+pub fn copy(from: &str, to: &str) -> Result<()> {
+    let source = existing_preset(from)?;
+    let target = preset_path(to)?;
+    if target.exists() {
+        anyhow::bail!("Preset '{}' already exists", to);
+    }
+
+    fs::copy(&source, &target)?;
+    println!("{} Copied {} → {}", "✓".green(), from, to.green());
+    Ok(())
+}
+
+/// Print a preset and how it differs from the current config
+pub fn show(name: &str) -> Result<()> {
+    let path = existing_preset(name)?;
+    let content = fs::read_to_string(&path)?;
+    let preset: Config = toml::from_str(&content)
+        .with_context(|| format!("Preset '{}' is not a valid config", name))?;
+
+    println!("{} {}", "Preset:".cyan().bold(), path.display());
+    println!();
+    println!("{}", content.trim_end());
+    println!();
+
+    let mut preset_values = BTreeMap::new();
+    let mut current_values = BTreeMap::new();
+    flatten(&toml::Value::try_from(&preset)?, "", &mut preset_values);
+    flatten(&toml::Value::try_from(Config::load_file()?)?, "", &mut current_values);
+
+    let mut keys: Vec<&String> = preset_values.keys().chain(current_values.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let changes: Vec<(&String, Option<&String>, Option<&String>)> = keys
+        .into_iter()
+        .map(|k| (k, current_values.get(k), preset_values.get(k)))
+        .filter(|(_, current, preset)| current != preset)
+        .collect();
+
+    println!("{}", "Differences from current config:".cyan().bold());
+    if changes.is_empty() {
+        println!("  {}", "none (this preset is active)".green());
+    }
+    for (key, current, preset) in changes {
+        println!(
+            "  {} {} → {}",
+            key.yellow(),
+            current.map(String::as_str).unwrap_or("(unset)").red(),
+            preset.map(String::as_str).unwrap_or("(unset)").green()
+        );
+    }
+
+    Ok(())
+}
+
+/// Edit a copy of `content` in $EDITOR and save it to `path` once it parses
+fn edit_and_save(path: &std::path::Path, content: &str) -> Result<()> {
+    let scratch = path.with_extension("toml.edit");
+    fs::write(&scratch, content)?;
+
+    let result = (|| loop {
+        open_editor(&scratch)?;
+        let edited = fs::read_to_string(&scratch)?;
+
+        match save_validated(path, &edited) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                println!("{} {:#}", "✗".red(), e);
+                print!("Re-open the editor? [Y/n] ");
+                io::stdout().flush()?;
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                if matches!(input.trim().to_lowercase().as_str(), "n" | "no") {
+                    anyhow::bail!("Preset not saved");
+                }
+            }
+        }
+    })();
+
+    let _ = fs::remove_file(&scratch);
+    result
+}
+
+/// Write a preset only if it parses as a config; semantic problems are warnings
+/// since presets are often written for another machine
+fn save_validated(path: &std::path::Path, content: &str) -> Result<()> {
+    let config: Config = toml::from_str(content).context("Preset is not a valid config")?;
+
+    fs::write(path, content)?;
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    println!("{} Saved preset {}", "✓".green(), name.green());
+
+    for problem in config.validate() {
+        println!("  {} {}", "⚠️".yellow(), problem);
+    }
+    Ok(())
+}
+
+/// Open `path` in $VISUAL or $EDITOR and wait for it to close
+pub fn open_editor(path: &std::path::Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+
+    // Allow editors with arguments, e.g. EDITOR="code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor))?;
+
+    if !status.success() {
+        anyhow::bail!("Editor exited with {}", status);
+    }
+    Ok(())
+}
+
+/// Flatten a TOML tree into dotted keys for diffing
+fn flatten(value: &toml::Value, prefix: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(value, &key, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::backends::llamacpp::LlamaCppBackend;
+use crate::config::Config;
+
+/// Target types accepted by llama-quantize
+const QUANT_TYPES: &[&str] = &[
+    "Q4_0", "Q4_1", "Q5_0", "Q5_1", "IQ2_XXS", "IQ2_XS", "IQ2_S", "IQ2_M", "IQ1_S", "IQ1_M",
+    "TQ1_0", "TQ2_0", "Q2_K", "Q2_K_S", "IQ3_XXS", "IQ3_S", "IQ3_M", "Q3_K", "IQ3_XS", "Q3_K_S",
+    "Q3_K_M", "Q3_K_L", "IQ4_NL", "IQ4_XS", "Q4_K", "Q4_K_S", "Q4_K_M", "Q5_K", "Q5_K_S", "Q5_K_M",
+    "Q6_K", "Q8_0", "F16", "BF16", "F32",
+];
+
+/// One model produced by `vork quantize`, kept in `~/.vork/quantized.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedModel {
+    pub output: PathBuf,
+    pub source: PathBuf,
+    pub quant: String,
+    pub created: DateTime<Local>,
+}
+
+impl QuantizedModel {
+    fn manifest_path() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("quantized.json"))
+    }
+
+    /// Recorded outputs that still exist on disk
+    pub fn load_all() -> Vec<Self> {
+        let Ok(path) = Self::manifest_path() else {
+            return vec![];
+        };
+        let entries: Vec<Self> = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        entries.into_iter().filter(|e| e.output.exists()).collect()
+    }
+
+    fn record(&self) -> Result<()> {
+        let mut entries = Self::load_all();
+        entries.retain(|e| e.output != self.output);
+        entries.push(self.clone());
+        fs::create_dir_all(Config::config_dir()?)?;
+        fs::write(Self::manifest_path()?, serde_json::to_string_pretty(&entries)?)
+            .context("Failed to update quantized model list")
+    }
+}
+
+pub struct QuantizeOptions {
+    pub to: String,
+    pub output: Option<PathBuf>,
+    pub imatrix: Option<PathBuf>,
+    pub force: bool,
+}
+
+pub fn execute(model: &str, options: QuantizeOptions) -> Result<()> {
+    let config = Config::load()?;
+    let backend = LlamaCppBackend::new();
+
+    let quant = options.to.to_uppercase();
+    if !QUANT_TYPES.contains(&quant.as_str()) {
+        anyhow::bail!("Unknown quantization '{}'. Supported: {}", options.to, QUANT_TYPES.join(", "));
+    }
+    if (quant.starts_with("IQ1") || quant.starts_with("IQ2")) && options.imatrix.is_none() {
+        println!(
+            "{} {} usually needs an importance matrix (--imatrix) for usable quality",
+            "⚠️ ".yellow(),
+            quant
+        );
+    }
+
+    let tool = backend
+        .find_tool("llama-quantize")
+        .or_else(|| backend.find_tool("quantize"))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "llama-quantize not found next to llama-server or on PATH. Build it with \
+                `cmake --build build --target llama-quantize` in your llama.cpp checkout"
+            )
+        })?;
+
+    let source = backend.find_model(model)?;
+    let output = match options.output {
+        Some(path) => path,
+        None => source.with_file_name(output_name(&source, &quant)),
+    };
+    if output == source {
+        anyhow::bail!("{} is already {}", source.display(), quant);
+    }
+    if output.exists() && !options.force {
+        anyhow::bail!("{} already exists (use --force to overwrite)", output.display());
+    }
+
+    let source_size = fs::metadata(&source).map(|m| m.len()).unwrap_or(0);
+    println!("{} {}", "Quantizing:".green().bold(), source.display());
+    println!("{} {}", "Target:".cyan(), quant.yellow());
+    println!("{} {}", "Output:".cyan(), output.display());
+    println!();
+
+    let mut command = Command::new(&tool);
+    if let Some(ref imatrix) = options.imatrix {
+        command.arg("--imatrix").arg(imatrix);
+    }
+    command
+        .arg(&source)
+        .arg(&output)
+        .arg(&quant)
+        .arg(config.llamacpp.threads.to_string());
+
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {}", tool.display()))?;
+    if !status.success() {
+        let _ = fs::remove_file(&output);
+        anyhow::bail!("llama-quantize failed ({})", status);
+    }
+
+    QuantizedModel {
+        output: output.clone(),
+        source: source.clone(),
+        quant: quant.clone(),
+        created: Local::now(),
+    }
+    .record()?;
+
+    let output_size = fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+    println!();
+    println!(
+        "{} {} → {} ({:.1} GB → {:.1} GB)",
+        "✓".green(),
+        file_name(&source),
+        file_name(&output).green(),
+        source_size as f64 / 1e9,
+        output_size as f64 / 1e9
+    );
+    if output.starts_with(backend.models_dir()) {
+        println!(
+            "Use it with: vork config set assistant.model {}",
+            backend.get_model_alias(&output)
+        );
+    } else {
+        println!(
+            "{} {} is outside models_dir ({}), so vork will not list it",
+            "Note:".yellow(),
+            output.display(),
+            config.llamacpp.models_dir
+        );
+    }
+
+    Ok(())
+}
+
+/// Source file name with its quantization swapped for `quant`, or with
+/// `quant` appended when the name does not mention one
+fn output_name(source: &Path, quant: &str) -> String {
+    let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
+    match super::models::quant_segment(stem).and_then(|segment| stem.rfind(segment).map(|i| (i, segment.len()))) {
+        Some((start, len)) => format!("{}{}{}.gguf", &stem[..start], quant, &stem[start + len..]),
+        None => format!("{}-{}.gguf", stem, quant),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
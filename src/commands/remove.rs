@@ -24,6 +24,22 @@ pub async fn execute(model: &str) -> Result<()> {
         }
     }
 
+    if config.llamacpp.enabled {
+        let llamacpp = backends::llamacpp::LlamaCppBackend::new();
+        if let Ok(models) = llamacpp.list_models().await {
+            if models.iter().any(|m| m.name == model) {
+                println!(
+                    "{} {} {} {}",
+                    "Removing".red().bold(),
+                    model.yellow(),
+                    "from".red().bold(),
+                    "llama.cpp".cyan()
+                );
+                return llamacpp.remove_model(model).await;
+            }
+        }
+    }
+
     anyhow::bail!(
         "{} {} {}",
         "Model".red(),
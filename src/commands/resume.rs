@@ -3,7 +3,8 @@ use colored::Colorize;
 use std::io::{self, Write};
 
 use crate::config::Config;
-use crate::llm::{LlamaClient, Session, ApprovalSystem};
+use crate::llm::{LlamaClient, Session, ApprovalSystem, UsageTracker};
+use crate::llm::arguments;
 use crate::llm::tools::{get_available_tools, execute_tool};
 
 pub async fn execute(session_id: Option<String>, last: bool) -> Result<()> {
@@ -48,15 +49,21 @@ pub async fn execute(session_id: Option<String>, last: bool) -> Result<()> {
             .clone()
     };
 
+    session.resume_transcript();
     println!("{}", "=== Resuming Session ===".green().bold());
     println!("{} {}", "Session ID:".cyan(), session.id);
     println!("{} {}", "Working Dir:".cyan(), session.working_directory.display());
+    if let Some(notice) = session.working_dir_notice() {
+        println!("{}", notice.yellow());
+    }
     println!();
 
-    let client = LlamaClient::new(
-        config.assistant.server_url.clone(),
+    let client = LlamaClient::for_config(
+        &config,
+        config.chat_server_url(),
         config.assistant.model.clone(),
     );
+    let mut usage = UsageTracker::new("resume", &session.id, &config.assistant.model);
     let approval_system = ApprovalSystem::new(
         config.assistant.approval_policy.clone(),
         config.assistant.sandbox_mode.clone(),
@@ -89,13 +96,19 @@ pub async fn execute(session_id: Option<String>, last: bool) -> Result<()> {
 
         // Main loop: keep calling LLM until it stops requesting tool calls
         loop {
+            usage.check_budget()?;
+            let tools = get_available_tools();
+            if let Some(notice) = session.conversation.fit_to_context(&client, Some(&tools)).await? {
+                println!("{}", notice.yellow());
+            }
             let response = client
                 .chat_completion(
                     session.conversation.get_messages(),
-                    Some(get_available_tools()),
+                    Some(&tools),
                 )
                 .await
                 .context("Failed to get response from LLM")?;
+            usage.record_response(response.usage.as_ref());
 
             let choice = response
                 .choices
@@ -106,10 +119,15 @@ pub async fn execute(session_id: Option<String>, last: bool) -> Result<()> {
             if let Some(tool_calls) = &choice.message.tool_calls {
                 // Execute each tool call
                 for tool_call in tool_calls {
+                    usage.record_tool_call();
                     let tool_name = &tool_call.function.name;
-                    let arguments: serde_json::Value =
-                        serde_json::from_str(&tool_call.function.arguments)
-                            .context("Failed to parse tool arguments")?;
+                    let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                        Ok(arguments) => arguments,
+                        Err(e) => {
+                            session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                            continue;
+                        }
+                    };
 
                     println!(
                         "{} {} {}",
@@ -118,7 +136,7 @@ pub async fn execute(session_id: Option<String>, last: bool) -> Result<()> {
                         tool_name.yellow().bold()
                     );
 
-                    match execute_tool(tool_name, arguments, Some(&approval_system)).await {
+                    match execute_tool(tool_name, arguments, Some(&approval_system), &session.tool_dir()).await {
                         Ok(result) => {
                             session.conversation.add_tool_result(tool_name, &result);
                         }
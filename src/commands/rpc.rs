@@ -0,0 +1,495 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::error::VorkError;
+use crate::llm::tools::{execute_tool, get_available_tools};
+use crate::llm::{arguments, ApprovalSystem, LlamaClient, Session, UsageTracker};
+
+/// Maximum number of LLM round-trips per prompt
+const MAX_ITERATIONS: usize = 50;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A prompt is running; the server handles one request at a time
+const SERVER_BUSY: i64 = -32002;
+const REQUEST_FAILED: i64 = -32803;
+
+const METHODS: &[&str] = &["initialize", "session/open", "session/prompt", "session/close", "shutdown", "exit"];
+
+static NEXT_APPROVAL_ID: AtomicUsize = AtomicUsize::new(1);
+
+struct RpcError {
+    code: i64,
+    message: String,
+    /// `VorkError::kind`, sent as `error.data.kind` so plugins can react to it
+    kind: Option<&'static str>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), kind: None }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(error: anyhow::Error) -> Self {
+        let kind = VorkError::find(&error).map(VorkError::kind);
+        Self { kind, ..Self::new(REQUEST_FAILED, format!("{:#}", error)) }
+    }
+}
+
+type RpcResult = std::result::Result<Value, RpcError>;
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct OpenParams {
+    agent: Option<String>,
+    /// Continue a saved session (`vork resume` ids work here too)
+    resume: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptParams {
+    session_id: String,
+    prompt: String,
+    #[serde(default)]
+    files: Vec<FileContext>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionParams {
+    session_id: String,
+}
+
+/// A file the editor attaches to a prompt. `text` is the buffer content,
+/// which may differ from the disk copy when the buffer is unsaved.
+#[derive(Debug, Deserialize)]
+struct FileContext {
+    uri: Option<String>,
+    path: Option<String>,
+    text: Option<String>,
+    selection: Option<Range>,
+}
+
+/// LSP position: zero-based line and UTF-16 character offset
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Position {
+    line: usize,
+    character: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TextEdit {
+    range: Range,
+    new_text: String,
+}
+
+struct EditorSession {
+    session: Session,
+    usage: UsageTracker,
+    /// Its limits apply while this session's prompts run
+    agent: Option<Agent>,
+}
+
+/// Serve editor plugins over stdio: one JSON-RPC 2.0 message per line.
+/// Everything meant for humans goes to stderr.
+pub async fn execute(server_url: Option<String>, model: Option<String>, agent: Option<String>) -> Result<()> {
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    let client = LlamaClient::for_config(&config, server_url.clone(), model.clone());
+    let approval = ApprovalSystem::new(
+        config.assistant.approval_policy.clone(),
+        config.assistant.sandbox_mode.clone(),
+    )
+    .with_prompter(Box::new(ask_editor));
+    let mut sessions: HashMap<String, EditorSession> = HashMap::new();
+
+    eprintln!("{} vork rpc: {} at {}", "🔌".cyan(), model, server_url);
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(line.trim()) {
+            Ok(message) => message,
+            Err(e) => {
+                send(&error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())));
+                continue;
+            }
+        };
+
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method {
+            "initialize" => Ok(json!({
+                "name": "vork",
+                "version": env!("CARGO_PKG_VERSION"),
+                "methods": METHODS,
+                "model": model,
+            })),
+            "session/open" => open_session(&mut sessions, params, agent.as_deref(), &model),
+            "session/prompt" => prompt(&mut sessions, params, &client, &approval).await,
+            "session/close" => close_session(&mut sessions, params),
+            "shutdown" => Ok(Value::Null),
+            "exit" => break,
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        };
+
+        // Notifications (no id) get no reply
+        if let Some(id) = message.get("id").cloned() {
+            match result {
+                Ok(result) => send(&json!({ "jsonrpc": "2.0", "id": id, "result": result })),
+                Err(error) => send(&error_response(id, error)),
+            }
+        }
+    }
+
+    for editor in sessions.values_mut() {
+        editor.session.save()?;
+    }
+    Ok(())
+}
+
+fn open_session(
+    sessions: &mut HashMap<String, EditorSession>,
+    params: Value,
+    default_agent: Option<&str>,
+    model: &str,
+) -> RpcResult {
+    let params: OpenParams = parse_params(params)?;
+    let mut session = match params.resume {
+        Some(ref id) => {
+            let mut session = Session::load(id).with_context(|| format!("Failed to load session: {}", id))?;
+            session.resume_transcript();
+            session
+        }
+        None => Session::new(std::env::current_dir().map_err(anyhow::Error::from)?),
+    };
+    if let Some(notice) = session.working_dir_notice() {
+        eprintln!("{}", notice);
+    }
+    // Ids are timestamps, so sessions opened in the same second need telling apart
+    let base = session.id.clone();
+    let mut n = 1;
+    while sessions.contains_key(&session.id) {
+        n += 1;
+        session.id = format!("{}-{}", base, n);
+    }
+
+    let name = params.agent.as_deref().or(default_agent);
+    let agent = name.map(Agent::load_or_create_defaults).transpose()?;
+    if let Some(ref agent) = agent {
+        session.conversation.set_system_prompt(agent.system_prompt.clone());
+    }
+    let mut usage = UsageTracker::new("rpc", &session.id, model);
+    usage.set_agent(name);
+
+    let id = session.id.clone();
+    sessions.insert(id.clone(), EditorSession { session, usage, agent });
+    Ok(json!({ "sessionId": id }))
+}
+
+fn close_session(sessions: &mut HashMap<String, EditorSession>, params: Value) -> RpcResult {
+    let params: SessionParams = parse_params(params)?;
+    let mut editor = sessions
+        .remove(&params.session_id)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown session: {}", params.session_id)))?;
+    editor.session.save()?;
+    Ok(Value::Null)
+}
+
+/// Run the agent on one prompt. Files the model writes are not touched on
+/// disk; they come back as a WorkspaceEdit for the editor to apply.
+async fn prompt(
+    sessions: &mut HashMap<String, EditorSession>,
+    params: Value,
+    client: &LlamaClient,
+    approval: &ApprovalSystem,
+) -> RpcResult {
+    let params: PromptParams = parse_params(params)?;
+    let editor = sessions
+        .get_mut(&params.session_id)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown session: {}", params.session_id)))?;
+    let cwd = editor.session.tool_dir();
+    approval.set_agent(editor.agent.as_ref());
+
+    // Editor buffers, then proposed edits, shadow the disk for this prompt
+    let mut overlay: HashMap<PathBuf, String> = HashMap::new();
+    let mut originals: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut content = params.prompt.clone();
+    for file in &params.files {
+        let path = file_path(file, &cwd)?;
+        if let Some(ref text) = file.text {
+            overlay.insert(path.clone(), text.clone());
+        }
+        let text = match overlay.get(&path) {
+            Some(text) => text.clone(),
+            None => fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?,
+        };
+        content.push_str(&file_context(&path, &cwd, &text, file.selection));
+    }
+    editor.session.conversation.add_user_message(content);
+
+    let mut message = None;
+    let mut commands = vec![];
+    let denied_before = approval.denied_count();
+
+    for _ in 0..MAX_ITERATIONS {
+        editor.usage.check_budget()?;
+        let tools = editor.agent.as_ref().is_none_or(|a| a.tools_enabled).then(get_available_tools);
+        if let Some(notice) = editor.session.conversation.fit_to_context(client, tools.as_deref()).await? {
+            eprintln!("{}", notice);
+        }
+        let response = client
+            .chat_completion(editor.session.conversation.get_messages(), tools.as_deref())
+            .await
+            .context("Failed to get response from LLM")?;
+        if let Some(notice) = client.take_failover_notice() {
+            eprintln!("{}", notice.yellow());
+        }
+        editor.usage.record_response(response.usage.as_ref());
+
+        let choice = response
+            .choices
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+        let Some(tool_calls) = &choice.message.tool_calls else {
+            if let Some(ref text) = choice.message.content {
+                editor.session.conversation.add_assistant_message(text.clone());
+                message = Some(text.clone());
+            }
+            break;
+        };
+
+        for tool_call in tool_calls {
+            let tool_name = tool_call.function.name.as_str();
+            let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                Ok(arguments) => arguments,
+                Err(e) => {
+                    editor.session.conversation.add_tool_result(tool_name, &format!("Error: {}", e));
+                    continue;
+                }
+            };
+            editor.usage.record_tool_call();
+            send(&json!({
+                "jsonrpc": "2.0",
+                "method": "session/progress",
+                "params": { "sessionId": params.session_id, "tool": tool_name, "arguments": arguments },
+            }));
+
+            let path = arguments["path"].as_str().map(|p| cwd.join(p));
+            let result = match (tool_name, path) {
+                ("read_file", Some(path)) if overlay.contains_key(&path) => {
+                    let text = &overlay[&path];
+                    Ok(format!("📖 Read {} lines from {}\n\n{}", text.lines().count(), path.display(), text))
+                }
+                ("write_file", Some(path)) => {
+                    let text = arguments["content"].as_str().unwrap_or_default().to_string();
+                    let shown = arguments["path"].as_str().unwrap_or_default();
+                    if approval.should_approve_write(shown)? {
+                        let previous = overlay.get(&path).cloned().or_else(|| fs::read_to_string(&path).ok());
+                        originals.entry(path.clone()).or_insert(previous);
+                        let lines = text.lines().count();
+                        overlay.insert(path, text);
+                        Ok(format!("✅ Proposed {} lines for {} (the editor applies the change)", lines, shown))
+                    } else {
+                        Err(VorkError::ApprovalDenied { action: format!("Write to {}", shown) }.into())
+                    }
+                }
+                _ => {
+                    let denied = approval.denied_count();
+                    let result = execute_tool(tool_name, arguments.clone(), Some(approval), &cwd).await;
+                    if tool_name == "bash_exec" && approval.denied_count() == denied {
+                        if let Some(command) = arguments["command"].as_str() {
+                            commands.push(command.to_string());
+                        }
+                    }
+                    result
+                }
+            };
+            let result = result.unwrap_or_else(|e| format!("Error: {}", e));
+            editor.session.conversation.add_tool_result(tool_name, &result);
+        }
+    }
+
+    editor.session.save()?;
+
+    let mut document_changes = vec![];
+    let mut changed: Vec<&PathBuf> = originals.keys().collect();
+    changed.sort();
+    for path in changed {
+        let uri = path_to_uri(path);
+        let original = originals[path].as_deref();
+        if original.is_none() {
+            document_changes.push(json!({ "kind": "create", "uri": uri, "options": { "ignoreIfExists": true } }));
+        }
+        if let Some(edit) = text_edit(original.unwrap_or_default(), &overlay[path]) {
+            document_changes.push(json!({ "textDocument": { "uri": uri, "version": null }, "edits": [edit] }));
+        }
+    }
+
+    Ok(json!({
+        "message": message,
+        "edit": { "documentChanges": document_changes },
+        "commands": commands,
+        "denied": approval.denied_count() - denied_before,
+    }))
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Ask the editor to approve an operation with an `approval/request` call,
+/// reading stdin until its answer arrives. Requests sent in the meantime are
+/// turned away, since the prompt that needs the answer is still running.
+fn ask_editor(message: &str) -> Result<bool> {
+    let id = format!("approval-{}", NEXT_APPROVAL_ID.fetch_add(1, Ordering::Relaxed));
+    send(&json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "approval/request",
+        "params": { "message": message },
+    }));
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(false);
+        }
+        let Ok(reply) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if reply.get("method").is_none() && reply["id"] == json!(id) {
+            return Ok(reply["result"]["approved"].as_bool().unwrap_or(false));
+        }
+        if let (Some(_), Some(other)) = (reply.get("method"), reply.get("id")) {
+            let error = RpcError::new(SERVER_BUSY, "Waiting for approval of the running prompt");
+            send(&error_response(other.clone(), error));
+        }
+    }
+}
+
+fn send(message: &Value) {
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{}", message);
+    let _ = stdout.flush();
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(kind) = error.kind {
+        body["data"] = json!({ "kind": kind });
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": body })
+}
+
+fn file_path(file: &FileContext, cwd: &Path) -> std::result::Result<PathBuf, RpcError> {
+    let path = match (&file.uri, &file.path) {
+        (Some(uri), _) => uri_to_path(uri).ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Not a file URI: {}", uri)))?,
+        (None, Some(path)) => PathBuf::from(path),
+        (None, None) => return Err(RpcError::new(INVALID_PARAMS, "File context needs a uri or path")),
+    };
+    Ok(cwd.join(path))
+}
+
+/// Prompt section for an attached file, limited to the selection when there is one
+fn file_context(path: &Path, cwd: &Path, text: &str, selection: Option<Range>) -> String {
+    let shown = path.strip_prefix(cwd).unwrap_or(path).display();
+    match selection {
+        Some(range) => {
+            let lines: Vec<&str> = text
+                .lines()
+                .skip(range.start.line)
+                .take(range.end.line.saturating_sub(range.start.line) + 1)
+                .collect();
+            format!(
+                "\n\nFile: {} (selected lines {}-{}):\n```\n{}\n```",
+                shown,
+                range.start.line + 1,
+                range.end.line + 1,
+                lines.join("\n")
+            )
+        }
+        None => format!("\n\nFile: {}:\n```\n{}\n```", shown, text.trim_end()),
+    }
+}
+
+/// One edit replacing the lines that differ between `original` and `updated`
+fn text_edit(original: &str, updated: &str) -> Option<TextEdit> {
+    if original == updated {
+        return None;
+    }
+    let old: Vec<&str> = original.split_inclusive('\n').collect();
+    let new: Vec<&str> = updated.split_inclusive('\n').collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    Some(TextEdit {
+        range: Range {
+            start: position_at(&old, prefix),
+            end: position_at(&old, old.len() - suffix),
+        },
+        new_text: new[prefix..new.len() - suffix].concat(),
+    })
+}
+
+/// Position of the start of line `index`, or the end of the text when
+/// `index` is past the last line
+fn position_at(lines: &[&str], index: usize) -> Position {
+    match lines.last() {
+        Some(last) if index >= lines.len() && !last.ends_with('\n') => Position {
+            line: lines.len() - 1,
+            character: last.encode_utf16().count(),
+        },
+        _ => Position { line: index, character: 0 },
+    }
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let encoded: Vec<String> = path
+        .to_string_lossy()
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect();
+    format!("file://{}", encoded.join("/"))
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    urlencoding::decode(path).ok().map(|p| PathBuf::from(p.into_owned()))
+}
@@ -0,0 +1,479 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::llm::client::Message;
+use crate::llm::tools::{is_binary, walk};
+use crate::llm::LlamaClient;
+
+/// Larger files are generated, vendored or data
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Lines longer than this are minified code, where every long string
+/// looks random
+const MAX_ENTROPY_LINE: usize = 1000;
+
+/// Bits per character above which a long token is likely a key
+const ENTROPY_THRESHOLD: f64 = 4.5;
+
+/// Lines carrying this are never reported, for test fixtures and the like
+const IGNORE_MARKER: &str = "vork-scan:ignore";
+
+/// Lock files are full of hashes that look like secrets
+const LOCK_FILES: &[&str] = &["Cargo.lock", "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "go.sum", "poetry.lock", "composer.lock", "Gemfile.lock"];
+
+/// Rule id, description and pattern. The first capture group, when there
+/// is one, is the secret itself.
+const SECRET_RULES: &[(&str, &str, &str)] = &[
+    ("private-key", "Private key", r"-----BEGIN ((RSA|EC|DSA|OPENSSH|PGP|ENCRYPTED) )?PRIVATE KEY( BLOCK)?-----"),
+    ("aws-access-key", "AWS access key id", r"\b((?:AKIA|ASIA)[0-9A-Z]{16})\b"),
+    ("github-token", "GitHub token", r"\b((?:ghp|gho|ghu|ghs|ghr)_[A-Za-z0-9]{36}|github_pat_[A-Za-z0-9_]{60,})\b"),
+    ("gitlab-token", "GitLab personal access token", r"\b(glpat-[A-Za-z0-9_-]{20,})"),
+    ("slack-token", "Slack token", r"\b(xox[abprs]-[A-Za-z0-9-]{10,})"),
+    ("slack-webhook", "Slack webhook URL", r"(https://hooks\.slack\.com/services/[A-Za-z0-9/]{20,})"),
+    ("stripe-key", "Stripe live key", r"\b([sr]k_live_[A-Za-z0-9]{20,})"),
+    ("google-api-key", "Google API key", r"\b(AIza[0-9A-Za-z_-]{35})"),
+    ("llm-api-key", "OpenAI or Anthropic API key", r"\b(sk-(?:proj-|ant-[a-z0-9]+-)?[A-Za-z0-9_-]{32,})"),
+    ("jwt", "JSON Web Token", r"\b(eyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,})"),
+    ("url-credentials", "Password in a URL", r"[a-zA-Z][a-zA-Z0-9+.-]*://[^/\s:@]+:([^/\s:@]{3,})@"),
+    (
+        "assigned-secret",
+        "Hardcoded password, token or key",
+        r#"(?i)[a-z0-9_.-]*(?:api[_-]?key|secret|token|passw(?:or)?d|access[_-]?key|credentials?)[a-z0-9_.-]*["']?\s*[:=]\s*["']([^"'\s]{8,})["']"#,
+    ),
+    ("high-entropy", "High-entropy string", r#"["'`]([A-Za-z0-9+/=_-]{32,})["'`]"#),
+];
+
+/// Values that are obviously not real secrets
+const PLACEHOLDERS: &[&str] = &["example", "placeholder", "changeme", "password", "your_", "your-", "xxxx", "dummy", "redacted", "<", "${", "{{", "process.env", "os.environ"];
+
+/// License name and phrases that all have to appear in the text
+const LICENSES: &[(&str, &[&str])] = &[
+    ("AGPL-3.0", &["GNU AFFERO GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("LGPL-3.0", &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("LGPL-2.1", &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 2.1"]),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE", "Version 2"]),
+    ("MPL-2.0", &["Mozilla Public License", "2.0"]),
+    ("Apache-2.0", &["Apache License", "Version 2.0"]),
+    ("BSD-3-Clause", &["Redistribution and use in source and binary forms", "Neither the name"]),
+    ("BSD-2-Clause", &["Redistribution and use in source and binary forms"]),
+    ("ISC", &["Permission to use, copy, modify, and/or distribute this software for any purpose"]),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    ("Unlicense", &["This is free and unencumbered software released into the public domain"]),
+    ("CC0-1.0", &["CC0 1.0 Universal"]),
+];
+
+/// Licenses that put conditions on code that links to or includes them
+const COPYLEFT: &[&str] = &["AGPL", "GPL", "LGPL", "MPL", "EUPL", "CDDL"];
+
+pub struct ScanOptions {
+    pub paths: Vec<PathBuf>,
+    /// JSON report; the Markdown report is written next to it
+    pub output: PathBuf,
+    /// Send the findings to an agent to sort real secrets from false positives
+    pub triage: bool,
+    /// Exit with status 1 when a secret is found (and not triaged away)
+    pub fail_on_secrets: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SecretFinding {
+    file: String,
+    line: usize,
+    rule: &'static str,
+    description: &'static str,
+    /// The start of the secret, never the whole of it
+    preview: String,
+    /// real, false-positive or unsure, when triaged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verdict: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LicenseFinding {
+    file: String,
+    license: String,
+    /// license file, SPDX header or manifest
+    source: &'static str,
+    copyleft: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ScanReport {
+    generated: String,
+    files_scanned: usize,
+    secrets: Vec<SecretFinding>,
+    licenses: Vec<LicenseFinding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license_notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Verdict {
+    index: usize,
+    verdict: String,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Triage {
+    #[serde(default)]
+    secrets: Vec<Verdict>,
+    #[serde(default)]
+    license_notes: Option<String>,
+}
+
+pub async fn execute(
+    options: ScanOptions,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent_name: Option<String>,
+) -> Result<i32> {
+    let markdown_path = options.output.with_extension("md");
+    // Reports from earlier runs are not source
+    let own_reports: Vec<PathBuf> = [&options.output, &markdown_path].iter().filter_map(|p| p.canonicalize().ok()).collect();
+    let roots = if options.paths.is_empty() { vec![PathBuf::from(".")] } else { options.paths.clone() };
+
+    let mut report = ScanReport {
+        generated: chrono::Local::now().to_rfc3339(),
+        files_scanned: 0,
+        secrets: vec![],
+        licenses: vec![],
+        license_notes: None,
+    };
+    for root in &roots {
+        if !root.exists() {
+            anyhow::bail!("{} does not exist", root.display());
+        }
+        for entry in walk(root, false).build() {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            if path.canonicalize().is_ok_and(|path| own_reports.contains(&path)) {
+                continue;
+            }
+            if entry.metadata().map_or(true, |metadata| metadata.len() > MAX_FILE_BYTES) {
+                continue;
+            }
+            let Ok(bytes) = fs::read(path) else {
+                continue;
+            };
+            let shown = path.strip_prefix("./").unwrap_or(path).to_string_lossy().to_string();
+            if is_binary(&shown, &bytes) {
+                continue;
+            }
+            report.files_scanned += 1;
+            let text = String::from_utf8_lossy(&bytes);
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if !LOCK_FILES.contains(&name) {
+                report.secrets.extend(find_secrets(&shown, &text));
+            }
+            report.licenses.extend(find_licenses(&shown, name, &text));
+        }
+    }
+
+    eprintln!(
+        "{} {} files: {} possible secrets, {} license declarations",
+        "🔎 Scanned".cyan().bold(),
+        report.files_scanned,
+        report.secrets.len(),
+        report.licenses.len()
+    );
+
+    if options.triage && !(report.secrets.is_empty() && report.licenses.is_empty()) {
+        let config = Config::load()?;
+        let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+        let model = model.unwrap_or_else(|| config.assistant.model.clone());
+        let client = LlamaClient::for_config(&config, server_url, model);
+        let system_prompt = Agent::load_or_create_defaults(agent_name.as_deref().unwrap_or("security-auditor"))
+            .map(|a| a.system_prompt)
+            .unwrap_or_else(|_| "You are a security auditing specialist.".to_string());
+        eprintln!("{}", "🛡️  Triaging findings...".cyan());
+        match triage(&client, &system_prompt, &report).await {
+            Ok(triage) => {
+                for verdict in triage.secrets {
+                    if let Some(finding) = verdict.index.checked_sub(1).and_then(|i| report.secrets.get_mut(i)) {
+                        finding.verdict = Some(verdict.verdict.to_lowercase());
+                        finding.reason = verdict.reason;
+                    }
+                }
+                report.license_notes = triage.license_notes.filter(|notes| !notes.trim().is_empty());
+            }
+            Err(e) => eprintln!("{} {:#}", "⚠️  Triage failed, reporting raw findings:".yellow(), e),
+        }
+    }
+
+    fs::write(&options.output, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("Failed to write {}", options.output.display()))?;
+    fs::write(&markdown_path, markdown_report(&report))
+        .with_context(|| format!("Failed to write {}", markdown_path.display()))?;
+
+    let secrets: Vec<&SecretFinding> = report.secrets.iter().filter(|s| s.verdict.as_deref() != Some("false-positive")).collect();
+    println!();
+    for secret in &secrets {
+        println!(
+            "{} {}:{} {} {}",
+            "secret".red(),
+            secret.file,
+            secret.line,
+            secret.description,
+            secret.preview.dimmed()
+        );
+    }
+    let mut licenses: BTreeMap<&str, usize> = BTreeMap::new();
+    for license in &report.licenses {
+        *licenses.entry(&license.license).or_default() += 1;
+    }
+    for (license, count) in &licenses {
+        let label = if COPYLEFT.iter().any(|c| license.starts_with(c)) { license.yellow() } else { license.normal() };
+        println!("{} {} ({})", "license".cyan(), label, count);
+    }
+    if let Some(notes) = &report.license_notes {
+        println!("\n{}", notes);
+    }
+    println!();
+    println!("{} {}", "JSON:".cyan(), options.output.display());
+    println!("{} {}", "Report:".cyan(), markdown_path.display());
+
+    Ok(if options.fail_on_secrets && !secrets.is_empty() { 1 } else { 0 })
+}
+
+fn secret_rules() -> &'static [(&'static str, &'static str, Regex)] {
+    static RULES: OnceLock<Vec<(&str, &str, Regex)>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        SECRET_RULES
+            .iter()
+            .map(|(id, description, pattern)| (*id, *description, Regex::new(pattern).expect("valid secret pattern")))
+            .collect()
+    })
+}
+
+fn find_secrets(file: &str, text: &str) -> Vec<SecretFinding> {
+    let mut findings = vec![];
+    for (number, line) in text.lines().enumerate() {
+        if line.contains(IGNORE_MARKER) {
+            continue;
+        }
+        let mut seen = HashSet::new();
+        for (rule, description, regex) in secret_rules() {
+            // The catch-all rules only when nothing specific matched
+            let fallback = matches!(*rule, "assigned-secret" | "high-entropy");
+            if fallback && !seen.is_empty() {
+                break;
+            }
+            if *rule == "high-entropy" && line.len() > MAX_ENTROPY_LINE {
+                continue;
+            }
+            for captures in regex.captures_iter(line) {
+                let secret = captures.get(1).or_else(|| captures.get(0)).map_or("", |m| m.as_str());
+                let lower = secret.to_lowercase();
+                if PLACEHOLDERS.iter().any(|p| lower.contains(p)) || !seen.insert(secret) {
+                    continue;
+                }
+                if fallback && !looks_random(secret, *rule == "high-entropy") {
+                    continue;
+                }
+                let preview = if *rule == "private-key" {
+                    secret.to_string()
+                } else {
+                    let length = secret.chars().count();
+                    format!("{}… ({} chars)", secret.chars().take((length / 4).min(6)).collect::<String>(), length)
+                };
+                findings.push(SecretFinding {
+                    file: file.to_string(),
+                    line: number + 1,
+                    rule,
+                    description,
+                    preview,
+                    verdict: None,
+                    reason: None,
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Mixed letters and digits with enough entropy to be generated rather than
+/// typed. `strict` is for strings that matched on shape alone.
+fn looks_random(value: &str, strict: bool) -> bool {
+    let has_digit = value.chars().any(|c| c.is_ascii_digit());
+    let has_letter = value.chars().any(|c| c.is_ascii_alphabetic());
+    let threshold = if strict { ENTROPY_THRESHOLD } else { 3.0 };
+    has_digit && has_letter && entropy(value) >= threshold
+}
+
+/// Shannon entropy in bits per character
+fn entropy(value: &str) -> f64 {
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let length = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / length;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn find_licenses(file: &str, name: &str, text: &str) -> Vec<LicenseFinding> {
+    let finding = |license: String, source| LicenseFinding {
+        file: file.to_string(),
+        copyleft: COPYLEFT.iter().any(|c| license.starts_with(c)),
+        license,
+        source,
+    };
+    let upper = name.to_uppercase();
+    if upper.starts_with("LICENSE") || upper.starts_with("LICENCE") || upper.starts_with("COPYING") {
+        let license = LICENSES
+            .iter()
+            .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+            .map_or("unrecognized", |(license, _)| license);
+        return vec![finding(license.to_string(), "license file")];
+    }
+
+    let mut findings = vec![];
+    match name {
+        "Cargo.toml" | "pyproject.toml" => {
+            if let Ok(manifest) = text.parse::<toml::Table>() {
+                let table = manifest.get("package").or_else(|| manifest.get("project"));
+                let license = table.and_then(|t| t.get("license")).and_then(|l| match l {
+                    toml::Value::String(s) => Some(s.clone()),
+                    toml::Value::Table(t) => t.get("text").and_then(|v| v.as_str()).map(str::to_string),
+                    _ => None,
+                });
+                findings.extend(license.map(|license| finding(license, "manifest")));
+            }
+        }
+        "package.json" => {
+            if let Ok(package) = serde_json::from_str::<serde_json::Value>(text) {
+                findings.extend(package["license"].as_str().map(|license| finding(license.to_string(), "manifest")));
+            }
+        }
+        _ => {}
+    }
+    // SPDX headers sit at the top of source files
+    for line in text.lines().take(20) {
+        if let Some((_, id)) = line.split_once("SPDX-License-Identifier:") {
+            let id = id.trim().trim_end_matches("*/").trim_end_matches("-->").trim();
+            if !id.is_empty() {
+                findings.push(finding(id.to_string(), "SPDX header"));
+            }
+            break;
+        }
+    }
+    findings
+}
+
+async fn triage(client: &LlamaClient, system_prompt: &str, report: &ScanReport) -> Result<Triage> {
+    let secrets: Vec<String> = report
+        .secrets
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("{}. {}:{} [{}] {} {}", i + 1, s.file, s.line, s.rule, s.description, s.preview))
+        .collect();
+    let licenses: Vec<String> = report
+        .licenses
+        .iter()
+        .map(|l| format!("- {}: {} ({})", l.file, l.license, l.source))
+        .collect();
+
+    let prompt = format!(
+        "A pattern-based scan of this repository found the possible secrets and the license declarations below. \
+        Secrets are shown redacted.\n\n\
+        For each secret decide whether it is a real credential, a false positive (test fixture, example, hash, \
+        public identifier) or unsure, judging by the file and rule. Then note any license problems, such as \
+        copyleft code in a permissively licensed project or files without a clear license.\n\n\
+        Reply with JSON only:\n\
+        {{\"secrets\": [{{\"index\": <number>, \"verdict\": \"real\" | \"false-positive\" | \"unsure\", \"reason\": \"<one sentence>\"}}], \
+        \"license_notes\": \"<a few sentences, or empty>\"}}\n\n\
+        Secrets:\n{}\n\nLicenses:\n{}",
+        if secrets.is_empty() { "(none)".to_string() } else { secrets.join("\n") },
+        if licenses.is_empty() { "(none)".to_string() } else { licenses.join("\n") }
+    );
+
+    let response = client
+        .chat_completion(
+            &[
+                Message {
+                    role: "system".to_string(),
+                    content: system_prompt.to_string(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt,
+                },
+            ],
+            None,
+        )
+        .await
+        .context("Failed to get response from LLM")?;
+
+    let content = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
+
+    let json = match (content.find('{'), content.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => anyhow::bail!("The model did not return a JSON object"),
+    };
+    serde_json::from_str(json).context("The model returned a malformed triage")
+}
+
+fn markdown_report(report: &ScanReport) -> String {
+    let mut out = format!(
+        "# Secret and License Scan\n\nGenerated by `vork scan` on {} over {} files.\n\n## Secrets\n\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        report.files_scanned
+    );
+    if report.secrets.is_empty() {
+        out.push_str("No secrets found.\n");
+    } else {
+        let triaged = report.secrets.iter().any(|s| s.verdict.is_some());
+        out.push_str(if triaged { "| File | Line | Rule | Preview | Verdict |\n|---|---:|---|---|---|\n" } else { "| File | Line | Rule | Preview |\n|---|---:|---|---|\n" });
+        for secret in &report.secrets {
+            out.push_str(&format!("| `{}` | {} | {} | `{}` |", secret.file, secret.line, secret.rule, secret.preview));
+            if triaged {
+                let verdict = secret.verdict.as_deref().unwrap_or("-");
+                match &secret.reason {
+                    Some(reason) => out.push_str(&format!(" {}: {} |", verdict, reason.replace('|', "/"))),
+                    None => out.push_str(&format!(" {} |", verdict)),
+                }
+            }
+            out.push('\n');
+        }
+    }
+
+    out.push_str("\n## Licenses\n\n");
+    if report.licenses.is_empty() {
+        out.push_str("No license files, manifest licenses or SPDX headers found.\n");
+    } else {
+        out.push_str("| File | License | Source |\n|---|---|---|\n");
+        for license in &report.licenses {
+            let name = if license.copyleft { format!("{} (copyleft)", license.license) } else { license.license.clone() };
+            out.push_str(&format!("| `{}` | {} | {} |\n", license.file, name, license.source));
+        }
+    }
+    if let Some(notes) = &report.license_notes {
+        out.push_str(&format!("\n{}\n", notes.trim()));
+    }
+    out
+}
@@ -0,0 +1,592 @@
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDateTime, Timelike};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use super::exec::{self, ExecOptions, ExecReport};
+use crate::agents::Agent;
+use crate::config::{Config, ScheduledTask};
+use crate::llm::{ApprovalSystem, LlamaClient, Session, UsageTracker};
+
+/// Runs missed while the scheduler was stopped are caught up (once) if they
+/// fell within this many days
+const CATCH_UP_DAYS: i64 = 7;
+
+/// Where each task got to, kept across restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleState {
+    /// Last minute the scheduler looked at, in local time
+    last_checked: Option<NaiveDateTime>,
+    tasks: BTreeMap<String, TaskState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TaskState {
+    last_run: NaiveDateTime,
+    status: String,
+    session_id: Option<String>,
+}
+
+impl ScheduleState {
+    fn path() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("schedule_state.json"))
+    }
+
+    fn load() -> Self {
+        Self::path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::create_dir_all(Config::config_dir()?)?;
+        fs::write(Self::path()?, serde_json::to_string_pretty(self)?).context("Failed to save schedule state")
+    }
+}
+
+/// A parsed five-field cron expression
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day of month or weekday was `*`; cron matches either field when
+    /// both are restricted
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    fn parse(expr: &str) -> Result<Self> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            anyhow::bail!("Cron expression '{}' needs 5 fields: minute hour day month weekday", expr);
+        }
+        let weekdays = parse_field(fields[4], 0, 7, &WEEKDAY_NAMES).context("weekday")?;
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, &[]).context("minute")?,
+            hours: parse_field(fields[1], 0, 23, &[]).context("hour")?,
+            days: parse_field(fields[2], 1, 31, &[]).context("day of month")?,
+            months: parse_field(fields[3], 1, 12, &MONTH_NAMES).context("month")?,
+            // 7 is another way to write Sunday
+            weekdays: (weekdays | (weekdays >> 7)) & 0x7f,
+            any_day: fields[2].starts_with('*'),
+            any_weekday: fields[4].starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, time: &NaiveDateTime) -> bool {
+        let day = bit(self.days, time.day());
+        let weekday = bit(self.weekdays, time.weekday().num_days_from_sunday());
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        day_matches && bit(self.months, time.month())
+    }
+
+    /// First matching minute after `time`, within about four years
+    fn next_after(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut candidate = minute_floor(time) + ChronoDuration::minutes(1);
+        let limit = candidate + ChronoDuration::days(4 * 366);
+        while candidate < limit {
+            if !self.day_matches(&candidate) {
+                candidate = candidate.date().and_hms_opt(0, 0, 0)? + ChronoDuration::days(1);
+            } else if !bit(self.hours, candidate.hour()) {
+                candidate = candidate.with_minute(0)? + ChronoDuration::hours(1);
+            } else if !bit(self.minutes, candidate.minute()) {
+                candidate += ChronoDuration::minutes(1);
+            } else {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// One cron field as a bitmask: `*`, numbers or names, `a-b` ranges,
+/// `/step` and comma-separated lists
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |text: &str| -> Result<u32> {
+        let lower = text.to_lowercase();
+        // Month names count from 1, weekday names from 0 like the numbers
+        if let Some(index) = names.iter().position(|name| *name == lower) {
+            return Ok(index as u32 + min);
+        }
+        let number: u32 = text.parse().with_context(|| format!("'{}' is not a number", text))?;
+        if number < min || number > max {
+            anyhow::bail!("{} is outside {}-{}", number, min, max);
+        }
+        Ok(number)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().with_context(|| format!("bad step in '{}'", part))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            anyhow::bail!("step in '{}' must be at least 1", part);
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // `5/15` means from 5 to the end in steps of 15
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if start > end {
+            anyhow::bail!("range '{}' runs backwards", range);
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn minute_floor(time: NaiveDateTime) -> NaiveDateTime {
+    time.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(time)
+}
+
+fn now() -> NaiveDateTime {
+    minute_floor(Local::now().naive_local())
+}
+
+/// Options for `vork schedule add`
+pub struct AddOptions {
+    pub cron: String,
+    pub prompt: String,
+    pub name: Option<String>,
+    pub agent: Option<String>,
+    pub workspace: Option<String>,
+    pub notify: Option<String>,
+    pub full_auto: bool,
+}
+
+pub fn add(options: AddOptions) -> Result<()> {
+    let cron = Cron::parse(&options.cron).context("Invalid cron expression")?;
+    let mut config = Config::load_file()?;
+
+    let name = match options.name {
+        Some(name) => name,
+        None => default_name(&options.prompt, &config.schedules),
+    };
+    if config.schedules.iter().any(|t| t.name == name) {
+        anyhow::bail!("A scheduled task named '{}' already exists (remove it first or pick --name)", name);
+    }
+    if let Some(ref agent) = options.agent {
+        Agent::load_or_create_defaults(agent)?;
+    }
+    let workspace = match options.workspace {
+        Some(dir) => dir,
+        None => env::current_dir()?.display().to_string(),
+    };
+
+    config.schedules.push(ScheduledTask {
+        name: name.clone(),
+        cron: options.cron,
+        prompt: options.prompt,
+        agent: options.agent,
+        workspace: Some(workspace.clone()),
+        notify: options.notify,
+        full_auto: options.full_auto,
+    });
+    config.save()?;
+
+    println!("{} Scheduled '{}' in {}", "✓".green(), name.cyan(), workspace);
+    if let Some(next) = cron.next_after(now()) {
+        println!("  Next run: {}", next.format("%Y-%m-%d %H:%M"));
+    }
+    println!("  Tasks run while `vork schedule run` is running (or from cron with `vork schedule run --once`)");
+    Ok(())
+}
+
+/// A short name from the prompt's first words, unique among `existing`
+fn default_name(prompt: &str, existing: &[ScheduledTask]) -> String {
+    let base: String = prompt
+        .split_whitespace()
+        .take(3)
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if base.is_empty() { "task".to_string() } else { base };
+    let mut name = base.clone();
+    let mut n = 2;
+    while existing.iter().any(|t| t.name == name) {
+        name = format!("{}-{}", base, n);
+        n += 1;
+    }
+    name
+}
+
+pub fn list() -> Result<()> {
+    let config = Config::load()?;
+    if config.schedules.is_empty() {
+        println!("No scheduled tasks. Add one with: vork schedule add \"0 7 * * *\" \"audit the repo\"");
+        return Ok(());
+    }
+    let state = ScheduleState::load();
+    let now = now();
+
+    for task in &config.schedules {
+        println!("{} {}", task.name.cyan().bold(), format!("[{}]", task.cron).dimmed());
+        println!("  {}", task.prompt);
+        let mut details = vec![];
+        if let Some(ref agent) = task.agent {
+            details.push(format!("agent {}", agent));
+        }
+        if let Some(ref workspace) = task.workspace {
+            details.push(format!("in {}", workspace));
+        }
+        if task.full_auto {
+            details.push("full auto".to_string());
+        }
+        if let Some(ref notify) = task.notify {
+            details.push(format!("notify {}", notify));
+        }
+        if !details.is_empty() {
+            println!("  {}", details.join(", ").dimmed());
+        }
+        match Cron::parse(&task.cron) {
+            Ok(cron) => {
+                if let Some(next) = cron.next_after(now) {
+                    println!("  Next run: {}", next.format("%Y-%m-%d %H:%M"));
+                }
+            }
+            Err(e) => println!("  {} {:#}", "Invalid cron:".red(), e),
+        }
+        if let Some(last) = state.tasks.get(&task.name) {
+            let status = if last.status == "success" { last.status.green() } else { last.status.yellow() };
+            let session = last.session_id.as_deref().map(|id| format!(" (session {})", id)).unwrap_or_default();
+            println!("  Last run: {} {}{}", last.last_run.format("%Y-%m-%d %H:%M"), status, session);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+pub fn remove(name: &str) -> Result<()> {
+    let mut config = Config::load_file()?;
+    let before = config.schedules.len();
+    config.schedules.retain(|t| t.name != name);
+    if config.schedules.len() == before {
+        anyhow::bail!("No scheduled task named '{}'", name);
+    }
+    config.save()?;
+
+    let mut state = ScheduleState::load();
+    if state.tasks.remove(name).is_some() {
+        state.save()?;
+    }
+    println!("{} Removed '{}'", "✓".green(), name);
+    Ok(())
+}
+
+/// Run due tasks every minute until interrupted. With `name`, run that task
+/// now and exit; with `once`, run whatever came due since the last check
+/// and exit, for system cron or a systemd timer.
+pub async fn run(name: Option<String>, once: bool, server_url: Option<String>, model: Option<String>) -> Result<()> {
+    let start_dir = env::current_dir()?;
+
+    if let Some(name) = name {
+        let config = Config::load()?;
+        let task = config
+            .schedules
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| anyhow::anyhow!("No scheduled task named '{}'", name))?;
+        let mut state = ScheduleState::load();
+        let outcome = run_task(task, &config, server_url.clone(), model.clone(), &start_dir).await;
+        record(&mut state, task, outcome);
+        return state.save();
+    }
+
+    if !once {
+        eprintln!("{} Scheduler running (Ctrl+C to stop)", "⏰".cyan());
+    }
+    loop {
+        // Re-read the config each minute so `schedule add` takes effect without a restart
+        let config = Config::load()?;
+        let mut state = ScheduleState::load();
+        let now = now();
+        let since = state
+            .last_checked
+            .unwrap_or(now - ChronoDuration::minutes(1))
+            .max(now - ChronoDuration::days(CATCH_UP_DAYS));
+
+        for task in &config.schedules {
+            let cron = match Cron::parse(&task.cron) {
+                Ok(cron) => cron,
+                Err(e) => {
+                    eprintln!("{} Skipping '{}': {:#}", "⚠️ ".yellow(), task.name, e);
+                    continue;
+                }
+            };
+            // Several missed runs of one task collapse into one
+            let since = match state.tasks.get(&task.name) {
+                Some(last) => since.max(last.last_run),
+                None => since,
+            };
+            if cron.next_after(since).is_some_and(|due| due <= now) {
+                let outcome = run_task(task, &config, server_url.clone(), model.clone(), &start_dir).await;
+                record(&mut state, task, outcome);
+                state.save()?;
+            }
+        }
+        state.last_checked = Some(now);
+        state.save()?;
+
+        if once {
+            return Ok(());
+        }
+        let wait = 60 - Local::now().second() as u64;
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(wait.max(1))) => {}
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("{} Scheduler stopped", "⏰".cyan());
+                return Ok(());
+            }
+        }
+    }
+}
+
+struct Outcome {
+    status: String,
+    session_id: Option<String>,
+    message: String,
+}
+
+/// Run one task in a fresh session in its workspace and send its notification
+async fn run_task(
+    task: &ScheduledTask,
+    config: &Config,
+    server_url: Option<String>,
+    model: Option<String>,
+    start_dir: &Path,
+) -> Outcome {
+    eprintln!("{} Running '{}'", "⏰".cyan(), task.name.yellow());
+    let outcome = match execute_task(task, config, server_url, model, start_dir).await {
+        Ok(report) => {
+            let status = serde_json::to_value(report.status)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default();
+            Outcome { status, session_id: Some(report.session_id.clone()), message: describe(&report) }
+        }
+        Err(e) => Outcome { status: "error".to_string(), session_id: None, message: format!("{:#}", e) },
+    };
+    if outcome.status == "success" {
+        eprintln!("{} '{}' finished{}", "✓".green(), task.name, session_suffix(&outcome));
+    } else {
+        eprintln!("{} '{}' ended with {}: {}", "✗".red(), task.name, outcome.status, outcome.message);
+    }
+    if let Some(ref target) = task.notify {
+        if let Err(e) = notify(target, task, &outcome).await {
+            eprintln!("{} Notify for '{}' failed: {:#}", "⚠️ ".yellow(), task.name, e);
+        }
+    }
+    outcome
+}
+
+async fn execute_task(
+    task: &ScheduledTask,
+    config: &Config,
+    server_url: Option<String>,
+    model: Option<String>,
+    start_dir: &Path,
+) -> Result<ExecReport> {
+    let workspace = match task.workspace {
+        Some(ref dir) => PathBuf::from(shellexpand::tilde(dir).to_string()),
+        None => start_dir.to_path_buf(),
+    };
+    // Tools run in the session's directory, so the scheduler itself never
+    // changes directory and tasks due together cannot move each other
+    if !workspace.is_dir() {
+        anyhow::bail!("Workspace {} does not exist", workspace.display());
+    }
+
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+    let client = LlamaClient::for_config(config, server_url, model.clone());
+
+    let agent = task.agent.as_deref().map(Agent::load_or_create_defaults).transpose()?;
+    let mut session = Session::new(workspace.clone());
+    // Session ids are the second they started; tasks due together must not share one
+    let base_id = session.id.clone();
+    let mut n = 1;
+    while session.session_path()?.exists() {
+        n += 1;
+        session.id = format!("{}-{}", base_id, n);
+    }
+    if let Some(ref agent) = agent {
+        session.conversation.set_system_prompt(agent.system_prompt.clone());
+    }
+    let mut usage = UsageTracker::new("schedule", &session.id, &model);
+    usage.set_agent(task.agent.as_deref());
+    usage.set_project(&workspace);
+    let approval = Arc::new(
+        ApprovalSystem::unattended(task.full_auto, &config.remote_approval)?
+            .with_agent(agent.as_ref())
+            .with_workspace(workspace),
+    );
+    let options = ExecOptions {
+        agent: task.agent.clone(),
+        ..Default::default()
+    };
+
+    let report = exec::run(&task.prompt, &mut session, &client, &approval, &mut usage, &options).await?;
+    session.save()?;
+    Ok(report)
+}
+
+/// The answer, plus what changed, for notifications
+fn describe(report: &ExecReport) -> String {
+    let mut out = report.message.clone().unwrap_or_else(|| "(no answer)".to_string());
+    if !report.files_changed.is_empty() {
+        out.push_str(&format!("\n\nChanged: {}", report.files_changed.join(", ")));
+    }
+    if let Some(ref error) = report.error {
+        out.push_str(&format!("\n\n{}", error));
+    }
+    out
+}
+
+fn session_suffix(outcome: &Outcome) -> String {
+    outcome
+        .session_id
+        .as_deref()
+        .map(|id| format!(" (session {}, `vork resume {}` to follow up)", id, id))
+        .unwrap_or_default()
+}
+
+fn record(state: &mut ScheduleState, task: &ScheduledTask, outcome: Outcome) {
+    state.tasks.insert(
+        task.name.clone(),
+        TaskState { last_run: now(), status: outcome.status, session_id: outcome.session_id },
+    );
+}
+
+/// POST the result as JSON to a URL, or run a shell command with the
+/// message on stdin and VORK_TASK, VORK_STATUS and VORK_SESSION set
+async fn notify(target: &str, task: &ScheduledTask, outcome: &Outcome) -> Result<()> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        let payload = json!({
+            "task": task.name,
+            "status": outcome.status,
+            "session_id": outcome.session_id,
+            "message": outcome.message,
+            // Slack and Discord incoming webhooks read these
+            "text": format!("[{}] {}: {}", task.name, outcome.status, outcome.message),
+            "content": format!("[{}] {}: {}", task.name, outcome.status, outcome.message.chars().take(1800).collect::<String>()),
+        });
+        let response = reqwest::Client::new().post(target).json(&payload).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("{} returned {}", target, response.status());
+        }
+        return Ok(());
+    }
+
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(target)
+        .env("VORK_TASK", &task.name)
+        .env("VORK_STATUS", &outcome.status)
+        .env("VORK_SESSION", outcome.session_id.as_deref().unwrap_or_default())
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run notify command")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may not read its input
+        let _ = stdin.write_all(outcome.message.as_bytes()).await;
+    }
+    let status = child.wait().await?;
+    if !status.success() {
+        anyhow::bail!("notify command exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expr: &str, after: &str) -> NaiveDateTime {
+        Cron::parse(expr).unwrap().next_after(at(after)).unwrap()
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(next("*/15 * * * *", "2026-10-16 09:31"), at("2026-10-16 09:45"));
+        assert_eq!(next("*/15 * * * *", "2026-10-16 09:45"), at("2026-10-16 10:00"));
+        assert_eq!(next("5/20 * * * *", "2026-10-16 09:26"), at("2026-10-16 09:45"));
+        assert_eq!(next("0 0-12/6 * * *", "2026-10-16 06:00"), at("2026-10-16 12:00"));
+    }
+
+    #[test]
+    fn ranges_and_lists() {
+        // Friday evening to Monday morning
+        assert_eq!(next("0 9-17 * * mon-fri", "2026-10-16 17:30"), at("2026-10-19 09:00"));
+        assert_eq!(next("0,30 8,12 * * *", "2026-10-16 08:30"), at("2026-10-16 12:00"));
+        assert_eq!(next("0 0 1 jan,jul *", "2026-10-16 00:00"), at("2027-01-01 00:00"));
+    }
+
+    #[test]
+    fn day_of_month_or_weekday() {
+        // Both restricted: the 1st or any Monday, whichever comes first
+        assert_eq!(next("0 0 1 * mon", "2026-10-16 12:00"), at("2026-10-19 00:00"));
+        assert_eq!(next("0 0 1 * mon", "2026-10-31 12:00"), at("2026-11-01 00:00"));
+        // One restricted: only that one counts
+        assert_eq!(next("0 0 1 * *", "2026-10-16 12:00"), at("2026-11-01 00:00"));
+        assert_eq!(next("0 0 * * 7", "2026-10-16 12:00"), at("2026-10-18 00:00"));
+        // As in Vixie cron, a field starting with * counts as unrestricted,
+        // so this needs a Monday that is also the 1st, 11th, 21st or 31st
+        assert_eq!(next("0 0 */10 * mon", "2026-10-16 12:00"), at("2026-12-21 00:00"));
+    }
+
+    #[test]
+    fn bad_expressions() {
+        for expr in ["60 * * * *", "* * * *", "5-1 * * * *", "*/0 * * * *", "* * * * funday"] {
+            assert!(Cron::parse(expr).is_err(), "{}", expr);
+        }
+        assert_eq!(next("@daily", "2026-10-16 12:00"), at("2026-10-17 00:00"));
+    }
+}
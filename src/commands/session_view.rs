@@ -0,0 +1,292 @@
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use std::collections::HashSet;
+use std::io;
+
+use crate::llm::conversation::tool_result;
+use crate::llm::Session;
+
+/// Lines of a tool result shown when it is expanded; the rest are counted
+const MAX_TOOL_LINES: usize = 200;
+
+enum Kind {
+    Prompt,
+    Reply,
+    Tool(String),
+}
+
+/// One message of the session as shown in the viewer
+struct Entry {
+    kind: Kind,
+    text: String,
+    timing: Option<String>,
+}
+
+struct Viewer {
+    title: String,
+    entries: Vec<Entry>,
+    /// Tool results shown in full
+    expanded: HashSet<usize>,
+    list: ListState,
+    /// Text being typed after `/`
+    typing: Option<String>,
+    query: String,
+}
+
+impl Viewer {
+    fn new(session: &Session) -> Self {
+        let conversation = &session.conversation;
+        let entries = conversation
+            .messages
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter_map(|(index, message)| {
+                let (kind, text) = match tool_result(message) {
+                    Some((tool, result)) => (Kind::Tool(tool.to_string()), result.to_string()),
+                    None if message.role == "user" => (Kind::Prompt, message.content.clone()),
+                    None if message.role == "assistant" && !message.content.trim().is_empty() => {
+                        (Kind::Reply, message.content.clone())
+                    }
+                    None => return None,
+                };
+                Some(Entry { kind, text, timing: conversation.timing(index) })
+            })
+            .collect();
+        let mut list = ListState::default();
+        list.select(Some(0));
+        Self {
+            title: format!(
+                "📜 Session {} · {} · {} (read-only)",
+                session.label(),
+                session.working_directory.display(),
+                session.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            ),
+            entries,
+            expanded: HashSet::new(),
+            list,
+            typing: None,
+            query: String::new(),
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.list.selected().unwrap_or(0)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list.select(Some(index.min(self.entries.len().saturating_sub(1))));
+    }
+
+    fn matches(&self, index: usize) -> bool {
+        !self.query.is_empty() && self.entries[index].text.to_lowercase().contains(&self.query.to_lowercase())
+    }
+
+    /// Move to the next entry matching the search, wrapping around
+    fn find(&mut self, forward: bool) {
+        let count = self.entries.len();
+        let start = self.selected();
+        for step in 1..=count {
+            let index = if forward { (start + step) % count } else { (start + count - step) % count };
+            if self.matches(index) {
+                if matches!(self.entries[index].kind, Kind::Tool(_)) {
+                    self.expanded.insert(index);
+                }
+                self.select(index);
+                return;
+            }
+        }
+    }
+
+    /// Handle a key; returns false to quit
+    fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        if let Some(typing) = self.typing.as_mut() {
+            match code {
+                KeyCode::Enter => {
+                    self.query = self.typing.take().unwrap_or_default();
+                    let index = self.selected();
+                    if !self.matches(index) {
+                        self.find(true);
+                    }
+                }
+                KeyCode::Esc => self.typing = None,
+                KeyCode::Backspace => {
+                    typing.pop();
+                }
+                KeyCode::Char(c) => typing.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        let selected = self.selected();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.select(selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(selected + 1),
+            KeyCode::PageUp => self.select(selected.saturating_sub(10)),
+            KeyCode::PageDown => self.select(selected + 10),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if self.expanded.contains(&selected) {
+                    self.expanded.remove(&selected);
+                } else {
+                    self.expanded.insert(selected);
+                }
+            }
+            KeyCode::Char('a') => {
+                let tools: Vec<usize> = (0..self.entries.len())
+                    .filter(|index| matches!(self.entries[*index].kind, Kind::Tool(_)))
+                    .collect();
+                if tools.iter().all(|index| self.expanded.contains(index)) {
+                    self.expanded.clear();
+                } else {
+                    self.expanded.extend(tools);
+                }
+            }
+            KeyCode::Char('/') => self.typing = Some(String::new()),
+            KeyCode::Char('n') => self.find(true),
+            KeyCode::Char('N') => self.find(false),
+            _ => {}
+        }
+        true
+    }
+
+    fn item(&self, index: usize, width: usize) -> ListItem<'_> {
+        let entry = &self.entries[index];
+        let timing = entry.timing.as_ref().map(|timing| format!("  {}", timing)).unwrap_or_default();
+        let (heading, color) = match &entry.kind {
+            Kind::Prompt => ("👤 You".to_string(), Color::Blue),
+            Kind::Reply => ("🐴 Vork".to_string(), Color::Cyan),
+            Kind::Tool(name) => {
+                let lines = entry.text.lines().count();
+                let marker = if self.expanded.contains(&index) { "▾" } else { "▸" };
+                (format!("{} 🔧 {} · {} line{}", marker, name, lines, if lines == 1 { "" } else { "s" }), Color::Yellow)
+            }
+        };
+        let mut lines = vec![Line::from(vec![
+            Span::styled(heading, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(timing, Style::default().fg(Color::DarkGray)),
+        ])];
+
+        let body: Vec<&str> = match entry.kind {
+            Kind::Tool(_) if !self.expanded.contains(&index) => vec![],
+            Kind::Tool(_) => entry.text.lines().take(MAX_TOOL_LINES).collect(),
+            _ => entry.text.lines().collect(),
+        };
+        let highlight = (!self.query.is_empty()).then(|| self.query.to_lowercase());
+        let mut in_code = matches!(entry.kind, Kind::Tool(_));
+        for line in &body {
+            let fence = line.trim_start().starts_with("```");
+            let mut style = if fence {
+                Style::default().fg(Color::DarkGray)
+            } else if in_code {
+                Style::default().fg(Color::Gray)
+            } else if line.starts_with('#') {
+                Style::default().fg(color).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            if fence && !matches!(entry.kind, Kind::Tool(_)) {
+                in_code = !in_code;
+            }
+            if highlight.as_ref().is_some_and(|query| line.to_lowercase().contains(query)) {
+                style = style.bg(Color::DarkGray);
+            }
+            for wrapped in textwrap::wrap(line, width.max(20)) {
+                lines.push(Line::from(Span::styled(format!("  {}", wrapped), style)));
+            }
+        }
+        if matches!(entry.kind, Kind::Tool(_)) && self.expanded.contains(&index) && entry.text.lines().count() > MAX_TOOL_LINES {
+            lines.push(Line::from(Span::styled(
+                format!("  [… {} more lines]", entry.text.lines().count() - MAX_TOOL_LINES),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(""));
+        ListItem::new(lines)
+    }
+}
+
+fn draw(f: &mut Frame, viewer: &mut Viewer) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(f.area());
+
+    let width = chunks[0].width.saturating_sub(4) as usize;
+    let items: Vec<ListItem> = (0..viewer.entries.len()).map(|index| viewer.item(index, width)).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(viewer.title.clone()))
+        .highlight_symbol("│")
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+    let mut state = viewer.list.clone();
+    f.render_stateful_widget(list, chunks[0], &mut state);
+    *viewer.list.offset_mut() = state.offset();
+
+    let footer = match &viewer.typing {
+        Some(typing) => format!("🔎 /{}▏ (Enter: search, Esc: cancel)", typing),
+        None if !viewer.query.is_empty() => format!(
+            "🔎 \"{}\" · n/N next/previous match · ↑↓ move · Enter expand tool · a expand all · / search · q quit",
+            viewer.query
+        ),
+        None => "↑↓ move · Enter expand tool · a expand all · / search · n/N next/previous match · q quit".to_string(),
+    };
+    f.render_widget(
+        Paragraph::new(footer).block(Block::default().borders(Borders::ALL)).style(Style::default().fg(Color::Cyan)),
+        chunks[1],
+    );
+}
+
+/// Browse a saved session without a server: prompts and replies in full,
+/// tool results collapsed until opened
+pub fn execute(id: Option<String>) -> Result<()> {
+    let session = match id {
+        Some(id) => Session::load(&id).with_context(|| format!("No session {}", id))?,
+        None => Session::get_last_session()?.ok_or_else(|| anyhow::anyhow!("No sessions found"))?,
+    };
+    let mut viewer = Viewer::new(&session);
+    if viewer.entries.is_empty() {
+        println!("Session {} has no messages.", session.label());
+        return Ok(());
+    }
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = (|| -> Result<()> {
+        loop {
+            terminal.draw(|f| draw(f, &mut viewer))?;
+            match event::read()? {
+                Event::Key(key) if !viewer.key(key.code, key.modifiers) => return Ok(()),
+                Event::Mouse(mouse) => match mouse.kind {
+                    MouseEventKind::ScrollUp => viewer.select(viewer.selected().saturating_sub(1)),
+                    MouseEventKind::ScrollDown => viewer.select(viewer.selected() + 1),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    })();
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    terminal.show_cursor()?;
+    result
+}
@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::llm::conversation::tool_result;
+use crate::llm::transcript::fenced;
+use crate::llm::Session;
+
+/// Longest tool result kept in an export; file contents and listings are
+/// rarely worth more
+const MAX_RESULT: usize = 4000;
+
+pub fn list(limit: usize, project_only: bool) -> Result<()> {
+    let current = env::current_dir()?;
+    let sessions: Vec<Session> = Session::list_sessions()?
+        .into_iter()
+        .filter(|session| !project_only || session.working_directory == current)
+        .collect();
+    if sessions.is_empty() {
+        println!("No sessions{}.", if project_only { " started in this directory" } else { "" });
+        return Ok(());
+    }
+
+    for session in sessions.iter().take(limit) {
+        let prompts: Vec<&str> = session
+            .conversation
+            .messages
+            .iter()
+            .filter(|m| m.role == "user" && tool_result(m).is_none())
+            .map(|m| m.content.lines().next().unwrap_or(""))
+            .collect();
+        println!(
+            "{}{}  {}  {}",
+            session.id.yellow(),
+            session.name.as_ref().map(|name| format!(" {}", name.bold())).unwrap_or_default(),
+            session.updated_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            session.working_directory.display().to_string().dimmed()
+        );
+        println!(
+            "    {} prompt{}: {}",
+            prompts.len(),
+            if prompts.len() == 1 { "" } else { "s" },
+            preview(prompts.first().copied().unwrap_or(""), 80)
+        );
+    }
+    if sessions.len() > limit {
+        println!("{}", format!("[{} older sessions not shown; pass --limit]", sessions.len() - limit).dimmed());
+    }
+    Ok(())
+}
+
+fn preview(text: &str, width: usize) -> String {
+    match text.char_indices().nth(width) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// A session by id, or the most recent one
+fn find(id: Option<&str>) -> Result<Session> {
+    match id {
+        Some(id) => Session::load(id).with_context(|| format!("No session {}", id)),
+        None => Session::get_last_session()?.ok_or_else(|| anyhow::anyhow!("No sessions found")),
+    }
+}
+
+/// A piece of an exported session
+enum Cell {
+    Markdown(String),
+    /// A bash_exec command with what it printed
+    Command {
+        command: String,
+        stdout: String,
+        stderr: String,
+        exit_code: Option<i32>,
+        /// When the result came back and how long it took
+        timing: Option<String>,
+    },
+}
+
+/// The conversation as cells: prompts and replies as text, commands the
+/// model ran with their output, and other tool results shortened
+fn cells(session: &Session) -> Vec<Cell> {
+    let mut cells = vec![Cell::Markdown(format!(
+        "# vork session {}\n\nStarted {} in `{}`",
+        session.id,
+        session.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        session.working_directory.display()
+    ))];
+    for (index, message) in session.conversation.messages.iter().enumerate().skip(1) {
+        let timing = session.conversation.timing(index);
+        let suffix = timing.as_ref().map(|timing| format!(" · {}", timing)).unwrap_or_default();
+        if let Some((tool, result)) = tool_result(message) {
+            cells.push(match command(tool, result, timing) {
+                Some(cell) => cell,
+                None => Cell::Markdown(format!("**🔧 {}**{}\n\n{}", tool, suffix, fenced(&shorten(result), ""))),
+            });
+            continue;
+        }
+        let speaker = match message.role.as_str() {
+            "user" => "🧑 You",
+            "assistant" => "🤖 Assistant",
+            _ => continue,
+        };
+        cells.push(Cell::Markdown(format!("**{}**{}\n\n{}", speaker, suffix, message.content.trim())));
+    }
+    cells
+}
+
+/// Parse a bash_exec result back into the command and its output
+fn command(tool: &str, result: &str, timing: Option<String>) -> Option<Cell> {
+    if tool != "bash_exec" {
+        return None;
+    }
+    let (heading, rest) = result.split_once(": ")?;
+    if !heading.contains("Executed") && !heading.contains("TIMEOUT") {
+        return None;
+    }
+    let (command, rest, exit_code) = match rest.split_once("\nExit code: ") {
+        Some((command, rest)) => {
+            let (code, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+            (command, rest, code.trim().parse().ok())
+        }
+        None => {
+            let (command, rest) = rest.split_once("\nThe command was stopped")?;
+            (command, rest, None)
+        }
+    };
+    let (_, output) = rest.split_once("Stdout:\n")?;
+    let (stdout, stderr) = output.split_once("\n\nStderr:\n").unwrap_or((output, ""));
+    let mut stderr = stderr.to_string();
+    if heading.contains("TIMEOUT") {
+        stderr.push_str(&format!("\n[{}]", heading.trim_start_matches("⏱️ ")));
+    }
+    Some(Cell::Command {
+        command: command.to_string(),
+        stdout: shorten(stdout),
+        stderr: shorten(stderr.trim_matches('\n')),
+        exit_code,
+        timing,
+    })
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(MAX_RESULT) {
+        Some((end, _)) => format!("{}\n[… {} more characters]", &text[..end], text[end..].chars().count()),
+        None => text.to_string(),
+    }
+}
+
+fn markdown(cells: &[Cell]) -> String {
+    let mut text = String::new();
+    for cell in cells {
+        match cell {
+            Cell::Markdown(markdown) => text.push_str(markdown),
+            Cell::Command { command, stdout, stderr, exit_code, timing } => {
+                if let Some(timing) = timing {
+                    text.push_str(&format!("_{}_\n\n", timing));
+                }
+                text.push_str(&fenced(command, "bash"));
+                let output = [stdout.trim_end(), stderr.trim_end()].into_iter().filter(|s| !s.is_empty()).collect::<Vec<_>>();
+                if !output.is_empty() {
+                    text.push_str("\n\n");
+                    text.push_str(&fenced(&output.join("\n"), "text"));
+                }
+                if let Some(code) = exit_code.filter(|code| *code != 0) {
+                    text.push_str(&format!("\n\nExit code {}", code));
+                }
+            }
+        }
+        text.push_str("\n\n");
+    }
+    text
+}
+
+/// nbformat wants sources and outputs as lists of lines
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// A notebook with a bash kernel, so the commands can be run again
+fn notebook(session: &Session, cells: &[Cell]) -> Value {
+    let mut execution_count = 0;
+    let cells: Vec<Value> = cells
+        .iter()
+        .map(|cell| match cell {
+            Cell::Markdown(markdown) => json!({
+                "cell_type": "markdown",
+                "metadata": {},
+                "source": lines(markdown),
+            }),
+            Cell::Command { command, stdout, stderr, exit_code, timing } => {
+                execution_count += 1;
+                let mut outputs = vec![];
+                for (name, text) in [("stdout", stdout), ("stderr", stderr)] {
+                    if !text.trim().is_empty() {
+                        outputs.push(json!({ "output_type": "stream", "name": name, "text": lines(text) }));
+                    }
+                }
+                json!({
+                    "cell_type": "code",
+                    "execution_count": execution_count,
+                    "metadata": { "vork": { "exit_code": exit_code, "timing": timing } },
+                    "source": lines(command),
+                    "outputs": outputs,
+                })
+            }
+        })
+        .collect();
+    json!({
+        "cells": cells,
+        "metadata": {
+            "kernelspec": { "display_name": "Bash", "language": "bash", "name": "bash" },
+            "language_info": { "name": "bash" },
+            "vork": {
+                "session": session.id,
+                "working_directory": session.working_directory,
+                "created_at": session.created_at,
+            },
+        },
+        "nbformat": 4,
+        "nbformat_minor": 4,
+    })
+}
+
+pub fn export(id: Option<String>, format: &str, output: Option<PathBuf>) -> Result<()> {
+    let session = find(id.as_deref())?;
+    let cells = cells(&session);
+    let (text, extension) = match format {
+        "ipynb" | "notebook" => (serde_json::to_string_pretty(&notebook(&session, &cells))?, "ipynb"),
+        "markdown" | "md" => (markdown(&cells), "md"),
+        _ => anyhow::bail!("Unknown format '{}' (use markdown or ipynb)", format),
+    };
+
+    let path = output.unwrap_or_else(|| PathBuf::from(format!("vork-session-{}.{}", session.id, extension)));
+    if path.as_os_str() == "-" {
+        print!("{}", text);
+        return Ok(());
+    }
+    fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    let commands = cells.iter().filter(|cell| matches!(cell, Cell::Command { .. })).count();
+    println!(
+        "{} Exported session {} to {} ({} cells, {} command{})",
+        "✓".green(),
+        session.id,
+        path.display(),
+        cells.len(),
+        commands,
+        if commands == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
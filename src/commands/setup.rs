@@ -3,13 +3,13 @@ use colored::Colorize;
 use std::io::{self, Write};
 use std::path::Path;
 
-use crate::config::{Config, ApprovalPolicy, SandboxMode};
+use crate::config::{self, Config, ApprovalPolicy, SandboxMode};
 
 pub fn execute() -> Result<()> {
     println!("{}", "=== Vork Configuration Setup ===".green().bold());
     println!();
 
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_file().unwrap_or_default();
 
     // Model directory
     println!("{}", "📁 Model Configuration".cyan().bold());
@@ -236,3 +236,109 @@ pub fn execute() -> Result<()> {
 
     Ok(())
 }
+
+/// Abbreviated setup shown the first time vork runs without a config file
+pub fn first_run() -> Result<()> {
+    println!("{}", "=== Welcome to Vork ===".green().bold());
+    println!(
+        "No config found at {}. Let's set up the essentials",
+        Config::config_path()?.display().to_string().cyan()
+    );
+    println!("(press Enter to accept a default; run {} later for every option).", "vork setup".cyan());
+    println!();
+
+    let mut config = Config::detected();
+    let has_ollama = config::find_in_path("ollama").is_some();
+
+    println!("{}", "Detected:".cyan().bold());
+    match &config.llamacpp.binary_path {
+        Some(binary) => println!("  {} llama-server at {}", "✓".green(), binary),
+        None => println!("  {} llama-server not found", "✗".red()),
+    }
+    if has_ollama {
+        println!("  {} ollama", "✓".green());
+    } else {
+        println!("  {} ollama not found", "✗".red());
+    }
+    println!();
+
+    let backend = ask("Backend (llamacpp/ollama)", &config.default_backend)?;
+    if backend == "llamacpp" || backend == "ollama" {
+        config.default_backend = backend;
+    } else {
+        println!("{} Unknown backend, keeping {}", "⚠️".yellow(), config.default_backend);
+    }
+
+    if config.default_backend == "llamacpp" {
+        let models_dir = ask("Models directory (.gguf files)", &config.llamacpp.models_dir)?;
+        let expanded = shellexpand::tilde(&models_dir).to_string();
+        if !Path::new(&expanded).exists() {
+            std::fs::create_dir_all(&expanded)?;
+            println!("{} Created {}", "✓".green(), expanded);
+        }
+        config.llamacpp.models_dir = models_dir;
+
+        let current = config.llamacpp.binary_path.clone().unwrap_or_default();
+        let binary = ask("Path to llama-server", &current)?;
+        if binary.is_empty() {
+            println!("{} No llama-server set; the server will not be auto-started", "⚠️".yellow());
+            config.llamacpp.binary_path = None;
+        } else {
+            if !Path::new(&*shellexpand::tilde(&binary)).is_file() {
+                println!("{} File not found: {} (saved anyway)", "⚠️".yellow(), binary);
+            }
+            config.llamacpp.binary_path = Some(binary);
+        }
+    } else {
+        config.ollama.api_url = ask("Ollama API URL", &config.ollama.api_url)?;
+        config.assistant.model = ask("Model (pulled on first use)", "qwen2.5-coder:7b")?;
+    }
+    config.assistant.backend = config.default_backend.clone();
+
+    println!();
+    println!("How much should the assistant do without asking?");
+    println!("  1. auto        - Auto-approve workspace edits, ask for anything else");
+    println!("  2. always-ask  - Prompt for every operation");
+    println!("  3. never       - Full automation (dangerous!)");
+    let (policy, sandbox) = match ask("Select", "1")?.as_str() {
+        "2" => (ApprovalPolicy::AlwaysAsk, SandboxMode::WorkspaceWrite),
+        "3" => (ApprovalPolicy::Never, SandboxMode::DangerFullAccess),
+        _ => (ApprovalPolicy::Auto, SandboxMode::WorkspaceWrite),
+    };
+    config.assistant.approval_policy = policy;
+    config.assistant.sandbox_mode = sandbox;
+
+    config.save()?;
+    println!();
+    println!(
+        "{} Configuration saved to {}",
+        "✓".green(),
+        Config::config_path()?.display().to_string().cyan()
+    );
+
+    let problems = config.validate();
+    if !problems.is_empty() {
+        println!("{} {} setting(s) still need attention:", "⚠️".yellow(), problems.len());
+        for problem in &problems {
+            println!("  • {}", problem);
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Prompt with a default shown in brackets; empty input returns the default
+fn ask(label: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", label);
+    } else {
+        print!("{} [{}]: ", label, default.yellow());
+    }
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    Ok(if input.is_empty() { default.to_string() } else { input.to_string() })
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use chrono::{Duration, Local, Utc};
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+use crate::llm::stats::UsageRecord;
+
+/// Totals for one row of a breakdown table
+#[derive(Default)]
+struct Totals {
+    sessions: usize,
+    requests: usize,
+    tokens: usize,
+    tool_calls: usize,
+    wall_time_secs: f64,
+    cost_usd: f64,
+}
+
+impl Totals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.sessions += 1;
+        self.requests += record.requests;
+        self.tokens += record.total_tokens;
+        self.tool_calls += record.tool_calls;
+        self.wall_time_secs += record.wall_time_secs;
+        self.cost_usd += record.cost_usd;
+    }
+}
+
+pub fn execute(days: Option<u32>, project_only: bool) -> Result<()> {
+    let mut records = UsageRecord::load_all()?;
+
+    if let Some(days) = days {
+        let cutoff = Utc::now() - Duration::days(days as i64);
+        records.retain(|r| r.timestamp >= cutoff);
+    }
+    if project_only {
+        let cwd = std::env::current_dir()?;
+        records.retain(|r| r.project == cwd);
+    }
+
+    println!("{}", "=== Vork Usage ===".green().bold());
+    if records.is_empty() {
+        println!("No usage recorded yet (stats are written to {})", UsageRecord::stats_path()?.display());
+        return Ok(());
+    }
+
+    let mut total = Totals::default();
+    let mut prompt_tokens = 0;
+    let mut completion_tokens = 0;
+    for record in &records {
+        total.add(record);
+        prompt_tokens += record.prompt_tokens;
+        completion_tokens += record.completion_tokens;
+    }
+
+    println!("{} {}", "Sessions:".cyan(), total.sessions);
+    println!("{} {}", "Requests:".cyan(), total.requests);
+    println!(
+        "{} {} ({} prompt / {} completion)",
+        "Tokens:".cyan(),
+        total.tokens,
+        prompt_tokens,
+        completion_tokens
+    );
+    println!("{} {}", "Tool calls:".cyan(), total.tool_calls);
+    println!("{} {}", "Wall time:".cyan(), format_duration(total.wall_time_secs));
+    if total.cost_usd > 0.0 {
+        println!("{} ${:.4}", "Cost:".cyan(), total.cost_usd);
+    }
+
+    print_breakdown("By day", &records, |r| {
+        r.timestamp.with_timezone(&Local).format("%Y-%m-%d").to_string()
+    });
+    if !project_only {
+        print_breakdown("By project", &records, |r| r.project.display().to_string());
+    }
+    print_breakdown("By agent", &records, |r| {
+        r.agent.clone().unwrap_or_else(|| "(default)".to_string())
+    });
+    print_breakdown("By model", &records, |r| match &r.preset {
+        Some(preset) => format!("{} [{}]", r.model, preset),
+        None => r.model.clone(),
+    });
+    print_breakdown("By command", &records, |r| r.command.clone());
+
+    Ok(())
+}
+
+fn print_breakdown<F>(title: &str, records: &[UsageRecord], key: F)
+where
+    F: Fn(&UsageRecord) -> String,
+{
+    let mut rows: BTreeMap<String, Totals> = BTreeMap::new();
+    for record in records {
+        rows.entry(key(record)).or_default().add(record);
+    }
+
+    println!();
+    println!("{}", title.yellow().bold());
+    println!(
+        "  {:<40} {:>8} {:>10} {:>12} {:>8} {:>10} {:>10}",
+        "", "sessions", "requests", "tokens", "tools", "time", "cost"
+    );
+    for (name, totals) in &rows {
+        println!(
+            "  {:<40} {:>8} {:>10} {:>12} {:>8} {:>10} {:>10}",
+            truncate(name, 40),
+            totals.sessions,
+            totals.requests,
+            totals.tokens,
+            totals.tool_calls,
+            format_duration(totals.wall_time_secs),
+            format!("${:.2}", totals.cost_usd)
+        );
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count <= width {
+        text.to_string()
+    } else {
+        // Keep the tail: for paths the last components are the useful part
+        let tail: String = text.chars().skip(count - (width - 1)).collect();
+        format!("…{}", tail)
+    }
+}
+
+fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use colored::Colorize;
 use crate::backends;
+use crate::llm::server::RunningServer;
 
 pub async fn execute() -> Result<()> {
     println!("{}", "LLM Backend Status:".green().bold());
@@ -18,6 +19,22 @@ pub async fn execute() -> Result<()> {
         println!("  {} {}", name.bold(), status);
     }
 
+    let servers = RunningServer::list();
+    if !servers.is_empty() {
+        println!();
+        println!("{}", "Servers started by vork (reused while the model and settings match):".cyan());
+        for server in servers {
+            println!(
+                "  :{} {} pid {}, {}k ctx, since {}",
+                server.port,
+                server.preset.as_deref().unwrap_or(&server.model).yellow(),
+                server.pid,
+                server.llamacpp.context_size / 1024,
+                server.started_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            );
+        }
+    }
+
     println!();
 
     Ok(())
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use arboard::Clipboard;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -13,43 +13,31 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
+use notify::{RecursiveMode, Watcher};
+use chrono::{DateTime, Local};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
 use std::env;
 use std::io;
-
-use crate::config::Config;
-use crate::llm::{LlamaClient, ServerManager, Session, ApprovalSystem};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, ProjectConfig};
+use crate::error::VorkError;
+use crate::llm::{LlamaClient, ServerManager, Session, ApprovalSystem, UsageTracker};
+use crate::llm::{arguments, planner, processes};
+use crate::llm::conversation::{format_took, tool_result, Compaction};
+use crate::llm::history::{self, PastPrompt};
+use crate::llm::templates::{self, Template};
+use crate::llm::router::{RouteRequest, Router};
+use crate::llm::client::ChatBackend;
 use crate::llm::tools::{get_available_tools, execute_tool};
 use crate::agents::Agent;
-
-fn detect_current_preset(config: &Config) -> String {
-    // Try to match current config against available presets
-    if let Ok(config_dir) = Config::config_dir() {
-        let presets_dir = config_dir.join("presets");
-        if let Ok(entries) = std::fs::read_dir(&presets_dir) {
-            for entry in entries.flatten() {
-                if entry.path().extension().and_then(|s| s.to_str()) == Some("toml") {
-                    if let Some(name) = entry.path().file_stem() {
-                        let name_str = name.to_string_lossy();
-                        if name_str == "README" {
-                            continue;
-                        }
-                        // Read preset and compare key fields
-                        if let Ok(preset_content) = std::fs::read_to_string(entry.path()) {
-                            if let Ok(preset_config) = toml::from_str::<Config>(&preset_content) {
-                                // Match on context_size and cuda_visible_devices as key identifiers
-                                if preset_config.llamacpp.context_size == config.llamacpp.context_size
-                                    && preset_config.llamacpp.cuda_visible_devices == config.llamacpp.cuda_visible_devices {
-                                    return name_str.to_string();
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    "custom".to_string()
-}
+use crate::backends::openai_compat::model_for_server;
+use crate::voice::{self, Recording, Speaker};
 
 fn parse_color(color_name: &str) -> Color {
     match color_name.to_lowercase().as_str() {
@@ -73,15 +61,14 @@ fn parse_color(color_name: &str) -> Color {
     }
 }
 
-fn fetch_gpu_stats() -> Vec<GpuStats> {
-    use std::process::Command;
-
-    let output = Command::new("nvidia-smi")
-        .args(&[
+async fn fetch_gpu_stats() -> Vec<GpuStats> {
+    let output = tokio::process::Command::new("nvidia-smi")
+        .args([
             "--query-gpu=name,memory.used,memory.total,utilization.gpu,temperature.gpu",
             "--format=csv,noheader,nounits"
         ])
-        .output();
+        .output()
+        .await;
 
     let Ok(output) = output else {
         return vec![];
@@ -111,6 +98,94 @@ fn fetch_gpu_stats() -> Vec<GpuStats> {
         .collect()
 }
 
+/// Every preset name in ~/.vork/presets, with "auto" first
+fn discover_presets() -> Vec<String> {
+    let mut presets = vec![];
+    if let Ok(config_dir) = Config::config_dir() {
+        if let Ok(entries) = std::fs::read_dir(config_dir.join("presets")) {
+            for entry in entries.flatten() {
+                if let Some(name) = entry.path().file_stem() {
+                    if entry.path().extension().and_then(|s| s.to_str()) == Some("toml")
+                        && name != "README" {
+                        presets.push(name.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+    }
+    presets.sort();
+    presets.insert(0, "auto".to_string());
+    presets
+}
+
+/// Watches ~/.vork/agents and ~/.vork/presets so edits show up mid-session
+struct ConfigWatch {
+    _watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<PathBuf>,
+    presets_changed: bool,
+    agents_changed: BTreeSet<String>,
+    last_event: Instant,
+}
+
+impl ConfigWatch {
+    /// Editors write a file in several steps; wait this long for them to finish
+    const SETTLE: Duration = Duration::from_millis(300);
+
+    fn start() -> Option<Self> {
+        let config_dir = Config::config_dir().ok()?;
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        })
+        .ok()?;
+        for dir in ["agents", "presets"] {
+            let _ = watcher.watch(&config_dir.join(dir), RecursiveMode::NonRecursive);
+        }
+        Some(Self {
+            _watcher: watcher,
+            events,
+            presets_changed: false,
+            agents_changed: BTreeSet::new(),
+            last_event: Instant::now(),
+        })
+    }
+
+    /// Collect pending events; returns (presets changed, changed agent names)
+    /// once the files have settled
+    fn poll(&mut self) -> Option<(bool, BTreeSet<String>)> {
+        for path in self.events.try_iter() {
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            // Editor scratch files and swap files
+            if name.starts_with('.') {
+                continue;
+            }
+            let dir = path.parent().and_then(|p| p.file_name()).and_then(|s| s.to_str());
+            match (dir, path.extension().and_then(|s| s.to_str())) {
+                (Some("presets"), Some("toml")) => self.presets_changed = true,
+                (Some("agents"), Some("json" | "md")) => {
+                    self.agents_changed.insert(name.to_string());
+                }
+                _ => continue,
+            }
+            self.last_event = Instant::now();
+        }
+
+        if (!self.presets_changed && self.agents_changed.is_empty()) || self.last_event.elapsed() < Self::SETTLE {
+            return None;
+        }
+        Some((std::mem::take(&mut self.presets_changed), std::mem::take(&mut self.agents_changed)))
+    }
+}
+
+#[derive(PartialEq)]
 struct GpuStats {
     name: String,
     memory_used: u32,
@@ -119,6 +194,37 @@ struct GpuStats {
     temperature: u32,
 }
 
+/// Ctrl+R search through the prompts of past sessions
+struct HistorySearch {
+    query: String,
+    prompts: Vec<PastPrompt>,
+    selected: usize,
+}
+
+impl HistorySearch {
+    fn matches(&self) -> Vec<&PastPrompt> {
+        history::search(&self.prompts, &self.query)
+    }
+}
+
+/// Where the active agent came from, shown next to it in the status bar
+#[derive(Clone, Copy)]
+enum AgentSource {
+    Flag,
+    Project,
+    Auto,
+}
+
+impl AgentSource {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Flag => "--agent",
+            Self::Project => ProjectConfig::FILE_NAME,
+            Self::Auto => "auto-selected",
+        }
+    }
+}
+
 struct App {
     input: String,
     messages: Vec<(String, String)>, // (role, content)
@@ -126,7 +232,7 @@ struct App {
     input_scroll: u16,  // Vertical scroll offset for input box
     auto_scroll: bool,  // Auto-scroll to follow new messages
     session: Session,
-    client: LlamaClient,
+    client: Arc<LlamaClient>,
     approval_system: ApprovalSystem,
     status: String,
     tokens_used: usize,
@@ -148,18 +254,51 @@ struct App {
     selected_preset_index: usize,
     model_override: Option<String>,  // None = auto, Some = forced preset
     current_preset_name: String,  // Track current preset for display
+    usage: UsageTracker,
+    recording: Option<Recording>,  // Ctrl+T dictation in progress
+    agent_preset: Option<String>,  // Preferred preset of the active agent
+    speaker: Speaker,
+    speak_enabled: bool,  // /speak toggles reading replies aloud
+    agent_name: Option<String>,  // Active agent, re-read from disk by /reload
+    agent_source: Option<AgentSource>,
+    project_preset: Option<String>,  // `preset` from .vork.toml, routed to for a new conversation
+    config_watch: Option<ConfigWatch>,
+    plan_mode: bool,  // /plan: show a plan for approval before tools run
+    raw_mode: bool,  // /raw: prompts go to the model without the system prompt or tools
+    tools_enabled: bool,  // False for agents that chat without tools
+    awaiting_plan: bool,  // A plan is shown and the next message answers it
+    history_search: Option<HistorySearch>,
+    show_times: bool,  // /times toggles message times and turn durations
+    stamps: HashMap<usize, (DateTime<Local>, Option<chrono::Duration>)>,  // Index in `messages` → when it was sent, and for replies how long the turn took
+    turn_started: DateTime<Local>,
+    line_cache: RefCell<LineCache>,
+    compaction: Option<tokio::task::JoinHandle<(Compaction, Result<String>)>>,  // Older messages being summarized in the background
 }
 
 impl App {
-    fn new(server_url: String, model: String, config: Config, agent: Option<Agent>) -> Self {
+    fn new(
+        server_url: String,
+        model: String,
+        config: Config,
+        agent: Option<(Agent, AgentSource)>,
+        project_preset: Option<String>,
+    ) -> Self {
+        let agent_source = agent.as_ref().map(|(_, source)| *source);
+        let agent = agent.map(|(agent, _)| agent);
         let working_dir = env::current_dir().unwrap_or_default();
-        let mut session = Session::new(working_dir);
+        let mut session = Session::open(working_dir.clone()).unwrap_or_else(|_| Session::new(working_dir));
+        let resumed = session.conversation.messages.len() > 1;
         session.conversation.set_max_context(config.llamacpp.context_limit);
-        let client = LlamaClient::new(server_url.clone(), model.clone());
+        let tools_enabled = agent.as_ref().is_none_or(|a| a.tools_enabled);
+        session.conversation.set_tools(tools_enabled.then(get_available_tools).as_deref());
+        let client = Arc::new(LlamaClient::for_config(&config, server_url.clone(), model.clone()));
+        client.quiet_queue();
         let approval_system = ApprovalSystem::new(
             config.assistant.approval_policy.clone(),
             config.assistant.sandbox_mode.clone(),
-        );
+        )
+        .with_agent(agent.as_ref())
+        .with_prompter(Box::new(ask_in_modal));
 
         // Extract agent color and title
         let agent_color = if let Some(ref agent) = agent {
@@ -176,42 +315,22 @@ impl App {
 
         // Use agent's system prompt if provided
         if let Some(ref agent) = agent {
-            session.conversation.messages[0].content = agent.system_prompt.clone();
+            session.conversation.set_system_prompt(agent.system_prompt.clone());
         }
 
-        let agent_info = if let Some(ref agent) = agent {
-            format!(" | Agent: {}", agent.name)
-        } else {
-            String::new()
-        };
-
         // Find available presets - automatically discover all .toml files in presets directory
-        let mut available_presets = vec!["auto".to_string()];  // Start with "auto" option
-        if let Ok(config_dir) = Config::config_dir() {
-            let presets_dir = config_dir.join("presets");
-            if presets_dir.exists() {
-                if let Ok(entries) = std::fs::read_dir(&presets_dir) {
-                    for entry in entries.flatten() {
-                        if let Some(name) = entry.path().file_stem() {
-                            if entry.path().extension().and_then(|s| s.to_str()) == Some("toml")
-                                && name != "README" {
-                                available_presets.push(name.to_string_lossy().to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        // Sort presets but keep "auto" first
-        let mut other_presets = available_presets[1..].to_vec();
-        other_presets.sort();
-        available_presets = vec!["auto".to_string()];
-        available_presets.extend(other_presets);
+        let available_presets = discover_presets();
 
-        // Detect current preset by comparing config file
-        let current_preset_name = detect_current_preset(&config);
+        // The preset last switched to, marked when config.toml was edited since
+        let current_preset_name = Config::active_preset()
+            .map(|active| active.to_string())
+            .unwrap_or_else(|| "custom".to_string());
         let context_info = format!("{}k ctx", config.llamacpp.context_size / 1024);
 
+        let mut usage = UsageTracker::new("tui", &session.id, &model);
+        usage.set_agent(agent.as_ref().map(|a| a.name.as_str()));
+        usage.set_preset(Some(&current_preset_name));
+
         let mut app = Self {
             input: String::new(),
             messages: vec![],
@@ -221,7 +340,7 @@ impl App {
             session,
             client,
             approval_system,
-            status: format!("Preset: {} ({}) | Mode: auto{}", current_preset_name, context_info, agent_info),
+            status: format!("Preset: {} ({}) | Mode: auto", current_preset_name, context_info),
             tokens_used: 0,
             processing: false,
             spinner_state: 0,
@@ -231,7 +350,7 @@ impl App {
             header_title: header_title.clone(),
             agent_explicitly_set: agent.is_some(),
             first_message: true,
-            input_history: vec![],
+            input_history: history::load_inputs(),
             history_index: None,
             current_input_backup: String::new(),
             gpu_stats: vec![],
@@ -240,6 +359,25 @@ impl App {
             selected_preset_index: 0,
             model_override: None,  // Start in auto mode
             current_preset_name: current_preset_name.clone(),
+            usage,
+            recording: None,
+            agent_preset: agent.as_ref().and_then(|a| a.preferred_preset.clone()),
+            speaker: Speaker::default(),
+            speak_enabled: config.voice.speak,
+            agent_name: agent.as_ref().map(|a| a.name.clone()),
+            agent_source,
+            project_preset,
+            config_watch: ConfigWatch::start(),
+            plan_mode: config.assistant.require_plan_approval,
+            raw_mode: false,
+            tools_enabled,
+            awaiting_plan: false,
+            history_search: None,
+            show_times: true,
+            stamps: HashMap::new(),
+            turn_started: Local::now(),
+            line_cache: RefCell::default(),
+            compaction: None,
         };
 
         // Add system message with agent info
@@ -254,6 +392,9 @@ impl App {
             welcome_msg,
         ));
 
+        if resumed {
+            app.show_session();
+        }
         app
     }
 
@@ -267,9 +408,11 @@ impl App {
 
         // Add user message to display immediately
         self.messages.push(("user".to_string(), user_message.clone()));
+        self.turn_started = Local::now();
+        self.stamp_last(None);
 
-        // Add to input history
-        self.input_history.push(user_message);
+        // Add to input history, which outlives this run
+        let _ = history::remember_input(&mut self.input_history, &user_message);
         self.history_index = None;
         self.current_input_backup.clear();
 
@@ -296,76 +439,106 @@ impl App {
         let user_message = self.input_history.last().unwrap().clone();
 
         // Auto-select agent based on first message if no agent was explicitly set
-        if self.first_message && !self.agent_explicitly_set {
+        if self.first_message && !self.agent_explicitly_set && !self.raw_mode {
             if let Ok(Some(agent)) = Agent::auto_select(&user_message) {
-                // Update session with agent's system prompt
-                self.session.conversation.messages[0].content = agent.system_prompt.clone();
-
-                // Update UI with agent's color and title
-                self.agent_color = parse_color(&agent.color);
-                self.header_title = agent.title.clone().unwrap_or_else(|| format!("🤖 {}", agent.name));
-
-                // Switch model preset if agent has a preference AND no manual override is set
-                if self.model_override.is_none() {
-                    if let Some(ref preferred_preset) = agent.preferred_preset {
-                        // Attempt to switch preset (no intermediate messages during switch)
-                        if let Err(e) = self.switch_to_preset(preferred_preset).await {
-                            self.messages.push((
-                                "system".to_string(),
-                                format!("🎯 Agent: {} - {} | ⚠️  Failed to switch model: {}",
-                                    agent.name, agent.description, e),
-                            ));
-                        } else {
-                            // Update current preset tracking
-                            self.current_preset_name = preferred_preset.clone();
-
-                            // Update status bar
-                            if let Ok(config) = Config::load() {
-                                let context_info = format!("{}k ctx", config.llamacpp.context_size / 1024);
-                                self.status = format!("Preset: {} ({}) | Mode: auto", preferred_preset, context_info);
-                            }
-
-                            // Single message after successful switch
-                            self.messages.push((
-                                "system".to_string(),
-                                format!("🎯 Agent: {} - {} | ✅ Model: {}",
-                                    agent.name, agent.description, preferred_preset),
-                            ));
-                        }
-                    } else {
-                        // Show agent selection message
-                        self.messages.push((
-                            "system".to_string(),
-                            format!("🎯 Auto-selected agent: {} - {}", agent.name, agent.description),
-                        ));
-                    }
-                } else {
-                    // Model override is set, don't auto-switch
-                    self.messages.push((
-                        "system".to_string(),
-                        format!("🎯 Auto-selected agent: {} - {} (using forced model: {})",
-                            agent.name, agent.description, self.model_override.as_ref().unwrap()),
-                    ));
-                }
+                self.apply_agent(&agent);
+                self.agent_source = Some(AgentSource::Auto);
+
+                let forced = self
+                    .model_override
+                    .as_ref()
+                    .map(|forced| format!(" (using forced model: {})", forced))
+                    .unwrap_or_default();
+                self.messages.push((
+                    "system".to_string(),
+                    format!("🎯 Auto-selected agent: {} - {}{}", agent.name, agent.description, forced),
+                ));
             }
-            self.first_message = false;
         }
+        let first_turn = self.first_message;
+        self.first_message = false;
 
-        let start_time = std::time::Instant::now();
-        let mut total_tokens = 0usize;
+        // In auto mode, pick the preset for this request
+        if self.model_override.is_none() {
+            self.route_request(&user_message, first_turn).await;
+        }
 
-        self.session.conversation.add_user_message(user_message);
+        let mut total_tokens = 0usize;
+        let mut generation_secs = 0.0;
+
+        // In plan mode a new request gets a plan first; "y" carries it out
+        // and anything else is taken as a correction to plan again
+        let approving = self.awaiting_plan && planner::is_approval(&user_message);
+        self.awaiting_plan = false;
+        if approving {
+            self.session.conversation.add_user_message(planner::PLAN_APPROVED.to_string());
+        } else {
+            self.session.conversation.add_user_message(user_message);
+        }
+        if self.plan_mode && !approving && !self.raw_mode {
+            let (plan, response) = planner::plan(&self.client, self.session.conversation.get_messages()).await?;
+            self.usage.record_response(response.usage.as_ref());
+            if let Some(last_msg) = self.messages.last() {
+                if last_msg.0 == "system" && last_msg.1 == "💭 Thinking..." {
+                    self.messages.pop();
+                }
+            }
+            self.session.conversation.add_assistant_message(plan.clone());
+            self.messages.push(("assistant".to_string(), format!("📋 Plan:\n{}", plan)));
+            self.stamp_last(Some(Local::now() - self.turn_started));
+            self.messages.push((
+                "system".to_string(),
+                "📋 Reply y to carry out this plan, or say what to change".to_string(),
+            ));
+            self.awaiting_plan = true;
+            self.session.save()?;
+            self.processing = false;
+            if self.auto_scroll {
+                self.scroll = u16::MAX;
+            }
+            return Ok(());
+        }
 
         // Process with LLM
         loop {
+            if let Err(e) = self.usage.check_budget() {
+                self.messages.push(("error".to_string(), format!("💸 {}", e)));
+                break;
+            }
+
+            // Over the threshold with a summary on the way: the request
+            // waits for it rather than going out oversized
+            if self.compaction.is_some() && self.session.conversation.needs_compaction() {
+                self.messages.push(("system".to_string(), "⏳ Waiting for the summary of older messages".to_string()));
+                self.finish_compaction(true).await;
+            }
+            let tools = self.tools();
+            if let Some(notice) = self.session.conversation.fit_to_context(&self.client, tools.as_deref()).await? {
+                self.messages.push(("system".to_string(), notice));
+            }
+            let messages = self.session.conversation.get_messages();
+            let request_start = std::time::Instant::now();
             let response = self
                 .client
                 .chat_completion(
-                    self.session.conversation.get_messages(),
-                    Some(get_available_tools()),
+                    if self.raw_mode { &messages[1..] } else { messages },
+                    tools.as_deref(),
                 )
                 .await
                 .context("Failed to get response from LLM")?;
+            self.usage.record_response(response.usage.as_ref());
+
+            let (tokens, secs) = response.generation(request_start.elapsed());
+            total_tokens += tokens;
+            generation_secs += secs;
+
+            if let Some(notice) = self.client.take_failover_notice() {
+                self.status = format!("{} | Model: {}", notice, self.client.model());
+                self.messages.push(("system".to_string(), notice));
+            }
+            if let Some(notice) = self.client.take_queue_notice() {
+                self.messages.push(("system".to_string(), notice));
+            }
 
             let choice = response
                 .choices
@@ -383,16 +556,23 @@ impl App {
 
                 for tool_call in tool_calls {
                     let tool_name = &tool_call.function.name;
-                    let arguments: serde_json::Value =
-                        serde_json::from_str(&tool_call.function.arguments)
-                            .context("Failed to parse tool arguments")?;
+                    let arguments = match arguments::parse(tool_name, &tool_call.function.arguments) {
+                        Ok(arguments) => arguments,
+                        Err(e) => {
+                            let error_msg = format!("Error: {}", e);
+                            self.session.conversation.add_tool_result(tool_name, &error_msg);
+                            self.messages.push(("error".to_string(), error_msg));
+                            continue;
+                        }
+                    };
 
                     self.messages.push((
                         "tool".to_string(),
                         format!("🔧 Executing: {}", tool_name),
                     ));
+                    self.usage.record_tool_call();
 
-                    match execute_tool(tool_name, arguments, Some(&self.approval_system)).await {
+                    match execute_tool(tool_name, arguments, Some(&self.approval_system), &self.session.tool_dir()).await {
                         Ok(result) => {
                             self.session.conversation.add_tool_result(tool_name, &result);
                             // Show truncated result
@@ -441,12 +621,21 @@ impl App {
                     .join("\n");
 
                 if !filtered_content.is_empty() {
+                    if self.speak_enabled {
+                        if let Err(e) = Config::load().and_then(|c| self.speaker.say(&c.voice, &filtered_content)) {
+                            self.speak_enabled = false;
+                            self.messages.push((
+                                "system".to_string(),
+                                format!("🔇 Speech turned off: {}", e),
+                            ));
+                        }
+                    }
                     self.messages
                         .push(("assistant".to_string(), filtered_content.clone()));
+                    self.stamp_last(Some(Local::now() - self.turn_started));
                     self.session
                         .conversation
                         .add_assistant_message(filtered_content.clone());
-                    total_tokens += filtered_content.len() / 4; // Rough estimate
                 } else if content.trim().is_empty() {
                     // If content is empty or only whitespace, show a warning
                     self.messages.push((
@@ -465,21 +654,13 @@ impl App {
             break;
         }
 
-        // Calculate tokens/second
-        let elapsed = start_time.elapsed().as_secs_f64();
-        if elapsed > 0.0 && total_tokens > 0 {
-            self.tokens_per_second = total_tokens as f64 / elapsed;
+        // Generation speed across every response in this turn
+        if generation_secs > 0.0 && total_tokens > 0 {
+            self.tokens_per_second = total_tokens as f64 / generation_secs;
         }
         self.tokens_used += total_tokens;
 
-        // Compact conversation if needed
-        let compacted = self.session.conversation.compact_if_needed(&self.client).await?;
-        if compacted {
-            self.messages.push((
-                "system".to_string(),
-                "🔄 Context compaction completed: Older messages have been summarized to save space while preserving key information.".to_string()
-            ));
-        }
+        self.start_compaction();
 
         self.session.save()?;
         self.processing = false;
@@ -499,10 +680,78 @@ impl App {
             max,
             percentage
         );
+        if let Some(cost) = self.usage.cost() {
+            self.status.push_str(&format!(" | Cost: ${:.4}", cost));
+        }
 
         Ok(())
     }
 
+    /// Summarize older messages in a task of its own once the context is 75%
+    /// full, so the turn that crosses the line does not wait for it
+    fn start_compaction(&mut self) {
+        if self.compaction.is_some() || !self.session.conversation.needs_compaction() {
+            return;
+        }
+        let Some(compaction) = self.session.conversation.compaction() else {
+            return;
+        };
+        let client = self.client.clone();
+        self.compaction = Some(tokio::spawn(async move {
+            let summary = compaction.summarize(&client).await;
+            (compaction, summary)
+        }));
+    }
+
+    /// Apply a background summary once it is ready, or with `wait` as soon
+    /// as it is; returns whether one was
+    async fn finish_compaction(&mut self, wait: bool) -> bool {
+        if !self.compaction.as_ref().is_some_and(|task| wait || task.is_finished()) {
+            return false;
+        }
+        let Some(task) = self.compaction.take() else {
+            return false;
+        };
+        match task.await {
+            Ok((compaction, Ok(summary))) => {
+                // Compacted another way meanwhile (/compact, a smaller preset)
+                if !self.session.conversation.apply_compaction(&compaction, summary) {
+                    return false;
+                }
+                self.messages.push((
+                    "system".to_string(),
+                    "🔄 Context compaction completed: Older messages have been summarized to save space while preserving key information.".to_string()
+                ));
+                let _ = self.session.save();
+            }
+            Ok((_, Err(e))) => self.messages.push(("error".to_string(), format!("❌ Context compaction failed: {:#}", e))),
+            Err(e) => self.messages.push(("error".to_string(), format!("❌ Context compaction failed: {}", e))),
+        }
+        if let Some(notice) = self.client.take_failover_notice() {
+            self.messages.push(("system".to_string(), notice));
+        }
+        if self.auto_scroll {
+            self.scroll = u16::MAX;
+        }
+        true
+    }
+
+    /// Report a failed request in the chat instead of leaving the TUI, with
+    /// a hint for the errors the user can do something about
+    fn show_send_error(&mut self, error: &anyhow::Error) {
+        self.processing = false;
+        if self.messages.last().is_some_and(|(role, text)| role == "system" && text == "💭 Thinking...") {
+            self.messages.pop();
+        }
+        self.messages.push(("error".to_string(), format!("❌ {:#}", error)));
+        if let Some(hint) = VorkError::find(error).and_then(VorkError::hint) {
+            self.messages.push(("system".to_string(), format!("💡 {}", hint)));
+        }
+        if self.auto_scroll {
+            self.scroll = u16::MAX;
+        }
+    }
+
     async fn handle_compact_command(&mut self) -> Result<()> {
         self.input.clear();
         self.input_scroll = 0;
@@ -524,72 +773,8 @@ impl App {
             "🔄 Starting manual context compaction...".to_string()
         ));
 
-        // Temporarily override the needs_compaction check by setting a flag
-        let system_msg = self.session.conversation.messages[0].clone();
-        let messages_to_compact: Vec<_> = self.session.conversation.messages.iter()
-            .skip(1)
-            .take(self.session.conversation.messages.len() - 11)
-            .cloned()
-            .collect();
-        let recent_messages: Vec<_> = self.session.conversation.messages.iter()
-            .skip(self.session.conversation.messages.len() - 10)
-            .cloned()
-            .collect();
-
-        if messages_to_compact.is_empty() {
-            self.messages.push((
-                "system".to_string(),
-                "❌ Cannot compact: Not enough messages to summarize.".to_string()
-            ));
-            return Ok(());
-        }
-
-        // Create summarization prompt
-        let conversation_text = messages_to_compact.iter()
-            .map(|m| format!("{}: {}", m.role, m.content))
-            .collect::<Vec<_>>()
-            .join("\n\n");
-
-        let summary_prompt = format!(
-            "Summarize the following conversation history concisely, preserving key facts, decisions, and context. Focus on:\n\
-            - Important technical details and decisions\n\
-            - File modifications and their purposes\n\
-            - Commands executed and their results\n\
-            - Any errors or issues encountered\n\n\
-            Conversation:\n{}\n\n\
-            Provide a concise summary in 2-3 paragraphs:",
-            conversation_text
-        );
-
-        // Get summary from LLM
-        let response = self.client.chat_completion(vec![
-            super::super::llm::client::Message {
-                role: "user".to_string(),
-                content: summary_prompt,
-            }
-        ], None).await?;
-
-        let summary_response = response.choices[0].message.content.clone()
-            .unwrap_or_default();
-
-        // Rebuild conversation with summary
-        let summary_msg = super::super::llm::client::Message {
-            role: "assistant".to_string(),
-            content: format!("[Conversation summary of {} messages]\n\n{}",
-                messages_to_compact.len(), summary_response),
-        };
-
-        // Recalculate tokens
-        self.session.conversation.estimated_tokens =
-            (system_msg.content.len() / 4) + 10 +
-            (summary_msg.content.len() / 4) + 10;
-        for msg in &recent_messages {
-            self.session.conversation.estimated_tokens += (msg.content.len() / 4) + 10;
-        }
-
-        // Rebuild messages
-        self.session.conversation.messages = vec![system_msg, summary_msg];
-        self.session.conversation.messages.extend(recent_messages);
+        // Compact now, even below the usual threshold
+        self.session.conversation.compact(&self.client).await?;
 
         let (used_after, _, _) = self.session.conversation.get_context_usage();
         let saved_tokens = used_before.saturating_sub(used_after);
@@ -637,28 +822,70 @@ impl App {
         let config_dir = Config::config_dir()?;
         let presets_dir = config_dir.join("presets");
         let preset_path = presets_dir.join(format!("{}.toml", preset_name));
-        let config_path = Config::config_path()?;
 
-        if !preset_path.exists() {
+        if !tokio::fs::try_exists(&preset_path).await? {
             anyhow::bail!("Preset file not found: {:?}", preset_path);
         }
 
-        // Copy preset to config for persistence
-        std::fs::copy(&preset_path, &config_path)
-            .context("Failed to copy preset to config")?;
+        let preset: Config = toml::from_str(&tokio::fs::read_to_string(&preset_path).await?)
+            .with_context(|| format!("Preset '{}' is not a valid config", preset_name))?;
 
-        // Kill existing llama-server
-        let _ = std::process::Command::new("pkill")
-            .arg("llama-server")
-            .output();
+        // Copy preset to config for persistence
+        let name = preset_name.to_string();
+        let current = processes::blocking(move || -> Result<Config> {
+            let current = Config::load_file()?;
+            Config::apply_preset(&name)?;
+            Ok(current)
+        })
+        .await?;
 
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        // Keep the current URL (it may come from --server) unless the preset
+        // moves to a different chat backend
+        let backend = ChatBackend::from_name(&preset.assistant.backend);
+        let server_url = if backend == self.client.backend() {
+            self.client.base_url().to_string()
+        } else {
+            preset.chat_server_url()
+        };
 
-        // Start new server with the new config
-        crate::backends::llamacpp::LlamaCppBackend::start_server(8080)?;
+        // Same model and server settings: only client-side values change, so
+        // keep the running server unless it is not answering. Ollama loads
+        // models on demand and never needs a restart.
+        let restart = backend == ChatBackend::LlamaCpp
+            && (current.assistant.model != preset.assistant.model
+                || current.llamacpp.requires_restart(&preset.llamacpp)
+                || ServerManager::wait_for_health(&server_url, Duration::from_millis(500)).await.is_err());
+
+        if restart {
+            let _ = tokio::process::Command::new("pkill")
+                .arg("llama-server")
+                .output()
+                .await;
+            ServerManager::wait_for_shutdown(&server_url, Duration::from_secs(10)).await?;
+
+            // Finding the binary and the model file walks the disk
+            processes::blocking(|| crate::backends::llamacpp::LlamaCppBackend::start_server(8080)).await?;
+            ServerManager::wait_for_health(&server_url, Duration::from_secs(120)).await?;
+        }
 
-        // Give server time to initialize
-        tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+        self.client = Arc::new(LlamaClient::for_config(&preset, server_url, preset.assistant.model.clone()));
+        self.client.quiet_queue();
+        self.session.conversation.set_max_context(preset.llamacpp.context_limit);
+
+        // A smaller window may no longer hold the conversation: shrink it now
+        // instead of letting the new server truncate the next prompt
+        let tools = self.tools();
+        match self.session.conversation.fit_to_context(&self.client, tools.as_deref()).await {
+            Ok(Some(notice)) => {
+                self.messages.push(("system".to_string(), notice));
+                self.session.save()?;
+            }
+            Ok(None) => {}
+            Err(e) => self.messages.push((
+                "error".to_string(),
+                format!("⚠️  The conversation may not fit {}'s context and could not be compacted: {}", preset_name, e),
+            )),
+        }
 
         Ok(())
     }
@@ -697,6 +924,7 @@ impl App {
                 // Set override so this model is used for all agents
                 self.model_override = Some(preset_name.clone());
                 self.current_preset_name = preset_name.clone();
+                self.usage.set_preset(Some(&preset_name));
 
                 // Reload config to get new context size
                 if let Ok(new_config) = Config::load() {
@@ -723,6 +951,285 @@ impl App {
         Ok(())
     }
 
+    /// Let the router pick a preset for this request and log its decision
+    async fn route_request(&mut self, prompt: &str, first_turn: bool) {
+        let Ok(router) = Router::load() else {
+            return;
+        };
+        let request = RouteRequest {
+            prompt,
+            context_tokens: self.session.conversation.context_tokens() + prompt.len() / 4,
+            first_turn,
+            agent_preset: self.agent_preset.as_deref(),
+            project_preset: self.project_preset.as_deref(),
+            current_preset: &self.current_preset_name,
+        };
+        let Some(route) = router.route(&request) else {
+            return;
+        };
+
+        match self.switch_to_preset(&route.preset).await {
+            Ok(()) => {
+                self.current_preset_name = route.preset.clone();
+                self.usage.set_preset(Some(&route.preset));
+                if let Ok(config) = Config::load() {
+                    let context_info = format!("{}k ctx", config.llamacpp.context_size / 1024);
+                    self.status = format!("Preset: {} ({}) | Mode: auto", route.preset, context_info);
+                }
+                self.messages.push((
+                    "system".to_string(),
+                    format!("🧭 Routed to {} - {}", route.preset, route.reason),
+                ));
+            }
+            Err(e) => self.messages.push((
+                "system".to_string(),
+                format!("🧭 Routing to {} ({}) failed: {}", route.preset, route.reason, e),
+            )),
+        }
+    }
+
+    /// Make `agent` the active agent: its prompt, limits, colors and preset
+    fn apply_agent(&mut self, agent: &Agent) {
+        self.session.conversation.set_system_prompt(agent.system_prompt.clone());
+        self.usage.set_agent(Some(&agent.name));
+        self.approval_system.set_agent(Some(agent));
+        self.agent_name = Some(agent.name.clone());
+
+        // Update UI with agent's color and title
+        self.agent_color = parse_color(&agent.color);
+        self.header_title = agent.title.clone().unwrap_or_else(|| format!("🤖 {}", agent.name));
+        self.agent_preset = agent.preferred_preset.clone();
+        self.tools_enabled = agent.tools_enabled;
+        self.session.conversation.set_tools(self.tools().as_deref());
+    }
+
+    /// Tools sent with requests: none in raw mode, where the model sees the
+    /// conversation alone, or for agents with tools turned off
+    fn tools(&self) -> Option<Vec<serde_json::Value>> {
+        (!self.raw_mode && self.tools_enabled).then(get_available_tools)
+    }
+
+    /// Pick up agent and preset files edited during the session. The preset
+    /// list refreshes on its own; the active agent changes only on /reload
+    /// so its prompt never shifts under a running conversation.
+    /// Pick up edited presets and agents; returns whether anything changed
+    fn poll_config_changes(&mut self) -> bool {
+        let Some((presets_changed, agents_changed)) = self.config_watch.as_mut().and_then(ConfigWatch::poll) else {
+            return false;
+        };
+
+        if presets_changed {
+            self.refresh_presets();
+            self.messages.push((
+                "system".to_string(),
+                format!("🔄 Presets reloaded ({} available)", self.available_presets.len() - 1),
+            ));
+        }
+        if let Some(ref name) = self.agent_name {
+            if agents_changed.contains(name) {
+                self.messages.push((
+                    "system".to_string(),
+                    format!("✏️  Agent {} changed on disk - /reload to use it", name),
+                ));
+            }
+        }
+        if self.auto_scroll {
+            self.scroll = u16::MAX;
+        }
+        true
+    }
+
+    fn refresh_presets(&mut self) {
+        let selected = self.available_presets.get(self.selected_preset_index).cloned();
+        self.available_presets = discover_presets();
+        self.selected_preset_index = selected
+            .and_then(|name| self.available_presets.iter().position(|p| *p == name))
+            .unwrap_or(0);
+    }
+
+    /// Re-read the active agent (and the preset list) from disk
+    fn handle_reload_command(&mut self) {
+        self.input.clear();
+        self.input_scroll = 0;
+        self.refresh_presets();
+
+        let message = match self.agent_name.clone() {
+            Some(name) => match Agent::load(&name) {
+                Ok(agent) => {
+                    self.apply_agent(&agent);
+                    format!("🔄 Reloaded agent {} and {} presets", name, self.available_presets.len() - 1)
+                }
+                Err(e) => format!("❌ Could not reload agent {}: {:#} (keeping the previous version)", name, e),
+            },
+            None => format!("🔄 Reloaded {} presets (no agent active)", self.available_presets.len() - 1),
+        };
+        self.messages.push(("system".to_string(), message));
+    }
+
+    fn handle_plan_command(&mut self) {
+        self.input.clear();
+        self.input_scroll = 0;
+
+        self.plan_mode = !self.plan_mode;
+        if !self.plan_mode {
+            self.awaiting_plan = false;
+        }
+        self.messages.push((
+            "system".to_string(),
+            if self.plan_mode {
+                "📋 Plan mode on: each request gets a plan to approve before tools run (/plan to turn off)".to_string()
+            } else {
+                "📋 Plan mode off".to_string()
+            },
+        ));
+    }
+
+    /// `/prompt <name> [key=value ...] [text]`: put the filled-in template in
+    /// the input to be sent; `/prompt` alone lists the templates. Returns
+    /// whether there is a prompt to send.
+    fn handle_prompt_command(&mut self) -> bool {
+        let args = self.input.trim().trim_start_matches("/prompt").trim().to_string();
+        self.input.clear();
+        self.input_scroll = 0;
+
+        let Some(name) = args.split_whitespace().next() else {
+            let listing = match Template::list() {
+                Ok(list) if list.is_empty() => Template::dir()
+                    .map(|dir| format!("📝 No prompt templates yet: add <name>.md files to {}", dir.display()))
+                    .unwrap_or_default(),
+                Ok(list) => {
+                    let mut listing = "📝 Prompt templates (/prompt <name> [key=value ...] [text]):".to_string();
+                    for template in list {
+                        let variables = template.variables();
+                        listing.push_str(&format!("\n  {} - {}", template.name, template.summary()));
+                        if !variables.is_empty() {
+                            listing.push_str(&format!(" [{}]", variables.join(", ")));
+                        }
+                    }
+                    listing
+                }
+                Err(e) => format!("❌ {:#}", e),
+            };
+            self.messages.push(("system".to_string(), listing));
+            return false;
+        };
+
+        let (vars, text) = templates::parse_args(&args[name.len()..]);
+        match Template::load(name).and_then(|template| template.render(&vars, &text)) {
+            Ok(prompt) => {
+                self.input = prompt;
+                true
+            }
+            Err(e) => {
+                self.messages.push(("error".to_string(), format!("❌ {:#}", e)));
+                false
+            }
+        }
+    }
+
+    fn handle_raw_command(&mut self) {
+        self.input.clear();
+        self.input_scroll = 0;
+
+        self.raw_mode = !self.raw_mode;
+        self.session.conversation.set_tools(self.tools().as_deref());
+        self.messages.push((
+            "system".to_string(),
+            if self.raw_mode {
+                "🗣️  Raw mode on: prompts go to the model as they are, without the coding-assistant prompt or tools (/raw to turn off)".to_string()
+            } else {
+                "🗣️  Raw mode off".to_string()
+            },
+        ));
+    }
+
+    fn handle_times_command(&mut self) {
+        self.input.clear();
+        self.input_scroll = 0;
+
+        self.show_times = !self.show_times;
+        self.messages.push((
+            "system".to_string(),
+            if self.show_times {
+                "🕒 Showing message times and turn durations".to_string()
+            } else {
+                "🕒 Message times hidden".to_string()
+            },
+        ));
+    }
+
+    /// Remember when the newest message was shown
+    fn stamp_last(&mut self, took: Option<chrono::Duration>) {
+        if let Some(index) = self.messages.len().checked_sub(1) {
+            self.stamps.insert(index, (Local::now(), took));
+        }
+    }
+
+    fn handle_speak_command(&mut self) {
+        self.input.clear();
+        self.input_scroll = 0;
+
+        self.speak_enabled = !self.speak_enabled;
+        if !self.speak_enabled {
+            self.speaker.stop();
+        }
+        self.messages.push((
+            "system".to_string(),
+            if self.speak_enabled {
+                "🔊 Replies will be read aloud (/speak to turn off)".to_string()
+            } else {
+                "🔇 Replies will no longer be read aloud".to_string()
+            },
+        ));
+    }
+
+    fn start_dictation(&mut self) {
+        match Config::load().and_then(|config| Recording::start(&config.voice)) {
+            Ok(recording) => self.recording = Some(recording),
+            Err(e) => self.messages.push((
+                "system".to_string(),
+                format!("❌ Dictation unavailable: {}", e),
+            )),
+        }
+    }
+
+    /// Stop recording and append the transcript to the input box
+    async fn finish_dictation(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+
+        let transcript = match (recording.stop(), Config::load()) {
+            (Ok(audio), Ok(config)) => {
+                let transcript = voice::transcribe(&config.voice, &audio).await;
+                let _ = std::fs::remove_file(&audio);
+                transcript
+            }
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+
+        // Drop the "Transcribing..." placeholder
+        self.messages.pop();
+
+        match transcript {
+            Ok(text) if !text.is_empty() => {
+                if !self.input.is_empty() && !self.input.ends_with(' ') {
+                    self.input.push(' ');
+                }
+                self.input.push_str(&text);
+                self.history_index = None;
+            }
+            Ok(_) => self.messages.push((
+                "system".to_string(),
+                "🎙️  No speech recognized".to_string(),
+            )),
+            Err(e) => self.messages.push((
+                "system".to_string(),
+                format!("❌ Transcription failed: {}", e),
+            )),
+        }
+    }
+
     fn handle_copy_command(&mut self) -> Result<()> {
         self.input.clear();
         self.input_scroll = 0;
@@ -776,6 +1283,105 @@ impl App {
         Ok(())
     }
 
+    fn open_history_search(&mut self) {
+        match history::past_prompts() {
+            Ok(prompts) => {
+                self.history_search = Some(HistorySearch { query: String::new(), prompts, selected: 0 })
+            }
+            Err(e) => self.messages.push(("error".to_string(), format!("❌ Could not read past sessions: {}", e))),
+        }
+    }
+
+    /// Keys while the history search is open: type to filter, Enter puts
+    /// the prompt in the input box, Tab resumes its session
+    fn history_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = self.history_search.as_mut() else {
+            return;
+        };
+        let count = search.matches().len();
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.history_search = None,
+            KeyCode::Char('c') if control => self.history_search = None,
+            KeyCode::Up => search.selected = search.selected.saturating_sub(1),
+            KeyCode::Down => search.selected = (search.selected + 1).min(count.saturating_sub(1)),
+            // Ctrl+R again steps to the next older match, as in a shell
+            KeyCode::Char('r') if control => search.selected = (search.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Backspace => {
+                search.query.pop();
+                search.selected = 0;
+            }
+            KeyCode::Char(c) if !control => {
+                search.query.push(c);
+                search.selected = 0;
+            }
+            KeyCode::Enter => {
+                if let Some(prompt) = search.matches().get(search.selected) {
+                    self.input = prompt.text.clone();
+                    self.input_scroll = 0;
+                    self.history_index = None;
+                }
+                self.history_search = None;
+            }
+            KeyCode::Tab => {
+                let id = search.matches().get(search.selected).map(|prompt| prompt.session_id.clone());
+                self.history_search = None;
+                if let Some(id) = id {
+                    if let Err(e) = self.resume_session(&id) {
+                        self.messages.push(("error".to_string(), format!("❌ Could not resume session {}: {}", id, e)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace the current session with a saved one
+    fn resume_session(&mut self, id: &str) -> Result<()> {
+        let mut session = Session::load(id)?;
+        session.conversation.set_max_context(self.session.conversation.max_context);
+        session.resume_transcript();
+        self.session = session;
+        self.show_session();
+        Ok(())
+    }
+
+    /// Show the conversation of a session that is being continued
+    fn show_session(&mut self) {
+        let session = &self.session;
+        self.messages.clear();
+        self.stamps.clear();
+        let mut turn_started = None;
+        for (index, message) in session.conversation.messages.iter().enumerate().skip(1) {
+            let time = session.conversation.time_of(index).map(|time| time.with_timezone(&Local));
+            if let Some((tool, _)) = tool_result(message) {
+                self.messages.push(("tool".to_string(), format!("🔧 Executing: {}", tool)));
+                continue;
+            } else if message.role == "user" {
+                self.messages.push(("user".to_string(), message.content.clone()));
+                turn_started = time;
+                if let Some(time) = time {
+                    self.stamps.insert(self.messages.len() - 1, (time, None));
+                }
+            } else if message.role == "assistant" && !message.content.trim().is_empty() {
+                self.messages.push(("assistant".to_string(), message.content.clone()));
+                if let Some(time) = time {
+                    self.stamps.insert(self.messages.len() - 1, (time, turn_started.map(|started| time - started)));
+                }
+            }
+        }
+        let mut notice = format!("📂 Resumed session {} from {}", session.label(), session.working_directory.display());
+        if let Some(dir_notice) = session.working_dir_notice() {
+            notice.push_str(&format!("\n{}", dir_notice));
+        }
+        self.messages.push(("system".to_string(), notice));
+
+        self.first_message = false;
+        self.history_index = None;
+        self.auto_scroll = true;
+        self.scroll = u16::MAX;
+    }
+
     fn history_prev(&mut self) {
         if self.input_history.is_empty() {
             return;
@@ -817,35 +1423,52 @@ impl App {
 
 pub async fn execute(server_url: Option<String>, model: Option<String>, agent_name: Option<String>) -> Result<()> {
     let config = Config::load()?;
+    let project = ProjectConfig::load(&env::current_dir()?)?;
+
+    // Load agent if specified; the project's default beats picking one from
+    // the first prompt
+    let agent = match (agent_name, &project.agent) {
+        (Some(name), _) => Some((Agent::load(&name)?, AgentSource::Flag)),
+        (None, Some(name)) => {
+            let agent = Agent::load_or_create_defaults(name)
+                .with_context(|| format!("{} sets agent = \"{}\"", ProjectConfig::FILE_NAME, name))?;
+            Some((agent, AgentSource::Project))
+        }
+        (None, None) => None,
+    };
 
-    // Load agent if specified
-    let agent = if let Some(name) = agent_name {
-        Some(Agent::load(&name)?)
-    } else {
-        None
+    // An explicit server may be LM Studio, Jan, etc.: use a model it serves
+    let model = match (model, &server_url) {
+        (Some(model), _) => model,
+        (None, Some(url)) => model_for_server(url, &config.assistant.model).await,
+        (None, None) => config.assistant.model.clone(),
     };
 
-    // Auto-start server if not specified
+    // Auto-start server if not specified (Ollama manages its own)
     let server_url = if let Some(url) = server_url {
         url
+    } else if config.assistant.backend == "ollama" {
+        config.chat_server_url()
     } else {
         let mut server_manager = ServerManager::new()?;
         server_manager.start_server().await?
     };
 
-    let model = model.unwrap_or_else(|| config.assistant.model.clone());
-
     // Warm up model with a tiny prompt (async, non-blocking)
-    let warmup_client = LlamaClient::new(server_url.clone(), model.clone());
-    tokio::spawn(async move {
-        let _ = warmup_client.chat_completion(
-            vec![crate::llm::client::Message {
-                role: "user".to_string(),
-                content: "Hi".to_string(),
-            }],
-            None,
-        ).await;
-    });
+    if config.assistant.warmup {
+        let warmup_client = LlamaClient::for_config(&config, server_url.clone(), model.clone());
+        warmup_client.quiet_queue();
+        let prompt = config.assistant.warmup_prompt.clone();
+        tokio::spawn(async move {
+            let _ = warmup_client.chat_completion(
+                &[crate::llm::client::Message {
+                    role: "user".to_string(),
+                    content: prompt,
+                }],
+                None,
+            ).await;
+        });
+    }
 
     // Setup terminal
     enable_raw_mode()?;
@@ -854,8 +1477,10 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, agent_na
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(server_url, model, config, agent);
+    let mut app = App::new(server_url, model, config, agent, project.preset);
 
+    #[cfg(unix)]
+    watch_job_control();
     let res = run_app(&mut terminal, &mut app).await;
 
     // Restore terminal
@@ -874,11 +1499,78 @@ pub async fn execute(server_url: Option<String>, model: Option<String>, agent_na
     Ok(())
 }
 
+/// Set by a SIGTSTP from outside (`kill -TSTP`), handled like Ctrl+Z
+#[cfg(unix)]
+static STOP_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+/// Set by SIGCONT, after which the screen is set up and drawn again
+#[cfg(unix)]
+static CONTINUED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Catch SIGTSTP so the terminal is restored before stopping, and SIGCONT
+/// to take the screen back however the process was stopped
+#[cfg(unix)]
+fn watch_job_control() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::signal::unix::{signal, SignalKind};
+
+    for (number, flag) in [(libc::SIGTSTP, &STOP_REQUESTED), (libc::SIGCONT, &CONTINUED)] {
+        let flag: &'static AtomicBool = flag;
+        if let Ok(mut signals) = signal(SignalKind::from_raw(number)) {
+            tokio::spawn(async move {
+                while signals.recv().await.is_some() {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            });
+        }
+    }
+}
+
+/// Ctrl+Z: give the terminal back to the shell and stop like any job;
+/// `fg` resumes with the screen redrawn
+#[cfg(unix)]
+fn suspend<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, crossterm::cursor::Show)?;
+    // SIGTSTP is caught above, so stop with SIGSTOP; this returns on SIGCONT
+    // SAFETY: raise only sends a signal to this process
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+    resume_screen(terminal)
+}
+
+#[cfg(unix)]
+fn resume_screen<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    CONTINUED.store(false, std::sync::atomic::Ordering::SeqCst);
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(())
+}
+
+/// Send what is in the input box as the next prompt
+async fn send_input<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    // A summary that just finished goes in before this turn
+    app.finish_compaction(false).await;
+    // Prepare UI for processing before async call
+    app.prepare_send_message();
+    // Force immediate redraw to show processing state
+    terminal.draw(|f| ui(f, app))?;
+    // Now do the async LLM work
+    if let Err(e) = app.do_send_message().await {
+        app.show_send_error(&e);
+    }
+    Ok(())
+}
+
 async fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
 ) -> Result<()> {
     let mut gpu_update_counter = 0;
+    // Draw only when something on screen may have changed, so an idle TUI
+    // does no work between key presses
+    let mut dirty = true;
     loop {
         // Clamp scroll before drawing
         let max_scroll = app.messages.len().saturating_sub(1);
@@ -886,41 +1578,82 @@ async fn run_app<B: ratatui::backend::Backend>(
             app.scroll = max_scroll as u16;
         }
 
-        terminal.draw(|f| ui(f, app))?;
+        #[cfg(unix)]
+        {
+            use std::sync::atomic::Ordering;
+            if STOP_REQUESTED.swap(false, Ordering::SeqCst) {
+                suspend(terminal)?;
+                dirty = true;
+            } else if CONTINUED.load(Ordering::SeqCst) {
+                resume_screen(terminal)?;
+                dirty = true;
+            }
+        }
+
+        if REPAINT.swap(false, std::sync::atomic::Ordering::SeqCst) {
+            terminal.clear()?;
+            dirty = true;
+        }
+
+        // The spinner moves on every tick while processing
+        if dirty || app.processing {
+            terminal.draw(|f| ui(f, app))?;
+            dirty = false;
+        }
 
         // Update spinner animation when processing
         if app.processing {
             app.spinner_state = (app.spinner_state + 1) % 10;
         }
 
+        dirty |= app.poll_config_changes();
+        dirty |= app.finish_compaction(false).await;
+
         // Update GPU stats every 1 second (10 iterations * 100ms)
         gpu_update_counter += 1;
         if gpu_update_counter >= 10 {
-            app.gpu_stats = fetch_gpu_stats();
+            let gpu_stats = fetch_gpu_stats().await;
+            // Message times ("5s ago") move on by themselves
+            dirty |= gpu_stats != app.gpu_stats || (app.show_times && !app.stamps.is_empty());
+            app.gpu_stats = gpu_stats;
             gpu_update_counter = 0;
         }
 
         if event::poll(std::time::Duration::from_millis(100))? {
+            dirty = true;
             match event::read()? {
+                Event::Key(key) if app.history_search.is_some() => app.history_search_key(key),
                 Event::Key(key) => {
                     match key.code {
                         KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             return Ok(());
                         }
-                        KeyCode::Char(c) => {
-                            if !app.processing {
-                                app.input.push(c);
-                                // Reset history navigation when typing
-                                app.history_index = None;
-                            }
+                        #[cfg(unix)]
+                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            suspend(terminal)?;
                         }
-                        KeyCode::Backspace => {
-                            if !app.processing {
-                                app.input.pop();
-                                // Reset history navigation when editing
-                                app.history_index = None;
+                        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) && !app.processing => {
+                            app.open_history_search();
+                        }
+                        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) && !app.processing => {
+                            if app.recording.is_some() {
+                                app.messages.push(("system".to_string(), "📝 Transcribing...".to_string()));
+                                terminal.draw(|f| ui(f, app))?;
+                                app.finish_dictation().await;
+                            } else {
+                                app.start_dictation();
                             }
                         }
+                        KeyCode::Char(c) if !app.processing => {
+                            app.input.push(c);
+                            // Reset history navigation when typing
+                            app.history_index = None;
+                        }
+                        KeyCode::Backspace if !app.processing => {
+                            app.input.pop();
+                            // Reset history navigation when editing
+                            app.history_index = None;
+                        }
                         KeyCode::Enter => {
                             if app.model_selector_active {
                                 // Confirm model selection
@@ -936,13 +1669,22 @@ async fn run_app<B: ratatui::backend::Backend>(
                                     app.handle_model_command().await?;
                                 } else if input == "/copy" {
                                     app.handle_copy_command()?;
+                                } else if input == "/speak" {
+                                    app.handle_speak_command();
+                                } else if input == "/reload" {
+                                    app.handle_reload_command();
+                                } else if input == "/plan" {
+                                    app.handle_plan_command();
+                                } else if input == "/raw" {
+                                    app.handle_raw_command();
+                                } else if input == "/times" {
+                                    app.handle_times_command();
+                                } else if input == "/prompt" || input.starts_with("/prompt ") {
+                                    if app.handle_prompt_command() {
+                                        send_input(terminal, app).await?;
+                                    }
                                 } else {
-                                    // Prepare UI for processing before async call
-                                    app.prepare_send_message();
-                                    // Force immediate redraw to show processing state
-                                    terminal.draw(|f| ui(f, app))?;
-                                    // Now do the async LLM work
-                                    app.do_send_message().await?;
+                                    send_input(terminal, app).await?;
                                 }
                             }
                         }
@@ -974,19 +1716,15 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 app.history_next();
                             }
                         }
-                        KeyCode::Tab => {
-                            if !app.processing && !app.model_selector_active {
-                                app.model_selector_active = true;
-                            }
+                        KeyCode::Tab if !app.processing && !app.model_selector_active => {
+                            app.model_selector_active = true;
                         }
-                        KeyCode::Esc => {
-                            if app.model_selector_active {
-                                app.model_selector_active = false;
-                                app.messages.push((
-                                    "system".to_string(),
-                                    "❌ Model selection cancelled".to_string()
-                                ));
-                            }
+                        KeyCode::Esc if app.model_selector_active => {
+                            app.model_selector_active = false;
+                            app.messages.push((
+                                "system".to_string(),
+                                "❌ Model selection cancelled".to_string()
+                            ));
                         }
                         KeyCode::PageUp => {
                             // Scroll up - disable auto-scroll
@@ -1044,13 +1782,138 @@ async fn run_app<B: ratatui::backend::Backend>(
     }
 }
 
+/// Wrapped lines of one message, without its time
+struct WrappedMessage {
+    /// Hash of the role and text the lines were made from
+    hash: u64,
+    lines: Vec<Line<'static>>,
+    /// Width of the "👤 You: " prefix, which continuation lines are indented by
+    indent: usize,
+}
+
+/// The conversation's wrapped lines, kept between frames so a draw only
+/// wraps messages that are new or changed; a resize or a new agent color
+/// wraps everything again
+#[derive(Default)]
+struct LineCache {
+    width: u16,
+    color: Color,
+    messages: Vec<WrappedMessage>,
+}
+
+impl LineCache {
+    fn sync(&mut self, messages: &[(String, String)], width: u16, color: Color) {
+        if (self.width, self.color) != (width, color) {
+            (self.width, self.color) = (width, color);
+            self.messages.clear();
+        }
+        self.messages.truncate(messages.len());
+        for (index, (role, content)) in messages.iter().enumerate() {
+            let mut hasher = DefaultHasher::new();
+            (role, content).hash(&mut hasher);
+            let hash = hasher.finish();
+            if self.messages.get(index).is_some_and(|cached| cached.hash == hash) {
+                continue;
+            }
+            let (lines, indent) = message_lines(role, content, color, width);
+            let wrapped = WrappedMessage { hash, lines, indent };
+            match self.messages.get_mut(index) {
+                Some(cached) => *cached = wrapped,
+                None => self.messages.push(wrapped),
+            }
+        }
+    }
+}
+
+/// A message as it appears in the conversation: the speaker's prefix on
+/// the first line and the text wrapped to `available_width`
+fn message_lines(role: &str, content: &str, agent_color: Color, available_width: u16) -> (Vec<Line<'static>>, usize) {
+    let style = match role {
+        "user" => Style::default().fg(Color::Blue),
+        "assistant" => Style::default().fg(agent_color),
+        "tool" => Style::default().fg(Color::Yellow),
+        "tool_result" => Style::default().fg(Color::Gray),
+        "error" => Style::default().fg(Color::Red),
+        "system" => Style::default().fg(agent_color),
+        _ => Style::default(),
+    };
+
+    let prefix = match role {
+        "user" => "👤 You",
+        "assistant" => "🐴 Vork",
+        "tool" => "🔧 Tool",
+        "tool_result" => "📄 Result",
+        "error" => "❌ Error",
+        "system" => "ℹ️  System",
+        _ => role,
+    };
+
+    let prefix_text = format!("{}: ", prefix);
+    let prefix_len = prefix_text.chars().count();
+    let wrap_width = available_width.saturating_sub(prefix_len as u16).max(20) as usize;
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    // Wrap each line of content
+    for (line_idx, line) in content.lines().enumerate() {
+        if line_idx == 0 {
+            // First line includes the prefix
+            for wrapped_line in textwrap::wrap(line, wrap_width) {
+                if lines.is_empty() {
+                    // Very first line with prefix
+                    lines.push(Line::from(vec![
+                        Span::styled(
+                            prefix_text.clone(),
+                            style.add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(wrapped_line.to_string(), style),
+                    ]));
+                } else {
+                    // Continuation lines indented
+                    lines.push(Line::from(vec![
+                        Span::styled(
+                            " ".repeat(prefix_len),
+                            style,
+                        ),
+                        Span::styled(wrapped_line.to_string(), style),
+                    ]));
+                }
+            }
+        } else {
+            // Subsequent lines (newlines in original content)
+            for wrapped_line in textwrap::wrap(line, wrap_width) {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        " ".repeat(prefix_len),
+                        style,
+                    ),
+                    Span::styled(wrapped_line.to_string(), style),
+                ]));
+            }
+        }
+    }
+
+    // Handle empty content
+    if lines.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(
+                prefix_text,
+                style.add_modifier(Modifier::BOLD),
+            ),
+        ]));
+    }
+
+    (lines, prefix_len)
+}
+
 fn ui(f: &mut Frame, app: &App) {
     let size = f.area();
 
     // Handle very small terminals gracefully
     if size.height < 10 || size.width < 20 {
         let error_msg = Paragraph::new("Terminal too small! Please resize.")
-            .style(Style::default().fg(Color::Red));
+            .style(Style::default().fg(Color::Red))
+            .wrap(Wrap { trim: true });
         f.render_widget(error_msg, size);
         return;
     }
@@ -1075,7 +1938,11 @@ fn ui(f: &mut Frame, app: &App) {
         .split(size);
 
     // Header with agent-specific color and title
-    let header = Paragraph::new(app.header_title.clone())
+    let header_text = match &app.session.name {
+        Some(name) => format!("{} · {}", app.header_title, name),
+        None => app.header_title.clone(),
+    };
+    let header = Paragraph::new(header_text)
         .style(
             Style::default()
                 .fg(app.agent_color)
@@ -1085,87 +1952,23 @@ fn ui(f: &mut Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(header, chunks[0]);
 
-    // Messages with text wrapping
+    // Messages with text wrapping, re-wrapped only when they change
     let available_width = chunks[1].width.saturating_sub(4); // Account for borders and padding
-    let messages: Vec<ListItem> = app
+    let mut cache = app.line_cache.borrow_mut();
+    cache.sync(&app.messages, available_width, app.agent_color);
+    let messages: Vec<ListItem> = cache
         .messages
         .iter()
-        .map(|(role, content)| {
-            let style = match role.as_str() {
-                "user" => Style::default().fg(Color::Blue),
-                "assistant" => Style::default().fg(app.agent_color),
-                "tool" => Style::default().fg(Color::Yellow),
-                "tool_result" => Style::default().fg(Color::Gray),
-                "error" => Style::default().fg(Color::Red),
-                "system" => Style::default().fg(app.agent_color),
-                _ => Style::default(),
-            };
-
-            let prefix = match role.as_str() {
-                "user" => "👤 You",
-                "assistant" => "🐴 Vork",
-                "tool" => "🔧 Tool",
-                "tool_result" => "📄 Result",
-                "error" => "❌ Error",
-                "system" => "ℹ️  System",
-                _ => role,
-            };
-
-            let prefix_text = format!("{}: ", prefix);
-            let prefix_len = prefix_text.chars().count();
-            let wrap_width = available_width.saturating_sub(prefix_len as u16).max(20) as usize;
-
-            let mut lines: Vec<Line> = Vec::new();
-
-            // Wrap each line of content
-            for (line_idx, line) in content.lines().enumerate() {
-                if line_idx == 0 {
-                    // First line includes the prefix
-                    for wrapped_line in textwrap::wrap(line, wrap_width) {
-                        if lines.is_empty() {
-                            // Very first line with prefix
-                            lines.push(Line::from(vec![
-                                Span::styled(
-                                    prefix_text.clone(),
-                                    style.add_modifier(Modifier::BOLD),
-                                ),
-                                Span::styled(wrapped_line.to_string(), style),
-                            ]));
-                        } else {
-                            // Continuation lines indented
-                            lines.push(Line::from(vec![
-                                Span::styled(
-                                    " ".repeat(prefix_len),
-                                    style,
-                                ),
-                                Span::styled(wrapped_line.to_string(), style),
-                            ]));
-                        }
-                    }
-                } else {
-                    // Subsequent lines (newlines in original content)
-                    for wrapped_line in textwrap::wrap(line, wrap_width) {
-                        lines.push(Line::from(vec![
-                            Span::styled(
-                                " ".repeat(prefix_len),
-                                style,
-                            ),
-                            Span::styled(wrapped_line.to_string(), style),
-                        ]));
-                    }
+        .enumerate()
+        .map(|(index, message)| {
+            let mut lines = message.lines.clone();
+            if let Some((time, took)) = app.stamps.get(&index).filter(|_| app.show_times) {
+                let mut stamp = format!("{}{}", " ".repeat(message.indent), ago(*time));
+                if let Some(took) = took {
+                    stamp.push_str(&format!(" · took {}", format_took(*took)));
                 }
+                lines.push(Line::from(Span::styled(stamp, Style::default().fg(Color::DarkGray))));
             }
-
-            // Handle empty content
-            if lines.is_empty() {
-                lines.push(Line::from(vec![
-                    Span::styled(
-                        prefix_text,
-                        style.add_modifier(Modifier::BOLD),
-                    ),
-                ]));
-            }
-
             ListItem::new(lines)
         })
         .collect();
@@ -1201,11 +2004,25 @@ fn ui(f: &mut Frame, app: &App) {
             "⏳ PROCESSING - Please wait, response incoming...",
             Color::Yellow,
         )
+    } else if app.recording.is_some() {
+        (
+            format!("💬 {}", app.input),
+            Style::default().fg(Color::White),
+            "🎙️  RECORDING - Ctrl+T to stop and transcribe",
+            Color::Red,
+        )
+    } else if app.raw_mode {
+        (
+            format!("💬 {}", app.input),
+            Style::default().fg(Color::White),
+            "🗣️  RAW - no coding-assistant prompt or tools (/raw to turn off)",
+            Color::Magenta,
+        )
     } else {
         (
             format!("💬 {}", app.input),
             Style::default().fg(Color::White),
-            "✅ Ready (Ctrl+↑↓ scroll input | Right-click paste | /compact /model /copy)",
+            "✅ Ready (Ctrl+↑↓ scroll input | Ctrl+R history | Ctrl+T dictate | Right-click paste | /compact /model /copy /speak /plan /raw /prompt /times /reload)",
             Color::Green,
         )
     };
@@ -1223,17 +2040,21 @@ fn ui(f: &mut Frame, app: &App) {
     f.render_widget(input, chunks[2]);
 
     // Status bar with processing indicator and tokens/s
+    let status = match (&app.agent_name, app.agent_source) {
+        (Some(name), Some(source)) => format!("{} │ 🤖 {} ({})", app.status, name, source.label()),
+        _ => app.status.clone(),
+    };
     let status_text = if app.processing {
         let spinner = spinner_frames[app.spinner_state];
         if app.tokens_per_second > 0.0 {
-            format!("{} {} │ {:.1} tok/s │ ⏳ Processing...", spinner, app.status, app.tokens_per_second)
+            format!("{} {} │ {:.1} tok/s │ ⏳ Processing...", spinner, status, app.tokens_per_second)
         } else {
-            format!("{} {} │ ⏳ Processing...", spinner, app.status)
+            format!("{} {} │ ⏳ Processing...", spinner, status)
         }
     } else if app.tokens_per_second > 0.0 {
-        format!("{} │ {:.1} tok/s │ ✅ Idle", app.status, app.tokens_per_second)
+        format!("{} │ {:.1} tok/s │ ✅ Idle", status, app.tokens_per_second)
     } else {
-        format!("{} │ ✅ Ready", app.status)
+        format!("{} │ ✅ Ready", status)
     };
 
     let status_style = if app.processing {
@@ -1398,6 +2219,140 @@ fn ui(f: &mut Frame, app: &App) {
 
         f.render_stateful_widget(model_list, popup_area, &mut list_state);
     }
+
+    if let Some(search) = &app.history_search {
+        let matches = search.matches();
+        let popup_width = size.width.saturating_sub(4).min(100);
+        let popup_height = (matches.len() as u16).clamp(1, 12) + 2;
+        let popup_area = centered_rect(popup_width, popup_height, size);
+
+        let items: Vec<ListItem> = if matches.is_empty() {
+            vec![ListItem::new("  No matching prompts").style(Style::default().fg(Color::DarkGray))]
+        } else {
+            matches.iter().enumerate()
+                .map(|(idx, prompt)| {
+                    let marker = if idx == search.selected { "→" } else { " " };
+                    let project = prompt.working_directory.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| prompt.working_directory.display().to_string());
+                    let content = format!(
+                        "{} {}  {:<16} {}",
+                        marker,
+                        prompt.time.with_timezone(&chrono::Local).format("%m-%d %H:%M"),
+                        project,
+                        prompt.text.lines().next().unwrap_or("")
+                    );
+                    let style = if idx == search.selected {
+                        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    ListItem::new(content).style(style)
+                })
+                .collect()
+        };
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("🔎 History: {}▏ (Enter: use, Tab: resume session, Esc: cancel)", search.query))
+                    .border_style(Style::default().fg(Color::Cyan))
+            )
+            .style(Style::default().bg(Color::Black));
+
+        let mut list_state = ratatui::widgets::ListState::default();
+        list_state.select(Some(search.selected));
+
+        f.render_widget(ratatui::widgets::Clear, popup_area);
+        f.render_stateful_widget(list, popup_area, &mut list_state);
+    }
+}
+
+/// Lines of an approval question shown before the rest is cut
+const MAX_APPROVAL_LINES: usize = 12;
+
+/// Set when an approval modal was drawn over the screen, so the main loop
+/// repaints everything rather than only what it thinks changed
+static REPAINT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// The approval question in a box over the middle of the screen, and the
+/// answer once it is given
+fn draw_approval(f: &mut Frame, message: &str, answer: Option<bool>) {
+    let size = f.area();
+    let width = size.width.saturating_sub(4).min(72);
+    let text_width = width.saturating_sub(4).max(1) as usize;
+
+    let mut lines: Vec<Line> = message
+        .lines()
+        .flat_map(|line| textwrap::wrap(line, text_width))
+        .map(|line| Line::from(line.into_owned()))
+        .collect();
+    if lines.len() > MAX_APPROVAL_LINES {
+        let hidden = lines.len() - MAX_APPROVAL_LINES;
+        lines.truncate(MAX_APPROVAL_LINES);
+        lines.push(Line::from(Span::styled(
+            format!("[… {} more lines]", hidden),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(match answer {
+        None => Line::from(vec![
+            Span::styled("y", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(": approve   "),
+            Span::styled("n", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw("/Esc: deny"),
+        ]),
+        Some(true) => Line::from(Span::styled("✓ Approved", Style::default().fg(Color::Green))),
+        Some(false) => Line::from(Span::styled("✗ Denied", Style::default().fg(Color::Red))),
+    });
+
+    let popup_area = centered_rect(width, (lines.len() as u16 + 2).min(size.height), size);
+    let popup = Paragraph::new(lines)
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("🔒 Approval needed")
+                .border_style(Style::default().fg(Color::Yellow))
+        );
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
+/// The TUI's approval prompter. Stdin is in raw mode, so the usual
+/// terminal prompt would never see Enter; ask with a modal and a key instead.
+fn ask_in_modal(message: &str) -> Result<bool> {
+    // A fresh terminal starts from a blank buffer, so it only writes the
+    // cells the modal covers and the conversation stays visible around it
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    terminal.draw(|f| draw_approval(f, message, None))?;
+    let approved = loop {
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => break true,
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => break false,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break false,
+                _ => {}
+            }
+        }
+    };
+    terminal.draw(|f| draw_approval(f, message, Some(approved)))?;
+    REPAINT.store(true, std::sync::atomic::Ordering::SeqCst);
+    Ok(approved)
+}
+
+/// How long ago `time` was, or the date once it is a day old
+fn ago(time: DateTime<Local>) -> String {
+    let secs = (Local::now() - time).num_seconds();
+    match secs {
+        ..=4 => "just now".to_string(),
+        5..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => time.format("%Y-%m-%d %H:%M").to_string(),
+    }
 }
 
 // Helper function to create a centered rectangle
@@ -1420,3 +2375,127 @@ fn centered_rect(width: u16, height: u16, r: ratatui::layout::Rect) -> ratatui::
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::backend::TestBackend;
+    use std::sync::OnceLock;
+
+    /// An App with nothing from this machine in it: a throwaway HOME, the
+    /// default config and a fixed list of presets
+    fn app() -> App {
+        static HOME: OnceLock<tempfile::TempDir> = OnceLock::new();
+        HOME.get_or_init(|| {
+            let home = tempfile::TempDir::new().unwrap();
+            env::set_var("HOME", home.path());
+            home
+        });
+        let mut app = App::new("http://localhost:8080".to_string(), "test-model".to_string(), Config::default(), None, None);
+        app.config_watch = None;
+        app.available_presets = ["balanced", "fast", "quality-30b"].map(String::from).to_vec();
+        app
+    }
+
+    fn render(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(draw).unwrap();
+        terminal
+    }
+
+    #[test]
+    fn main_screen() {
+        let mut app = app();
+        app.input = "and the tests?".to_string();
+        app.messages.push(("user".to_string(), "What does src/main.rs do?".to_string()));
+        app.messages.push(("tool".to_string(), "read_file src/main.rs".to_string()));
+        app.messages.push((
+            "assistant".to_string(),
+            "It parses the command line with clap and hands each subcommand to its module in src/commands.".to_string(),
+        ));
+        assert_snapshot!(render(100, 30, |f| ui(f, &app)).backend());
+    }
+
+    #[test]
+    fn model_selector() {
+        let mut app = app();
+        app.model_selector_active = true;
+        app.selected_preset_index = 1;
+        assert_snapshot!(render(100, 30, |f| ui(f, &app)).backend());
+    }
+
+    #[test]
+    fn approval_modal() {
+        let app = app();
+        let terminal = render(100, 30, |f| {
+            ui(f, &app);
+            draw_approval(f, "Execute potentially dangerous command: curl -fsSL https://example.com/install.sh | sh", None);
+        });
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn small_terminal() {
+        let app = app();
+        assert_snapshot!(render(18, 6, |f| ui(f, &app)).backend());
+    }
+
+    #[test]
+    fn status_bar_says_where_the_agent_came_from() {
+        let mut app = app();
+        app.apply_agent(&Agent::builtin("rust-expert").unwrap());
+        app.agent_source = Some(AgentSource::Project);
+        let screen = render(160, 30, |f| ui(f, &app)).backend().to_string();
+        assert!(screen.contains("🤖 rust-expert (.vork.toml)"), "{}", screen);
+    }
+
+    #[test]
+    fn line_cache_rewraps_only_what_changed() {
+        let mut messages = vec![
+            ("user".to_string(), "first".to_string()),
+            ("assistant".to_string(), "a reply long enough to wrap onto a second line".to_string()),
+        ];
+        let mut cache = LineCache::default();
+        cache.sync(&messages, 30, Color::Cyan);
+        assert_eq!(cache.messages.len(), 2);
+        let reply = cache.messages[1].hash;
+
+        messages[0].1.push_str(" and more");
+        messages.push(("error".to_string(), "oops".to_string()));
+        cache.sync(&messages, 30, Color::Cyan);
+        assert_eq!(cache.messages.len(), 3);
+        assert_eq!(cache.messages[1].hash, reply);
+
+        let narrow = cache.messages[1].lines.len();
+        cache.sync(&messages, 80, Color::Cyan);
+        assert!(cache.messages[1].lines.len() < narrow);
+
+        messages.truncate(1);
+        cache.sync(&messages, 80, Color::Cyan);
+        assert_eq!(cache.messages.len(), 1);
+    }
+
+    #[test]
+    fn background_summary_keeps_newer_messages() {
+        let mut app = app();
+        let conversation = &mut app.session.conversation;
+        for turn in 0..10 {
+            conversation.add_user_message(format!("question {}", turn));
+            conversation.add_assistant_message(format!("answer {}", turn));
+        }
+        let compaction = conversation.compaction().unwrap();
+
+        // The user keeps going while the summary is written
+        conversation.add_user_message("one more".to_string());
+        conversation.add_assistant_message("sure".to_string());
+        assert!(conversation.apply_compaction(&compaction, "They talked.".to_string()));
+        assert_eq!(conversation.messages.len(), 1 + 1 + 12);
+        assert!(conversation.messages[1].content.starts_with("[Conversation summary of 10 messages]"));
+        assert_eq!(conversation.messages[2].content, "question 5");
+        assert_eq!(conversation.messages.last().unwrap().content, "sure");
+
+        // Already summarized: a second result is dropped
+        assert!(!conversation.apply_compaction(&compaction, "Again.".to_string()));
+    }
+}
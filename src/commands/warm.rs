@@ -0,0 +1,46 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::time::Instant;
+
+use crate::config::Config;
+use crate::llm::client::Message;
+use crate::llm::{LlamaClient, ServerManager};
+
+/// Switch to `preset` if given, make sure a server is up, and send the
+/// warm-up request so the model is in memory before the first real prompt
+pub async fn execute(preset: Option<String>, server_url: Option<String>, model: Option<String>) -> Result<()> {
+    if let Some(name) = &preset {
+        Config::apply_preset(name)?;
+        println!("{} Switched to preset {}", "✓".green(), name.green());
+    }
+    let config = Config::load()?;
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+
+    // Ollama loads models on demand; the request below is what loads it
+    let server_url = match server_url {
+        Some(url) => url,
+        None if config.assistant.backend == "ollama" => config.chat_server_url(),
+        None => ServerManager::new()?.start_server().await?,
+    };
+
+    println!("{} {}", "🔥 Warming up".cyan(), model.yellow());
+    let started = Instant::now();
+    let client = LlamaClient::for_config(&config, server_url.clone(), model.clone());
+    client
+        .chat_completion(
+            &[Message {
+                role: "user".to_string(),
+                content: config.assistant.warmup_prompt.clone(),
+            }],
+            None,
+        )
+        .await
+        .with_context(|| format!("Warm-up request to {} failed", server_url))?;
+    println!(
+        "{} {} is loaded ({:.1}s)",
+        "✓".green(),
+        model.green(),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::agents::Agent;
+use crate::config::Config;
+use crate::llm::LlamaClient;
+use crate::llm::client::Message;
+use super::fix::{approval_system, detect_check_command, fix_loop, run_check};
+
+/// Directories that never trigger a run regardless of the glob allowlist
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules", ".vork", "__pycache__", "dist", "build"];
+
+pub struct WatchOptions {
+    pub cmds: Vec<String>,
+    pub globs: Vec<String>,
+    /// Let the agent fix new failures instead of only annotating them
+    pub fix: bool,
+    pub max_iterations: usize,
+    /// Auto-approve the fixes' edits inside the workspace
+    pub auto: bool,
+}
+
+pub async fn execute(
+    options: WatchOptions,
+    server_url: Option<String>,
+    model: Option<String>,
+    agent_name: Option<String>,
+) -> Result<()> {
+    let WatchOptions { cmds, globs, fix, max_iterations, auto } = options;
+    let config = Config::load()?;
+    let server_url = server_url.unwrap_or_else(|| config.chat_server_url());
+    let model = model.unwrap_or_else(|| config.assistant.model.clone());
+    let root = env::current_dir()?;
+
+    // CLI flags override the [watch] section of the config
+    let mut checks = if cmds.is_empty() { config.watch.checks.clone() } else { cmds };
+    if checks.is_empty() {
+        checks.push(detect_check_command(&root).ok_or_else(|| {
+            anyhow::anyhow!("No checks configured; pass --cmd or set [watch].checks in config")
+        })?);
+    }
+    let globs = if globs.is_empty() { config.watch.globs.clone() } else { globs };
+    let matcher = build_globset(&globs)?;
+    let debounce = Duration::from_millis(config.watch.debounce_ms);
+
+    let agent = Agent::load_or_create_defaults(agent_name.as_deref().unwrap_or("debugger")).ok();
+    let client = LlamaClient::for_config(&config, server_url, model);
+    let approval_system = approval_system(&config, auto, agent.as_ref());
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        }
+    })
+    .context("Failed to create file watcher")?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .context("Failed to watch working directory")?;
+
+    println!("{}", "=== Vork Watch ===".green().bold());
+    println!("{} {}", "Watching:".cyan(), root.display());
+    println!("{} {}", "Globs:".cyan(), globs.join(", "));
+    for check in &checks {
+        println!("{} {}", "Check:".cyan(), check.yellow());
+    }
+    println!(
+        "{} {}",
+        "On failure:".cyan(),
+        if fix { "fix automatically" } else { "annotate diagnostics" }
+    );
+    println!("{}", "Press Ctrl+C to stop".dimmed());
+    println!();
+
+    // Last failing output per check, so only *new* failures reach the model
+    let mut last_failures: HashMap<String, String> = HashMap::new();
+
+    while let Some(first) = rx.recv().await {
+        let mut changed = vec![first];
+
+        // Debounce: collect everything that arrives within the quiet window
+        while let Ok(Some(path)) = tokio::time::timeout(debounce, rx.recv()).await {
+            changed.push(path);
+        }
+
+        let relevant: Vec<&PathBuf> = changed
+            .iter()
+            .filter(|p| is_watched(p, &root, &matcher))
+            .collect();
+        if relevant.is_empty() {
+            continue;
+        }
+
+        println!(
+            "{} {} file(s) changed ({})",
+            "👀".cyan(),
+            relevant.len(),
+            relevant[0].strip_prefix(&root).unwrap_or(relevant[0]).display()
+        );
+
+        for check in &checks {
+            let (success, output) = run_check(check).await?;
+            if success {
+                if last_failures.remove(check).is_some() {
+                    println!("{} {} is passing again", "✓".green(), check);
+                } else {
+                    println!("{} {}", "✓".green(), check);
+                }
+                continue;
+            }
+
+            if last_failures.get(check) == Some(&output) {
+                println!("{} {} (unchanged failure)", "✗".red(), check);
+                continue;
+            }
+            println!("{} {}", "✗".red(), check.red());
+            last_failures.insert(check.clone(), output.clone());
+
+            if fix {
+                let outcome = fix_loop(&client, &approval_system, check, max_iterations, agent.as_ref()).await?;
+                if outcome.passed {
+                    last_failures.remove(check);
+                }
+                println!(
+                    "{} {} after {} attempt(s), {} file(s) changed",
+                    if outcome.passed { "✓".green() } else { "✗".red() },
+                    if outcome.passed { "Fixed" } else { "Still failing" },
+                    outcome.attempts,
+                    outcome.files_changed.len()
+                );
+            } else if let Err(e) = annotate(&client, check, &output).await {
+                println!("{} Failed to annotate: {}", "⚠️".yellow(), e);
+            }
+        }
+
+        // Drop events caused by our own checks or fixes
+        while rx.try_recv().is_ok() {}
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Ask the model for a short explanation of a failing check (no tools)
+async fn annotate(client: &LlamaClient, check: &str, output: &str) -> Result<()> {
+    let tail: Vec<&str> = output.lines().collect();
+    let tail = tail[tail.len().saturating_sub(120)..].join("\n");
+
+    let response = client
+        .chat_completion(
+            &[Message {
+                role: "user".to_string(),
+                content: format!(
+                    "`{}` just failed. Explain each diagnostic below in one or two lines \
+                    (file:line, cause, suggested fix). Be terse.\n\n```\n{}\n```",
+                    check, tail
+                ),
+            }],
+            None,
+        )
+        .await?;
+
+    if let Some(content) = response.choices.first().and_then(|c| c.message.content.as_ref()) {
+        println!("{}", content);
+    }
+    Ok(())
+}
+
+fn build_globset(globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).with_context(|| format!("Invalid glob: {}", glob))?);
+    }
+    Ok(builder.build()?)
+}
+
+fn is_watched(path: &Path, root: &Path, matcher: &GlobSet) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let ignored = relative.components().any(|c| {
+        IGNORED_DIRS.contains(&c.as_os_str().to_string_lossy().as_ref())
+    });
+    !ignored && matcher.is_match(relative)
+}
@@ -1,7 +1,54 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// Prefix for environment variable overrides
+const ENV_PREFIX: &str = "VORK_";
+
+/// Profile selected with `--profile`; takes precedence over VORK_PROFILE
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Config problems are reported once per process, not on every load
+static PROBLEMS_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// KV cache types accepted by llama-server's --cache-type-k/v
+const CACHE_TYPES: &[&str] = &["f32", "f16", "bf16", "q8_0", "q4_0", "q4_1", "iq4_nl", "q5_0", "q5_1"];
+
+/// The preset config.toml was last switched to
+pub struct ActivePreset {
+    pub name: String,
+    /// config.toml no longer matches the preset file
+    pub modified: bool,
+}
+
+impl std::fmt::Display for ActivePreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modified {
+            write!(f, "{} (modified)", self.name)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// A semantic problem in the config, with a suggested fix
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    pub key: String,
+    pub message: String,
+    pub fix: String,
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}\n    → {}", self.key, self.message, self.fix)
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
@@ -10,15 +57,558 @@ pub struct Config {
     pub llamacpp: LlamaCppConfig,
     #[serde(default)]
     pub assistant: AssistantConfig,
+    #[serde(default)]
+    pub watch: WatchConfig,
+    #[serde(default)]
+    pub pr: PrConfig,
+    #[serde(default)]
+    pub cost: CostConfig,
+    #[serde(default)]
+    pub voice: VoiceConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub remote: RemoteConfig,
+    #[serde(default)]
+    pub container: ContainerConfig,
+    #[serde(default)]
+    pub bot: BotConfig,
+    #[serde(default)]
+    pub queue: QueueConfig,
+    #[serde(default)]
+    pub remote_approval: RemoteApprovalConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    /// `[[schedule]]` entries run by `vork schedule run`
+    #[serde(default, rename = "schedule", skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduledTask>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Named set of overrides, e.g. `[profiles.laptop]` pointing at a remote server
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProfileConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_backend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ollama_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<ApprovalPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_mode: Option<SandboxMode>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CostConfig {
+    /// Per-model pricing for remote backends; models without an entry are free
+    #[serde(default)]
+    pub pricing: HashMap<String, ModelPricing>,
+    /// Stop a session once it has cost this much (USD)
+    #[serde(default)]
+    pub session_budget: Option<f64>,
+    /// Stop all sessions once this calendar month has cost this much (USD)
+    #[serde(default)]
+    pub monthly_budget: Option<f64>,
+}
+
+/// Prices in USD per million tokens
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ModelPricing {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+impl ModelPricing {
+    pub fn cost(&self, prompt_tokens: usize, completion_tokens: usize) -> f64 {
+        (prompt_tokens as f64 * self.prompt + completion_tokens as f64 * self.completion) / 1_000_000.0
+    }
+}
+
+/// How the TUI picks a preset per request in auto mode
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoutingConfig {
+    /// Route on prompt content and context size; when false only the agent's
+    /// preferred preset is used
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Opening prompts shorter than this go to the fastest benchmarked preset (0 disables)
+    #[serde(default = "default_quick_prompt_chars")]
+    pub quick_prompt_chars: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_quick_prompt_chars() -> usize {
+    160
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quick_prompt_chars: default_quick_prompt_chars(),
+        }
+    }
+}
+
+/// `vork bot`: drive the agent from a Slack or Discord channel
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BotConfig {
+    /// "slack" or "discord"
+    #[serde(default = "default_bot_platform")]
+    pub platform: String,
+    /// Channel id to listen in
+    #[serde(default)]
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Environment variable holding the bot token (default: SLACK_BOT_TOKEN or DISCORD_BOT_TOKEN)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    /// Directory the agent works in (default: where the bot was started)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Seconds between polls for new messages
+    #[serde(default = "default_bot_poll_secs")]
+    pub poll_secs: u64,
+    /// Run commands flagged as dangerous too; otherwise they are skipped
+    #[serde(default)]
+    pub full_auto: bool,
+    /// API base URL, for proxies (default: the platform's public API)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+}
+
+fn default_bot_platform() -> String {
+    "slack".to_string()
+}
+
+fn default_bot_poll_secs() -> u64 {
+    5
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            platform: default_bot_platform(),
+            channel: String::new(),
+            token: None,
+            token_env: None,
+            workspace: None,
+            agent: None,
+            poll_secs: default_bot_poll_secs(),
+            full_auto: false,
+            api_url: None,
+        }
+    }
+}
+
+/// Approving commands from a browser or chat app when nobody is at the terminal
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteApprovalConfig {
+    /// Send `vork bot` and `vork schedule` approvals here instead of refusing them
+    #[serde(default)]
+    pub enabled: bool,
+    /// URL POSTed with each request and its approval link (Slack/Discord webhooks work)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    /// Address the approval page listens on
+    #[serde(default = "default_approval_listen")]
+    pub listen: String,
+    /// Base URL for links, when the page is reached through another name or a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_url: Option<String>,
+    /// Seconds before an unanswered request is denied and its link expires
+    #[serde(default = "default_approval_expire_secs")]
+    pub expire_secs: u64,
+}
+
+fn default_approval_listen() -> String {
+    "127.0.0.1:8765".to_string()
+}
+
+fn default_approval_expire_secs() -> u64 {
+    600
+}
+
+impl Default for RemoteApprovalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook: None,
+            listen: default_approval_listen(),
+            public_url: None,
+            expire_secs: default_approval_expire_secs(),
+        }
+    }
+}
+
+/// Sharing one llama-server between several vork processes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueConfig {
+    /// Queue requests when the server's slots are all busy
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Requests one process may have in flight at once
+    #[serde(default = "default_max_per_client")]
+    pub max_per_client: u32,
+    /// Concurrent requests the server handles (default: the server's
+    /// reported slots, else llamacpp.parallel)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slots: Option<u32>,
+}
+
+fn default_max_per_client() -> u32 {
+    1
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_per_client: default_max_per_client(),
+            slots: None,
+        }
+    }
+}
+
+/// A prompt run on a cron schedule by `vork schedule run`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledTask {
+    pub name: String,
+    /// Five-field cron expression in local time (minute hour day month weekday)
+    pub cron: String,
+    pub prompt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Directory the task runs in (default: where the scheduler was started)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// URL to POST the result to, or a shell command to run with it on stdin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<String>,
+    /// Allow edits and commands that would otherwise need approval
+    #[serde(default)]
+    pub full_auto: bool,
+}
+
+impl BotConfig {
+    pub fn token(&self) -> Option<String> {
+        let default_env = match self.platform.as_str() {
+            "discord" => "DISCORD_BOT_TOKEN",
+            _ => "SLACK_BOT_TOKEN",
+        };
+        self.token
+            .clone()
+            .or_else(|| env::var(self.token_env.as_deref().unwrap_or(default_env)).ok())
+            .filter(|t| !t.is_empty())
+    }
+}
+
+/// Running shell commands inside the project's dev container
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContainerConfig {
+    /// "ask" (prompt when one is running), "always" or "never"
+    #[serde(default = "default_container_mode")]
+    pub mode: String,
+    /// docker-compose service to use when there is no devcontainer.json
+    #[serde(default = "default_container_service")]
+    pub service: String,
+    /// Working directory inside the container (default: from devcontainer.json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<String>,
+}
+
+fn default_container_mode() -> String {
+    "ask".to_string()
+}
+
+fn default_container_service() -> String {
+    "dev".to_string()
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_container_mode(),
+            service: default_container_service(),
+            workdir: None,
+        }
+    }
+}
+
+/// Run file and shell tools on another machine over SSH (`--remote`)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RemoteConfig {
+    /// `user@host`, or any name from ~/.ssh/config; tools stay local when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Remote workspace; relative tool paths resolve here (default: the login directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<String>,
+    /// Extra arguments for ssh, e.g. ["-p", "2222", "-i", "~/.ssh/board"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ssh_args: Vec<String>,
+}
+
+/// Dictation via whisper.cpp and spoken replies
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VoiceConfig {
+    /// Recorder command with a `{file}` placeholder (arecord, sox or ffmpeg when unset)
+    #[serde(default)]
+    pub record_command: Option<String>,
+    /// whisper.cpp CLI (whisper-cli on PATH when unset)
+    #[serde(default)]
+    pub whisper_binary: Option<String>,
+    /// ggml model used with the whisper.cpp CLI
+    #[serde(default)]
+    pub whisper_model: Option<String>,
+    /// whisper.cpp server to use instead of the CLI
+    #[serde(default)]
+    pub whisper_url: Option<String>,
+    /// Spoken language, e.g. "en" (auto-detected when unset)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Read replies aloud in the TUI from the start (toggle with /speak)
+    #[serde(default)]
+    pub speak: bool,
+    /// TTS command that reads text from stdin (piper or system TTS when unset)
+    #[serde(default)]
+    pub tts_command: Option<String>,
+    /// Piper voice model (.onnx), used when piper is installed
+    #[serde(default)]
+    pub piper_model: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PrConfig {
+    /// Markdown template for the PR body (falls back to .github/pull_request_template.md)
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Base branch to compare against when the current branch has no upstream
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolsConfig {
+    /// Tool results longer than this many characters are saved to
+    /// .vork/outputs and replaced by a preview the model can page through
+    #[serde(default = "default_inline_output_limit")]
+    pub inline_output_limit: usize,
+    /// Seconds a bash_exec command may run before it is stopped (0: no limit).
+    /// The model can ask for more per call.
+    #[serde(default = "default_bash_timeout")]
+    pub bash_timeout: u64,
+    /// Environment variables model-run commands inherit; a trailing `*`
+    /// matches a prefix. Names that look like secrets are dropped unless
+    /// listed exactly.
+    #[serde(default = "default_env_allowlist")]
+    pub env_allowlist: Vec<String>,
+}
+
+fn default_inline_output_limit() -> usize {
+    16_000
+}
+
+fn default_bash_timeout() -> u64 {
+    120
+}
+
+fn default_env_allowlist() -> Vec<String> {
+    [
+        "PATH", "HOME", "USER", "LOGNAME", "SHELL", "TERM", "LANG", "LANGUAGE", "LC_*", "TZ", "TMPDIR", "TMP", "TEMP",
+        "EDITOR", "VISUAL", "PAGER", "DISPLAY", "WAYLAND_DISPLAY", "XDG_*", "CARGO_HOME", "RUSTUP_HOME", "GOPATH",
+        "GOROOT", "JAVA_HOME", "NVM_DIR", "PYENV_ROOT", "VIRTUAL_ENV", "CONDA_PREFIX", "SYSTEMROOT", "COMSPEC",
+        "PATHEXT", "APPDATA", "LOCALAPPDATA", "USERPROFILE",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect()
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            inline_output_limit: default_inline_output_limit(),
+            bash_timeout: default_bash_timeout(),
+            env_allowlist: default_env_allowlist(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchConfig {
+    /// Check commands to run on save (auto-detected when empty)
+    #[serde(default)]
+    pub checks: Vec<String>,
+    /// Glob allowlist of paths that trigger a run
+    #[serde(default = "default_watch_globs")]
+    pub globs: Vec<String>,
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+fn default_watch_globs() -> Vec<String> {
+    vec!["**/*".to_string()]
+}
+
+fn default_debounce_ms() -> u64 {
+    750
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            checks: vec![],
+            globs: default_watch_globs(),
+            debounce_ms: default_debounce_ms(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AssistantConfig {
+    /// Where chat completions go: "llamacpp" (server_url) or "ollama" (ollama.api_url)
+    #[serde(default = "default_chat_backend")]
+    pub backend: String,
     pub server_url: String,
     pub model: String,
     pub approval_policy: ApprovalPolicy,
     pub sandbox_mode: SandboxMode,
     pub require_git_repo: bool,
+    /// Show a plan for approval before the first tool runs (exec, TUI)
+    #[serde(default)]
+    pub require_plan_approval: bool,
+    /// How tools reach the model: native `tools` requests, a prompted protocol
+    /// for servers without tool calling, or native with a fallback (auto)
+    #[serde(default)]
+    pub tool_calling: ToolCalling,
+    /// Per-request timeout; two timeouts in a row fail over to the next endpoint
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Endpoints tried in order when the server above is unreachable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<FallbackEndpoint>,
+    /// Directory where every session is also written as Markdown as it
+    /// happens, one <session-id>.md each
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_dir: Option<String>,
+    /// Send a small request when the TUI starts so the model is loaded
+    /// before the first prompt
+    #[serde(default = "default_true")]
+    pub warmup: bool,
+    /// The warm-up request, also sent by `vork warm`
+    #[serde(default = "default_warmup_prompt")]
+    pub warmup_prompt: String,
+    /// A smaller model for summarizing the history; unset, the chat model does it
+    #[serde(default, skip_serializing_if = "CompactionConfig::is_unset")]
+    pub compaction: CompactionConfig,
+}
+
+fn default_warmup_prompt() -> String {
+    "Hi".to_string()
+}
+
+/// `[assistant.compaction]`: where conversation summaries are generated,
+/// so a long session does not stop the big model to summarize itself
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CompactionConfig {
+    /// Preset whose server_url and model summarize; the active config is
+    /// not switched to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// OpenAI-compatible base URL without `/v1`, overriding the preset's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+}
+
+impl CompactionConfig {
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The endpoint summaries go to, or None to use the chat model. A URL
+    /// or model left out is taken from the preset, then from the chat's.
+    pub fn endpoint(&self, chat_url: &str, chat_model: &str) -> Result<Option<FallbackEndpoint>> {
+        if self.is_unset() {
+            return Ok(None);
+        }
+        let preset = match &self.preset {
+            Some(name) => {
+                let path = Config::config_dir()?.join("presets").join(format!("{}.toml", name));
+                let text = fs::read_to_string(&path)
+                    .with_context(|| format!("Compaction preset '{}' not found (see `vork presets list`)", name))?;
+                Some(toml::from_str::<Config>(&text).with_context(|| format!("Preset '{}' is not a valid config", name))?)
+            }
+            None => None,
+        };
+        let (url, model) = match &preset {
+            Some(preset) => (preset.chat_server_url(), preset.assistant.model.clone()),
+            None => (chat_url.to_string(), chat_model.to_string()),
+        };
+        Ok(Some(FallbackEndpoint {
+            url: self.url.clone().unwrap_or(url),
+            model: self.model.clone().unwrap_or(model),
+            api_key: self.api_key.clone(),
+            api_key_env: self.api_key_env.clone(),
+        }))
+    }
+}
+
+/// An OpenAI-compatible server to fail over to (vLLM, OpenRouter, ...)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FallbackEndpoint {
+    /// Base URL without `/v1`, e.g. https://openrouter.ai/api
+    pub url: String,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Environment variable holding the API key, to keep it out of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+}
+
+impl FallbackEndpoint {
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| self.api_key_env.as_ref().and_then(|name| env::var(name).ok()))
+    }
+}
+
+fn default_request_timeout_secs() -> u64 {
+    600
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolCalling {
+    /// Native tool calls, switching to prompted ones if the server rejects them
+    #[default]
+    Auto,
+    Native,
+    /// Tools described in the system prompt and called with ```tool blocks
+    Prompted,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -38,14 +628,27 @@ pub enum SandboxMode {
     DangerFullAccess,
 }
 
+fn default_chat_backend() -> String {
+    "llamacpp".to_string()
+}
+
 impl Default for AssistantConfig {
     fn default() -> Self {
         Self {
+            backend: default_chat_backend(),
             server_url: "http://localhost:8080".to_string(),
             model: "unknown".to_string(),
             approval_policy: ApprovalPolicy::Never,
             sandbox_mode: SandboxMode::DangerFullAccess,
             require_git_repo: false,
+            require_plan_approval: false,
+            tool_calling: ToolCalling::Auto,
+            request_timeout_secs: default_request_timeout_secs(),
+            fallbacks: vec![],
+            transcript_dir: None,
+            warmup: true,
+            warmup_prompt: default_warmup_prompt(),
+            compaction: CompactionConfig::default(),
         }
     }
 }
@@ -54,9 +657,12 @@ impl Default for AssistantConfig {
 pub struct OllamaConfig {
     pub enabled: bool,
     pub api_url: String,
+    /// Bearer token for remote Ollama instances behind an authenticating proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LlamaCppConfig {
     pub enabled: bool,
     pub models_dir: String,
@@ -64,6 +670,9 @@ pub struct LlamaCppConfig {
     pub context_size: u32,
     #[serde(default = "default_context_limit")]
     pub context_limit: usize,
+    /// Tokens kept free for the reply when fitting a conversation into context_limit
+    #[serde(default = "default_completion_reserve")]
+    pub completion_reserve: usize,
     pub ngl: u32,
     pub threads: u32,
     pub batch_size: u32,
@@ -78,34 +687,116 @@ fn default_context_limit() -> usize {
     32768
 }
 
+fn default_completion_reserve() -> usize {
+    2048
+}
+
+impl LlamaCppConfig {
+    /// Whether switching to `other` changes how llama-server is launched.
+    /// `context_limit` and `completion_reserve` are enforced client-side, so
+    /// they do not count.
+    pub fn requires_restart(&self, other: &LlamaCppConfig) -> bool {
+        let mut other = other.clone();
+        other.context_limit = self.context_limit;
+        other.completion_reserve = self.completion_reserve;
+        *self != other
+    }
+}
+
+/// Where models live by default: the platform data dir (XDG_DATA_HOME on Linux)
+pub fn default_models_dir() -> String {
+    dirs::data_dir()
+        .map(|d| d.join("vork").join("models").display().to_string())
+        .unwrap_or_else(|| "~/.vork/models".to_string())
+}
+
+/// Look for a llama-server binary on PATH and in common build locations
+pub fn discover_llama_server() -> Option<String> {
+    if let Some(path) = find_in_path("llama-server") {
+        return Some(path.display().to_string());
+    }
+
+    let home = dirs::home_dir()?;
+    [
+        home.join("llama.cpp/build/bin/llama-server"),
+        home.join(".local/bin/llama-server"),
+        PathBuf::from("/usr/local/bin/llama-server"),
+        PathBuf::from("/opt/homebrew/bin/llama-server"),
+    ]
+    .into_iter()
+    .find(|p| p.is_file())
+    .map(|p| p.display().to_string())
+}
+
+pub fn find_in_path(binary: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(binary))
+        .find(|p| p.is_file())
+}
+
 impl Default for Config {
     fn default() -> Self {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get() as u32)
+            .unwrap_or(4);
+
         Self {
             default_backend: "ollama".to_string(),
             ollama: OllamaConfig {
                 enabled: true,
                 api_url: "http://localhost:11434".to_string(),
+                api_key: None,
             },
             llamacpp: LlamaCppConfig {
                 enabled: true,
-                models_dir: "/media/k/vbox/models".to_string(),
-                binary_path: Some("/home/k/llama.cpp/build/bin/llama-server".to_string()),
-                context_size: 42768,
+                models_dir: default_models_dir(),
+                binary_path: None,
+                context_size: 32768,
                 context_limit: 32768,
-                ngl: 48,
-                threads: 20,
-                batch_size: 170,
-                parallel: 8,
-                cache_type_k: "bf16".to_string(),
-                cache_type_v: "bf16".to_string(),
+                completion_reserve: default_completion_reserve(),
+                ngl: 99,
+                threads,
+                batch_size: 512,
+                parallel: 1,
+                cache_type_k: "f16".to_string(),
+                cache_type_v: "f16".to_string(),
                 cuda_visible_devices: None,
             },
-            assistant: AssistantConfig::default(),
+            assistant: AssistantConfig {
+                backend: "ollama".to_string(),
+                ..AssistantConfig::default()
+            },
+            watch: WatchConfig::default(),
+            pr: PrConfig::default(),
+            cost: CostConfig::default(),
+            voice: VoiceConfig::default(),
+            routing: RoutingConfig::default(),
+            remote: RemoteConfig::default(),
+            container: ContainerConfig::default(),
+            bot: BotConfig::default(),
+            queue: QueueConfig::default(),
+            remote_approval: RemoteApprovalConfig::default(),
+            tools: ToolsConfig::default(),
+            schedules: vec![],
+            profiles: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
+    /// The defaults with what is installed here filled in: a llama-server
+    /// on PATH or in a common build location, preferred only when Ollama
+    /// is not installed
+    pub fn detected() -> Self {
+        let mut config = Self::default();
+        config.llamacpp.binary_path = discover_llama_server();
+        if config.llamacpp.binary_path.is_some() && find_in_path("ollama").is_none() {
+            config.default_backend = "llamacpp".to_string();
+            config.assistant.backend = "llamacpp".to_string();
+        }
+        config
+    }
+
     pub fn config_dir() -> Result<PathBuf> {
         let home = dirs::home_dir().context("Could not find home directory")?;
         Ok(home.join(".vork"))
@@ -115,22 +806,317 @@ impl Config {
         Ok(Self::config_dir()?.join("config.toml"))
     }
 
+    /// Records which preset config.toml was last copied from
+    fn active_preset_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("active_preset"))
+    }
+
+    /// Copy preset `name` over config.toml and record it as the active one
+    pub fn apply_preset(name: &str) -> Result<()> {
+        let preset_path = Self::config_dir()?.join("presets").join(format!("{}.toml", name));
+        if !preset_path.exists() {
+            anyhow::bail!("Preset '{}' not found (see `vork presets list`)", name);
+        }
+        fs::copy(&preset_path, Self::config_path()?)
+            .with_context(|| format!("Failed to copy preset {} to config", preset_path.display()))?;
+        fs::write(Self::active_preset_path()?, format!("{}\n", name))?;
+        Ok(())
+    }
+
+    /// The preset config.toml came from, and whether config.toml was edited
+    /// since. Without a record (configs from before presets were recorded),
+    /// a preset identical to config.toml is taken as active and recorded.
+    pub fn active_preset() -> Option<ActivePreset> {
+        let presets_dir = Self::config_dir().ok()?.join("presets");
+        let as_value = |path: &Path| -> Option<toml::Value> {
+            let config: Config = toml::from_str(&fs::read_to_string(path).ok()?).ok()?;
+            toml::Value::try_from(config).ok()
+        };
+        let current = as_value(&Self::config_path().ok()?)?;
+
+        if let Ok(name) = fs::read_to_string(Self::active_preset_path().ok()?) {
+            let name = name.trim().to_string();
+            let preset = as_value(&presets_dir.join(format!("{}.toml", name)));
+            return Some(ActivePreset { modified: preset.as_ref() != Some(&current), name });
+        }
+
+        let mut names: Vec<PathBuf> = fs::read_dir(&presets_dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        names.sort();
+        let name = names
+            .iter()
+            .find(|path| as_value(path).as_ref() == Some(&current))?
+            .file_stem()?
+            .to_string_lossy()
+            .to_string();
+        let _ = fs::write(Self::active_preset_path().ok()?, format!("{}\n", name));
+        Some(ActivePreset { name, modified: false })
+    }
+
+    /// Base URL for chat completions on the configured chat backend
+    pub fn chat_server_url(&self) -> String {
+        match self.assistant.backend.as_str() {
+            "ollama" => self.ollama.api_url.clone(),
+            _ => self.assistant.server_url.clone(),
+        }
+    }
+
+    /// Load the effective config, warning (once) about any problems in it
     pub fn load() -> Result<Self> {
+        let config = Self::load_resolved()?;
+
+        if !PROBLEMS_REPORTED.swap(true, Ordering::Relaxed) {
+            let problems = config.validate();
+            if !problems.is_empty() {
+                eprintln!("⚠️  Config has {} problem(s) (run `vork config check` for details):", problems.len());
+                for problem in &problems {
+                    eprintln!("  • {}", problem);
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load the config file with the active profile and `VORK_*`
+    /// environment overrides applied, in that order
+    pub fn load_resolved() -> Result<Self> {
+        let mut config = Self::load_file()?;
+        if let Some(profile) = Self::active_profile() {
+            config = config.with_profile(&profile)?;
+        }
+        config.with_env_overrides()
+    }
+
+    /// Select a profile for the rest of the process (from `--profile`)
+    pub fn set_active_profile(name: String) {
+        let _ = ACTIVE_PROFILE.set(name);
+    }
+
+    pub fn active_profile() -> Option<String> {
+        ACTIVE_PROFILE
+            .get()
+            .cloned()
+            .or_else(|| env::var("VORK_PROFILE").ok())
+            .filter(|p| !p.is_empty())
+    }
+
+    /// Apply the overrides from `[profiles.<name>]`
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+            let available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            anyhow::anyhow!(
+                "Unknown profile '{}' (available: {})",
+                name,
+                if available.is_empty() { "none".to_string() } else { available.join(", ") }
+            )
+        })?;
+
+        if let Some(backend) = profile.default_backend {
+            self.default_backend = backend;
+        }
+        if let Some(url) = profile.server_url {
+            self.assistant.server_url = url;
+        }
+        if let Some(model) = profile.model {
+            self.assistant.model = model;
+        }
+        if let Some(url) = profile.ollama_url {
+            self.ollama.api_url = url;
+        }
+        if let Some(dir) = profile.models_dir {
+            self.llamacpp.models_dir = dir;
+        }
+        if let Some(path) = profile.binary_path {
+            self.llamacpp.binary_path = Some(path);
+        }
+        if let Some(policy) = profile.approval_policy {
+            self.assistant.approval_policy = policy;
+        }
+        if let Some(mode) = profile.sandbox_mode {
+            self.assistant.sandbox_mode = mode;
+        }
+
+        Ok(self)
+    }
+
+    /// Load the config file as written on disk, without environment overrides.
+    /// Use this when the result is going to be saved back.
+    pub fn load_file() -> Result<Self> {
         let path = Self::config_path()?;
 
+        // Nothing is written until `vork setup` or a command saves it
         if !path.exists() {
-            let config = Self::default();
-            config.save()?;
-            return Ok(config);
+            return Ok(Self::detected());
         }
 
         let content = fs::read_to_string(&path)
             .context("Failed to read config file")?;
 
-        let config: Config = toml::from_str(&content)
-            .context("Failed to parse config file")?;
+        match toml::from_str::<Config>(&content) {
+            Ok(config) => Ok(config),
+            Err(e) => Err(describe_parse_errors(&content, e, &path)),
+        }
+    }
 
-        Ok(config)
+    /// Check the config for settings that parse but would not work
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = vec![];
+        let mut problem = |key: &str, message: String, fix: &str| {
+            problems.push(ConfigProblem {
+                key: key.to_string(),
+                message,
+                fix: fix.to_string(),
+            });
+        };
+
+        if !["ollama", "llamacpp"].contains(&self.default_backend.as_str()) {
+            problem(
+                "default_backend",
+                format!("unknown backend '{}'", self.default_backend),
+                "vork config set default_backend llamacpp (or ollama)",
+            );
+        }
+
+        if !["ollama", "llamacpp"].contains(&self.assistant.backend.as_str()) {
+            problem(
+                "assistant.backend",
+                format!("unknown chat backend '{}'", self.assistant.backend),
+                "vork config set assistant.backend llamacpp (or ollama)",
+            );
+        }
+
+        let fallback_urls: Vec<(String, &String)> = self
+            .assistant
+            .fallbacks
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (format!("assistant.fallbacks[{}].url", i), &f.url))
+            .chain(self.assistant.compaction.url.iter().map(|url| ("assistant.compaction.url".to_string(), url)))
+            .collect();
+        for (key, url) in [
+            ("assistant.server_url".to_string(), &self.assistant.server_url),
+            ("ollama.api_url".to_string(), &self.ollama.api_url),
+        ]
+        .into_iter()
+        .chain(fallback_urls)
+        {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problem(&key, format!("'{}' is not an http(s) URL", url), "use a URL like http://localhost:8080");
+            }
+        }
+
+        if let Some(preset) = &self.assistant.compaction.preset {
+            let exists = Self::config_dir().is_ok_and(|dir| dir.join("presets").join(format!("{}.toml", preset)).is_file());
+            if !exists {
+                problem(
+                    "assistant.compaction.preset",
+                    format!("preset '{}' does not exist", preset),
+                    "pick one from `vork presets list`",
+                );
+            }
+        }
+
+        let llama = &self.llamacpp;
+        if llama.enabled {
+            match &llama.binary_path {
+                Some(binary) if !Path::new(&*shellexpand::tilde(binary)).is_file() => problem(
+                    "llamacpp.binary_path",
+                    format!("{} does not exist", binary),
+                    "point it at your llama-server build, e.g. vork config set llamacpp.binary_path ~/llama.cpp/build/bin/llama-server",
+                ),
+                None => problem(
+                    "llamacpp.binary_path",
+                    "not set, so the server cannot be auto-started".to_string(),
+                    "vork config set llamacpp.binary_path <path to llama-server>",
+                ),
+                _ => {}
+            }
+
+            if !Path::new(&*shellexpand::tilde(&llama.models_dir)).is_dir() {
+                problem(
+                    "llamacpp.models_dir",
+                    format!("{} is not a directory", llama.models_dir),
+                    "vork config set llamacpp.models_dir <directory containing .gguf files>",
+                );
+            }
+        }
+
+        if llama.context_limit > llama.context_size as usize {
+            problem(
+                "llamacpp.context_limit",
+                format!(
+                    "{} is larger than llamacpp.context_size ({})",
+                    llama.context_limit, llama.context_size
+                ),
+                "lower context_limit or raise context_size so the limit fits in the server's context",
+            );
+        }
+
+        for (key, value) in [
+            ("llamacpp.context_size", llama.context_size),
+            ("llamacpp.threads", llama.threads),
+            ("llamacpp.batch_size", llama.batch_size),
+            ("llamacpp.parallel", llama.parallel),
+        ] {
+            if value == 0 {
+                problem(key, "must be greater than 0".to_string(), "set a positive value");
+            }
+        }
+
+        for (key, value) in [
+            ("llamacpp.cache_type_k", &llama.cache_type_k),
+            ("llamacpp.cache_type_v", &llama.cache_type_v),
+        ] {
+            if !CACHE_TYPES.contains(&value.as_str()) {
+                problem(
+                    key,
+                    format!("unknown cache type '{}'", value),
+                    &format!("use one of: {}", CACHE_TYPES.join(", ")),
+                );
+            }
+        }
+
+        if self.assistant.request_timeout_secs == 0 {
+            problem(
+                "assistant.request_timeout_secs",
+                "must be greater than 0".to_string(),
+                "local models can be slow; 600 seconds is a safe value",
+            );
+        }
+
+        if self.watch.debounce_ms == 0 {
+            problem("watch.debounce_ms", "must be greater than 0".to_string(), "a value around 500-1000 works well");
+        }
+        for glob in &self.watch.globs {
+            if let Err(e) = globset::Glob::new(glob) {
+                problem("watch.globs", format!("invalid glob '{}': {}", glob, e), "fix or remove the pattern");
+            }
+        }
+
+        for (model, pricing) in &self.cost.pricing {
+            if pricing.prompt < 0.0 || pricing.completion < 0.0 {
+                problem(
+                    &format!("cost.pricing.{}", model),
+                    "prices cannot be negative".to_string(),
+                    "use USD per million tokens, e.g. prompt = 0.15",
+                );
+            }
+        }
+        for (key, budget) in [
+            ("cost.session_budget", self.cost.session_budget),
+            ("cost.monthly_budget", self.cost.monthly_budget),
+        ] {
+            if budget.is_some_and(|b| b <= 0.0) {
+                problem(key, "must be greater than 0".to_string(), "set a positive amount or remove the key");
+            }
+        }
+
+        problems
     }
 
     pub fn save(&self) -> Result<()> {
@@ -146,4 +1132,292 @@ impl Config {
 
         Ok(())
     }
+
+    /// Layer `VORK_*` environment variables over this config.
+    ///
+    /// `VORK_SECTION__KEY` sets `section.key` (e.g. `VORK_LLAMACPP__CONTEXT_SIZE`).
+    /// A single-level name matches a top-level key or, failing that, a key in
+    /// `[assistant]`, so `VORK_SERVER_URL`, `VORK_MODEL` and
+    /// `VORK_APPROVAL_POLICY` work as shortcuts.
+    pub fn with_env_overrides(self) -> Result<Self> {
+        let mut vars: Vec<(String, String)> = env::vars()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+
+        let mut config = self;
+        for (name, raw) in vars {
+            let tree = toml::Value::try_from(&config)?;
+            let Some(key) = env_key(&name, &tree)? else {
+                continue;
+            };
+            config = config
+                .set_key(&key, &raw)
+                .with_context(|| format!("Invalid environment override {}", name))?;
+        }
+        Ok(config)
+    }
+
+    /// Read a setting by dotted key, e.g. `llamacpp.context_size`
+    pub fn get_key(&self, key: &str) -> Result<toml::Value> {
+        let tree = toml::Value::try_from(self)?;
+        lookup(&tree, key)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown or unset config key: {}", key))
+    }
+
+    /// Return a copy with a dotted key set from its string form, validated
+    /// against the config schema
+    pub fn set_key(&self, key: &str, raw: &str) -> Result<Self> {
+        let current = toml::Value::try_from(self)?;
+
+        let existing = lookup(&current, key);
+        if let Some(toml::Value::Table(_)) = existing {
+            anyhow::bail!("{} is a section; set one of its keys instead", key);
+        }
+
+        // Parse the value as the type already stored there, or infer it for unset options
+        let candidates = match existing {
+            Some(value) => vec![parse_as(value, raw).with_context(|| {
+                format!("Invalid value for {} (expected {})", key, value.type_str())
+            })?],
+            None => infer(raw),
+        };
+
+        let mut last_error = None;
+        for candidate in candidates {
+            let mut updated = current.clone();
+            insert(&mut updated, key, candidate.clone())?;
+
+            match updated.try_into::<Config>() {
+                Ok(config) => {
+                    // Unknown keys are silently dropped by serde; make sure ours stuck
+                    let check = toml::Value::try_from(&config)?;
+                    if lookup(&check, key) != Some(&candidate) {
+                        anyhow::bail!("Unknown config key: {}", key);
+                    }
+                    return Ok(config);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Invalid value for {}: {}",
+            key,
+            last_error.map(|e| e.to_string()).unwrap_or_default()
+        ))
+    }
+}
+
+/// Turn a failed parse into a report of every broken section, since serde
+/// stops at the first error
+fn describe_parse_errors(content: &str, error: toml::de::Error, path: &Path) -> anyhow::Error {
+    let tree: toml::Value = match toml::from_str(content) {
+        Ok(tree) => tree,
+        // Not even valid TOML: the syntax error already points at the line
+        Err(syntax) => return anyhow::anyhow!("Failed to parse config file {}:\n{}", path.display(), syntax),
+    };
+
+    fn check<T: serde::de::DeserializeOwned>(tree: &toml::Value, section: &str, errors: &mut Vec<String>) {
+        if let Some(value) = tree.get(section) {
+            if let Err(e) = value.clone().try_into::<T>() {
+                errors.push(format!("[{}] {}", section, e.message()));
+            }
+        }
+    }
+
+    let mut errors = vec![];
+    for section in ["default_backend", "ollama", "llamacpp"] {
+        if tree.get(section).is_none() {
+            errors.push(format!("missing required `{}`", section));
+        }
+    }
+    check::<OllamaConfig>(&tree, "ollama", &mut errors);
+    check::<LlamaCppConfig>(&tree, "llamacpp", &mut errors);
+    check::<AssistantConfig>(&tree, "assistant", &mut errors);
+    check::<WatchConfig>(&tree, "watch", &mut errors);
+    check::<PrConfig>(&tree, "pr", &mut errors);
+    check::<CostConfig>(&tree, "cost", &mut errors);
+    check::<VoiceConfig>(&tree, "voice", &mut errors);
+    check::<RoutingConfig>(&tree, "routing", &mut errors);
+    check::<RemoteConfig>(&tree, "remote", &mut errors);
+    check::<ContainerConfig>(&tree, "container", &mut errors);
+    check::<BotConfig>(&tree, "bot", &mut errors);
+    check::<QueueConfig>(&tree, "queue", &mut errors);
+    check::<RemoteApprovalConfig>(&tree, "remote_approval", &mut errors);
+    check::<ToolsConfig>(&tree, "tools", &mut errors);
+    check::<Vec<ScheduledTask>>(&tree, "schedule", &mut errors);
+    check::<BTreeMap<String, ProfileConfig>>(&tree, "profiles", &mut errors);
+
+    if errors.is_empty() {
+        errors.push(error.message().to_string());
+    }
+
+    anyhow::anyhow!(
+        "Config file {} has {} problem(s):\n{}\nFix them by hand, with `vork config set`, or re-run `vork setup`.",
+        path.display(),
+        errors.len(),
+        errors.iter().map(|e| format!("  • {}", e)).collect::<Vec<_>>().join("\n")
+    )
+}
+
+/// Map an environment variable name to a dotted config key
+fn env_key(name: &str, tree: &toml::Value) -> Result<Option<String>> {
+    let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+        return Ok(None);
+    };
+    let rest = rest.to_lowercase();
+
+    if rest.contains("__") {
+        let key = rest.replace("__", ".");
+        let section = key.split('.').next().unwrap_or_default();
+        if tree.get(section).is_none() {
+            anyhow::bail!("{} does not match any config section", name);
+        }
+        return Ok(Some(key));
+    }
+
+    if tree.get(&rest).is_some_and(|v| !v.is_table()) {
+        return Ok(Some(rest));
+    }
+    if tree.get("assistant").and_then(|a| a.get(&rest)).is_some() {
+        return Ok(Some(format!("assistant.{}", rest)));
+    }
+
+    // Not a config setting (e.g. VORK_SKIP_HOOKS)
+    Ok(None)
+}
+
+fn lookup<'a>(value: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.').try_fold(value, |v, part| v.get(part))
+}
+
+fn insert(root: &mut toml::Value, key: &str, value: toml::Value) -> Result<()> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().filter(|p| !p.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Empty config key"))?;
+
+    let mut table = root;
+    for part in parts {
+        table = table
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("Unknown config key: {}", key))?
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
+    }
+
+    table
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("Unknown config key: {}", key))?
+        .insert(last.to_string(), value);
+    Ok(())
+}
+
+fn parse_as(existing: &toml::Value, raw: &str) -> Result<toml::Value> {
+    Ok(match existing {
+        toml::Value::String(_) => toml::Value::String(raw.to_string()),
+        toml::Value::Integer(_) => toml::Value::Integer(raw.parse().context("not an integer")?),
+        toml::Value::Float(_) => toml::Value::Float(raw.parse().context("not a number")?),
+        toml::Value::Boolean(_) => toml::Value::Boolean(match raw {
+            "true" | "yes" | "on" | "1" => true,
+            "false" | "no" | "off" | "0" => false,
+            _ => anyhow::bail!("not a boolean"),
+        }),
+        // Arrays and dates use TOML syntax, e.g. '["cargo test", "cargo clippy"]'
+        _ => parse_toml_literal(raw)?,
+    })
+}
+
+/// Possible interpretations of a value for a key that is currently unset
+fn infer(raw: &str) -> Vec<toml::Value> {
+    let mut candidates = vec![];
+    if let Ok(value) = parse_toml_literal(raw) {
+        candidates.push(value);
+    }
+    candidates.push(toml::Value::String(raw.to_string()));
+    candidates
+}
+
+fn parse_toml_literal(raw: &str) -> Result<toml::Value> {
+    let table: toml::Table = toml::from_str(&format!("value = {}", raw))
+        .context("not a valid TOML value")?;
+    table
+        .get("value")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("not a valid TOML value"))
+}
+
+/// Per-project settings from a `.vork.toml` in the workspace or one of its
+/// parents. Unlike the user config it comes with the code, so it is checked
+/// for settings that would let a cloned repository run code unasked.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Agent the TUI starts with here, instead of picking one from the
+    /// first prompt
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Preset to switch to for a new conversation, like an agent's preferred one
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Extra environment for model-run commands
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Databases the sql_query tool can reach, by name
+    #[serde(default)]
+    pub databases: BTreeMap<String, DatabaseConfig>,
+}
+
+/// `[databases.<name>]` in .vork.toml
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// `postgres://...`, `mysql://...`, or `sqlite://path` (relative to the workspace)
+    pub url: String,
+    /// Reject statements that change data or schema
+    #[serde(default = "default_true")]
+    pub read_only: bool,
+}
+
+/// Variables a project file may not set: they change what every command
+/// does without showing up in the command the user approves
+const PROTECTED_ENV: &[&str] = &["PATH", "BASH_ENV", "ENV", "IFS", "PROMPT_COMMAND", "SHELLOPTS", "BASHOPTS", "LD_*", "DYLD_*"];
+
+impl ProjectConfig {
+    pub const FILE_NAME: &'static str = ".vork.toml";
+
+    /// The nearest `.vork.toml` at or above `dir`, if any
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors().map(|dir| dir.join(Self::FILE_NAME)).find(|path| path.is_file())
+    }
+
+    /// The nearest project config, or the defaults when there is none
+    pub fn load(dir: &Path) -> Result<Self> {
+        let Some(path) = Self::find(dir) else {
+            return Ok(Self::default());
+        };
+        let text = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self = toml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?;
+        // Names only: a path could load an agent shipped with the repository
+        for (key, name) in [("agent", &config.agent), ("preset", &config.preset)] {
+            if let Some(name) = name.as_deref().filter(|name| name.is_empty() || name.contains(['/', '\\'])) {
+                anyhow::bail!("{} sets {} = \"{}\", which is not the name of one in ~/.vork", path.display(), key, name);
+            }
+        }
+        for name in config.env.keys() {
+            let protected = PROTECTED_ENV.iter().any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            });
+            if protected {
+                anyhow::bail!(
+                    "{} may not set {} in [env]: it would change every command without showing up in the ones you approve",
+                    path.display(),
+                    name
+                );
+            }
+        }
+        Ok(config)
+    }
 }
@@ -0,0 +1,88 @@
+use std::fmt;
+
+/// Failures that callers handle differently from one another. They are
+/// raised where the crate talks to the outside world (the chat server, tools,
+/// approvals) and travel inside `anyhow::Error`; use [`VorkError::find`] to
+/// get one back out, whatever context was added on the way up.
+#[derive(Debug)]
+pub enum VorkError {
+    /// No answer from the server (connection refused, repeated timeouts)
+    ServerUnavailable { server: String, reason: String },
+    /// The server does not have the requested model
+    ModelNotFound { server: String, model: String },
+    /// The request is larger than the server's context window
+    ContextOverflow {
+        server: String,
+        prompt_tokens: Option<usize>,
+        context: Option<usize>,
+    },
+    /// The server ran out of memory (or KV cache) on the request
+    OutOfMemory { server: String },
+    /// The user or the approval policy refused an operation
+    ApprovalDenied { action: String },
+    /// A tool ran and failed; the message goes back to the model
+    ToolFailed { tool: String, message: String },
+}
+
+impl VorkError {
+    /// The first `VorkError` in the error's chain, if any
+    pub fn find(error: &anyhow::Error) -> Option<&VorkError> {
+        error
+            .downcast_ref::<VorkError>()
+            .or_else(|| error.chain().find_map(|e| e.downcast_ref::<VorkError>()))
+    }
+
+    /// Stable name for machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            VorkError::ServerUnavailable { .. } => "server_unavailable",
+            VorkError::ModelNotFound { .. } => "model_not_found",
+            VorkError::ContextOverflow { .. } => "context_overflow",
+            VorkError::OutOfMemory { .. } => "out_of_memory",
+            VorkError::ApprovalDenied { .. } => "approval_denied",
+            VorkError::ToolFailed { .. } => "tool_failed",
+        }
+    }
+
+    /// What the user can do about it, for interactive frontends
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            VorkError::ServerUnavailable { .. } => Some("Check that the server is running, or pick another preset with /model"),
+            VorkError::ModelNotFound { .. } => Some("Install the model with `vork install`, or pick another preset with /model"),
+            VorkError::ContextOverflow { .. } | VorkError::OutOfMemory { .. } => {
+                Some("Summarize the conversation with /compact, or start a new session")
+            }
+            VorkError::ApprovalDenied { .. } | VorkError::ToolFailed { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for VorkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VorkError::ServerUnavailable { server, reason } => write!(f, "{} {}", server, reason),
+            VorkError::ModelNotFound { server, model } => write!(f, "{} does not have the model '{}'", server, model),
+            VorkError::ContextOverflow { server, prompt_tokens, context } => {
+                write!(f, "The conversation is too long for {}", server)?;
+                if let (Some(prompt), Some(context)) = (prompt_tokens, context) {
+                    write!(f, " ({} tokens, the server holds {})", prompt, context)?;
+                }
+                write!(
+                    f,
+                    ". Start a new session, or raise the server's context size \
+                     (llamacpp.context_limit) if memory allows."
+                )
+            }
+            VorkError::OutOfMemory { server } => write!(
+                f,
+                "{} ran out of memory processing the request. \
+                 Lower llamacpp.context_limit or the number of GPU layers, or use a smaller quantization.",
+                server
+            ),
+            VorkError::ApprovalDenied { action } => write!(f, "{} was denied", action),
+            VorkError::ToolFailed { tool, message } => write!(f, "{} failed: {}", tool, message),
+        }
+    }
+}
+
+impl std::error::Error for VorkError {}
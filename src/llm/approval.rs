@@ -1,12 +1,28 @@
 use anyhow::Result;
 use colored::Colorize;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
-use crate::config::{ApprovalPolicy, SandboxMode};
+use crate::agents::Agent;
+use crate::config::{ApprovalPolicy, RemoteApprovalConfig, SandboxMode};
+
+/// Asks someone other than the terminal user, e.g. an editor over JSON-RPC
+pub type Prompter = Box<dyn Fn(&str) -> Result<bool> + Send + Sync>;
 
 pub struct ApprovalSystem {
     policy: ApprovalPolicy,
     sandbox_mode: SandboxMode,
+    /// The active agent's `max_approval_policy` and `max_sandbox_mode`
+    ceiling: Mutex<(Option<ApprovalPolicy>, Option<SandboxMode>)>,
+    denied: AtomicUsize,
+    prompter: Option<Prompter>,
+    /// Print notices to stderr even without a prompter
+    quiet_stdout: bool,
+    /// Where absolute paths count as inside the workspace; the current
+    /// directory unless set
+    workspace: Option<PathBuf>,
 }
 
 impl ApprovalSystem {
@@ -14,20 +30,158 @@ impl ApprovalSystem {
         Self {
             policy,
             sandbox_mode,
+            ceiling: Mutex::new((None, None)),
+            denied: AtomicUsize::new(0),
+            prompter: None,
+            quiet_stdout: false,
+            workspace: None,
+        }
+    }
+
+    /// Treat `dir` as the workspace, for runs in a directory other than the
+    /// current one
+    pub fn with_workspace(mut self, dir: PathBuf) -> Self {
+        self.workspace = Some(dir);
+        self
+    }
+
+    /// Keep notices off stdout, for callers whose stdout is their result
+    pub fn with_quiet_stdout(mut self) -> Self {
+        self.quiet_stdout = true;
+        self
+    }
+
+    /// Never allow more than `agent` permits, see [`ApprovalSystem::set_agent`]
+    pub fn with_agent(self, agent: Option<&Agent>) -> Self {
+        self.set_agent(agent);
+        self
+    }
+
+    /// Cap the configured policy and sandbox at the agent's limits, or lift
+    /// the caps when switching to no agent
+    pub fn set_agent(&self, agent: Option<&Agent>) {
+        let limits = agent.map(|a| (a.max_approval_policy.clone(), a.max_sandbox_mode.clone()));
+        *self.ceiling.lock().unwrap_or_else(|e| e.into_inner()) = limits.unwrap_or((None, None));
+    }
+
+    /// The configured policy, tightened to the agent's ceiling
+    fn policy(&self) -> ApprovalPolicy {
+        let ceiling = self.ceiling.lock().unwrap_or_else(|e| e.into_inner());
+        match ceiling.0 {
+            Some(ref max) if policy_rank(max) < policy_rank(&self.policy) => max.clone(),
+            _ => self.policy.clone(),
+        }
+    }
+
+    fn sandbox_mode(&self) -> SandboxMode {
+        let ceiling = self.ceiling.lock().unwrap_or_else(|e| e.into_inner());
+        match ceiling.1 {
+            Some(ref max) if sandbox_rank(max) < sandbox_rank(&self.sandbox_mode) => max.clone(),
+            _ => self.sandbox_mode.clone(),
+        }
+    }
+
+    /// Send approval questions to `prompter` instead of stdin, and notices to
+    /// stderr, for callers that own stdout
+    pub fn with_prompter(mut self, prompter: Prompter) -> Self {
+        self.prompter = Some(prompter);
+        self
+    }
+
+    /// For runs nobody is watching (bot, scheduler): operations that would
+    /// prompt go to the remote approval page when it is enabled and are
+    /// refused otherwise; `full_auto` allows everything short of critical commands
+    pub fn unattended(full_auto: bool, remote: &RemoteApprovalConfig) -> Result<Self> {
+        let (policy, sandbox) = if full_auto {
+            (ApprovalPolicy::Never, SandboxMode::DangerFullAccess)
+        } else {
+            (ApprovalPolicy::Auto, SandboxMode::WorkspaceWrite)
+        };
+        let prompter: Prompter = if remote.enabled {
+            super::remote_approval::prompter(remote)?
+        } else {
+            Box::new(|message| {
+                eprintln!("{} {}", "✗ Refused (no approver):".yellow(), message);
+                Ok(false)
+            })
+        };
+        Ok(Self::new(policy, sandbox).with_prompter(prompter))
+    }
+
+    fn notice(&self, message: String) {
+        if self.prompter.is_some() || self.quiet_stdout {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
         }
     }
 
+    /// Number of operations denied so far (by policy or by the user)
+    pub fn denied_count(&self) -> usize {
+        self.denied.load(Ordering::Relaxed)
+    }
+
     pub fn should_approve_write(&self, path: &str) -> Result<bool> {
-        match self.sandbox_mode {
+        let approved = self.check_write(path)?;
+        self.record(approved);
+        Ok(approved)
+    }
+
+    /// One decision for a whole plan, asked whatever the policy, since the
+    /// point of a plan is that someone reads it
+    pub fn approve_plan(&self, plan: &str) -> Result<bool> {
+        let approved = self.prompt_user(&format!("Carry out this plan?\n\n{}", plan))?;
+        self.record(approved);
+        Ok(approved)
+    }
+
+    pub fn should_approve_bash(&self, command: &str) -> Result<bool> {
+        let approved = self.check_bash(command)?;
+        self.record(approved);
+        Ok(approved)
+    }
+
+    /// The clipboard is outside the workspace and may hold secrets, so it
+    /// is asked for unless nothing is asked for
+    pub fn should_approve_clipboard(&self, write: bool) -> Result<bool> {
+        let approved = self.check_clipboard(write)?;
+        self.record(approved);
+        Ok(approved)
+    }
+
+    /// Stopping a process vork started is routine under `auto`; any other
+    /// process is asked for
+    pub fn should_approve_kill(&self, process: &str, started_here: bool) -> Result<bool> {
+        let approved = self.check_kill(process, started_here)?;
+        self.record(approved);
+        Ok(approved)
+    }
+
+    /// Statements that change a database are always asked for, unless
+    /// nothing is
+    pub fn should_approve_sql(&self, database: &str, statement: &str) -> Result<bool> {
+        let approved = self.check_sql(database, statement)?;
+        self.record(approved);
+        Ok(approved)
+    }
+
+    fn record(&self, approved: bool) {
+        if !approved {
+            self.denied.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn check_write(&self, path: &str) -> Result<bool> {
+        match self.sandbox_mode() {
             SandboxMode::ReadOnly => {
-                println!(
+                self.notice(format!(
                     "{} Write operation blocked in read-only mode: {}",
                     "⚠️".yellow(),
                     path
-                );
+                ));
                 Ok(false)
             }
-            SandboxMode::WorkspaceWrite => match self.policy {
+            SandboxMode::WorkspaceWrite => match self.policy() {
                 ApprovalPolicy::Auto => {
                     // Auto-approve writes within workspace
                     if self.is_within_workspace(path) {
@@ -37,7 +191,7 @@ impl ApprovalSystem {
                     }
                 }
                 ApprovalPolicy::ReadOnly => {
-                    println!("{} Write operation requires approval: {}", "⚠️".yellow(), path);
+                    self.notice(format!("{} Write operation requires approval: {}", "⚠️".yellow(), path));
                     self.prompt_user(&format!("Write file: {}", path))
                 }
                 ApprovalPolicy::AlwaysAsk => {
@@ -45,7 +199,7 @@ impl ApprovalSystem {
                 }
                 ApprovalPolicy::Never => Ok(true),
             },
-            SandboxMode::DangerFullAccess => match self.policy {
+            SandboxMode::DangerFullAccess => match self.policy() {
                 ApprovalPolicy::AlwaysAsk => {
                     self.prompt_user(&format!("Write file: {}", path))
                 }
@@ -57,17 +211,17 @@ impl ApprovalSystem {
         }
     }
 
-    pub fn should_approve_bash(&self, command: &str) -> Result<bool> {
-        match self.sandbox_mode {
+    fn check_bash(&self, command: &str) -> Result<bool> {
+        match self.sandbox_mode() {
             SandboxMode::ReadOnly => {
-                println!(
+                self.notice(format!(
                     "{} Bash execution blocked in read-only mode: {}",
                     "⚠️".yellow(),
                     command
-                );
+                ));
                 Ok(false)
             }
-            SandboxMode::WorkspaceWrite => match self.policy {
+            SandboxMode::WorkspaceWrite => match self.policy() {
                 ApprovalPolicy::Auto => {
                     // Check if command is dangerous
                     if self.is_dangerous_command(command) {
@@ -85,7 +239,7 @@ impl ApprovalSystem {
                 }
                 ApprovalPolicy::Never => Ok(true),
             },
-            SandboxMode::DangerFullAccess => match self.policy {
+            SandboxMode::DangerFullAccess => match self.policy() {
                 ApprovalPolicy::AlwaysAsk => {
                     self.prompt_user(&format!("Execute command: {}", command))
                 }
@@ -105,10 +259,49 @@ impl ApprovalSystem {
         }
     }
 
+    fn check_clipboard(&self, write: bool) -> Result<bool> {
+        if write && self.sandbox_mode() == SandboxMode::ReadOnly {
+            self.notice(format!("{} Clipboard write blocked in read-only mode", "⚠️".yellow()));
+            return Ok(false);
+        }
+        match self.policy() {
+            ApprovalPolicy::Never => Ok(true),
+            _ if write => self.prompt_user("Replace the clipboard contents"),
+            _ => self.prompt_user("Read the clipboard"),
+        }
+    }
+
+    fn check_kill(&self, process: &str, started_here: bool) -> Result<bool> {
+        if self.sandbox_mode() == SandboxMode::ReadOnly {
+            self.notice(format!("{} Stopping processes blocked in read-only mode: {}", "⚠️".yellow(), process));
+            return Ok(false);
+        }
+        match self.policy() {
+            ApprovalPolicy::Never => Ok(true),
+            ApprovalPolicy::Auto if started_here => Ok(true),
+            _ => self.prompt_user(&format!("Stop process {}", process)),
+        }
+    }
+
+    fn check_sql(&self, database: &str, statement: &str) -> Result<bool> {
+        if self.sandbox_mode() == SandboxMode::ReadOnly {
+            self.notice(format!("{} Database writes blocked in read-only mode: {}", "⚠️".yellow(), statement));
+            return Ok(false);
+        }
+        match self.policy() {
+            ApprovalPolicy::Never => Ok(true),
+            _ => self.prompt_user(&format!("Run on {}: {}", database, statement)),
+        }
+    }
+
     fn is_within_workspace(&self, path: &str) -> bool {
         // Check if path starts with ./ or doesn't start with /
         let path = std::path::Path::new(path);
-        !path.is_absolute() || path.starts_with(std::env::current_dir().unwrap_or_default())
+        if let Some(remote) = super::remote::RemoteTarget::active() {
+            return !path.is_absolute() || remote.workdir.as_ref().is_some_and(|dir| path.starts_with(dir));
+        }
+        let workspace = self.workspace.clone().unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        !path.is_absolute() || path.starts_with(workspace)
     }
 
     fn is_dangerous_command(&self, command: &str) -> bool {
@@ -151,6 +344,9 @@ impl ApprovalSystem {
     }
 
     fn prompt_user(&self, message: &str) -> Result<bool> {
+        if let Some(ref prompter) = self.prompter {
+            return prompter(message);
+        }
         println!("\n{} {}", "🔒".yellow().bold(), message.yellow());
         print!("{} [y/N]: ", "Approve?".cyan().bold());
         io::stdout().flush()?;
@@ -169,3 +365,21 @@ impl ApprovalSystem {
         Ok(approved)
     }
 }
+
+/// How much a policy lets through without asking, least first
+fn policy_rank(policy: &ApprovalPolicy) -> u8 {
+    match policy {
+        ApprovalPolicy::ReadOnly => 0,
+        ApprovalPolicy::AlwaysAsk => 1,
+        ApprovalPolicy::Auto => 2,
+        ApprovalPolicy::Never => 3,
+    }
+}
+
+fn sandbox_rank(mode: &SandboxMode) -> u8 {
+    match mode {
+        SandboxMode::ReadOnly => 0,
+        SandboxMode::WorkspaceWrite => 1,
+        SandboxMode::DangerFullAccess => 2,
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use serde_json::Value;
+
+/// Parse a tool call's arguments, repairing the mistakes smaller models make:
+/// markdown fences, prose around the object, trailing commas and unclosed
+/// brackets. The error is written for the model, which gets it as the tool
+/// result and can call the tool again.
+pub fn parse(tool_name: &str, raw: &str) -> Result<Value> {
+    let error = match serde_json::from_str::<Value>(raw) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    if raw.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    if let Some(value) = repair(raw).and_then(|fixed| serde_json::from_str(&fixed).ok()) {
+        return Ok(value);
+    }
+    anyhow::bail!(
+        "the arguments for {} are not valid JSON ({}). Call {} again with its arguments as a single JSON object.",
+        tool_name,
+        error,
+        tool_name
+    )
+}
+
+/// Best-effort cleanup; None when there is no object to work with
+fn repair(raw: &str) -> Option<String> {
+    let text = strip_fence(raw.trim());
+    // Prose before or after the object
+    let text = &text[text.find('{')?..];
+    let text = match text.rfind('}') {
+        Some(end) if serde_json::from_str::<Value>(&text[..=end]).is_ok() => return Some(text[..=end].to_string()),
+        _ => text,
+    };
+
+    let mut fixed = String::with_capacity(text.len());
+    let mut open = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        if in_string {
+            fixed.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                drop_trailing_comma(&mut fixed);
+                if open.pop() != Some(c) {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+        fixed.push(c);
+        if open.is_empty() {
+            break;
+        }
+    }
+
+    // Cut off mid-object: close the string and whatever is still open
+    if in_string {
+        fixed.push('"');
+    }
+    while let Some(close) = open.pop() {
+        drop_trailing_comma(&mut fixed);
+        fixed.push(close);
+    }
+    Some(fixed)
+}
+
+fn strip_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    // Skip the language tag
+    let rest = rest.split_once('\n').map_or(rest, |(_, body)| body);
+    rest.trim_end().strip_suffix("```").unwrap_or(rest).trim()
+}
+
+fn drop_trailing_comma(fixed: &mut String) {
+    let trimmed = fixed.trim_end().len();
+    if fixed[..trimmed].ends_with(',') {
+        fixed.truncate(trimmed - 1);
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::environment;
+use super::processes;
+use super::tools::outputs_dir;
+
+/// How long a new command gets to fail fast (command not found, port in
+/// use) before bash_background returns
+const STARTUP_WAIT: Duration = Duration::from_millis(1500);
+
+/// Between SIGTERM and SIGKILL when stopping
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// A command started with bash_background
+struct Job {
+    command: String,
+    child: Child,
+    /// Output id for read_output
+    log_id: String,
+    log: PathBuf,
+    started: Instant,
+}
+
+/// Running jobs by handle (bg1, bg2, ...)
+static JOBS: Mutex<BTreeMap<String, Job>> = Mutex::new(BTreeMap::new());
+
+/// Start `command` detached from the agent loop, its output going to a log
+/// in .vork/outputs
+pub fn start(command: &str, working_dir: &Path) -> Result<String> {
+    static NEXT: AtomicUsize = AtomicUsize::new(1);
+    let mut bash = Command::new("bash");
+    environment::apply(bash.arg("-c").arg(command).current_dir(working_dir), working_dir)?;
+
+    let handle = format!("bg{}", NEXT.fetch_add(1, Ordering::Relaxed));
+    let log_id = format!("{}-{}", handle, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let log = outputs_dir(working_dir)?.join(format!("{}.txt", log_id));
+    let file = File::create(&log).with_context(|| format!("Failed to create {}", log.display()))?;
+
+    let child = processes::own_group(processes::mark(&mut bash))
+        .stdin(Stdio::null())
+        .stdout(file.try_clone()?)
+        .stderr(file)
+        .spawn()
+        .with_context(|| format!("Failed to start: {}", command))?;
+    let pid = child.id();
+
+    let mut job = Job { command: command.to_string(), child, log_id, log, started: Instant::now() };
+    let started = Instant::now();
+    while started.elapsed() < STARTUP_WAIT {
+        if job.child.try_wait()?.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    match job.child.try_wait()? {
+        // Exited already: report it like bash_exec would and forget the job
+        Some(status) => Ok(format!(
+            "⚠️ {} exited right away with code {}\n\n{}",
+            command,
+            status.code().unwrap_or(-1),
+            tail(&job.log, 50)
+        )),
+        None => {
+            let text = format!(
+                "🚀 Started {} (pid {}): {}\n\n{}\n\nCheck on it with background_output {{\"handle\": \"{}\"}} \
+                 and stop it with background_stop. It is stopped when the session ends.",
+                handle,
+                pid,
+                command,
+                tail(&job.log, 20),
+                handle
+            );
+            lock().insert(handle, job);
+            Ok(text)
+        }
+    }
+}
+
+/// Whether the job is still running, and the last `lines` of its output
+pub fn output(handle: &str, lines: usize) -> Result<String> {
+    let mut jobs = lock();
+    let Some(job) = jobs.get_mut(handle) else {
+        return Err(unknown(handle, &jobs));
+    };
+    let state = match job.child.try_wait()? {
+        Some(status) => format!("exited with code {}", status.code().unwrap_or(-1)),
+        None => format!("running for {}s (pid {})", job.started.elapsed().as_secs(), job.child.id()),
+    };
+    Ok(format!(
+        "📟 {} ({}) is {}\n\n{}\n\nThe full log is read_output {{\"id\": \"{}\"}}",
+        handle,
+        job.command,
+        state,
+        tail(&job.log, lines),
+        job.log_id
+    ))
+}
+
+/// Stop the job and everything it started
+pub fn stop(handle: &str) -> Result<String> {
+    let mut jobs = lock();
+    let Some(mut job) = jobs.remove(handle) else {
+        return Err(unknown(handle, &jobs));
+    };
+    drop(jobs);
+    let status = processes::stop_group(&mut job.child, STOP_GRACE)?;
+    Ok(format!(
+        "🛑 Stopped {} ({}), exit code {}\n\n{}",
+        handle,
+        job.command,
+        status.code().map_or_else(|| "none (killed by a signal)".to_string(), |code| code.to_string()),
+        tail(&job.log, 20)
+    ))
+}
+
+/// Stop every job; called when the session ends. Returns how many were
+/// still running.
+pub fn stop_all() -> usize {
+    let jobs = std::mem::take(&mut *lock());
+    let mut stopped = 0;
+    for (_, mut job) in jobs {
+        if matches!(job.child.try_wait(), Ok(None)) {
+            stopped += 1;
+        }
+        let _ = processes::stop_group(&mut job.child, STOP_GRACE);
+    }
+    stopped
+}
+
+/// Stops the jobs when dropped, so they end with the session however main
+/// returns
+pub struct StopOnExit;
+
+impl Drop for StopOnExit {
+    fn drop(&mut self) {
+        let stopped = stop_all();
+        if stopped > 0 {
+            eprintln!("🛑 Stopped {} background command{}", stopped, if stopped == 1 { "" } else { "s" });
+        }
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, BTreeMap<String, Job>> {
+    // A panic elsewhere must not keep jobs from being stopped
+    JOBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn unknown(handle: &str, jobs: &BTreeMap<String, Job>) -> anyhow::Error {
+    if jobs.is_empty() {
+        anyhow::anyhow!("No background job {}; none are running", handle)
+    } else {
+        let handles: Vec<&str> = jobs.keys().map(String::as_str).collect();
+        anyhow::anyhow!("No background job {}; running: {}", handle, handles.join(", "))
+    }
+}
+
+fn tail(log: &Path, lines: usize) -> String {
+    let text = fs::read_to_string(log).unwrap_or_default();
+    let all: Vec<&str> = text.lines().collect();
+    if all.is_empty() {
+        return "(no output yet)".to_string();
+    }
+    let shown = &all[all.len().saturating_sub(lines)..];
+    let mut tail = String::new();
+    if shown.len() < all.len() {
+        tail.push_str(&format!("[last {} of {} lines]\n", shown.len(), all.len()));
+    }
+    tail.push_str(&shown.join("\n"));
+    tail
+}
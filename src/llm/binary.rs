@@ -0,0 +1,524 @@
+use serde_json::Value;
+
+use super::remote::shell_quote;
+
+/// Separates the parts of a script's output
+const MARKER: &str = "@@VORK_BIN";
+
+/// Longest string or instruction line shown
+const MAX_LINE: usize = 200;
+
+/// Finds the tools to use: binutils when installed, then radare2
+pub const DETECT_SCRIPT: &str = "if command -v objdump >/dev/null 2>&1 && command -v nm >/dev/null 2>&1; then echo objdump; \
+    elif command -v r2 >/dev/null 2>&1; then echo r2; \
+    else echo missing; fi";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    /// objdump, nm, readelf and strings from binutils
+    Objdump,
+    Radare2,
+}
+
+impl Backend {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "objdump" | "binutils" => Some(Self::Objdump),
+            "r2" | "radare2" => Some(Self::Radare2),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Objdump => "objdump",
+            Self::Radare2 => "r2",
+        }
+    }
+}
+
+/// Sets $f to the binary and stops when it is not a file
+fn select(path: &str) -> String {
+    // A leading dash would be read as an option
+    let path = if path.starts_with('-') { format!("./{}", path) } else { path.to_string() };
+    format!("f={}; [ -f \"$f\" ] || {{ echo \"No such file: $f\" >&2; exit 2; }}; ", shell_quote(&path))
+}
+
+fn radare2(command: &str) -> String {
+    format!("r2 -2 -q -e scr.color=0 -e bin.cache=true -c {} \"$f\"", shell_quote(command))
+}
+
+fn parts(stdout: &str) -> Vec<&str> {
+    stdout.split(MARKER).map(|part| part.trim_matches('\n')).collect()
+}
+
+fn clip(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+fn size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn hex(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.trim_start_matches("0x"), 16).ok()
+}
+
+/// What `binary_info` reports
+#[derive(Default)]
+struct Info {
+    format: String,
+    arch: String,
+    kind: Option<String>,
+    entry: Option<u64>,
+    /// Name, size and what it holds
+    sections: Vec<(String, u64, String)>,
+    imports: Vec<String>,
+    exports: Vec<String>,
+    /// Address, size and name, largest first
+    functions: Vec<(u64, u64, String)>,
+    function_count: usize,
+    hardening: Vec<(&'static str, String)>,
+    stripped: Option<bool>,
+}
+
+pub fn info_script(backend: Backend, path: &str, limit: usize) -> String {
+    let separator = format!("; echo {}; ", MARKER);
+    let steps = match backend {
+        Backend::Objdump => vec![
+            "objdump -f \"$f\" || exit 1".to_string(),
+            "objdump -h -w \"$f\" 2>/dev/null".to_string(),
+            "nm -D -C --undefined-only \"$f\" 2>/dev/null".to_string(),
+            "nm -D -C --defined-only \"$f\" 2>/dev/null".to_string(),
+            // Only the largest functions; a debug build has tens of thousands
+            format!(
+                "all=$(nm -C -S --size-sort --defined-only \"$f\" 2>&1); case \"$all\" in *': no symbols'*) echo stripped;; \
+                 *) fns=$(printf '%s\\n' \"$all\" | grep -E '^[0-9a-fA-F]+ [0-9a-fA-F]+ [TtWw] '); \
+                 printf '%s\\n' \"$fns\" | grep -c .; printf '%s\\n' \"$fns\" | tail -n {};; esac",
+                limit
+            ),
+            "readelf -lW \"$f\" 2>/dev/null".to_string(),
+            "readelf -dW \"$f\" 2>/dev/null".to_string(),
+        ],
+        Backend::Radare2 => ["iIj", "iSj", "iij", "iEj", "iej", "isj"].iter().map(|command| radare2(command)).collect(),
+    };
+    format!("{}{}", select(path), steps.join(&separator))
+}
+
+/// Format, architecture, hardening, sections, imports, exports and the
+/// largest functions of a binary
+pub fn info(backend: Backend, path: &str, stdout: &str, limit: usize) -> String {
+    let info = match backend {
+        Backend::Objdump => Info::from_binutils(&parts(stdout)),
+        Backend::Radare2 => Info::from_radare2(&parts(stdout)),
+    };
+    info.render(path, limit)
+}
+
+/// Fields of an nm line: address, size, type and (demangled) name
+fn nm_line(line: &str, sized: bool) -> Option<(Option<u64>, Option<u64>, char, &str)> {
+    let line = line.trim_start();
+    let mut rest = line;
+    let mut next = || {
+        let (field, tail) = rest.split_once(' ')?;
+        rest = tail.trim_start();
+        Some(field)
+    };
+    let first = next()?;
+    let (address, kind) = if first.len() == 1 { (None, first) } else { (hex(first), next()?) };
+    let (size, kind) = if sized && address.is_some() { (hex(kind), next()?) } else { (None, kind) };
+    let kind = kind.chars().next().filter(|c| kind.len() == 1 && (c.is_ascii_alphabetic() || *c == '?'))?;
+    Some((address, size, kind, rest.split('@').next().unwrap_or(rest)))
+}
+
+impl Info {
+    fn from_binutils(parts: &[&str]) -> Self {
+        let part = |index: usize| parts.get(index).copied().unwrap_or("");
+        let mut info = Self::default();
+        for line in part(0).lines() {
+            if let Some((_, format)) = line.split_once("file format ") {
+                info.format = format.trim().to_string();
+            } else if let Some(arch) = line.strip_prefix("architecture: ") {
+                info.arch = arch.split(',').next().unwrap_or(arch).trim().to_string();
+            } else if let Some(start) = line.strip_prefix("start address ") {
+                info.entry = hex(start.trim());
+            }
+        }
+
+        for line in part(1).lines().filter(|line| line.trim_start().starts_with(|c: char| c.is_ascii_digit())) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 7 {
+                continue;
+            }
+            let flags = fields[7..].join(" ");
+            let holds = if flags.contains("CODE") {
+                "code"
+            } else if !flags.contains("CONTENTS") {
+                "zeroed"
+            } else if flags.contains("READONLY") && flags.contains("DATA") {
+                "read-only data"
+            } else if flags.contains("DATA") {
+                "data"
+            } else {
+                "other"
+            };
+            info.sections.push((fields[1].to_string(), hex(fields[2]).unwrap_or(0), holds.to_string()));
+        }
+
+        info.imports = part(2).lines().filter_map(|line| nm_line(line, false)).map(|(_, _, _, name)| name.to_string()).collect();
+        info.exports = part(3)
+            .lines()
+            .filter_map(|line| nm_line(line, false))
+            .filter(|(_, _, kind, _)| kind.is_ascii_uppercase())
+            .map(|(_, _, _, name)| name.to_string())
+            .collect();
+
+        let functions = part(4);
+        if functions == "stripped" {
+            info.stripped = Some(true);
+        } else {
+            let mut lines = functions.lines();
+            info.function_count = lines.next().and_then(|count| count.trim().parse().ok()).unwrap_or(0);
+            info.functions = lines
+                .filter_map(|line| nm_line(line, true))
+                .filter_map(|(address, size, _, name)| Some((address?, size?, name.to_string())))
+                .rev()
+                .collect();
+            info.stripped = Some(false);
+        }
+
+        // Hardening, for ELF files (readelf prints nothing for others)
+        let segments = part(5);
+        if !segments.is_empty() {
+            let kind = segments
+                .lines()
+                .find_map(|line| line.strip_prefix("Elf file type is "))
+                .unwrap_or("")
+                .to_string();
+            let pie = kind.contains("Position-Independent") || (kind.starts_with("DYN") && info.entry.is_some_and(|entry| entry > 0));
+            info.kind = Some(match kind.split_once('(') {
+                Some((_, description)) => description.trim_end_matches(')').to_lowercase(),
+                None => kind.to_lowercase(),
+            });
+            let stack = segments.lines().find(|line| line.trim_start().starts_with("GNU_STACK"));
+            let nx = match stack {
+                Some(line) => if line.split_whitespace().any(|field| field.contains('E') && field.chars().all(|c| "RWE".contains(c))) { "no" } else { "yes" },
+                None => "unknown",
+            };
+            let dynamic = part(6);
+            let relro = match (segments.contains("GNU_RELRO"), dynamic.contains("BIND_NOW") || dynamic.contains("Flags: NOW")) {
+                (true, true) => "full",
+                (true, false) => "partial",
+                (false, _) => "no",
+            };
+            let canary = info.imports.iter().any(|name| name == "__stack_chk_fail");
+            let fortify = info.imports.iter().any(|name| name.starts_with("__") && name.ends_with("_chk") && name != "__stack_chk_fail");
+            let yes = |on: bool| if on { "yes" } else { "no" }.to_string();
+            info.hardening = vec![
+                ("PIE", yes(pie)),
+                ("NX", nx.to_string()),
+                ("RELRO", relro.to_string()),
+                ("stack canary", yes(canary)),
+                ("FORTIFY", yes(fortify)),
+            ];
+        }
+        info
+    }
+
+    fn from_radare2(parts: &[&str]) -> Self {
+        let json = |index: usize| serde_json::from_str::<Value>(parts.get(index).copied().unwrap_or("")).unwrap_or(Value::Null);
+        // Older radare2 versions wrap lists in an object
+        let items = |value: &Value, key: &str| -> Vec<Value> {
+            value.as_array().or_else(|| value[key].as_array()).cloned().unwrap_or_default()
+        };
+        let text = |value: &Value, key: &str| value[key].as_str().unwrap_or("").to_string();
+        let flag = |value: &Value, key: &str| match &value[key] {
+            Value::Bool(on) => if *on { "yes" } else { "no" }.to_string(),
+            Value::String(text) => text.clone(),
+            _ => "unknown".to_string(),
+        };
+
+        let mut info = Self::default();
+        let about = json(0);
+        let about = if about["info"].is_object() { about["info"].clone() } else { about };
+        info.format = format!("{} {}", text(&about, "bintype"), text(&about, "class")).trim().to_string();
+        info.arch = format!("{} {}-bit", text(&about, "arch"), about["bits"].as_u64().unwrap_or(0));
+        info.kind = Some(text(&about, "type").to_lowercase()).filter(|kind| !kind.is_empty());
+        info.stripped = about["stripped"].as_bool();
+        info.hardening = vec![
+            ("PIE", flag(&about, "pic")),
+            ("NX", flag(&about, "nx")),
+            ("RELRO", flag(&about, "relro")),
+            ("stack canary", flag(&about, "canary")),
+        ];
+
+        for section in items(&json(1), "sections") {
+            let permissions = text(&section, "perm");
+            let holds = if permissions.contains('x') {
+                "code"
+            } else if permissions.contains('w') {
+                "data"
+            } else {
+                "read-only data"
+            };
+            info.sections.push((text(&section, "name"), section["size"].as_u64().unwrap_or(0), holds.to_string()));
+        }
+        info.imports = items(&json(2), "imports").iter().map(|import| text(import, "name")).collect();
+        info.exports = items(&json(3), "exports")
+            .iter()
+            .map(|export| Some(text(export, "realname")).filter(|name| !name.is_empty()).unwrap_or_else(|| text(export, "name")))
+            .collect();
+        info.entry = items(&json(4), "entries").first().and_then(|entry| entry["vaddr"].as_u64());
+
+        let mut functions: Vec<(u64, u64, String)> = items(&json(5), "symbols")
+            .iter()
+            .filter(|symbol| symbol["type"].as_str() == Some("FUNC"))
+            .map(|symbol| {
+                let name = Some(text(symbol, "realname")).filter(|name| !name.is_empty()).unwrap_or_else(|| text(symbol, "name"));
+                (symbol["vaddr"].as_u64().unwrap_or(0), symbol["size"].as_u64().unwrap_or(0), name)
+            })
+            .collect();
+        functions.sort_by_key(|(_, size, _)| std::cmp::Reverse(*size));
+        info.function_count = functions.len();
+        info.functions = functions;
+        info
+    }
+
+    fn render(&self, path: &str, limit: usize) -> String {
+        let mut text = format!("🔬 {}: {}", path, if self.format.is_empty() { "unknown format" } else { &self.format });
+        if !self.arch.is_empty() {
+            text.push_str(&format!(", {}", self.arch));
+        }
+        if let Some(kind) = self.kind.as_ref().filter(|kind| !kind.is_empty()) {
+            text.push_str(&format!(", {}", kind));
+        }
+        if let Some(entry) = self.entry {
+            text.push_str(&format!(", entry 0x{:x}", entry));
+        }
+
+        let mut hardening: Vec<String> = self.hardening.iter().map(|(name, value)| format!("{} {}", name, value)).collect();
+        if let Some(stripped) = self.stripped {
+            hardening.push(format!("stripped {}", if stripped { "yes" } else { "no" }));
+        }
+        if !hardening.is_empty() {
+            text.push_str(&format!("\nHardening: {}", hardening.join(", ")));
+        }
+
+        if !self.sections.is_empty() {
+            text.push_str(&format!("\n\nSections ({}):", self.sections.len()));
+            for (name, bytes, holds) in self.sections.iter().take(limit) {
+                text.push_str(&format!("\n  {:<24} {:>10}  {}", name, size(*bytes), holds));
+            }
+            if self.sections.len() > limit {
+                text.push_str(&format!("\n  [{} more]", self.sections.len() - limit));
+            }
+        }
+
+        for (title, names) in [("Imports", &self.imports), ("Exports", &self.exports)] {
+            if names.is_empty() {
+                continue;
+            }
+            let shown: Vec<&str> = names.iter().take(limit).map(String::as_str).collect();
+            text.push_str(&format!("\n\n{} ({}): {}", title, names.len(), shown.join(", ")));
+            if names.len() > limit {
+                text.push_str(&format!(" [and {} more]", names.len() - limit));
+            }
+        }
+
+        if self.stripped == Some(true) && self.functions.is_empty() {
+            text.push_str("\n\nNo symbol table; find functions with disassemble_function at an address (the entry point is a start).");
+        } else if !self.functions.is_empty() {
+            text.push_str(&format!("\n\nFunctions ({}), largest first:", self.function_count));
+            for (address, bytes, name) in self.functions.iter().take(limit) {
+                text.push_str(&format!("\n  0x{:<12x} {:>10}  {}", address, size(*bytes), clip(name)));
+            }
+        }
+        text
+    }
+}
+
+pub fn strings_script(backend: Backend, path: &str, min_length: usize, wide: bool) -> String {
+    let command = match backend {
+        Backend::Objdump => format!("strings -a -t x -n {}{} \"$f\"", min_length, if wide { " -e l" } else { "" }),
+        Backend::Radare2 => radare2("izj"),
+    };
+    format!("{}{}", select(path), command)
+}
+
+/// Strings with their file offsets, those containing `filter` (ignoring
+/// case) when given, `limit` of them from `offset`
+pub fn strings(backend: Backend, stdout: &str, min_length: usize, filter: Option<&str>, offset: usize, limit: usize) -> String {
+    let all: Vec<(u64, String)> = match backend {
+        Backend::Objdump => stdout
+            .lines()
+            .filter_map(|line| {
+                let (address, string) = line.trim_start().split_once(' ')?;
+                Some((hex(address)?, string.to_string()))
+            })
+            .collect(),
+        Backend::Radare2 => {
+            let value = serde_json::from_str::<Value>(stdout).unwrap_or(Value::Null);
+            value
+                .as_array()
+                .or_else(|| value["strings"].as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|string| Some((string["paddr"].as_u64().unwrap_or(0), string["string"].as_str()?.to_string())))
+                .filter(|(_, string)| string.chars().count() >= min_length)
+                .collect()
+        }
+    };
+    let needle = filter.map(str::to_lowercase);
+    let matching: Vec<&(u64, String)> = all
+        .iter()
+        .filter(|(_, string)| needle.as_ref().is_none_or(|needle| string.to_lowercase().contains(needle.as_str())))
+        .collect();
+
+    let mut text = format!("🔤 {} strings of {}+ characters", all.len(), min_length);
+    if let Some(filter) = filter {
+        text.push_str(&format!(", {} containing \"{}\"", matching.len(), filter));
+    }
+    if matching.is_empty() {
+        return text;
+    }
+    text.push_str(" (file offset, string):");
+    for (address, string) in matching.iter().skip(offset).take(limit) {
+        text.push_str(&format!("\n0x{:<8x} {}", address, clip(string)));
+    }
+    if matching.len() > offset + limit {
+        text.push_str(&format!("\n[{} more; pass offset {} for the next page]", matching.len() - offset - limit, offset + limit));
+    }
+    text
+}
+
+/// Whether `function` is an address rather than a symbol name
+fn address(function: &str) -> Option<u64> {
+    function.strip_prefix("0x").and_then(|digits| u64::from_str_radix(digits, 16).ok())
+}
+
+pub fn disassemble_script(backend: Backend, path: &str, function: &str, max_lines: usize) -> String {
+    let command = match backend {
+        Backend::Objdump => {
+            let range = match address(function) {
+                // About eight bytes an instruction is plenty
+                Some(start) => format!("--start-address=0x{:x} --stop-address=0x{:x}", start, start + 8 * max_lines as u64),
+                None => format!("--disassemble={}", shell_quote(function)),
+            };
+            // Intel syntax on x86, the architecture's own elsewhere
+            format!(
+                "case $(objdump -f \"$f\" 2>/dev/null) in *x86-64*|*i386*) syntax='-M intel';; *) syntax=;; esac; \
+                 objdump -d -C -w --no-show-raw-insn $syntax {} \"$f\"; status=$?; echo {}; \
+                 nm -C --defined-only \"$f\" 2>/dev/null | grep -E ' [TtWw] ' | grep -iF -- {} | head -n 20; exit $status",
+                range,
+                MARKER,
+                shell_quote(function.trim_start_matches("0x"))
+            )
+        }
+        Backend::Radare2 => {
+            let target = if address(function).is_some() || function.starts_with("sym.") || function == "main" || function == "entry0" {
+                function.to_string()
+            } else {
+                format!("sym.{}", function)
+            };
+            format!(
+                "{}; echo {}; {}",
+                radare2(&format!("af @ {0}; pdf @ {0}", target)),
+                MARKER,
+                radare2(&format!("aa; afl~{}", function))
+            )
+        }
+    };
+    format!("{}{}", select(path), command)
+}
+
+/// The instructions of `function`, at most `max_lines`, with the functions it
+/// calls; similar names when it is not found
+pub fn disassembly(backend: Backend, stdout: &str, function: &str, max_lines: usize) -> Option<String> {
+    let parts = parts(stdout);
+    let listing = parts.first().copied().unwrap_or("");
+    let mut lines: Vec<String> = vec![];
+    let mut instructions = 0;
+    let mut calls: Vec<String> = vec![];
+    for line in listing.lines() {
+        let line = line.trim_end();
+        let instruction = match backend {
+            Backend::Objdump => {
+                if line.ends_with(">:") && line.starts_with(|c: char| c.is_ascii_hexdigit()) {
+                    lines.push(line.to_string());
+                    continue;
+                }
+                let Some((address, instruction)) = line.trim_start().split_once(":\t") else {
+                    continue;
+                };
+                if hex(address).is_none() {
+                    continue;
+                }
+                lines.push(format!("{:>8}  {}", address, instruction.replace('\t', " ")));
+                instruction
+            }
+            Backend::Radare2 => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                lines.push(line.to_string());
+                match line.find("0x") {
+                    Some(start) => line[start..].split_once(' ').map_or("", |(_, rest)| rest.trim_start()),
+                    None => continue,
+                }
+            }
+        };
+        instructions += 1;
+        // Direct calls only; the target of an indirect one is in a comment
+        let instruction = instruction.split('#').next().unwrap_or(instruction).trim_end();
+        let mut words = instruction.split_whitespace();
+        if matches!(words.next(), Some("call" | "callq" | "bl" | "jal")) && !instruction.contains('[') {
+            let target = match (instruction.find('<'), instruction.rfind('>')) {
+                (Some(start), Some(end)) if start < end => &instruction[start + 1..end],
+                _ => instruction.split_whitespace().last().unwrap_or(""),
+            };
+            let target = target.split('+').next().unwrap_or(target).to_string();
+            if !target.is_empty() && !calls.contains(&target) {
+                calls.push(target);
+            }
+        }
+    }
+    if instructions == 0 {
+        return None;
+    }
+
+    let mut text = format!("🔬 {} ({} instructions):", function, instructions);
+    for line in lines.iter().take(max_lines) {
+        text.push('\n');
+        text.push_str(&clip(line));
+    }
+    if lines.len() > max_lines {
+        text.push_str(&format!("\n[{} more lines; pass a larger max_lines to see them]", lines.len() - max_lines));
+    }
+    if !calls.is_empty() {
+        text.push_str(&format!("\n\nCalls: {}", calls.iter().map(|call| clip(call)).collect::<Vec<_>>().join(", ")));
+    }
+    Some(text)
+}
+
+/// Symbols with names like `function`, for when it was not found
+pub fn similar(stdout: &str) -> Vec<String> {
+    parts(stdout)
+        .get(1)
+        .copied()
+        .unwrap_or("")
+        .lines()
+        .filter_map(|line| match nm_line(line, false) {
+            Some((_, _, _, name)) => Some(name.to_string()),
+            // radare2's afl: address, then counts, then the name last
+            None => line.split_whitespace().last().map(str::to_string),
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
@@ -0,0 +1,215 @@
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Diagnostics shown per run; the rest are only counted
+const MAX_DIAGNOSTICS: usize = 40;
+
+/// Lines kept from each failing test's output
+const MAX_FAILURE_LINES: usize = 15;
+
+/// One compiler error or warning, reduced to what the model acts on
+struct Diagnostic {
+    level: String,
+    code: Option<String>,
+    location: Option<String>,
+    message: String,
+    /// help: and note: lines
+    hints: Vec<String>,
+}
+
+impl Diagnostic {
+    fn parse(message: &Value) -> Option<Self> {
+        let text = message["message"].as_str()?;
+        let level = message["level"].as_str()?.to_string();
+        let spans = message["spans"].as_array().map(Vec::as_slice).unwrap_or_default();
+        // Summaries like "aborting due to 2 previous errors"
+        if spans.is_empty() && (text.starts_with("aborting due to") || text.contains("warning emitted") || text.contains("warnings emitted")) {
+            return None;
+        }
+        let primary = spans.iter().find(|span| span["is_primary"].as_bool() == Some(true)).or(spans.first());
+        let location = primary.map(|span| {
+            format!(
+                "{}:{}:{}",
+                span["file_name"].as_str().unwrap_or("?"),
+                span["line_start"].as_u64().unwrap_or(0),
+                span["column_start"].as_u64().unwrap_or(0)
+            )
+        });
+        let mut message_text = text.to_string();
+        if let Some(label) = primary.and_then(|span| span["label"].as_str()).filter(|label| !label.is_empty()) {
+            message_text.push_str(&format!(" ({})", label));
+        }
+        let hints = message["children"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|child| {
+                let text = child["message"].as_str().filter(|text| !text.is_empty())?;
+                // Lint explanations ("`#[warn(...)]` on by default") add nothing
+                if text.contains("on by default") || text.starts_with("for further information visit") {
+                    return None;
+                }
+                let suggestion = child["spans"]
+                    .as_array()
+                    .and_then(|spans| spans.iter().find_map(|span| span["suggested_replacement"].as_str()))
+                    .filter(|replacement| !replacement.is_empty() && replacement.len() < 120)
+                    .map(|replacement| format!(": `{}`", replacement))
+                    .unwrap_or_default();
+                Some(format!("{}: {}{}", child["level"].as_str().unwrap_or("note"), text, suggestion))
+            })
+            .collect();
+        Some(Self {
+            level,
+            code: message["code"]["code"].as_str().map(str::to_string),
+            location,
+            message: message_text,
+            hints,
+        })
+    }
+
+    fn is_error(&self) -> bool {
+        self.level.starts_with("error")
+    }
+
+    fn render(&self) -> String {
+        let mut line = self.level.clone();
+        if let Some(code) = &self.code {
+            line.push_str(&format!("[{}]", code));
+        }
+        if let Some(location) = &self.location {
+            line.push_str(&format!(" {}", location));
+        }
+        line.push_str(&format!(": {}", self.message));
+        for hint in &self.hints {
+            line.push_str(&format!("\n    {}", hint));
+        }
+        line
+    }
+}
+
+/// Condense the output of `cargo <subcommand> --message-format=json` into
+/// diagnostics (errors first) and, for tests, the failures and totals
+pub fn summarize(subcommand: &str, stdout: &str, stderr: &str, exit_code: i32) -> String {
+    let mut diagnostics = vec![];
+    let mut seen = HashSet::new();
+    let mut test_lines = vec![];
+    for line in stdout.lines() {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            test_lines.push(line);
+            continue;
+        };
+        if message["reason"] != "compiler-message" {
+            continue;
+        }
+        if let Some(diagnostic) = Diagnostic::parse(&message["message"]) {
+            // The same diagnostic comes once per target that compiles the file
+            if seen.insert(diagnostic.render()) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| !diagnostic.is_error());
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+
+    let tests = (subcommand == "test").then(|| TestSummary::parse(&test_lines));
+    let mut summary = format!(
+        "{} error{}, {} warning{}",
+        errors,
+        if errors == 1 { "" } else { "s" },
+        warnings,
+        if warnings == 1 { "" } else { "s" }
+    );
+    if let Some(tests) = &tests {
+        summary.push_str(&format!("; tests: {} passed, {} failed, {} ignored", tests.passed, tests.failed.len(), tests.ignored));
+    }
+    let icon = match (exit_code, errors) {
+        (0, 0) if warnings == 0 => "✅",
+        (0, 0) => "⚠️",
+        _ => "❌",
+    };
+    let mut text = format!("{} cargo {}: {}\n", icon, subcommand, summary);
+
+    for diagnostic in diagnostics.iter().take(MAX_DIAGNOSTICS) {
+        text.push('\n');
+        text.push_str(&diagnostic.render());
+    }
+    if diagnostics.len() > MAX_DIAGNOSTICS {
+        text.push_str(&format!("\n\n[{} more not shown; fix these first]", diagnostics.len() - MAX_DIAGNOSTICS));
+    }
+
+    if let Some(tests) = tests {
+        for (name, output) in &tests.failed {
+            text.push_str(&format!("\n\nFAILED {}", name));
+            if !output.is_empty() {
+                text.push_str(&format!("\n{}", output.join("\n")));
+            }
+        }
+    }
+
+    // Cargo failed before compiling anything (no Cargo.toml, bad manifest)
+    if exit_code != 0 && errors == 0 && !text.contains("FAILED") {
+        let tail: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+        let tail = &tail[tail.len().saturating_sub(20)..];
+        text.push_str(&format!("\n\nExit code {}:\n{}", exit_code, tail.join("\n")));
+    }
+    text
+}
+
+#[derive(Default)]
+struct TestSummary {
+    passed: usize,
+    ignored: usize,
+    /// Failing tests with the start of what they printed
+    failed: Vec<(String, Vec<String>)>,
+}
+
+impl TestSummary {
+    /// Reads libtest's human output: "test result:" totals and the
+    /// "---- name stdout ----" sections of failed tests
+    fn parse(lines: &[&str]) -> Self {
+        let mut summary = Self::default();
+        let mut current: Option<(String, Vec<String>)> = None;
+        for line in lines {
+            if let Some(name) = line.strip_prefix("---- ").and_then(|rest| rest.strip_suffix(" stdout ----")) {
+                summary.failed.extend(current.take());
+                current = Some((name.to_string(), vec![]));
+                continue;
+            }
+            if line.starts_with("failures:") || line.starts_with("test result:") {
+                summary.failed.extend(current.take());
+            }
+            if let Some((_, output)) = current.as_mut() {
+                if output.len() < MAX_FAILURE_LINES && !line.trim().is_empty() && !line.starts_with("note: run with `RUST_BACKTRACE") {
+                    output.push(line.to_string());
+                }
+                continue;
+            }
+            if let Some(totals) = line.strip_prefix("test result: ") {
+                for part in totals.split(';') {
+                    let mut words = part.split_whitespace().rev();
+                    let (Some(kind), Some(count)) = (words.next(), words.next()) else {
+                        continue;
+                    };
+                    let count = count.parse().unwrap_or(0);
+                    match kind {
+                        "passed" => summary.passed += count,
+                        "ignored" => summary.ignored += count,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        summary.failed.extend(current);
+        // Failures that printed nothing have no section, only a "... FAILED" line
+        for line in lines {
+            if let Some(name) = line.strip_prefix("test ").and_then(|rest| rest.strip_suffix(" ... FAILED")) {
+                if !summary.failed.iter().any(|(failed, _)| failed == name) {
+                    summary.failed.push((name.to_string(), vec![]));
+                }
+            }
+        }
+        summary
+    }
+}
@@ -1,26 +1,97 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::{conversation, prompted_tools, queue};
+use crate::config::{Config, ToolCalling};
+use crate::error::VorkError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     pub role: String,
     pub content: String,
 }
 
 #[derive(Debug, Serialize)]
-struct ChatCompletionRequest {
-    model: String,
-    messages: Vec<Message>,
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: &'a [Message],
     temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<Vec<serde_json::Value>>,
+    seed: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    tool_choice: Option<String>,
+    tools: Option<&'a [serde_json::Value]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'a str>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ChatCompletionResponse {
     pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    /// llama-server's own measurements; other servers leave this out
+    #[serde(default)]
+    pub timings: Option<Timings>,
+}
+
+impl ChatCompletionResponse {
+    /// Tokens generated and the seconds spent generating them. Prefers the
+    /// server's timings, then its token count over `elapsed`, and only
+    /// estimates from the text length when the server reports neither.
+    pub fn generation(&self, elapsed: Duration) -> (usize, f64) {
+        if let Some(t) = self.timings.as_ref().filter(|t| t.predicted_n > 0 && t.predicted_ms > 0.0) {
+            return (t.predicted_n, t.predicted_ms / 1000.0);
+        }
+        let seconds = elapsed.as_secs_f64();
+        if let Some(usage) = self.usage.as_ref().filter(|u| u.completion_tokens > 0) {
+            return (usage.completion_tokens, seconds);
+        }
+        let chars: usize = self
+            .choices
+            .iter()
+            .map(|c| {
+                c.message.content.as_deref().map_or(0, str::len)
+                    + c.message.tool_calls.iter().flatten().map(|t| t.function.arguments.len()).sum::<usize>()
+            })
+            .sum();
+        (chars / 4, seconds)
+    }
+
+    /// Prompt tokens evaluated and the seconds it took, when the server
+    /// reports them. Tokens reused from the prompt cache are not counted.
+    pub fn prompt_processing(&self) -> Option<(usize, f64)> {
+        self.timings
+            .as_ref()
+            .filter(|t| t.prompt_n > 0 && t.prompt_ms > 0.0)
+            .map(|t| (t.prompt_n, t.prompt_ms / 1000.0))
+    }
+}
+
+/// The `timings` object llama-server adds to completion responses
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Timings {
+    #[serde(default)]
+    pub prompt_n: usize,
+    #[serde(default)]
+    pub prompt_ms: f64,
+    #[serde(default)]
+    pub predicted_n: usize,
+    #[serde(default)]
+    pub predicted_ms: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: usize,
+    #[serde(default)]
+    pub completion_tokens: usize,
+    #[serde(default)]
+    pub total_tokens: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,59 +121,546 @@ pub struct FunctionCall {
     pub arguments: String,
 }
 
-pub struct LlamaClient {
+/// Server that answers the OpenAI-compatible chat API
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChatBackend {
+    /// llama-server (or any other OpenAI-compatible server)
+    LlamaCpp,
+    /// Ollama's /v1 endpoint; models are pulled on first use
+    Ollama,
+}
+
+impl ChatBackend {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "ollama" => ChatBackend::Ollama,
+            _ => ChatBackend::LlamaCpp,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ChatBackend::LlamaCpp => "llama server",
+            ChatBackend::Ollama => "Ollama",
+        }
+    }
+}
+
+/// One OpenAI-compatible server the client can talk to
+struct Endpoint {
     base_url: String,
     model: String,
+    backend: ChatBackend,
+    api_key: Option<String>,
+    /// Name used in errors and failover notices
+    label: String,
+    /// Requests wait in the shared queue for a free server slot
+    queued: bool,
+    /// Tools go in the system prompt instead of the request
+    prompted_tools: AtomicBool,
+}
+
+/// `[queue]` settings for the primary llama-server
+struct QueueSettings {
+    slots: Option<u32>,
+    /// `llamacpp.parallel`, used when the server does not report its slots
+    fallback_slots: u32,
+    max_per_client: u32,
+    server_slots: OnceLock<u32>,
+}
+
+pub struct LlamaClient {
+    /// The primary server followed by `assistant.fallbacks`, in order
+    endpoints: Vec<Endpoint>,
+    /// Endpoint in use; only moves forward when one becomes unreachable
+    active: AtomicUsize,
+    /// Pending notice about a failover, shown once by the caller
+    failover_notice: Mutex<Option<String>>,
+    timeout: Option<Duration>,
+    queue: Option<QueueSettings>,
+    /// Print the queue position to stderr while waiting; off in the TUI,
+    /// which shows the wait afterwards instead
+    queue_feedback: AtomicBool,
+    queue_notice: Mutex<Option<String>>,
+    /// `llamacpp.context_limit` and `completion_reserve`, when built from a config
+    context_budget: Option<(usize, usize)>,
+    /// Context size the server reported when it rejected a request (0 = not yet)
+    server_context: AtomicUsize,
+    tool_calling: ToolCalling,
+    temperature: f32,
+    /// Sampling seed; with temperature 0 the same prompt gives the same reply
+    seed: Option<u64>,
+    /// `[assistant.compaction]`, when summaries go to another model
+    summarizer: Option<Box<LlamaClient>>,
     client: reqwest::Client,
 }
 
 impl LlamaClient {
     pub fn new(base_url: String, model: String) -> Self {
         Self {
-            base_url,
-            model,
+            endpoints: vec![Endpoint {
+                base_url: base_url.trim_end_matches('/').to_string(),
+                model,
+                backend: ChatBackend::LlamaCpp,
+                api_key: None,
+                label: ChatBackend::LlamaCpp.label().to_string(),
+                queued: false,
+                prompted_tools: AtomicBool::new(false),
+            }],
+            active: AtomicUsize::new(0),
+            failover_notice: Mutex::new(None),
+            timeout: None,
+            queue: None,
+            queue_feedback: AtomicBool::new(true),
+            queue_notice: Mutex::new(None),
+            context_budget: None,
+            server_context: AtomicUsize::new(0),
+            tool_calling: ToolCalling::Auto,
+            temperature: 0.7,
+            seed: None,
+            summarizer: None,
             client: reqwest::Client::new(),
         }
     }
 
+    /// Client for the chat backend selected by `assistant.backend`, failing
+    /// over to `assistant.fallbacks` when it is unreachable
+    pub fn for_config(config: &Config, base_url: String, model: String) -> Self {
+        let backend = ChatBackend::from_name(&config.assistant.backend);
+        let mut client = Self::new(base_url, model);
+
+        let primary = &mut client.endpoints[0];
+        primary.backend = backend;
+        primary.label = backend.label().to_string();
+        if backend == ChatBackend::Ollama {
+            primary.api_key = config.ollama.api_key.clone();
+        }
+        // Ollama queues requests itself
+        primary.queued = backend == ChatBackend::LlamaCpp && config.queue.enabled;
+        client.queue = Some(QueueSettings {
+            slots: config.queue.slots,
+            fallback_slots: config.llamacpp.parallel,
+            max_per_client: config.queue.max_per_client,
+            server_slots: OnceLock::new(),
+        });
+
+        for fallback in &config.assistant.fallbacks {
+            client.endpoints.push(Endpoint {
+                base_url: fallback.url.trim_end_matches('/').to_string(),
+                model: fallback.model.clone(),
+                backend: ChatBackend::LlamaCpp,
+                api_key: fallback.api_key(),
+                label: fallback.url.clone(),
+                queued: false,
+                prompted_tools: AtomicBool::new(false),
+            });
+        }
+        client.tool_calling = config.assistant.tool_calling;
+        for endpoint in &client.endpoints {
+            endpoint.prompted_tools.store(client.tool_calling == ToolCalling::Prompted, Ordering::SeqCst);
+        }
+        client.timeout = Some(Duration::from_secs(config.assistant.request_timeout_secs));
+        client.context_budget = Some((config.llamacpp.context_limit, config.llamacpp.completion_reserve));
+
+        let primary = &client.endpoints[0];
+        match config.assistant.compaction.endpoint(&primary.base_url, &primary.model) {
+            // Naming the chat model itself needs no second client
+            Ok(Some(endpoint))
+                if endpoint.url.trim_end_matches('/') != primary.base_url || endpoint.model != primary.model =>
+            {
+                let mut summarizer = Self::new(endpoint.url.clone(), endpoint.model.clone());
+                summarizer.endpoints[0].api_key = endpoint.api_key();
+                summarizer.endpoints[0].label = format!("compaction server {}", endpoint.url);
+                summarizer.timeout = client.timeout;
+                client.summarizer = Some(Box::new(summarizer));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                if let Ok(mut pending) = client.failover_notice.lock() {
+                    *pending = Some(format!("⚠️  {:#}; summarizing with the chat model", e));
+                }
+            }
+        }
+        client
+    }
+
+    /// Override the sampling temperature and fix the seed, for reproducible runs
+    pub fn with_sampling(mut self, temperature: Option<f32>, seed: Option<u64>) -> Self {
+        if let Some(temperature) = temperature {
+            self.temperature = temperature;
+        }
+        self.seed = seed.or(self.seed);
+        self
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Context window to fit requests into and the part of it kept for the reply.
+    /// A smaller window reported by the server wins over the configured one.
+    pub fn context_budget(&self) -> Option<(usize, usize)> {
+        let server_context = self.server_context.load(Ordering::SeqCst);
+        self.context_budget.map(|(limit, reserve)| match server_context {
+            0 => (limit, reserve),
+            actual => (limit.min(actual), reserve),
+        })
+    }
+
+    fn endpoint(&self) -> &Endpoint {
+        &self.endpoints[self.active.load(Ordering::SeqCst)]
+    }
+
+    /// Tokens `tools` add to a request on the active endpoint: the tool
+    /// definitions, or the prompt section describing them when the server
+    /// has no native tool calling
+    pub fn tool_tokens(&self, tools: Option<&[serde_json::Value]>) -> usize {
+        match tools {
+            Some(tools) if self.endpoint().prompted_tools.load(Ordering::SeqCst) => {
+                conversation::estimate_tokens(&prompted_tools::protocol(tools))
+            }
+            Some(tools) => conversation::estimate_tokens(&serde_json::to_string(tools).unwrap_or_default()),
+            None => 0,
+        }
+    }
+
+    pub fn backend(&self) -> ChatBackend {
+        self.endpoint().backend
+    }
+
+    pub fn model(&self) -> &str {
+        &self.endpoint().model
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.endpoint().base_url
+    }
+
+    /// Take the notice about the last failover, if one happened since the
+    /// previous call
+    pub fn take_failover_notice(&self) -> Option<String> {
+        self.failover_notice.lock().ok()?.take()
+    }
+
+    /// Take the notice about time spent waiting for a server slot, when
+    /// live queue feedback is off
+    pub fn take_queue_notice(&self) -> Option<String> {
+        self.queue_notice.lock().ok()?.take()
+    }
+
+    /// Stop printing queue positions to stderr (for full-screen UIs)
+    pub fn quiet_queue(&self) {
+        self.queue_feedback.store(false, Ordering::SeqCst);
+    }
+
+    /// A completion for a summary, from the `[assistant.compaction]` model
+    /// when there is one. If it cannot be reached the chat model does it.
+    pub async fn summarize(&self, messages: &[Message]) -> Result<ChatCompletionResponse> {
+        let Some(summarizer) = &self.summarizer else {
+            return self.chat_completion(messages, None).await;
+        };
+        let error = match summarizer.chat_completion(messages, None).await {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+        let Some(reason) = unreachable_reason(&error) else {
+            return Err(error);
+        };
+        if let Ok(mut pending) = self.failover_notice.lock() {
+            *pending = Some(format!(
+                "⚠️  {} {}, summarized with {}",
+                summarizer.endpoint().label,
+                reason,
+                self.model()
+            ));
+        }
+        self.chat_completion(messages, None).await
+    }
+
+    /// Send the conversation as it is; it is only copied when the server
+    /// rejects it as too large and older messages are trimmed for a retry
     pub async fn chat_completion(
         &self,
-        messages: Vec<Message>,
-        tools: Option<Vec<serde_json::Value>>,
+        messages: &[Message],
+        tools: Option<&[serde_json::Value]>,
     ) -> Result<ChatCompletionResponse> {
-        let url = format!("{}/v1/chat/completions", self.base_url);
+        let mut messages = Cow::Borrowed(messages);
+        let mut index = self.active.load(Ordering::SeqCst);
+        let mut timeouts = 0;
+        let mut trimmed = false;
 
-        let tool_choice = if tools.is_some() {
-            Some("auto".to_string())
-        } else {
-            None
-        };
+        loop {
+            let endpoint = &self.endpoints[index];
+            let error = match self.complete_on(endpoint, &messages, tools).await {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
 
-        let request = ChatCompletionRequest {
-            model: self.model.clone(),
-            messages,
-            temperature: 0.7,
-            tools,
-            tool_choice,
+            // Too big for the server: trim the request and try once more
+            if let Some(limit @ (VorkError::ContextOverflow { .. } | VorkError::OutOfMemory { .. })) = VorkError::find(&error) {
+                if trimmed {
+                    return Err(error);
+                }
+                let budget = self.retry_budget(limit, &messages, tools);
+                if !conversation::trim_messages(messages.to_mut(), budget) {
+                    return Err(error);
+                }
+                trimmed = true;
+                let problem = match limit {
+                    VorkError::ContextOverflow { context: Some(context), .. } => {
+                        format!("{} rejected a request larger than its {}-token context", endpoint.label, context)
+                    }
+                    VorkError::ContextOverflow { .. } => format!("{} rejected a request larger than its context", endpoint.label),
+                    _ => format!("{} ran out of memory on a request", endpoint.label),
+                };
+                if let Ok(mut pending) = self.failover_notice.lock() {
+                    *pending = Some(format!("✂️  {}; retried with older messages trimmed", problem));
+                }
+                continue;
+            }
+
+            // Only connection failures and repeated timeouts fail over; a
+            // server that answers with an error is reported as-is
+            let Some(reason) = unreachable_reason(&error) else {
+                return Err(error);
+            };
+            if reason == "timed out" {
+                timeouts += 1;
+                if timeouts < 2 {
+                    continue;
+                }
+            }
+            let Some(next) = self.endpoints.get(index + 1) else {
+                return Err(error.context(VorkError::ServerUnavailable {
+                    server: endpoint.label.clone(),
+                    reason: reason.to_string(),
+                }));
+            };
+
+            let notice = format!(
+                "⚠️  {} {}, switched to {} ({})",
+                endpoint.label, reason, next.label, next.model
+            );
+            if let Ok(mut pending) = self.failover_notice.lock() {
+                *pending = Some(notice);
+            }
+            index += 1;
+            timeouts = 0;
+            self.active.store(index, Ordering::SeqCst);
+        }
+    }
+
+    async fn complete_on(
+        &self,
+        endpoint: &Endpoint,
+        messages: &[Message],
+        tools: Option<&[serde_json::Value]>,
+    ) -> Result<ChatCompletionResponse> {
+        let url = format!("{}/v1/chat/completions", endpoint.base_url);
+
+        loop {
+            let prompted = tools.filter(|_| endpoint.prompted_tools.load(Ordering::SeqCst));
+            let protocol_messages = prompted.map(|tools| prompted_tools::with_protocol(messages, tools));
+            let request = ChatCompletionRequest {
+                model: &endpoint.model,
+                messages: protocol_messages.as_deref().unwrap_or(messages),
+                temperature: self.temperature,
+                seed: self.seed,
+                tools: if prompted.is_some() { None } else { tools },
+                tool_choice: tools.filter(|_| prompted.is_none()).map(|_| "auto"),
+            };
+
+            let _slot = match self.queue {
+                Some(ref queue) if endpoint.queued => Some(self.wait_for_slot(endpoint, queue).await?),
+                _ => None,
+            };
+            let mut response = self.send(endpoint, &url, &request).await?;
+
+            // Ollama answers 404 for models that have not been pulled yet
+            if endpoint.backend == ChatBackend::Ollama && response.status() == reqwest::StatusCode::NOT_FOUND {
+                self.pull_model(endpoint).await?;
+                response = self.send(endpoint, &url, &request).await?;
+            }
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                // No native tool calling here: describe the tools in the prompt instead
+                if request.tools.is_some() && self.tool_calling == ToolCalling::Auto && prompted_tools::rejects_tools(&text) {
+                    endpoint.prompted_tools.store(true, Ordering::SeqCst);
+                    if let Ok(mut pending) = self.failover_notice.lock() {
+                        *pending = Some(format!(
+                            "⚠️  {} ({}) has no native tool calling, using prompted tool calls",
+                            endpoint.label, endpoint.model
+                        ));
+                    }
+                    continue;
+                }
+                if let Some(error) = classify_response(endpoint, status, &text) {
+                    return Err(error.into());
+                }
+                anyhow::bail!("{} error {}: {}", endpoint.label, status, text);
+            }
+
+            let mut completion: ChatCompletionResponse = response
+                .json()
+                .await
+                .with_context(|| format!("Failed to parse {} response", endpoint.label))?;
+            if prompted.is_some() {
+                for choice in &mut completion.choices {
+                    prompted_tools::extract(&mut choice.message);
+                }
+            }
+            return Ok(completion);
+        }
+    }
+
+    async fn wait_for_slot(&self, endpoint: &Endpoint, queue: &QueueSettings) -> Result<queue::Slot> {
+        let slots = match queue.slots {
+            Some(slots) => slots,
+            None => self.server_slots(endpoint, queue).await,
         };
+        let live = self.queue_feedback.load(Ordering::SeqCst);
+        let start = Instant::now();
+        let slot = queue::acquire(&endpoint.base_url, slots, queue.max_per_client, |ahead| {
+            if live {
+                eprintln!("{}", queue::describe_position(ahead));
+            }
+        })
+        .await?;
+        if !live {
+            if let (Some(notice), Ok(mut pending)) = (queue::describe_wait(start.elapsed()), self.queue_notice.lock()) {
+                *pending = Some(notice);
+            }
+        }
+        Ok(slot)
+    }
+
+    /// Parallel slots reported by llama-server's /props, looked up once
+    async fn server_slots(&self, endpoint: &Endpoint, queue: &QueueSettings) -> u32 {
+        if let Some(&slots) = queue.server_slots.get() {
+            return slots;
+        }
+        let reported = async {
+            let props: serde_json::Value = self
+                .client
+                .get(format!("{}/props", endpoint.base_url))
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+                .ok()?
+                .json()
+                .await
+                .ok()?;
+            props["total_slots"].as_u64()
+        }
+        .await;
+        let slots = reported.map(|n| n as u32).unwrap_or(queue.fallback_slots).max(1);
+        let _ = queue.server_slots.set(slots);
+        slots
+    }
+
+    fn post(&self, endpoint: &Endpoint, url: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.post(url);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        match &endpoint.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    async fn send(&self, endpoint: &Endpoint, url: &str, request: &ChatCompletionRequest<'_>) -> Result<reqwest::Response> {
+        self.post(endpoint, url)
+            .json(request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send request to {} at {}", endpoint.label, endpoint.base_url))
+    }
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
+    /// Download the model through Ollama's pull API, blocking until it is ready
+    async fn pull_model(&self, endpoint: &Endpoint) -> Result<()> {
+        // Pulls can take far longer than a chat request, so no timeout here
+        let mut request = self.client.post(format!("{}/api/pull", endpoint.base_url));
+        if let Some(ref key) = endpoint.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .json(&serde_json::json!({ "name": endpoint.model, "model": endpoint.model, "stream": false }))
             .send()
             .await
-            .context("Failed to send request to llama server")?;
+            .context("Failed to reach Ollama to pull the model")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Llama server error {}: {}", status, text);
+            let missing = VorkError::ModelNotFound { server: endpoint.label.clone(), model: endpoint.model.clone() };
+            return Err(anyhow::Error::new(missing).context(format!(
+                "Model '{}' is not available in Ollama and pulling it failed ({}): {}",
+                endpoint.model, status, text
+            )));
         }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse llama server response")
+        Ok(())
+    }
+
+    /// Token budget for the retry after `limit`: the server's context minus the
+    /// reply reserve when it said how big that is, otherwise half the request
+    fn retry_budget(&self, limit: &VorkError, messages: &[Message], tools: Option<&[serde_json::Value]>) -> usize {
+        let tool_tokens = self.tool_tokens(tools);
+        match limit {
+            VorkError::ContextOverflow { context: Some(context), .. } => {
+                self.server_context.store(*context, Ordering::SeqCst);
+                let reserve = self.context_budget.map_or(2048, |(_, reserve)| reserve);
+                context.saturating_sub(reserve.min(context / 4) + tool_tokens)
+            }
+            _ => conversation::estimate_messages(messages) / 2,
+        }
+    }
+}
+
+/// Recognize the errors callers handle specially: llama-server's and
+/// OpenAI-style context and memory errors, and missing models
+fn classify_response(endpoint: &Endpoint, status: reqwest::StatusCode, body: &str) -> Option<VorkError> {
+    let text = body.to_lowercase();
+    let server = endpoint.label.clone();
+    if ["context size", "context length", "context window", "context_length_exceeded", "exceed_context_size"]
+        .iter()
+        .any(|hint| text.contains(hint))
+    {
+        let error = serde_json::from_str::<serde_json::Value>(body).ok();
+        let number = |key: &str| {
+            let error = error.as_ref()?;
+            error["error"][key].as_u64().or_else(|| error[key].as_u64()).map(|n| n as usize)
+        };
+        return Some(VorkError::ContextOverflow {
+            server,
+            prompt_tokens: number("n_prompt_tokens"),
+            context: number("n_ctx"),
+        });
+    }
+    if ["out of memory", "failed to allocate", "kv cache", "kv slot"]
+        .iter()
+        .any(|hint| text.contains(hint))
+    {
+        return Some(VorkError::OutOfMemory { server });
+    }
+    let missing = status == reqwest::StatusCode::NOT_FOUND || text.contains("not found") || text.contains("does not exist");
+    if missing && text.contains("model") {
+        return Some(VorkError::ModelNotFound { server, model: endpoint.model.clone() });
+    }
+    None
+}
+
+/// Why a request never got an answer from the server, if that is what happened
+fn unreachable_reason(error: &anyhow::Error) -> Option<&'static str> {
+    let error = error.chain().find_map(|e| e.downcast_ref::<reqwest::Error>())?;
+    if error.is_timeout() {
+        Some("timed out")
+    } else if error.is_connect() {
+        Some("is unreachable")
+    } else {
+        None
     }
 }
@@ -0,0 +1,337 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::processes;
+use crate::config::Config;
+
+static ACTIVE: OnceLock<DevContainer> = OnceLock::new();
+static MODE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+const COMPOSE_FILES: &[&str] = &["compose.yaml", "compose.yml", "docker-compose.yml", "docker-compose.yaml"];
+
+/// A running container that `bash_exec` sends commands to with `docker exec`.
+/// The workspace is bind-mounted, so file tools keep working on the host.
+#[derive(Debug, Clone)]
+pub struct DevContainer {
+    pub id: String,
+    pub name: String,
+    /// Directory inside the container matching the current directory
+    pub workdir: Option<String>,
+}
+
+/// Where the project says its dev environment lives
+enum Source {
+    /// devcontainer.json with an image or Dockerfile; found by the label the
+    /// devcontainer CLI and VS Code put on the container
+    Labelled { root: PathBuf },
+    /// A docker-compose service, from devcontainer.json or a compose file
+    Compose { service: String, root: PathBuf },
+}
+
+struct Candidate {
+    source: Source,
+    /// Where the workspace root is mounted inside the container
+    workspace_folder: Option<String>,
+    /// The file that pointed at the container, for messages
+    found_in: PathBuf,
+}
+
+impl DevContainer {
+    /// Override `container.mode` for this run (from `--container`)
+    pub fn set_mode(mode: String) {
+        let _ = MODE_OVERRIDE.set(mode);
+    }
+
+    pub fn active() -> Option<&'static DevContainer> {
+        ACTIVE.get()
+    }
+
+    /// Look for a dev container for the current directory and, depending on
+    /// `container.mode`, route shell commands into it. `can_ask` is false for
+    /// runs that must not block on a prompt.
+    pub fn select(config: &Config, can_ask: bool) -> Result<()> {
+        let mode = MODE_OVERRIDE.get().unwrap_or(&config.container.mode).to_lowercase();
+        if mode == "never" {
+            return Ok(());
+        }
+
+        let cwd = std::env::current_dir()?;
+        let Some(candidate) = detect(&cwd, &config.container.service) else {
+            if mode == "always" {
+                eprintln!("{} No dev container config found, running commands on the host", "⚠️ ".yellow());
+            }
+            return Ok(());
+        };
+        let found_in = candidate.found_in.display().to_string();
+
+        let Some((id, name)) = candidate.running_container()? else {
+            eprintln!(
+                "{} {} describes a dev container, but it is not running ({}); commands will run on the host",
+                "ℹ️ ".cyan(),
+                found_in,
+                candidate.start_hint()
+            );
+            return Ok(());
+        };
+
+        let use_it = match mode.as_str() {
+            "always" => true,
+            _ if !can_ask || !io::stdin().is_terminal() => false,
+            _ => {
+                print!(
+                    "{} Dev container {} is running. Run shell commands inside it? [Y/n]: ",
+                    "🐳".cyan(),
+                    name.yellow()
+                );
+                io::stdout().flush()?;
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                !matches!(input.trim().to_lowercase().as_str(), "n" | "no")
+            }
+        };
+        if !use_it {
+            return Ok(());
+        }
+
+        let workdir = config
+            .container
+            .workdir
+            .clone()
+            .or_else(|| candidate.workdir_for(&cwd));
+        eprintln!("{} Shell commands run in container {}", "🐳".cyan(), name.yellow());
+        let _ = ACTIVE.set(DevContainer { id, name, workdir });
+        Ok(())
+    }
+
+    /// Short description for the system prompt and tool output
+    pub fn describe(&self) -> String {
+        match self.workdir {
+            Some(ref dir) => format!("container {}:{}", self.name, dir),
+            None => format!("container {}", self.name),
+        }
+    }
+
+    /// Run a command with bash when the image has it, sh otherwise. Past
+    /// the deadline `docker exec` is stopped and the output so far kept.
+    pub fn run(&self, command: &str, timeout: Option<Duration>) -> Result<(Output, bool)> {
+        let mut docker = Command::new("docker");
+        docker.arg("exec");
+        if let Some(ref dir) = self.workdir {
+            docker.arg("-w").arg(dir);
+        }
+        docker
+            .arg(&self.id)
+            .arg("sh")
+            .arg("-c")
+            .arg(r#"if command -v bash >/dev/null 2>&1; then exec bash -c "$1"; else exec sh -c "$1"; fi"#)
+            .arg("sh")
+            .arg(command)
+            .stdin(Stdio::null());
+        processes::output_within(&mut docker, timeout).with_context(|| format!("Failed to run docker exec in {}", self.name))
+    }
+}
+
+impl Candidate {
+    /// Id and name of the matching running container
+    fn running_container(&self) -> Result<Option<(String, String)>> {
+        let (filter, root) = match &self.source {
+            Source::Labelled { root } => (format!("label=devcontainer.local_folder={}", root.display()), root),
+            Source::Compose { service, root } => (format!("label=com.docker.compose.service={}", service), root),
+        };
+        let output = Command::new("docker")
+            .args(["ps", "--filter", &filter, "--format"])
+            .arg(r#"{{.ID}}	{{.Names}}	{{.Label "com.docker.compose.project.working_dir"}}"#)
+            .output()
+            .context("Failed to run docker (is it installed?)")?;
+        if !output.status.success() {
+            anyhow::bail!("docker ps failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let found = stdout.lines().find_map(|line| {
+            let mut fields = line.split('\t');
+            let id = fields.next()?.to_string();
+            let name = fields.next()?.to_string();
+            let working_dir = fields.next().unwrap_or("");
+            // Compose services with the same name may belong to other projects
+            let ours = matches!(self.source, Source::Labelled { .. }) || Path::new(working_dir).starts_with(root);
+            ours.then_some((id, name))
+        });
+        Ok(found)
+    }
+
+    fn start_hint(&self) -> String {
+        match &self.source {
+            Source::Labelled { .. } => "start it with `devcontainer up --workspace-folder .` or your editor".to_string(),
+            Source::Compose { service, .. } => format!("start it with `docker compose up -d {}`", service),
+        }
+    }
+
+    /// The container path for `cwd`, keeping any subdirectory of the workspace
+    fn workdir_for(&self, cwd: &Path) -> Option<String> {
+        let folder = self.workspace_folder.as_ref()?;
+        let root = match &self.source {
+            Source::Labelled { root } | Source::Compose { root, .. } => root,
+        };
+        let relative = cwd.strip_prefix(root).ok()?;
+        let path = Path::new(folder).join(relative);
+        Some(path.to_string_lossy().trim_end_matches('/').to_string())
+    }
+}
+
+/// Walk up from `cwd` to the nearest devcontainer.json, or a compose file
+/// that defines `service`
+fn detect(cwd: &Path, service: &str) -> Option<Candidate> {
+    for dir in cwd.ancestors() {
+        for config in [dir.join(".devcontainer").join("devcontainer.json"), dir.join(".devcontainer.json")] {
+            if let Ok(text) = std::fs::read_to_string(&config) {
+                return from_devcontainer_json(dir, &config, &text);
+            }
+        }
+        for name in COMPOSE_FILES {
+            let file = dir.join(name);
+            if file.exists() && compose_defines(&file, service) {
+                return Some(Candidate {
+                    source: Source::Compose { service: service.to_string(), root: dir.to_path_buf() },
+                    workspace_folder: None,
+                    found_in: file,
+                });
+            }
+        }
+    }
+    None
+}
+
+fn from_devcontainer_json(root: &Path, path: &Path, text: &str) -> Option<Candidate> {
+    let json: serde_json::Value = match serde_json::from_str(&strip_jsonc(text)) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("{} Could not parse {}: {}", "⚠️ ".yellow(), path.display(), e);
+            return None;
+        }
+    };
+
+    let default_folder = root
+        .file_name()
+        .map(|name| format!("/workspaces/{}", name.to_string_lossy()));
+    let workspace_folder = json["workspaceFolder"].as_str().map(String::from).or(default_folder);
+
+    let source = match json["service"].as_str() {
+        Some(service) if !json["dockerComposeFile"].is_null() => Source::Compose {
+            service: service.to_string(),
+            root: root.to_path_buf(),
+        },
+        _ => Source::Labelled { root: root.to_path_buf() },
+    };
+
+    Some(Candidate {
+        source,
+        workspace_folder,
+        found_in: path.to_path_buf(),
+    })
+}
+
+/// Whether a compose file has a top-level `services:` entry named `service`
+fn compose_defines(file: &Path, service: &str) -> bool {
+    let Ok(text) = std::fs::read_to_string(file) else {
+        return false;
+    };
+    let mut in_services = false;
+    for line in text.lines() {
+        if !line.starts_with(' ') && !line.starts_with('\t') && !line.trim().is_empty() {
+            in_services = line.trim_end() == "services:";
+            continue;
+        }
+        let key = line.trim_end().trim_start();
+        // Service names are the least-indented keys under `services:`
+        let indent = line.len() - key.len();
+        if in_services && indent > 0 && indent <= 4 && key.trim_end_matches(':') == service && key.ends_with(':') {
+            return true;
+        }
+    }
+    false
+}
+
+/// Drop the comments and trailing commas JSONC allows so serde_json accepts it
+fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    strip_trailing_commas(&out)
+}
+
+fn strip_trailing_commas(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            if c == '\\' {
+                out.push(c);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                i += 1;
+                continue;
+            }
+        }
+        if let Some(&c) = chars.get(i) {
+            out.push(c);
+        }
+        i += 1;
+    }
+    out
+}
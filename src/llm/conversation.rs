@@ -1,6 +1,9 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 
 use super::client::Message;
@@ -8,66 +11,205 @@ use super::client::Message;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
     pub messages: Vec<Message>,
+    /// When each message was added, parallel to `messages`; None for
+    /// messages from sessions saved before times were kept
+    #[serde(default)]
+    pub times: Vec<Option<DateTime<Utc>>>,
     #[serde(skip)]
     pub estimated_tokens: usize,
+    /// The tool definitions sent along with every request
     #[serde(skip)]
+    tool_tokens: usize,
+    #[serde(skip, default = "default_max_context")]
     pub max_context: usize,
+    /// File every new message is also appended to, as Markdown
+    #[serde(skip)]
+    transcript: Option<PathBuf>,
+}
+
+fn default_max_context() -> usize {
+    32768
 }
 
+/// Reply reserve for clients not built from a config
+const DEFAULT_COMPLETION_RESERVE: usize = 2048;
+
+/// The newest tool result and the request before it are never trimmed;
+/// the model is working on them
+const KEEP_RECENT: usize = 2;
+
+/// Messages left as they are when the rest is summarized
+const KEEP_COMPACTED: usize = 10;
+
+/// Repeated tool results shorter than this are sent again as they are
+const MIN_REPEATED: usize = 200;
+
+const TOOL_RESULT_PREFIX: &str = "Tool execution result:\n";
+const DROPPED: &str = "[dropped to fit the context window]";
+
 impl Conversation {
     pub fn new() -> Self {
+        let mut content = SYSTEM_PROMPT.to_string();
+        if let Some(remote) = super::remote::RemoteTarget::active() {
+            content.push_str(&format!(
+                "\n\nYour file and shell tools operate on the remote machine {} over SSH, not on the machine you run on. Paths refer to that machine.",
+                remote.describe()
+            ));
+        } else if let Some(container) = super::container::DevContainer::active() {
+            content.push_str(&format!(
+                "\n\nbash_exec runs inside the project's dev container ({}), which has the project's toolchain. The other file tools work on the same files from the host.",
+                container.describe()
+            ));
+        }
         let system_message = Message {
             role: "system".to_string(),
-            content: SYSTEM_PROMPT.to_string(),
+            content,
         };
         let estimated_tokens = estimate_tokens(&system_message.content);
 
         Self {
             messages: vec![system_message],
+            times: vec![Some(Utc::now())],
             estimated_tokens,
-            max_context: 32768, // Default, will be overridden
+            tool_tokens: 0,
+            max_context: default_max_context(), // Will be overridden
+            transcript: None,
+        }
+    }
+
+    /// Append new messages to `path` as they are added
+    pub fn tee_to(&mut self, path: PathBuf) {
+        self.transcript = Some(path);
+    }
+
+    fn transcribe(&self, text: &str) {
+        if let Some(path) = &self.transcript {
+            // A transcript that cannot be written must not stop the session
+            let _ = super::transcript::append(path, text);
+        }
+    }
+
+    fn push(&mut self, message: Message) {
+        self.times.resize(self.messages.len(), None);
+        self.messages.push(message);
+        self.times.push(Some(Utc::now()));
+    }
+
+    pub fn time_of(&self, index: usize) -> Option<DateTime<Utc>> {
+        self.times.get(index).copied().flatten()
+    }
+
+    /// How long message `index` took after the one before it: generation
+    /// for a reply, the model's turn and the tool run for a tool result.
+    /// None for prompts, which wait on the user.
+    pub fn took(&self, index: usize) -> Option<chrono::Duration> {
+        let message = self.messages.get(index)?;
+        if message.role == "user" && tool_result(message).is_none() {
+            return None;
         }
+        Some(self.time_of(index)? - self.time_of(index.checked_sub(1)?)?)
+    }
+
+    /// When message `index` was added, with how long it took if known, for
+    /// exports and the TUI
+    pub fn timing(&self, index: usize) -> Option<String> {
+        let time = self.time_of(index)?.with_timezone(&chrono::Local).format("%H:%M:%S");
+        Some(match self.took(index) {
+            Some(took) => format!("{} · {}", time, format_took(took)),
+            None => time.to_string(),
+        })
+    }
+
+    /// Replace everything between the system prompt and the last `keep`
+    /// messages with `summary`, keeping the times of what remains
+    pub fn replace_with_summary(&mut self, summary: Message, keep: usize) {
+        self.times.resize(self.messages.len(), None);
+        let older = 1..self.messages.len().saturating_sub(keep).max(1);
+        self.messages.splice(older.clone(), [summary]);
+        self.times.splice(older, [Some(Utc::now())]);
+    }
+
+    /// Re-estimate token usage from scratch (e.g. after loading from disk)
+    pub fn recalculate_tokens(&mut self) {
+        self.estimated_tokens = estimate_messages(&self.messages);
     }
 
     pub fn set_max_context(&mut self, max_context: usize) {
         self.max_context = max_context;
     }
 
+    /// Use an agent's system prompt instead of the default one
+    pub fn set_system_prompt(&mut self, prompt: String) {
+        self.messages[0].content = prompt;
+        self.recalculate_tokens();
+    }
+
+    /// Count the tool definitions requests carry, none when they are sent
+    /// without tools. `fit_to_context` keeps this current for each request.
+    pub fn set_tools(&mut self, tools: Option<&[serde_json::Value]>) {
+        self.tool_tokens = tools.map_or(0, |tools| estimate_tokens(&serde_json::to_string(tools).unwrap_or_default()));
+    }
+
+    /// Tokens a request takes: the messages and the tool definitions
+    pub fn context_tokens(&self) -> usize {
+        self.estimated_tokens + self.tool_tokens
+    }
+
     pub fn get_context_usage(&self) -> (usize, usize, f32) {
         // Returns (used, max, percentage)
-        let percentage = (self.estimated_tokens as f32 / self.max_context as f32) * 100.0;
-        (self.estimated_tokens, self.max_context, percentage)
+        let used = self.context_tokens();
+        let percentage = (used as f32 / self.max_context as f32) * 100.0;
+        (used, self.max_context, percentage)
     }
 
     pub fn add_user_message(&mut self, content: String) {
+        self.transcribe(&super::transcript::entry("user", &content, None));
         self.estimated_tokens += estimate_tokens(&content);
-        self.messages.push(Message {
+        self.push(Message {
             role: "user".to_string(),
             content,
         });
     }
 
     pub fn add_assistant_message(&mut self, content: String) {
+        self.transcribe(&super::transcript::entry("assistant", &content, None));
         self.estimated_tokens += estimate_tokens(&content);
-        self.messages.push(Message {
+        self.push(Message {
             role: "assistant".to_string(),
             content,
         });
     }
 
     pub fn add_tool_result(&mut self, tool_name: &str, result: &str) {
+        self.transcribe(&super::transcript::entry("user", result, Some(tool_name)));
+        let result = match self.holds_result(tool_name, result) {
+            true => format!(
+                "{}\n\n[Unchanged: this {} result is already in the conversation above, word for word]",
+                result.lines().next().unwrap_or_default(),
+                tool_name
+            ),
+            false => result.to_string(),
+        };
         // Add tool results as user messages since many models don't support "tool" role
-        let content = format!("Tool execution result:\nTool: {}\nResult:\n{}", tool_name, result);
+        let content = format!("{}Tool: {}\nResult:\n{}", TOOL_RESULT_PREFIX, tool_name, result);
         self.estimated_tokens += estimate_tokens(&content);
-        self.messages.push(Message {
+        self.push(Message {
             role: "user".to_string(),
             content,
         });
     }
 
+    /// Whether a read-only tool already returned exactly `result` and it
+    /// has not been summarized or dropped since
+    fn holds_result(&self, tool_name: &str, result: &str) -> bool {
+        result.len() >= MIN_REPEATED
+            && super::tools::registry().get(tool_name).is_some_and(|tool| tool.idempotent())
+            && self.messages.iter().any(|message| tool_result(message) == Some((tool_name, result)))
+    }
+
     /// Check if compaction is needed (at 75% capacity)
     pub fn needs_compaction(&self) -> bool {
-        self.estimated_tokens > (self.max_context * 3 / 4)
+        self.context_tokens() > (self.max_context * 3 / 4)
     }
 
     /// Compact the conversation by summarizing older messages
@@ -76,72 +218,113 @@ impl Conversation {
         if !self.needs_compaction() {
             return Ok(false);
         }
+        self.compact(client).await
+    }
 
-        // Keep system prompt (index 0) and last 10 messages
-        // Summarize everything in between
-        if self.messages.len() <= 11 {
-            // Not enough to compact
-            return Ok(false);
+    /// Make sure the next request fits in the context window, keeping room
+    /// for the reply, instead of letting llama-server truncate the prompt.
+    /// Old tool results are dropped first, oldest first; if that is not
+    /// enough the history is summarized. Returns a notice saying what changed.
+    pub async fn fit_to_context(
+        &mut self,
+        client: &super::client::LlamaClient,
+        tools: Option<&[serde_json::Value]>,
+    ) -> Result<Option<String>> {
+        let (limit, reserve) = match client.context_budget() {
+            Some((limit, reserve)) => {
+                self.max_context = limit;
+                (limit, reserve)
+            }
+            None => (self.max_context, DEFAULT_COMPLETION_RESERVE),
+        };
+        // Measured per request: the tool set, and whether it travels as
+        // definitions or as prompt text, can change between requests
+        self.tool_tokens = client.tool_tokens(tools);
+        let tool_tokens = self.tool_tokens;
+        let budget = limit.saturating_sub(reserve.min(limit / 4) + tool_tokens);
+        if self.estimated_tokens <= budget {
+            return Ok(None);
         }
 
-        let system_msg = self.messages[0].clone();
-        let messages_to_compact: Vec<_> = self.messages.iter()
-            .skip(1)
-            .take(self.messages.len() - 11)
-            .cloned()
-            .collect();
-        let recent_messages: Vec<_> = self.messages.iter()
-            .skip(self.messages.len() - 10)
-            .cloned()
-            .collect();
-
-        // Create summarization prompt
-        let conversation_text = messages_to_compact.iter()
-            .map(|m| format!("{}: {}", m.role, m.content))
-            .collect::<Vec<_>>()
-            .join("\n\n");
+        let dropped = drop_tool_results(&mut self.messages, &mut self.estimated_tokens, budget, KEEP_RECENT);
+        let compacted = self.estimated_tokens > budget && self.compact(client).await?;
 
-        let summary_prompt = format!(
-            "Summarize the following conversation history concisely, preserving key facts, decisions, and context. Focus on:\n\
-            - Important technical details and decisions\n\
-            - File modifications and their purposes\n\
-            - Commands executed and their results\n\
-            - Any errors or issues encountered\n\n\
-            Conversation:\n{}\n\n\
-            Provide a concise summary in 2-3 paragraphs:",
-            conversation_text
-        );
-
-        // Get summary from LLM
-        let response = client.chat_completion(vec![
-            Message {
-                role: "user".to_string(),
-                content: summary_prompt,
+        let mut actions = vec![];
+        if dropped > 0 {
+            actions.push(format!("dropped {} old tool result{}", dropped, if dropped == 1 { "" } else { "s" }));
+        }
+        if compacted {
+            actions.push("summarized older messages".to_string());
+        }
+        let mut notice = if actions.is_empty() {
+            String::new()
+        } else {
+            format!("✂️  {} to fit the {}-token context", actions.join(" and "), limit)
+        };
+        if self.estimated_tokens > budget {
+            if !notice.is_empty() {
+                notice.push_str("; ");
             }
-        ], None).await?;
+            notice.push_str(&format!(
+                "⚠️  the conversation (~{} tokens) still exceeds the context window and may be truncated",
+                self.estimated_tokens + tool_tokens
+            ));
+        }
+        Ok(Some(notice))
+    }
+
+    /// Summarize everything but the system prompt and the latest messages,
+    /// in place. Returns false when there is too little to summarize.
+    pub async fn compact(&mut self, client: &super::client::LlamaClient) -> Result<bool> {
+        let Some(compaction) = self.compaction() else {
+            return Ok(false);
+        };
+        let summary = compaction.summarize(client).await?;
+        Ok(self.apply_compaction(&compaction, summary))
+    }
 
-        let summary_response = response.choices[0].message.content.clone()
-            .unwrap_or_default();
+    /// The messages `compact` would summarize: all but the system prompt
+    /// and the last 10. None when there are too few. Only the text the
+    /// summary request needs is copied.
+    pub fn compaction(&self) -> Option<Compaction> {
+        if self.messages.len() <= KEEP_COMPACTED + 1 {
+            return None;
+        }
+        let older = &self.messages[1..self.messages.len() - KEEP_COMPACTED];
+        Some(Compaction {
+            count: older.len(),
+            digest: digest(older),
+            text: older
+                .iter()
+                .map(|m| format!("{}: {}", m.role, m.content))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        })
+    }
 
-        // Rebuild conversation with summary
+    /// Replace the messages `compaction` was taken from with `summary`.
+    /// Messages added since stay; if the older ones changed meanwhile
+    /// (compacted or trimmed another way) nothing happens and false is
+    /// returned.
+    pub fn apply_compaction(&mut self, compaction: &Compaction, summary: String) -> bool {
+        let count = compaction.count;
+        if self.messages.get(1..count + 1).map(digest) != Some(compaction.digest) {
+            return false;
+        }
         let summary_msg = Message {
             role: "assistant".to_string(),
-            content: format!("[Conversation summary of {} messages]\n\n{}",
-                messages_to_compact.len(), summary_response),
+            content: format!("[Conversation summary of {} messages]\n\n{}", count, summary),
         };
 
-        // Recalculate tokens
-        self.estimated_tokens = estimate_tokens(&system_msg.content);
-        self.estimated_tokens += estimate_tokens(&summary_msg.content);
-        for msg in &recent_messages {
-            self.estimated_tokens += estimate_tokens(&msg.content);
-        }
-
-        // Rebuild messages
-        self.messages = vec![system_msg, summary_msg];
-        self.messages.extend(recent_messages);
+        self.transcribe(&format!(
+            "\n_[{} earlier messages summarized to fit the context; they remain above]_\n",
+            count
+        ));
 
-        Ok(true)
+        let keep = self.messages.len() - count - 1;
+        self.replace_with_summary(summary_msg, keep);
+        self.recalculate_tokens();
+        true
     }
 
     #[allow(dead_code)]
@@ -158,11 +341,65 @@ impl Conversation {
         Ok(conversation)
     }
 
-    pub fn get_messages(&self) -> Vec<Message> {
-        self.messages.clone()
+    pub fn get_messages(&self) -> &[Message] {
+        &self.messages
     }
 }
 
+/// Older messages taken out to be summarized, see `Conversation::compaction`.
+/// The summary can be written while the conversation goes on.
+pub struct Compaction {
+    count: usize,
+    /// Of the messages, to tell whether they are still there when the
+    /// summary arrives
+    digest: u64,
+    text: String,
+}
+
+impl Compaction {
+    pub async fn summarize(&self, client: &super::client::LlamaClient) -> Result<String> {
+        let conversation_text = &self.text;
+
+        let summary_prompt = format!(
+            "Summarize the following conversation history concisely, preserving key facts, decisions, and context. Focus on:\n\
+            - Important technical details and decisions\n\
+            - File modifications and their purposes\n\
+            - Commands executed and their results\n\
+            - Any errors or issues encountered\n\n\
+            Conversation:\n{}\n\n\
+            Provide a concise summary in 2-3 paragraphs:",
+            conversation_text
+        );
+
+        let response = client.summarize(&[
+            Message {
+                role: "user".to_string(),
+                content: summary_prompt,
+            }
+        ]).await?;
+
+        Ok(response.choices.into_iter().next()
+            .and_then(|choice| choice.message.content)
+            .unwrap_or_default())
+    }
+}
+
+fn digest(messages: &[Message]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for message in messages {
+        (&message.role, &message.content).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// The tool name and result of a message added by `add_tool_result`
+pub fn tool_result(message: &Message) -> Option<(&str, &str)> {
+    let rest = message.content.strip_prefix(TOOL_RESULT_PREFIX)?;
+    let (tool_line, result) = rest.split_once('\n').unwrap_or((rest, ""));
+    let name = tool_line.strip_prefix("Tool: ")?;
+    Some((name, result.strip_prefix("Result:\n").unwrap_or(result)))
+}
+
 impl Default for Conversation {
     fn default() -> Self {
         Self::new()
@@ -175,9 +412,22 @@ You have access to the following tools:
 - read_file: Read the contents of files
 - write_file: Create or overwrite files with new content
 - list_files: List files in a directory
+- workspace_stats: Lines of code per language, largest files and git churn hotspots
 - bash_exec: Execute bash commands
 - search_files: Search for patterns in files using grep
 - analyze_image: Analyze images (PNG, JPG, GIF, BMP, WebP) - describe contents, read text, analyze UI
+- clipboard_read / clipboard_write: Read what the user copied, or copy text for them
+- bash_background / background_output / background_stop: Run a dev server or watcher without blocking, check its output, stop it
+- list_processes / kill_process: Find and stop processes you started, such as dev servers
+- run_snippet: Run a short Python or Node program in a temporary directory
+- cargo_check / cargo_clippy / cargo_test: Build, lint and test Rust code with compact diagnostics
+- coverage_report: Per-file test coverage and uncovered line ranges (cargo llvm-cov, tarpaulin or nyc)
+- profile_run: Profile a command (perf, flamegraph) for its hottest functions, or time it with hyperfine
+- binary_info: Format, hardening, sections, imports, exports and largest functions of a binary
+- binary_strings: Strings in a binary with their offsets, filtered and paged
+- disassemble_function: Disassemble one function by name or address, with the functions it calls
+- sql_query: Query a SQLite file or a database configured in .vork.toml (read-only by default)
+- add_finding / list_findings / resolve_finding: Track audit findings across sessions in .vork/findings.json
 
 CRITICAL: All user requests are WORKSPACE-RELATIVE by default.
 - When user says "put it in /docs/", they mean "./docs/" (relative to current workspace)
@@ -208,8 +458,62 @@ When the user asks you to modify code:
 You should be proactive in using tools to help solve problems. Don't just suggest changes - actually make them using the available tools.
 "#;
 
+/// Replace tool results before the `keep_recent` newest messages with a
+/// placeholder, oldest first, until the estimate is within `budget`.
+/// Returns how many were dropped.
+fn drop_tool_results(messages: &mut [Message], estimated: &mut usize, budget: usize, keep_recent: usize) -> usize {
+    let mut dropped = 0;
+    let keep_from = messages.len().saturating_sub(keep_recent);
+    for message in messages.iter_mut().take(keep_from).skip(1) {
+        if *estimated <= budget {
+            break;
+        }
+        let Some(rest) = message.content.strip_prefix(TOOL_RESULT_PREFIX) else {
+            continue;
+        };
+        if rest.ends_with(DROPPED) {
+            continue;
+        }
+        let tool_line = rest.lines().next().unwrap_or("Tool: unknown");
+        let placeholder = format!("{}{}\nResult:\n{}", TOOL_RESULT_PREFIX, tool_line, DROPPED);
+        *estimated = *estimated + estimate_tokens(&placeholder) - estimate_tokens(&message.content);
+        message.content = placeholder;
+        dropped += 1;
+    }
+    dropped
+}
+
+/// Shrink a request the server rejected as too large: every tool result but
+/// the newest message goes first, then the oldest messages after the task.
+/// The system prompt and the first request are kept. Returns whether
+/// anything was removed.
+pub(super) fn trim_messages(messages: &mut Vec<Message>, budget: usize) -> bool {
+    let mut estimated = estimate_messages(messages);
+    let mut trimmed = drop_tool_results(messages, &mut estimated, budget, 1) > 0;
+    while estimated > budget && messages.len() > 3 {
+        estimated -= estimate_tokens(&messages.remove(2).content);
+        trimmed = true;
+    }
+    trimmed
+}
+
+/// A message's duration: tenths of a second below a minute
+pub fn format_took(took: chrono::Duration) -> String {
+    let millis = took.num_milliseconds().max(0);
+    if millis < 60_000 {
+        format!("{:.1}s", millis as f64 / 1000.0)
+    } else {
+        let secs = millis / 1000;
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+pub(super) fn estimate_messages(messages: &[Message]) -> usize {
+    messages.iter().map(|m| estimate_tokens(&m.content)).sum()
+}
+
 /// Estimate token count (rough approximation: 1 token ≈ 4 characters)
-fn estimate_tokens(text: &str) -> usize {
+pub(super) fn estimate_tokens(text: &str) -> usize {
     // More accurate estimation considering:
     // - ~4 chars per token on average
     // - Extra tokens for formatting, role markers, etc.
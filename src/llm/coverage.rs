@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+/// Printed between the test run's output and the LCOV data
+const MARKER: &str = "@@VORK_LCOV";
+
+/// Uncovered ranges listed per file; the rest are only counted
+const MAX_RANGES: usize = 15;
+
+/// Finds which coverage tool fits the project: prints llvm-cov, tarpaulin,
+/// nyc, or what is missing
+pub const DETECT_SCRIPT: &str = "if [ -f Cargo.toml ]; then \
+    if cargo llvm-cov --version >/dev/null 2>&1; then echo llvm-cov; \
+    elif cargo tarpaulin --version >/dev/null 2>&1; then echo tarpaulin; \
+    else echo missing-rust; fi; \
+    elif [ -f package.json ]; then echo nyc; \
+    else echo missing-project; fi";
+
+/// The command that runs the tests under `tool` and writes LCOV to
+/// "$dir/lcov.info". Tests failing still produce a report.
+pub fn command(tool: &str, package: Option<&str>, test_command: &str) -> Option<String> {
+    let package = package.map(|package| format!(" -p {}", super::remote::shell_quote(package))).unwrap_or_default();
+    match tool {
+        "llvm-cov" => Some(format!("cargo llvm-cov --lcov --output-path \"$dir/lcov.info\" --ignore-run-fail{}", package)),
+        "tarpaulin" => Some(format!("cargo tarpaulin --out Lcov --output-dir \"$dir\" --no-fail-fast --skip-clean{}", package)),
+        "nyc" => Some(format!(
+            "npx --no-install nyc --reporter=lcov --report-dir \"$dir\" --temp-dir \"$dir/.nyc_output\" {}",
+            test_command
+        )),
+        _ => None,
+    }
+}
+
+/// Wrap a `command` so the LCOV it writes follows its own output on stdout,
+/// which works the same locally, in a container or over SSH
+pub fn script(command: &str) -> String {
+    format!(
+        "dir=$(mktemp -d) || exit 1; trap 'rm -rf \"$dir\"' EXIT; {}; status=$?; echo; echo \"{} $status $PWD\"; \
+         cat \"$dir/lcov.info\" 2>/dev/null; exit $status",
+        command, MARKER
+    )
+}
+
+/// Coverage of one source file
+struct FileCoverage {
+    path: String,
+    /// Instrumented lines and their hit counts
+    lines: BTreeMap<usize, u64>,
+}
+
+impl FileCoverage {
+    fn covered(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+
+    fn percent(&self) -> f64 {
+        if self.lines.is_empty() {
+            100.0
+        } else {
+            self.covered() as f64 * 100.0 / self.lines.len() as f64
+        }
+    }
+
+    /// Runs of uncovered lines, merged across lines that are not
+    /// instrumented (blank lines, comments, braces)
+    fn uncovered_ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = vec![];
+        let mut previous_uncovered = false;
+        for (&line, &hits) in &self.lines {
+            if hits > 0 {
+                previous_uncovered = false;
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if previous_uncovered => range.1 = line,
+                _ => ranges.push((line, line)),
+            }
+            previous_uncovered = true;
+        }
+        ranges
+    }
+}
+
+/// Split the output of `script` into the test run's own output and the
+/// LCOV data with the directory the paths are relative to
+pub fn split_output(stdout: &str) -> Option<(&str, &str, &str)> {
+    let start = stdout.rfind(MARKER)?;
+    let (run, rest) = stdout.split_at(start);
+    let (header, lcov) = rest.split_once('\n').unwrap_or((rest, ""));
+    let directory = header.splitn(3, ' ').nth(2).unwrap_or("").trim();
+    Some((run, directory, lcov))
+}
+
+/// Per-file coverage from LCOV, least covered first, with the uncovered
+/// line ranges; files outside `filter` and fully covered files are left out
+pub fn summarize(tool: &str, lcov: &str, root: &str, filter: Option<&str>, max_files: usize) -> String {
+    let mut files: Vec<FileCoverage> = vec![];
+    let mut current: Option<FileCoverage> = None;
+    for line in lcov.lines() {
+        if let Some(path) = line.strip_prefix("SF:") {
+            let path = path.strip_prefix(root).map(|p| p.trim_start_matches('/')).unwrap_or(path);
+            current = Some(FileCoverage { path: path.to_string(), lines: BTreeMap::new() });
+        } else if let Some(data) = line.strip_prefix("DA:") {
+            let mut fields = data.split(',');
+            let (Some(number), Some(hits)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            if let (Some(file), Ok(number), Ok(hits)) = (current.as_mut(), number.parse(), hits.parse::<u64>()) {
+                // The same line can be reported once per monomorphization
+                *file.lines.entry(number).or_insert(0) += hits;
+            }
+        } else if line == "end_of_record" {
+            files.extend(current.take());
+        }
+    }
+    files.extend(current);
+
+    // Merge records for the same file (tarpaulin and nyc can repeat them)
+    let mut merged: BTreeMap<String, FileCoverage> = BTreeMap::new();
+    for file in files {
+        let entry = merged
+            .entry(file.path.clone())
+            .or_insert_with(|| FileCoverage { path: file.path.clone(), lines: BTreeMap::new() });
+        for (line, hits) in file.lines {
+            *entry.lines.entry(line).or_insert(0) += hits;
+        }
+    }
+    let mut files: Vec<FileCoverage> = merged
+        .into_values()
+        .filter(|file| filter.is_none_or(|filter| file.path.starts_with(filter.trim_start_matches("./"))))
+        .collect();
+    if files.is_empty() {
+        return match filter {
+            Some(filter) => format!("ℹ️  No coverage data for files under {}", filter),
+            None => "ℹ️  The coverage report has no files".to_string(),
+        };
+    }
+
+    let total: usize = files.iter().map(|file| file.lines.len()).sum();
+    let covered: usize = files.iter().map(FileCoverage::covered).sum();
+    let percent = if total == 0 { 100.0 } else { covered as f64 * 100.0 / total as f64 };
+    files.sort_by(|a, b| a.percent().total_cmp(&b.percent()).then(b.lines.len().cmp(&a.lines.len())));
+    let (partial, complete): (Vec<&FileCoverage>, Vec<&FileCoverage>) =
+        files.iter().partition(|file| file.covered() < file.lines.len());
+    let mut text = format!(
+        "🧪 Coverage ({}): {:.1}% ({}/{} lines) in {} file{}",
+        tool,
+        percent,
+        covered,
+        total,
+        files.len(),
+        if files.len() == 1 { "" } else { "s" }
+    );
+    if !partial.is_empty() {
+        text.push('\n');
+    }
+    for file in partial.iter().take(max_files) {
+        let ranges = file.uncovered_ranges();
+        let mut shown: Vec<String> = ranges
+            .iter()
+            .take(MAX_RANGES)
+            .map(|(start, end)| if start == end { start.to_string() } else { format!("{}-{}", start, end) })
+            .collect();
+        if ranges.len() > MAX_RANGES {
+            shown.push(format!("and {} more", ranges.len() - MAX_RANGES));
+        }
+        text.push_str(&format!(
+            "\n{:>5.1}% {:>5}/{:<5} {}\n       uncovered: {}",
+            file.percent(),
+            file.covered(),
+            file.lines.len(),
+            file.path,
+            shown.join(", ")
+        ));
+    }
+    if partial.len() > max_files {
+        text.push_str(&format!("\n\n[{} more partly covered files not shown]", partial.len() - max_files));
+    }
+    if !complete.is_empty() {
+        let complete = complete.len();
+        text.push_str(&format!("\n\n{} file{} fully covered", complete, if complete == 1 { "" } else { "s" }));
+    }
+    text
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::config::{Config, ProjectConfig};
+
+/// Parts of a variable name that mark it as a credential
+const SECRET_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "AUTH", "COOKIE", "SESSION", "PRIVATE"];
+
+/// Give a model-run command the allowlisted part of vork's environment plus
+/// the project's `[env]` from .vork.toml, and nothing else
+pub fn apply<'a>(command: &'a mut Command, working_dir: &Path) -> Result<&'a mut Command> {
+    apply_with(command, working_dir, allowlist())
+}
+
+/// `apply` with an explicit allowlist instead of the configured one
+fn apply_with<'a>(command: &'a mut Command, working_dir: &Path, allowlist: &[String]) -> Result<&'a mut Command> {
+    let project = ProjectConfig::load(working_dir)?;
+    command.env_clear();
+    command.envs(env::vars_os().filter(|(name, _)| name.to_str().is_some_and(|name| inherited(name, allowlist))));
+    command.envs(&project.env);
+    Ok(command)
+}
+
+fn allowlist() -> &'static [String] {
+    static ALLOWLIST: OnceLock<Vec<String>> = OnceLock::new();
+    ALLOWLIST.get_or_init(|| {
+        Config::load()
+            .map(|config| config.tools.env_allowlist)
+            .unwrap_or_else(|_| crate::config::ToolsConfig::default().env_allowlist)
+    })
+}
+
+/// Allowlisted, and either not secret-looking or listed by its exact name
+fn inherited(name: &str, allowlist: &[String]) -> bool {
+    if allowlist.iter().any(|pattern| pattern == name) {
+        return true;
+    }
+    let upper = name.to_uppercase();
+    allowlist.iter().any(|pattern| pattern.strip_suffix('*').is_some_and(|prefix| name.starts_with(prefix)))
+        && !SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|pattern| pattern.to_string()).collect()
+    }
+
+    #[test]
+    fn names_match_exactly_or_by_prefix() {
+        let allowlist = allowlist(&["PATH", "LC_*"]);
+        assert!(inherited("PATH", &allowlist));
+        assert!(!inherited("PATHEXT", &allowlist));
+        assert!(!inherited("path", &allowlist));
+        assert!(inherited("LC_ALL", &allowlist));
+        assert!(inherited("LC_", &allowlist));
+        assert!(!inherited("LC", &allowlist));
+        assert!(!inherited("HOME", &allowlist));
+    }
+
+    #[test]
+    fn secrets_need_their_exact_name() {
+        let allowlist = allowlist(&["AWS_*", "GITHUB_TOKEN", "*"]);
+        assert!(inherited("AWS_REGION", &allowlist));
+        assert!(!inherited("AWS_SECRET_ACCESS_KEY", &allowlist));
+        assert!(!inherited("aws_session_token", &allowlist));
+        assert!(!inherited("OPENAI_API_KEY", &allowlist));
+        assert!(inherited("GITHUB_TOKEN", &allowlist));
+        assert!(inherited("EDITOR", &allowlist));
+    }
+
+    #[test]
+    fn only_the_allowlist_and_project_env_are_passed_on() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(ProjectConfig::FILE_NAME), "[env]\nRUST_LOG = \"debug\"\n").unwrap();
+        let mut command = Command::new("env");
+        command.env("VORK_TEST_LEFTOVER", "1");
+        let allowlist = allowlist(&["PATH", "LC_*"]);
+        apply_with(&mut command, dir.path(), &allowlist).unwrap();
+
+        let names: Vec<String> = command.get_envs().filter_map(|(name, _)| name.to_str().map(String::from)).collect();
+        assert!(names.contains(&"RUST_LOG".to_string()));
+        assert!(!names.contains(&"VORK_TEST_LEFTOVER".to_string()));
+        assert!(names.iter().all(|name| name == "RUST_LOG" || name == "PATH" || name.starts_with("LC_")));
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where findings are kept, relative to the workspace
+pub const FINDINGS_FILE: &str = ".vork/findings.json";
+
+/// Severities from most to least serious, as in `vork audit`
+pub const SEVERITIES: &[&str] = &["error", "warning", "note"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Open,
+    Resolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub id: u64,
+    pub file: String,
+    #[serde(default)]
+    pub line: Option<usize>,
+    pub severity: String,
+    /// Category such as stub, unwrap, security or a CWE id
+    pub rule: String,
+    pub message: String,
+    pub status: Status,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// How many times audits have reported it
+    #[serde(default = "default_times_seen")]
+    pub times_seen: usize,
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub resolution: Option<String>,
+    /// The issue it was exported to, so it is not exported twice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+}
+
+fn default_times_seen() -> usize {
+    1
+}
+
+impl Finding {
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.file, line),
+            None => self.file.clone(),
+        }
+    }
+
+    /// One line for lists: id, severity, location, rule and message
+    pub fn summary(&self) -> String {
+        format!("#{} {} {} [{}] {}", self.id, self.severity, self.location(), self.rule, self.message)
+    }
+}
+
+/// What `add` did with a reported issue
+pub enum Added {
+    New,
+    /// Already open; its line and message were refreshed
+    Known,
+    /// Was resolved, but has been reported again
+    Reopened,
+}
+
+/// An issue as reported by an auditor, before it is stored
+pub struct Report<'a> {
+    pub file: &'a str,
+    pub line: Option<usize>,
+    pub severity: &'a str,
+    pub rule: &'a str,
+    pub message: &'a str,
+}
+
+/// The workspace's findings, loaded from and saved to .vork/findings.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Findings {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+}
+
+impl Findings {
+    pub fn load(working_dir: &Path) -> Result<Self> {
+        let path = working_dir.join(FINDINGS_FILE);
+        let mut findings = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str::<Self>(&text).with_context(|| format!("Failed to parse {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        findings.path = path;
+        Ok(findings)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        // Written whole and renamed, so a crash never leaves half a file
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(self)?).with_context(|| format!("Failed to write {}", temp.display()))?;
+        fs::rename(&temp, &self.path).with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Record an issue, merging it into an existing finding for the same file
+    /// and rule at the same line or with the same message
+    pub fn add(&mut self, report: Report) -> (u64, Added) {
+        let file = report.file.trim_start_matches("./");
+        let rule = report.rule.trim().to_lowercase();
+        let message = report.message.trim();
+        let now = Utc::now();
+        let existing = self.findings.iter_mut().find(|finding| {
+            finding.file == file
+                && finding.rule == rule
+                && ((report.line.is_some() && finding.line == report.line) || same_message(&finding.message, message))
+        });
+        if let Some(finding) = existing {
+            let added = match finding.status {
+                Status::Open => Added::Known,
+                Status::Resolved => Added::Reopened,
+            };
+            finding.status = Status::Open;
+            finding.resolved_at = None;
+            finding.resolution = None;
+            finding.line = report.line.or(finding.line);
+            finding.message = message.to_string();
+            finding.severity = severity(report.severity).to_string();
+            finding.last_seen = now;
+            finding.times_seen += 1;
+            return (finding.id, added);
+        }
+
+        self.next_id = self.next_id.max(self.findings.iter().map(|f| f.id).max().unwrap_or(0)) + 1;
+        self.findings.push(Finding {
+            id: self.next_id,
+            file: file.to_string(),
+            line: report.line,
+            severity: severity(report.severity).to_string(),
+            rule: if rule.is_empty() { "quality".to_string() } else { rule },
+            message: message.to_string(),
+            status: Status::Open,
+            first_seen: now,
+            last_seen: now,
+            times_seen: 1,
+            resolved_at: None,
+            resolution: None,
+            issue: None,
+        });
+        (self.next_id, Added::New)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Finding> {
+        self.findings.iter().find(|finding| finding.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Finding> {
+        self.findings.iter_mut().find(|finding| finding.id == id)
+    }
+
+    pub fn resolve(&mut self, id: u64, note: Option<&str>) -> Result<&Finding> {
+        let finding = self.get_mut(id).ok_or_else(|| anyhow::anyhow!("No finding #{}", id))?;
+        if finding.status == Status::Resolved {
+            anyhow::bail!("Finding #{} is already resolved", id);
+        }
+        finding.status = Status::Resolved;
+        finding.resolved_at = Some(Utc::now());
+        finding.resolution = note.map(str::trim).filter(|note| !note.is_empty()).map(str::to_string);
+        Ok(finding)
+    }
+
+    pub fn reopen(&mut self, id: u64) -> Result<&Finding> {
+        let finding = self.get_mut(id).ok_or_else(|| anyhow::anyhow!("No finding #{}", id))?;
+        if finding.status == Status::Open {
+            anyhow::bail!("Finding #{} is already open", id);
+        }
+        finding.status = Status::Open;
+        finding.resolved_at = None;
+        finding.resolution = None;
+        Ok(finding)
+    }
+
+    /// Findings with the given status (any when None), in files starting
+    /// with `file`, at `severity` or worse; most severe first
+    pub fn list(&self, status: Option<Status>, file: Option<&str>, severity: Option<&str>) -> Vec<&Finding> {
+        let threshold = severity.map(rank);
+        let mut list: Vec<&Finding> = self
+            .findings
+            .iter()
+            .filter(|f| status.is_none_or(|status| f.status == status))
+            .filter(|f| file.is_none_or(|file| f.file.starts_with(file.trim_start_matches("./"))))
+            .filter(|f| threshold.is_none_or(|threshold| rank(&f.severity) <= threshold))
+            .collect();
+        list.sort_by(|a, b| rank(&a.severity).cmp(&rank(&b.severity)).then_with(|| (&a.file, a.line).cmp(&(&b.file, b.line))));
+        list
+    }
+}
+
+/// Map the scales auditors use onto error, warning and note
+pub fn severity(level: &str) -> &'static str {
+    match level.trim().to_lowercase().as_str() {
+        "error" | "critical" | "high" => "error",
+        "note" | "low" | "info" => "note",
+        _ => "warning",
+    }
+}
+
+fn rank(level: &str) -> usize {
+    let level = severity(level);
+    SEVERITIES.iter().position(|l| *l == level).unwrap_or(1)
+}
+
+/// Equal ignoring case, whitespace and trailing punctuation
+fn same_message(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end_matches(['.', '!'])
+            .to_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use super::conversation::tool_result;
+use super::Session;
+use crate::config::Config;
+
+/// Entries kept in ~/.vork/input_history, oldest dropped first
+const MAX_INPUTS: usize = 1000;
+
+/// A prompt typed in an earlier session
+pub struct PastPrompt {
+    pub text: String,
+    pub session_id: String,
+    pub working_directory: PathBuf,
+    /// When the session was last used
+    pub time: DateTime<Utc>,
+}
+
+/// Every prompt from saved sessions, newest first, each text once
+pub fn past_prompts() -> Result<Vec<PastPrompt>> {
+    let mut seen = HashSet::new();
+    let mut prompts = vec![];
+    for session in Session::list_sessions()? {
+        let texts: Vec<&str> = session
+            .conversation
+            .messages
+            .iter()
+            .filter(|m| m.role == "user" && tool_result(m).is_none())
+            .map(|m| m.content.trim())
+            .filter(|text| !text.is_empty())
+            .collect();
+        for text in texts.into_iter().rev() {
+            if seen.insert(text.to_string()) {
+                prompts.push(PastPrompt {
+                    text: text.to_string(),
+                    session_id: session.id.clone(),
+                    working_directory: session.working_directory.clone(),
+                    time: session.updated_at,
+                });
+            }
+        }
+    }
+    Ok(prompts)
+}
+
+/// Whether `text` contains every word of `query`, ignoring case
+pub fn matches(text: &str, query: &str) -> bool {
+    let text = text.to_lowercase();
+    query.to_lowercase().split_whitespace().all(|word| text.contains(word))
+}
+
+/// The prompts matching `query`
+pub fn search<'a>(prompts: &'a [PastPrompt], query: &str) -> Vec<&'a PastPrompt> {
+    prompts.iter().filter(|prompt| matches(&prompt.text, query)).collect()
+}
+
+fn inputs_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("input_history"))
+}
+
+/// What was typed into the TUI in earlier runs, oldest first. Entries are
+/// stored one per line as JSON strings so multi-line prompts survive.
+pub fn load_inputs() -> Vec<String> {
+    let Ok(text) = inputs_path().and_then(|path| Ok(fs::read_to_string(path)?)) else {
+        return vec![];
+    };
+    text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+fn push_input(inputs: &mut Vec<String>, input: &str) {
+    inputs.retain(|entry| entry != input);
+    inputs.push(input.to_string());
+    if inputs.len() > MAX_INPUTS {
+        inputs.drain(..inputs.len() - MAX_INPUTS);
+    }
+}
+
+/// Make `input` the newest entry, dropping an earlier copy, and save it.
+/// The file is re-read first so other running TUIs keep their entries.
+pub fn remember_input(inputs: &mut Vec<String>, input: &str) -> Result<()> {
+    push_input(inputs, input);
+    let mut saved = load_inputs();
+    push_input(&mut saved, input);
+
+    let path = inputs_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut text = String::new();
+    for entry in &saved {
+        text.push_str(&serde_json::to_string(entry)?);
+        text.push('\n');
+    }
+    fs::write(path, text)?;
+    Ok(())
+}
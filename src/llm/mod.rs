@@ -4,9 +4,33 @@ pub mod conversation;
 pub mod session;
 pub mod approval;
 pub mod server;
+pub mod stats;
+pub mod router;
+pub mod remote;
+pub mod container;
+pub mod queue;
+pub mod remote_approval;
+pub mod planner;
+pub mod prompted_tools;
+pub mod arguments;
+pub mod processes;
+pub mod background;
+pub mod environment;
+pub mod sql;
+pub mod cargo;
+pub mod workspace;
+pub mod findings;
+pub mod coverage;
+pub mod profile;
+pub mod binary;
+pub mod transcript;
+pub mod history;
+pub mod templates;
+pub mod outline;
 
 pub use client::LlamaClient;
 pub use conversation::Conversation;
 pub use session::Session;
 pub use approval::ApprovalSystem;
 pub use server::ServerManager;
+pub use stats::UsageTracker;
@@ -0,0 +1,149 @@
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::{ImplItem, Item, TraitItem};
+
+/// A named item in a source file, with its doc comments and attributes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    /// `name`, or `Type::name` for methods and items in inline modules
+    pub path: String,
+    /// 1-based and inclusive
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The items in `source`, for languages parsed here (Rust). None for other
+/// languages and for files that do not parse.
+pub fn outline(path: &str, source: &str) -> Option<Vec<Symbol>> {
+    if Path::new(path).extension().and_then(|e| e.to_str()) != Some("rs") {
+        return None;
+    }
+    let file = syn::parse_file(source).ok()?;
+    let mut symbols = vec![];
+    collect(&file.items, "", &mut symbols);
+    Some(symbols)
+}
+
+/// The symbol `name` refers to: an exact path first, then one whose path
+/// ends with it, e.g. `parse` or `Parser::parse` for `nested::Parser::parse`
+pub fn find<'a>(symbols: &'a [Symbol], name: &str) -> Option<&'a Symbol> {
+    symbols
+        .iter()
+        .find(|symbol| symbol.path == name)
+        .or_else(|| symbols.iter().find(|symbol| symbol.path.ends_with(&format!("::{}", name))))
+}
+
+fn collect(items: &[Item], prefix: &str, symbols: &mut Vec<Symbol>) {
+    let mut add = |name: String, spanned: &dyn Spanned| {
+        let span = spanned.span();
+        symbols.push(Symbol { path: format!("{}{}", prefix, name), start: span.start().line, end: span.end().line });
+    };
+    let mut modules = vec![];
+    for item in items {
+        match item {
+            Item::Fn(item) => add(item.sig.ident.to_string(), item),
+            Item::Struct(item) => add(item.ident.to_string(), item),
+            Item::Enum(item) => add(item.ident.to_string(), item),
+            Item::Union(item) => add(item.ident.to_string(), item),
+            Item::Type(item) => add(item.ident.to_string(), item),
+            Item::Const(item) => add(item.ident.to_string(), item),
+            Item::Static(item) => add(item.ident.to_string(), item),
+            Item::Macro(item) => {
+                if let Some(ident) = &item.ident {
+                    add(ident.to_string(), item);
+                }
+            }
+            Item::Trait(item) => {
+                let name = item.ident.to_string();
+                add(name.clone(), item);
+                for member in &item.items {
+                    if let TraitItem::Fn(method) = member {
+                        add(format!("{}::{}", name, method.sig.ident), method);
+                    }
+                }
+            }
+            Item::Impl(item) => {
+                let syn::Type::Path(ty) = item.self_ty.as_ref() else { continue };
+                let Some(name) = ty.path.segments.last().map(|segment| segment.ident.to_string()) else {
+                    continue;
+                };
+                add(name.clone(), item);
+                for member in &item.items {
+                    match member {
+                        ImplItem::Fn(method) => add(format!("{}::{}", name, method.sig.ident), method),
+                        ImplItem::Const(constant) => add(format!("{}::{}", name, constant.ident), constant),
+                        _ => {}
+                    }
+                }
+            }
+            Item::Mod(item) => {
+                add(item.ident.to_string(), item);
+                if let Some((_, items)) = &item.content {
+                    modules.push((format!("{}{}::", prefix, item.ident), items));
+                }
+            }
+            _ => {}
+        }
+    }
+    for (prefix, items) in modules {
+        collect(items, &prefix, symbols);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+use std::fmt;
+
+/// Reads tokens
+pub struct Parser<'a> {
+    text: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    // fn fake() in a comment
+    pub fn parse(&self) -> &str {
+        let brace = \"}\";
+        brace
+    }
+}
+
+impl fmt::Display for Parser<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, \"parser\")
+    }
+}
+
+mod nested {
+    pub fn parse() {}
+}
+
+macro_rules! shout {
+    ($e:expr) => {};
+}
+";
+
+    fn range(name: &str) -> Option<(usize, usize)> {
+        let symbols = outline("src/parser.rs", SOURCE).unwrap();
+        find(&symbols, name).map(|symbol| (symbol.start, symbol.end))
+    }
+
+    #[test]
+    fn items_and_methods_with_their_docs() {
+        assert_eq!(range("Parser"), Some((3, 6)));
+        assert_eq!(range("Parser::parse"), Some((10, 13)));
+        assert_eq!(range("parse"), Some((10, 13)));
+        assert_eq!(range("fmt"), Some((17, 19)));
+        assert_eq!(range("nested::parse"), Some((23, 23)));
+        assert_eq!(range("shout"), Some((26, 28)));
+        assert_eq!(range("fake"), None);
+    }
+
+    #[test]
+    fn other_languages_and_broken_files_have_no_outline() {
+        assert_eq!(outline("app.py", "def main():\n    pass\n"), None);
+        assert_eq!(outline("src/lib.rs", "fn broken( {"), None);
+    }
+}
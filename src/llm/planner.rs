@@ -0,0 +1,35 @@
+use anyhow::{Context, Result};
+
+use super::client::{ChatCompletionResponse, LlamaClient, Message};
+
+/// Sent without any tools, so the model can only describe what it would do
+const PLAN_REQUEST: &str = "Before doing anything, write a plan for this task. Do not call tools yet. \
+Reply with a short numbered list, one step per line, starting each step with [read], [write] or [run] \
+and naming the files or commands involved. Mark anything that deletes or overwrites data with (destructive).";
+
+/// Follows the plan in the conversation once someone approves it
+pub const PLAN_APPROVED: &str = "The plan is approved. Carry it out now, step by step.";
+
+/// Ask for a plan of the conversation's latest request. The planning
+/// instruction is not kept in the conversation; callers add the plan itself.
+pub async fn plan(client: &LlamaClient, messages: &[Message]) -> Result<(String, ChatCompletionResponse)> {
+    let mut messages = messages.to_vec();
+    messages.push(Message {
+        role: "user".to_string(),
+        content: PLAN_REQUEST.to_string(),
+    });
+    let response = client.chat_completion(&messages, None).await.context("Failed to get a plan")?;
+    let plan = response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.content.clone())
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty())
+        .context("The model returned an empty plan")?;
+    Ok((plan, response))
+}
+
+/// Whether a reply to a plan approves it
+pub fn is_approval(reply: &str) -> bool {
+    matches!(reply.trim().to_lowercase().as_str(), "y" | "yes" | "ok" | "approve" | "go" | "/approve")
+}
@@ -0,0 +1,287 @@
+use anyhow::Result;
+use std::ffi::OsStr;
+use std::future::Future;
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, Signal, System, UpdateKind};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Set on every command the model runs. Children inherit it even after their
+/// shell exits and they are reparented, so a dev server started with
+/// `npm run dev &` is still recognized as started in this session.
+const OWNER_VAR: &str = "VORK_OWNER_PID";
+
+/// How long `kill` waits for the process to exit before reporting it alive
+const EXIT_WAIT: Duration = Duration::from_secs(3);
+
+/// Tag a command as started by this vork
+pub fn mark(command: &mut Command) -> &mut Command {
+    command.env(OWNER_VAR, std::process::id().to_string())
+}
+
+/// Start the command in a process group of its own, so that `stop_group`
+/// reaches everything it spawns (npm's node, cargo's rustc)
+pub fn own_group(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command
+}
+
+/// Stop a child started with `own_group` and everything in its group:
+/// SIGTERM, then SIGKILL for whatever is left once `grace` has passed
+pub fn stop_group(child: &mut Child, grace: Duration) -> Result<ExitStatus> {
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", child.id());
+        let signal = |signal: &str| {
+            Command::new("kill")
+                .args([signal, "--", &group])
+                .stderr(std::process::Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        };
+        signal("-TERM");
+        let started = Instant::now();
+        // kill -0 succeeds while any process in the group is alive
+        while started.elapsed() < grace && signal("-0") {
+            let _ = child.try_wait()?;
+            thread::sleep(Duration::from_millis(50));
+        }
+        signal("-KILL");
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = grace;
+        let _ = child.kill();
+    }
+    Ok(child.wait()?)
+}
+
+/// Run the command to completion, or until `timeout` when there is one;
+/// then its process group is stopped and what it printed so far is kept.
+/// The flag says whether it timed out.
+pub fn output_within(command: &mut Command, timeout: Option<Duration>) -> Result<(Output, bool)> {
+    let mut child = own_group(command).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    // Read on threads so partial output survives a kill, and so a
+    // grandchild holding the pipes open cannot block past the deadline
+    let capture = |pipe: Option<Box<dyn Read + Send>>| {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&buffer);
+        let reader = thread::spawn(move || {
+            let Some(mut pipe) = pipe else { return };
+            let mut chunk = [0; 8192];
+            while let Ok(n @ 1..) = pipe.read(&mut chunk) {
+                sink.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(&chunk[..n]);
+            }
+        });
+        (buffer, reader)
+    };
+    let (stdout, stdout_reader) = capture(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+    let (stderr, stderr_reader) = capture(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+
+    let started = Instant::now();
+    let mut status = None;
+    let timed_out = loop {
+        if status.is_none() {
+            status = child.try_wait()?;
+        }
+        if status.is_some() && stdout_reader.is_finished() && stderr_reader.is_finished() {
+            break false;
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            break true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    let status = match status {
+        Some(status) if !timed_out => status,
+        _ => stop_group(&mut child, Duration::from_secs(2))?,
+    };
+    // Give the readers a moment to drain what was written before the kill
+    let drained = Instant::now();
+    while !(stdout_reader.is_finished() && stderr_reader.is_finished()) && drained.elapsed() < Duration::from_millis(500) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let take = |buffer: Arc<Mutex<Vec<u8>>>| std::mem::take(&mut *buffer.lock().unwrap_or_else(|e| e.into_inner()));
+    Ok((Output { status, stdout: take(stdout), stderr: take(stderr) }, timed_out))
+}
+
+/// Run blocking work (waiting on a child process, walking a directory) on
+/// the blocking pool, so the tasks sharing this runtime thread keep going
+pub async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
+}
+
+/// Drive a future that mixes awaits with blocking work it cannot hand to
+/// `blocking` because it borrows from the caller, like a tool waiting on its
+/// command or an approval prompt. The runtime moves its other tasks off this
+/// thread first; on a single-threaded runtime it is simply awaited.
+pub async fn run_blocking<F: Future>(future: F) -> F::Output {
+    match Handle::try_current() {
+        Ok(runtime) if runtime.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| runtime.block_on(future))
+        }
+        _ => future.await,
+    }
+}
+
+/// The machine's processes at one point in time
+pub struct Processes {
+    system: System,
+}
+
+impl Processes {
+    pub fn snapshot() -> Self {
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_memory()
+                .with_cmd(UpdateKind::Always)
+                .with_environ(UpdateKind::Always),
+        );
+        Self { system }
+    }
+
+    /// A process other than vork itself; threads are not processes here
+    pub fn find(&self, pid: u32) -> Option<&Process> {
+        self.system
+            .process(Pid::from_u32(pid))
+            .filter(|process| pid != std::process::id() && process.thread_kind().is_none())
+    }
+
+    /// Whether the process carries this vork's marker or descends from it
+    pub fn started_here(&self, process: &Process) -> bool {
+        let marker = format!("{}={}", OWNER_VAR, std::process::id());
+        if process.environ().iter().any(|var| var.as_os_str() == OsStr::new(&marker)) {
+            return true;
+        }
+        let me = Pid::from_u32(std::process::id());
+        let mut parent = process.parent();
+        // Bounded in case the table changed under us and holds a cycle
+        for _ in 0..64 {
+            match parent {
+                Some(pid) if pid == me => return true,
+                Some(pid) => parent = self.system.process(pid).and_then(|p| p.parent()),
+                None => break,
+            }
+        }
+        false
+    }
+
+    /// A table of the session's processes (or all of them), optionally only
+    /// those whose command line contains `filter`
+    pub fn list(&self, filter: Option<&str>, all: bool) -> String {
+        let mut rows: Vec<&Process> = self
+            .system
+            .processes()
+            .keys()
+            .filter_map(|pid| self.find(pid.as_u32()))
+            .filter(|process| process.status() != ProcessStatus::Zombie)
+            .filter(|process| all || self.started_here(process))
+            .filter(|process| filter.is_none_or(|filter| command_line(process).contains(filter)))
+            .collect();
+        rows.sort_by_key(|process| process.pid());
+
+        let scope = if all { "running" } else { "started in this session" };
+        if rows.is_empty() {
+            let mut text = match filter {
+                Some(filter) => format!("No processes {} match '{}'.", scope, filter),
+                None => format!("No processes {}.", scope),
+            };
+            if !all {
+                text.push_str(" Pass all: true to search every process on the machine.");
+            }
+            return text;
+        }
+
+        let mut text = format!(
+            "⚙️ {} process{} {}:\n\n{:>7} {:>7} {:>9} {:>8}  COMMAND\n",
+            rows.len(),
+            if rows.len() == 1 { "" } else { "es" },
+            scope,
+            "PID",
+            "PPID",
+            "MEMORY",
+            "RUNNING"
+        );
+        for process in rows {
+            text.push_str(&format!(
+                "{:>7} {:>7} {:>8.1}M {:>8}  {}\n",
+                process.pid(),
+                process.parent().map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+                process.memory() as f64 / 1_048_576.0,
+                duration(process.run_time()),
+                label(process)
+            ));
+        }
+        text
+    }
+
+    /// Send SIGTERM (or SIGKILL when `force`) and wait briefly for the exit
+    pub fn kill(&mut self, pid: u32, force: bool) -> Result<String> {
+        let process = self.find(pid).ok_or_else(|| anyhow::anyhow!("No process with pid {}", pid))?;
+        let label = label(process);
+        let (signal, name) = if force { (Signal::Kill, "SIGKILL") } else { (Signal::Term, "SIGTERM") };
+        // kill_with is None where the signal does not exist (Windows); kill() still works there
+        let sent = process.kill_with(signal).unwrap_or_else(|| process.kill());
+        if !sent {
+            anyhow::bail!("Could not signal process {} ({}); it may belong to another user", pid, label);
+        }
+
+        let started = Instant::now();
+        let pids = [Pid::from_u32(pid)];
+        while started.elapsed() < EXIT_WAIT {
+            thread::sleep(Duration::from_millis(100));
+            self.system.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+            let exited = self
+                .system
+                .process(pids[0])
+                .is_none_or(|process| process.status() == ProcessStatus::Zombie);
+            if exited {
+                return Ok(format!("🛑 Stopped {} ({})", pid, label));
+            }
+        }
+        let mut text = format!("⚠️ Sent {} to {} ({}) but it is still running", name, pid, label);
+        if !force {
+            text.push_str("; call kill_process again with force: true to kill it");
+        }
+        Ok(text)
+    }
+}
+
+fn command_line(process: &Process) -> String {
+    let cmd: Vec<_> = process.cmd().iter().map(|arg| arg.to_string_lossy()).collect();
+    if cmd.is_empty() {
+        process.name().to_string_lossy().into_owned()
+    } else {
+        cmd.join(" ")
+    }
+}
+
+/// The command line, shortened for tables and prompts
+pub fn label(process: &Process) -> String {
+    let command = command_line(process);
+    if command.chars().count() > 100 {
+        format!("{}…", command.chars().take(99).collect::<String>())
+    } else {
+        command
+    }
+}
+
+fn duration(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
@@ -0,0 +1,198 @@
+use serde_json::Value;
+
+use super::remote::shell_quote;
+
+/// Where profiles are kept, relative to the workspace
+pub const PROFILES_DIR: &str = ".vork/profiles";
+
+/// Printed between the profiled run's output and the report
+const MARKER: &str = "@@VORK_PROFILE";
+
+/// Symbols below this share of samples are left out of the report
+const PERCENT_LIMIT: &str = "0.3";
+
+/// Finds which profiler to use: flamegraph when installed, then perf, or
+/// prints missing
+pub const DETECT_SCRIPT: &str = "if command -v flamegraph >/dev/null 2>&1 && command -v perf >/dev/null 2>&1; then echo flamegraph; \
+    elif command -v perf >/dev/null 2>&1; then echo perf; \
+    elif command -v hyperfine >/dev/null 2>&1; then echo hyperfine; \
+    else echo missing; fi";
+
+/// What to profile and how
+pub struct Profile<'a> {
+    pub tool: &'a str,
+    pub command: &'a str,
+    /// A second command hyperfine times against the first
+    pub compare: Option<&'a str>,
+    /// hyperfine runs per command
+    pub runs: u64,
+    /// perf sampling frequency in Hz
+    pub frequency: u64,
+}
+
+impl Profile<'_> {
+    /// The profiler invocation as shown for approval
+    pub fn shown(&self) -> String {
+        match self.tool {
+            "hyperfine" => format!(
+                "hyperfine --runs {} {}{}",
+                self.runs,
+                shell_quote(self.command),
+                self.compare.map(|compare| format!(" {}", shell_quote(compare))).unwrap_or_default()
+            ),
+            tool => format!("{} -F {} -- {}", if tool == "perf" { "perf record" } else { tool }, self.frequency, self.command),
+        }
+    }
+
+    /// The profiler invocation, writing its artifacts to "$out"
+    fn command(&self) -> Option<String> {
+        // Run through sh so the command can use pipes, redirections and
+        // variables, from the workspace even when the profiler runs elsewhere
+        let run = |command: &str| format!("sh -c 'cd \"$0\" && eval \"$1\"' \"$PWD\" {}", shell_quote(command));
+        match self.tool {
+            "perf" => Some(format!(
+                "perf record -F {} -g -o \"$out/perf.data\" -- {}",
+                self.frequency,
+                run(self.command)
+            )),
+            // flamegraph always leaves perf.data in its working directory
+            "flamegraph" => Some(format!(
+                "(ws=$PWD; cd \"$out\" && flamegraph -F {} -o flamegraph.svg -- sh -c 'cd \"$0\" && eval \"$1\"' \"$ws\" {})",
+                self.frequency,
+                shell_quote(self.command)
+            )),
+            "hyperfine" => {
+                let mut command = format!(
+                    "hyperfine --warmup 1 --runs {} --export-json \"$out/hyperfine.json\" --export-markdown \"$out/hyperfine.md\" {}",
+                    self.runs,
+                    shell_quote(self.command)
+                );
+                if let Some(compare) = self.compare {
+                    command.push(' ');
+                    command.push_str(&shell_quote(compare));
+                }
+                Some(command)
+            }
+            _ => None,
+        }
+    }
+
+    /// The whole run: make the artifact directory `out`, profile, then print
+    /// the marker and the report (perf's hot symbols or hyperfine's JSON).
+    /// None for an unknown tool.
+    pub fn script(&self, out: &str) -> Option<String> {
+        let report = match self.tool {
+            "hyperfine" => "cat \"$out/hyperfine.json\" 2>/dev/null".to_string(),
+            _ => format!(
+                "[ -f \"$out/perf.data\" ] && perf report -i \"$out/perf.data\" --stdio --no-children --sort symbol --percent-limit {} \
+                 >\"$out/report.txt\" 2>\"$out/report.err\"; cat \"$out/report.txt\" 2>/dev/null",
+                PERCENT_LIMIT
+            ),
+        };
+        let command = self.command()?;
+        Some(format!(
+            "out={}; mkdir -p {} || exit 1; [ -f {}/.gitignore ] || echo '*' > {}/.gitignore; \
+             mkdir \"$out\" 2>/dev/null || out=$(mktemp -d \"$out-XXXX\") || exit 1; \
+             {}; status=$?; echo; echo \"{} $status $out\"; {}; exit $status",
+            shell_quote(out),
+            PROFILES_DIR,
+            PROFILES_DIR,
+            PROFILES_DIR,
+            command,
+            MARKER,
+            report
+        ))
+    }
+}
+
+/// Split the output of `Profile::script` into the run's own output, the
+/// artifact directory and the report
+pub fn split_output(stdout: &str) -> Option<(&str, &str, &str)> {
+    let start = stdout.rfind(MARKER)?;
+    let (run, rest) = stdout.split_at(start);
+    let (header, report) = rest.split_once('\n').unwrap_or((rest, ""));
+    let directory = header.splitn(3, ' ').nth(2).unwrap_or("").trim();
+    Some((run, directory, report))
+}
+
+/// The `top` hottest symbols from `perf report --stdio --sort symbol`, with
+/// the sample count
+pub fn hot_symbols(report: &str, top: usize) -> Option<String> {
+    let samples = report
+        .lines()
+        .find_map(|line| line.strip_prefix("# Samples:"))
+        .map(|samples| samples.split(" of event").next().unwrap_or(samples).trim().to_string());
+    let symbols: Vec<(f64, &str)> = report
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (percent, symbol) = line.trim().split_once('%')?;
+            let symbol = symbol.trim();
+            let symbol = symbol.strip_prefix("[.]").or_else(|| symbol.strip_prefix("[k]")).unwrap_or(symbol);
+            Some((percent.trim().parse().ok()?, without_hash(symbol.trim())))
+        })
+        .collect();
+    if symbols.is_empty() {
+        return None;
+    }
+
+    let mut text = format!("Hot symbols ({} samples, self time):", samples.as_deref().unwrap_or("?"));
+    for (percent, symbol) in symbols.iter().take(top) {
+        text.push_str(&format!("\n{:>6.2}%  {}", percent, symbol));
+    }
+    if symbols.len() > top {
+        text.push_str(&format!("\n[{} more symbols above {}%]", symbols.len() - top, PERCENT_LIMIT));
+    }
+    Some(text)
+}
+
+/// Rust symbols end in a hash that only adds noise
+fn without_hash(symbol: &str) -> &str {
+    match symbol.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => path,
+        _ => symbol,
+    }
+}
+
+/// Mean, spread and range per command from hyperfine's JSON export, and how
+/// much faster the quickest is
+pub fn timings(json: &str) -> Option<String> {
+    let results = serde_json::from_str::<Value>(json).ok()?["results"].as_array()?.clone();
+    let seconds = |result: &Value, key: &str| result[key].as_f64().unwrap_or(0.0);
+    let mut text = "Timings (mean ± σ, min … max):".to_string();
+    for result in &results {
+        text.push_str(&format!(
+            "\n{} ± {}  ({} … {}, {} runs)  {}",
+            duration(seconds(result, "mean")),
+            duration(seconds(result, "stddev")),
+            duration(seconds(result, "min")),
+            duration(seconds(result, "max")),
+            result["times"].as_array().map_or(0, Vec::len),
+            result["command"].as_str().unwrap_or("?")
+        ));
+    }
+    if results.len() > 1 {
+        let mut by_mean: Vec<&Value> = results.iter().collect();
+        by_mean.sort_by(|a, b| seconds(a, "mean").total_cmp(&seconds(b, "mean")));
+        let (fastest, slowest) = (by_mean[0], by_mean[by_mean.len() - 1]);
+        if seconds(fastest, "mean") > 0.0 {
+            text.push_str(&format!(
+                "\n\n{} is {:.2}x faster than {}",
+                fastest["command"].as_str().unwrap_or("?"),
+                seconds(slowest, "mean") / seconds(fastest, "mean"),
+                slowest["command"].as_str().unwrap_or("?")
+            ));
+        }
+    }
+    Some(text)
+}
+
+fn duration(seconds: f64) -> String {
+    if seconds >= 1.0 {
+        format!("{:.3} s", seconds)
+    } else if seconds >= 0.001 {
+        format!("{:.2} ms", seconds * 1000.0)
+    } else {
+        format!("{:.1} µs", seconds * 1_000_000.0)
+    }
+}
@@ -0,0 +1,148 @@
+use serde_json::Value;
+
+use super::client::{FunctionCall, Message, ResponseMessage, ToolCallResponse};
+
+/// Describe the tools in the system prompt and ask for ```tool blocks, for
+/// servers and models without native tool calling
+pub fn with_protocol(messages: &[Message], tools: &[Value]) -> Vec<Message> {
+    let protocol = protocol(tools);
+    let mut messages = messages.to_vec();
+    match messages.first_mut() {
+        Some(system) if system.role == "system" => system.content.push_str(&protocol),
+        _ => messages.insert(
+            0,
+            Message {
+                role: "system".to_string(),
+                content: protocol.trim_start().to_string(),
+            },
+        ),
+    }
+    messages
+}
+
+/// The system prompt section describing `tools`
+pub fn protocol(tools: &[Value]) -> String {
+    let mut protocol = String::from(
+        "\n\n## Calling tools\n\
+         This server has no native tool calling. To use a tool, reply with a fenced block \
+         tagged `tool` holding one JSON object, and nothing after it:\n\n\
+         ```tool\n{\"name\": \"read_file\", \"arguments\": {\"path\": \"src/main.rs\"}}\n```\n\n\
+         Use one block per tool call. The result comes back in the next message. \
+         When you need no more tools, answer normally without a tool block.\n\n\
+         Available tools:\n",
+    );
+    for tool in tools {
+        let function = &tool["function"];
+        protocol.push_str(&format!(
+            "- {}: {}\n  arguments: {}\n",
+            function["name"].as_str().unwrap_or_default(),
+            function["description"].as_str().unwrap_or_default(),
+            function["parameters"]
+        ));
+    }
+    protocol
+}
+
+/// Move tool calls written into the reply text into `tool_calls`. Accepts
+/// ```tool blocks (and ```json blocks shaped like a call), Hermes-style
+/// <tool_call> tags and a fence the model never closed.
+pub fn extract(message: &mut ResponseMessage) {
+    let Some(content) = message.content.take() else {
+        return;
+    };
+
+    let mut calls = vec![];
+    let mut text = String::new();
+    let mut rest = content.as_str();
+    while let Some((start, open, close)) = next_call(rest) {
+        let body_start = start + open.len();
+        let (body, after) = match rest[body_start..].find(close) {
+            Some(end) => (&rest[body_start..body_start + end], &rest[body_start + end + close.len()..]),
+            None => (&rest[body_start..], ""),
+        };
+        // A block explicitly marked as a call is one even if its JSON is broken
+        let call = match open {
+            "```json" => parse_call(body),
+            _ => parse_call(body).or_else(|| loose_call(body)),
+        };
+        match call {
+            Some(call) => {
+                text.push_str(&rest[..start]);
+                calls.push(ToolCallResponse {
+                    id: format!("call_{}", calls.len()),
+                    r#type: "function".to_string(),
+                    function: call,
+                });
+            }
+            // A json block that is not a tool call stays in the answer
+            None => text.push_str(&rest[..rest.len() - after.len()]),
+        }
+        rest = after;
+    }
+    text.push_str(rest);
+
+    let text = text.trim();
+    message.content = (!text.is_empty()).then(|| text.to_string());
+    if !calls.is_empty() {
+        message.tool_calls.get_or_insert_with(Vec::new).extend(calls);
+    }
+}
+
+/// Where the next candidate call starts: (offset, opening marker, closing marker)
+fn next_call(text: &str) -> Option<(usize, &'static str, &'static str)> {
+    ["```tool", "```json", "<tool_call>"]
+        .iter()
+        .filter_map(|open| {
+            // ```tool but not ```toml
+            text.match_indices(open)
+                .find(|(at, _)| !text[at + open.len()..].starts_with(|c: char| c.is_alphanumeric()))
+                .map(|(at, _)| (at, *open))
+        })
+        .min_by_key(|(at, _)| *at)
+        .map(|(at, open)| {
+            let close = if open == "<tool_call>" { "</tool_call>" } else { "```" };
+            (at, open, close)
+        })
+}
+
+/// `{"name": ..., "arguments": {...}}`; arguments may also be a JSON string.
+/// Arguments that are not valid JSON are passed on as written so the caller
+/// reports the parse error.
+fn parse_call(body: &str) -> Option<FunctionCall> {
+    let body = body.trim();
+    let value: Value = serde_json::from_str(body).ok()?;
+    let name = value["name"].as_str().or_else(|| value["tool"].as_str())?.to_string();
+    let arguments = match value.get("arguments").or_else(|| value.get("parameters")) {
+        Some(Value::String(raw)) => raw.clone(),
+        Some(arguments) => arguments.to_string(),
+        None => "{}".to_string(),
+    };
+    Some(FunctionCall { name, arguments })
+}
+
+/// Best effort for a call whose JSON does not parse: the name, and the raw
+/// text of the arguments object for the caller to repair or report
+fn loose_call(body: &str) -> Option<FunctionCall> {
+    let after_key = &body[body.find("\"name\"")? + 6..];
+    let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start().strip_prefix('"')?;
+    let name = &after_colon[..after_colon.find('"')?];
+    let arguments = body
+        .find("\"arguments\"")
+        .and_then(|at| {
+            let value = body[at + 11..].trim_start().strip_prefix(':')?;
+            let end = value.rfind('}')?;
+            Some(value[..end].trim().to_string())
+        })
+        .unwrap_or_else(|| "{}".to_string());
+    Some(FunctionCall { name: name.to_string(), arguments })
+}
+
+/// Whether a server error means it cannot take the `tools` parameter, e.g.
+/// llama-server without --jinja or an Ollama model without tool support
+pub fn rejects_tools(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("tool")
+        && ["support", "jinja", "unknown", "unexpected", "not allowed"]
+            .iter()
+            .any(|hint| error.contains(hint))
+}
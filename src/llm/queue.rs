@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+static PRIORITY: OnceLock<Priority> = OnceLock::new();
+
+/// How often a waiting client looks for a free slot
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Who is waiting on a request. Interactive clients are served before batch
+/// ones; within a priority it is first come, first served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Someone is watching: chat, ask, editors, the bot
+    Interactive,
+    /// Nobody is waiting on the answer: exec, audit, schedule, watch
+    Batch,
+}
+
+/// Set the priority of this process's requests (from main)
+pub fn set_priority(priority: Priority) {
+    let _ = PRIORITY.set(priority);
+}
+
+fn priority() -> Priority {
+    PRIORITY.get().copied().unwrap_or(Priority::Interactive)
+}
+
+/// A request slot on the server, released when dropped
+pub struct Slot {
+    path: PathBuf,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// This process's place in line, removed when it gets a slot or gives up
+struct Ticket {
+    path: PathBuf,
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Wait for one of the server's `slots` to be free. vork processes sharing
+/// a server coordinate through lock files in ~/.vork/queue, so a chat
+/// session is not stuck behind a nightly audit. `report` is called with the
+/// number of requests ahead whenever it changes while waiting.
+pub async fn acquire(base_url: &str, slots: u32, max_per_client: u32, report: impl Fn(usize)) -> Result<Slot> {
+    let dir = Config::config_dir()?.join("queue").join(dir_name(base_url));
+    fs::create_dir_all(&dir).context("Failed to create the request queue directory")?;
+    let pid = std::process::id();
+    let slots = slots.max(1);
+
+    // A client at its cap waits outside the line, so it never holds up the
+    // clients behind it
+    while held_by(&dir, pid) >= max_per_client.max(1) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let rank = match priority() {
+        Priority::Interactive => 0,
+        Priority::Batch => 1,
+    };
+    let ticket_name = format!("{}-{:024}-{}.wait", rank, nanos, pid);
+    let ticket = Ticket { path: dir.join(&ticket_name) };
+    fs::write(&ticket.path, pid.to_string()).context("Failed to join the request queue")?;
+
+    let mut last_reported = None;
+    loop {
+        remove_stale(&dir);
+        let mut waiting: Vec<String> = entries(&dir).filter(|name| name.ends_with(".wait")).collect();
+        waiting.sort();
+        let ahead = waiting.iter().position(|name| *name == ticket_name).unwrap_or(0);
+
+        if ahead < slots as usize {
+            for index in 0..slots {
+                let path = dir.join(format!("slot-{}.lock", index));
+                let created = OpenOptions::new().write(true).create_new(true).open(&path);
+                if let Ok(mut file) = created {
+                    file.write_all(pid.to_string().as_bytes())?;
+                    drop(ticket);
+                    return Ok(Slot { path });
+                }
+            }
+        }
+
+        if last_reported != Some(ahead) {
+            report(ahead);
+            last_reported = Some(ahead);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Feedback for a waiting client, e.g. "2 requests ahead"
+pub fn describe_position(ahead: usize) -> String {
+    match ahead {
+        0 => "⏳ Server busy, next in line for a slot".to_string(),
+        1 => "⏳ Server busy, 1 request ahead".to_string(),
+        n => format!("⏳ Server busy, {} requests ahead", n),
+    }
+}
+
+/// Time spent waiting, for callers that show it after the fact
+pub fn describe_wait(waited: Duration) -> Option<String> {
+    (waited >= Duration::from_secs(1))
+        .then(|| format!("⏳ Waited {}s for a free server slot", waited.as_secs()))
+}
+
+/// One queue directory per server, named after its URL
+fn dir_name(base_url: &str) -> String {
+    base_url
+        .trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+fn entries(dir: &Path) -> impl Iterator<Item = String> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+}
+
+fn owner(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn held_by(dir: &Path, pid: u32) -> u32 {
+    entries(dir)
+        .filter(|name| name.ends_with(".lock"))
+        .filter(|name| owner(&dir.join(name)) == Some(pid))
+        .count() as u32
+}
+
+/// Drop tickets and slots left behind by processes that were killed
+fn remove_stale(dir: &Path) {
+    for name in entries(dir) {
+        let path = dir.join(&name);
+        // A file that was just created may not have its pid written yet
+        let Some(pid) = owner(&path) else {
+            continue;
+        };
+        if !is_running(pid) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+fn is_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    if Path::new("/proc/self").exists() {
+        return Path::new(&format!("/proc/{}", pid)).exists();
+    }
+    if cfg!(unix) {
+        return Command::new("kill")
+            .arg("-0")
+            .arg(pid.to_string())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(true);
+    }
+    true
+}
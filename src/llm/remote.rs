@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::processes;
+use crate::config::Config;
+
+static REMOTE_OVERRIDE: OnceLock<String> = OnceLock::new();
+static ACTIVE: OnceLock<Option<RemoteTarget>> = OnceLock::new();
+
+/// Machine the file and shell tools operate on instead of the local one.
+/// Every call goes through the system `ssh`, so ~/.ssh/config, agents and
+/// jump hosts work as they do in a terminal.
+#[derive(Debug, Clone)]
+pub struct RemoteTarget {
+    pub host: String,
+    /// Directory commands start in and relative paths resolve against
+    pub workdir: Option<String>,
+    ssh_args: Vec<String>,
+}
+
+impl RemoteTarget {
+    /// Select a target for the rest of the process (from `--remote`)
+    pub fn set_override(spec: String) {
+        let _ = REMOTE_OVERRIDE.set(spec);
+    }
+
+    /// The target from `--remote`, falling back to `[remote]` in the config
+    pub fn active() -> Option<&'static RemoteTarget> {
+        ACTIVE
+            .get_or_init(|| {
+                let config = Config::load().ok();
+                let ssh_args = config.as_ref().map(|c| c.remote.ssh_args.clone()).unwrap_or_default();
+                let (host, workdir) = match REMOTE_OVERRIDE.get() {
+                    Some(spec) => split_spec(spec),
+                    None => {
+                        let remote = config?.remote;
+                        (remote.host?, remote.workdir)
+                    }
+                };
+                // `--remote local` turns a configured target off for one run
+                if host.is_empty() || host == "local" {
+                    return None;
+                }
+                Some(RemoteTarget { host, workdir, ssh_args })
+            })
+            .as_ref()
+    }
+
+    /// `host:path` for tool output, so the model can tell where a file lives
+    pub fn label(&self, path: &str) -> String {
+        format!("{}:{}", self.host, path)
+    }
+
+    /// Short description for the system prompt and status lines
+    pub fn describe(&self) -> String {
+        match self.workdir {
+            Some(ref dir) => format!("{}:{}", self.host, dir),
+            None => self.host.clone(),
+        }
+    }
+
+    fn ssh(&self, script: &str) -> Command {
+        let control_path = std::env::temp_dir().join("vork-ssh-%C");
+        let mut command = Command::new("ssh");
+        command
+            .arg("-o")
+            .arg("BatchMode=yes")
+            // Reuse one connection across tool calls instead of a handshake each time
+            .arg("-o")
+            .arg("ControlMaster=auto")
+            .arg("-o")
+            .arg(format!("ControlPath={}", control_path.display()))
+            .arg("-o")
+            .arg("ControlPersist=120")
+            .args(&self.ssh_args)
+            .arg(&self.host)
+            .arg("--")
+            .arg(script);
+        command
+    }
+
+    fn script(&self, command: &str) -> String {
+        match self.workdir {
+            Some(ref dir) => format!("cd {} && bash -c {}", shell_quote(dir), shell_quote(command)),
+            None => format!("bash -c {}", shell_quote(command)),
+        }
+    }
+
+    /// Run a bash command in the remote workspace
+    pub fn run(&self, command: &str) -> Result<Output> {
+        let output = self
+            .ssh(&self.script(command))
+            .stdin(Stdio::null())
+            .output()
+            .context("Failed to run ssh")?;
+        self.check_connection(&output)?;
+        Ok(output)
+    }
+
+    /// `run` for bash_exec: past the deadline ssh is stopped and the output
+    /// so far kept. The flag says whether it timed out.
+    pub fn run_within(&self, command: &str, timeout: Option<Duration>) -> Result<(Output, bool)> {
+        let (output, timed_out) = processes::output_within(self.ssh(&self.script(command)).stdin(Stdio::null()), timeout)
+            .context("Failed to run ssh")?;
+        self.check_connection(&output)?;
+        Ok((output, timed_out))
+    }
+
+    /// Run a command that must succeed, returning its stdout. `failure`
+    /// prefixes the remote error message when it does not.
+    fn run_checked(&self, command: &str, input: Option<&[u8]>, failure: String) -> Result<Vec<u8>> {
+        let mut child = self
+            .ssh(&self.script(command))
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run ssh")?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input)?;
+        }
+        let output = child.wait_with_output()?;
+        self.check_connection(&output)?;
+        if !output.status.success() {
+            anyhow::bail!("{}: {}", failure, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(output.stdout)
+    }
+
+    /// ssh exits with 255 when it never reached the command
+    fn check_connection(&self, output: &Output) -> Result<()> {
+        if output.status.code() == Some(255) {
+            anyhow::bail!(
+                "ssh to {} failed: {}",
+                self.host,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>> {
+        self.run_checked(
+            &format!("cat -- {}", shell_quote(path)),
+            None,
+            format!("Failed to read file: {}", self.label(path)),
+        )
+    }
+
+    /// Write a file, creating its parent directories
+    pub fn write(&self, path: &str, content: &[u8]) -> Result<()> {
+        let quoted = shell_quote(path);
+        self.run_checked(
+            &format!("mkdir -p -- \"$(dirname -- {0})\" && cat > {0}", quoted),
+            Some(content),
+            format!("Failed to write file: {}", self.label(path)),
+        )?;
+        Ok(())
+    }
+
+    /// Directory entries, with a trailing `/` on subdirectories
+    pub fn list_dir(&self, path: &str) -> Result<Vec<String>> {
+        let stdout = self.run_checked(
+            &format!("ls -1Ap -- {}", shell_quote(path)),
+            None,
+            format!("Failed to read directory: {}", self.label(path)),
+        )?;
+        Ok(String::from_utf8_lossy(&stdout).lines().map(String::from).collect())
+    }
+}
+
+/// `user@host[:workdir]`
+fn split_spec(spec: &str) -> (String, Option<String>) {
+    match spec.split_once(':') {
+        Some((host, dir)) if !dir.is_empty() => (host.to_string(), Some(dir.to_string())),
+        Some((host, _)) => (host.to_string(), None),
+        None => (spec.to_string(), None),
+    }
+}
+
+/// Single-quote a string for a POSIX shell
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
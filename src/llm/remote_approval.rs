@@ -0,0 +1,281 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use super::approval::Prompter;
+use crate::config::RemoteApprovalConfig;
+
+/// One approval page per process, shared by every run that asks
+static SERVER: Mutex<Option<Arc<Server>>> = Mutex::new(None);
+
+struct Server {
+    /// Base URL the links point at
+    base: String,
+    requests: Mutex<HashMap<String, Request>>,
+    answered: Condvar,
+}
+
+struct Request {
+    message: String,
+    expires: Instant,
+    decision: Option<bool>,
+}
+
+/// A prompter that waits for someone to approve or deny on a small web page.
+/// Each request gets an unguessable link that expires after
+/// `expire_secs`; the link is printed to stderr and sent to the webhook.
+pub fn prompter(config: &RemoteApprovalConfig) -> Result<Prompter> {
+    let server = start(config)?;
+    let webhook = config.webhook.clone();
+    let expire = Duration::from_secs(config.expire_secs.max(1));
+    Ok(Box::new(move |message| ask(&server, webhook.as_deref(), expire, message)))
+}
+
+fn start(config: &RemoteApprovalConfig) -> Result<Arc<Server>> {
+    let mut running = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(ref server) = *running {
+        return Ok(server.clone());
+    }
+
+    let listener = TcpListener::bind(&config.listen)
+        .with_context(|| format!("Failed to listen for approvals on {}", config.listen))?;
+    let base = match config.public_url {
+        Some(ref url) => url.trim_end_matches('/').to_string(),
+        None => format!("http://{}", listener.local_addr()?),
+    };
+    let server = Arc::new(Server {
+        base,
+        requests: Mutex::new(HashMap::new()),
+        answered: Condvar::new(),
+    });
+
+    let shared = server.clone();
+    // A thread per connection, so one that never sends its request cannot
+    // hold up the others until it times out
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let server = shared.clone();
+            std::thread::spawn(move || serve(stream, &server));
+        }
+    });
+    eprintln!("{} Approval page listening on {}", "🔐".cyan(), server.base);
+    *running = Some(server.clone());
+    Ok(server)
+}
+
+/// Block until the request is answered or expires (which denies it)
+fn ask(server: &Server, webhook: Option<&str>, expire: Duration, message: &str) -> Result<bool> {
+    let token = token()?;
+    let link = format!("{}/approve/{}", server.base, token);
+    let expires = Instant::now() + expire;
+    server.requests.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        token.clone(),
+        Request { message: message.to_string(), expires, decision: None },
+    );
+
+    eprintln!("{} {}", "🔒 Waiting for approval:".yellow(), message);
+    eprintln!("   {} (expires in {})", link, expiry(expire.as_secs()));
+    if let Some(url) = webhook {
+        notify(url, message, &link, expire);
+    }
+
+    let mut requests = server.requests.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        if let Some(decision) = requests.get(&token).and_then(|r| r.decision) {
+            requests.remove(&token);
+            if decision {
+                eprintln!("{}", "✓ Approved remotely".green());
+            } else {
+                eprintln!("{}", "✗ Denied remotely".red());
+            }
+            return Ok(decision);
+        }
+        let now = Instant::now();
+        if now >= expires {
+            requests.remove(&token);
+            eprintln!("{}", "✗ Approval link expired, denied".red());
+            return Ok(false);
+        }
+        requests = server
+            .answered
+            .wait_timeout(requests, expires - now)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+}
+
+/// 128 bits from the operating system's random source, so links cannot be guessed
+fn token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| anyhow::anyhow!("Failed to generate an approval token: {}", e))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// POST the request in the background; a slow webhook must not delay the link
+fn notify(url: &str, message: &str, link: &str, expire: Duration) {
+    let text = format!("🔒 vork needs approval: {}\n{}", message, link);
+    let payload = json!({
+        "message": message,
+        "approve_url": link,
+        "expires_in_secs": expire.as_secs(),
+        // Slack and Discord incoming webhooks read these
+        "text": text,
+        "content": text,
+    });
+    let url = url.to_string();
+    std::thread::spawn(move || {
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(anyhow::Error::from)
+            .and_then(|runtime| {
+                runtime.block_on(async {
+                    reqwest::Client::new().post(&url).json(&payload).send().await?.error_for_status()?;
+                    Ok(())
+                })
+            });
+        if let Err(e) = result {
+            eprintln!("{} {:#}", "⚠️  Approval webhook failed:".yellow(), e);
+        }
+    });
+}
+
+/// Handle one HTTP request. GET shows the request with Approve and Deny
+/// buttons; only the POST they submit decides, so link previews in chat
+/// apps cannot approve anything.
+fn serve(mut stream: TcpStream, server: &Server) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length.min(4096)];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    let (status, page) = match segments.as_slice() {
+        ["approve", token, rest @ ..] => {
+            let mut requests = server.requests.lock().unwrap_or_else(|e| e.into_inner());
+            match requests.get_mut(*token) {
+                Some(request) if request.decision.is_none() && request.expires > Instant::now() => {
+                    match (method, rest) {
+                        ("POST", [answer @ ("yes" | "no")]) => {
+                            let approved = *answer == "yes";
+                            request.decision = Some(approved);
+                            server.answered.notify_all();
+                            let verdict = if approved { "Approved ✓" } else { "Denied ✗" };
+                            ("200 OK", page(verdict, &request.message, None))
+                        }
+                        ("GET", []) => {
+                            let remaining = request.expires.saturating_duration_since(Instant::now()).as_secs();
+                            ("200 OK", page("vork needs approval", &request.message, Some((token, remaining))))
+                        }
+                        _ => ("405 Method Not Allowed", page("Not allowed", "Use the buttons on the approval page.", None)),
+                    }
+                }
+                _ => ("410 Gone", page("Link expired", "This request has expired or was already answered.", None)),
+            }
+        }
+        _ => ("404 Not Found", page("Not found", "", None)),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        page.len(),
+        page
+    )?;
+    Ok(())
+}
+
+fn expiry(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{} min", secs.div_ceil(60))
+    }
+}
+
+fn page(title: &str, message: &str, form: Option<(&str, u64)>) -> String {
+    let buttons = match form {
+        Some((token, remaining)) => format!(
+            r#"<form method="post" action="/approve/{0}/yes"><button class="yes">Approve</button></form>
+<form method="post" action="/approve/{0}/no"><button class="no">Deny</button></form>
+<p class="muted">Expires in {1}</p>"#,
+            token,
+            expiry(remaining)
+        ),
+        None => String::new(),
+    };
+    format!(
+        r#"<!doctype html>
+<html><head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{0}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; max-width: 40em; margin: 2em auto; padding: 0 1em; }}
+pre {{ background: #f4f4f4; padding: 1em; white-space: pre-wrap; word-break: break-all; }}
+form {{ display: inline; }}
+button {{ font-size: 1.2em; padding: 0.6em 1.4em; margin: 0.5em 0.5em 0 0; border: 0; border-radius: 6px; color: white; }}
+.yes {{ background: #2e7d32; }} .no {{ background: #c62828; }} .muted {{ color: #777; }}
+</style></head>
+<body><h2>{0}</h2><pre>{1}</pre>{2}</body></html>"#,
+        html_escape::encode_text(title),
+        html_escape::encode_text(message),
+        buttons
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random_hex() {
+        let (a, b) = (token().unwrap(), token().unwrap());
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn an_idle_connection_does_not_hold_up_answers() {
+        let config = RemoteApprovalConfig { listen: "127.0.0.1:0".to_string(), ..Default::default() };
+        let server = start(&config).unwrap();
+        let address = server.base.trim_start_matches("http://").to_string();
+        server.requests.lock().unwrap().insert(
+            "abc".to_string(),
+            Request { message: "rm -rf build".to_string(), expires: Instant::now() + Duration::from_secs(60), decision: None },
+        );
+
+        // Connects and never sends anything
+        let _idle = TcpStream::connect(&address).unwrap();
+        let mut answer = TcpStream::connect(&address).unwrap();
+        answer.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        answer.write_all(b"POST /approve/abc/yes HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        answer.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert_eq!(server.requests.lock().unwrap()["abc"].decision, Some(true));
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::fs;
+
+use crate::config::{Config, ProjectConfig, RoutingConfig};
+
+/// Image extensions that mark a request as needing a vision model
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// Model name fragments of vision-capable models (besides a "-vl-" part)
+const VISION_MARKERS: &[&str] = &["vision", "llava", "gemma3", "gemma-3", "minicpm-v", "pixtral", "moondream"];
+
+/// Switch to a larger context once the conversation fills this share of it
+const CONTEXT_HEADROOM: f64 = 0.8;
+
+/// A preset the router can pick, from ~/.vork/presets
+struct PresetInfo {
+    name: String,
+    context_limit: usize,
+    vision: bool,
+}
+
+/// The parts of ~/.vork/benchmark_results.json the router uses
+#[derive(Debug, Deserialize)]
+struct BenchmarkResults {
+    fastest_preset: String,
+}
+
+/// What the router knows about one request
+pub struct RouteRequest<'a> {
+    pub prompt: &'a str,
+    /// Estimated conversation size including the prompt
+    pub context_tokens: usize,
+    /// No earlier turns: switching now costs nothing in continuity
+    pub first_turn: bool,
+    pub agent_preset: Option<&'a str>,
+    /// `preset` from the project's .vork.toml, which outranks the agent's
+    pub project_preset: Option<&'a str>,
+    pub current_preset: &'a str,
+}
+
+/// A preset switch and the reason for it, shown in the transcript
+pub struct Route {
+    pub preset: String,
+    pub reason: String,
+}
+
+/// Picks a preset per request from prompt size, images, agent preference and
+/// benchmark results
+pub struct Router {
+    presets: Vec<PresetInfo>,
+    fastest: Option<String>,
+    config: RoutingConfig,
+}
+
+impl Router {
+    pub fn load() -> Result<Self> {
+        let config_dir = Config::config_dir()?;
+
+        let mut presets = vec![];
+        if let Ok(entries) = fs::read_dir(config_dir.join("presets")) {
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().map(|n| n.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                let Some(preset) = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|c| toml::from_str::<Config>(&c).ok())
+                else {
+                    continue;
+                };
+                presets.push(PresetInfo {
+                    name,
+                    context_limit: preset.llamacpp.context_limit.min(preset.llamacpp.context_size as usize),
+                    vision: is_vision_model(&preset.assistant.model),
+                });
+            }
+        }
+        presets.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let fastest = fs::read_to_string(config_dir.join("benchmark_results.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<BenchmarkResults>(&json).ok())
+            .map(|b| b.fastest_preset);
+
+        Ok(Self {
+            presets,
+            fastest,
+            config: Config::load()?.routing,
+        })
+    }
+
+    /// The preset to switch to, or None to stay on the current one. Hard
+    /// requirements (images, context size) come first, then the project's
+    /// and the agent's preference, then speed for short opening prompts.
+    pub fn route(&self, request: &RouteRequest) -> Option<Route> {
+        let enabled = self.config.enabled;
+        let route = enabled
+            .then(|| self.route_by_need(request))
+            .flatten()
+            .or_else(|| self.route_by_project(request))
+            .or_else(|| self.route_by_agent(request))
+            .or_else(|| enabled.then(|| self.route_by_speed(request)).flatten());
+
+        route.filter(|r| r.preset != request.current_preset)
+    }
+
+    /// The project's preset, like an agent's, applies when a conversation starts
+    fn route_by_project(&self, request: &RouteRequest) -> Option<Route> {
+        let preset = request.project_preset.filter(|_| request.first_turn)?;
+        self.exists(preset).then(|| Route {
+            preset: preset.to_string(),
+            reason: format!("set in {}", ProjectConfig::FILE_NAME),
+        })
+    }
+
+    /// Agent preference applies when a conversation starts
+    fn route_by_agent(&self, request: &RouteRequest) -> Option<Route> {
+        let preset = request.agent_preset.filter(|_| request.first_turn)?;
+        self.exists(preset).then(|| Route {
+            preset: preset.to_string(),
+            reason: "agent preference".to_string(),
+        })
+    }
+
+    fn route_by_need(&self, request: &RouteRequest) -> Option<Route> {
+        let current = self.presets.iter().find(|p| p.name == request.current_preset);
+
+        if mentions_image(request.prompt) && !current.is_some_and(|p| p.vision) {
+            if let Some(preset) = self.presets.iter().find(|p| p.vision) {
+                return Some(Route {
+                    preset: preset.name.clone(),
+                    reason: "prompt references an image".to_string(),
+                });
+            }
+        }
+
+        let needed = (request.context_tokens as f64 / CONTEXT_HEADROOM) as usize;
+        if current.is_some_and(|p| p.context_limit < needed) {
+            // Smallest context that fits (usually the fastest), else the largest
+            let preset = self
+                .presets
+                .iter()
+                .filter(|p| p.context_limit >= needed)
+                .min_by_key(|p| p.context_limit)
+                .or_else(|| self.presets.iter().max_by_key(|p| p.context_limit))?;
+            return Some(Route {
+                preset: preset.name.clone(),
+                reason: format!("~{}k tokens of context needed", request.context_tokens / 1000),
+            });
+        }
+
+        None
+    }
+
+    fn route_by_speed(&self, request: &RouteRequest) -> Option<Route> {
+        let quick = request.first_turn
+            && self.config.quick_prompt_chars > 0
+            && request.prompt.len() < self.config.quick_prompt_chars
+            && !request.prompt.contains('\n');
+        if quick {
+            let fastest = self.fastest.as_deref().filter(|f| self.exists(f))?;
+            return Some(Route {
+                preset: fastest.to_string(),
+                reason: "short prompt, fastest benchmarked preset".to_string(),
+            });
+        }
+
+        None
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.presets.iter().any(|p| p.name == name)
+    }
+}
+
+fn is_vision_model(model: &str) -> bool {
+    let model = model.to_lowercase();
+    model.split(|c: char| !c.is_ascii_alphanumeric()).any(|part| part == "vl")
+        || VISION_MARKERS.iter().any(|m| model.contains(m))
+}
+
+fn mentions_image(prompt: &str) -> bool {
+    prompt.split_whitespace().any(|word| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '.');
+        word.rsplit_once('.')
+            .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+    })
+}
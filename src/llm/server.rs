@@ -1,10 +1,82 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::time::Duration;
 use tokio::time::sleep;
 
-use crate::config::Config;
+use super::processes;
+use crate::config::{Config, LlamaCppConfig};
+
+/// A llama-server vork started, kept in ~/.vork/servers.json so later runs
+/// can use it instead of starting their own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningServer {
+    pub port: u16,
+    pub pid: u32,
+    pub model: String,
+    /// The preset it was started for, when there was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// The launch settings, compared with the next run's
+    pub llamacpp: LlamaCppConfig,
+    pub started_at: DateTime<Utc>,
+}
+
+fn is_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+impl RunningServer {
+    fn registry_path() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("servers.json"))
+    }
+
+    /// Recorded servers whose process is still alive
+    pub fn list() -> Vec<RunningServer> {
+        let servers: Vec<RunningServer> = Self::registry_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        servers.into_iter().filter(|server| is_alive(server.pid)).collect()
+    }
+
+    /// Remember that `pid` serves `config` on `port`, replacing whatever was
+    /// recorded for that port
+    pub fn record(port: u16, pid: u32, config: &Config) -> Result<()> {
+        let mut servers = Self::list();
+        servers.retain(|server| server.port != port);
+        servers.push(RunningServer {
+            port,
+            pid,
+            model: config.assistant.model.clone(),
+            preset: Config::active_preset().filter(|active| !active.modified).map(|active| active.name),
+            llamacpp: config.llamacpp.clone(),
+            started_at: Utc::now(),
+        });
+        let path = Self::registry_path()?;
+        fs::create_dir_all(Config::config_dir()?)?;
+        fs::write(path, serde_json::to_string_pretty(&servers)?)?;
+        Ok(())
+    }
+
+    /// The server on `port` if it was started with the same model and launch
+    /// settings as `config`
+    pub fn reusable(port: u16, config: &Config) -> Option<RunningServer> {
+        Self::list().into_iter().find(|server| {
+            server.port == port && server.model == config.assistant.model && !server.llamacpp.requires_restart(&config.llamacpp)
+        })
+    }
+}
 
 pub struct ServerManager {
     config: Config,
@@ -19,14 +91,15 @@ impl ServerManager {
     }
 
     /// Kill any existing llama-server instances
-    pub fn kill_existing_servers(&self) -> Result<()> {
+    pub async fn kill_existing_servers(&self) -> Result<()> {
         println!("{}", "🔍 Checking for existing llama-server instances...".cyan());
 
         // Use pkill to kill all llama-server processes
-        let output = Command::new("pkill")
+        let output = tokio::process::Command::new("pkill")
             .arg("-9")
             .arg("llama-server")
-            .output();
+            .output()
+            .await;
 
         match output {
             Ok(out) if out.status.success() => {
@@ -39,31 +112,51 @@ impl ServerManager {
 
         // Also check for any process on the configured port
         let port = 8080; // Default port
-        let output = Command::new("lsof")
+        let output = tokio::process::Command::new("lsof")
             .arg("-ti")
             .arg(format!(":{}", port))
-            .output();
+            .output()
+            .await;
 
         if let Ok(out) = output {
             if let Ok(pids) = String::from_utf8(out.stdout) {
                 for pid in pids.lines() {
-                    let _ = Command::new("kill")
+                    let _ = tokio::process::Command::new("kill")
                         .arg("-9")
                         .arg(pid.trim())
-                        .output();
+                        .output()
+                        .await;
                 }
             }
         }
 
         // Give processes time to die
-        std::thread::sleep(Duration::from_millis(500));
+        sleep(Duration::from_millis(500)).await;
 
         Ok(())
     }
 
-    /// Start the model server in the background
+    /// Start the model server in the background, or reuse the one an earlier
+    /// run started when it serves the same model with the same settings
     pub async fn start_server(&mut self) -> Result<String> {
-        self.kill_existing_servers()?;
+        let port = 8080;
+        let server_url = format!("http://localhost:{}", port);
+        // Checking the recorded servers runs `kill -0` on each
+        let config = self.config.clone();
+        if let Some(server) = processes::blocking(move || RunningServer::reusable(port, &config)).await {
+            if Self::wait_for_health(&server_url, Duration::from_secs(2)).await.is_ok() {
+                println!(
+                    "{} {} (pid {}, started {})",
+                    "♻️  Reusing llama-server for".green(),
+                    server.preset.as_deref().unwrap_or(&server.model).yellow(),
+                    server.pid,
+                    server.started_at.with_timezone(&chrono::Local).format("%H:%M")
+                );
+                return Ok(server_url);
+            }
+        }
+
+        self.kill_existing_servers().await?;
 
         println!("{}", "🚀 Starting llama-server...".green().bold());
 
@@ -76,21 +169,21 @@ impl ServerManager {
 
         // Find the model
         let models_dir = shellexpand::tilde(&self.config.llamacpp.models_dir).to_string();
-        let model_files = std::fs::read_dir(&models_dir)
-            .context("Failed to read models directory")?
-            .filter_map(|e| e.ok())
-            .filter(|e| {
-                e.path()
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    == Some("gguf")
-            })
-            .collect::<Vec<_>>();
+        let dir = models_dir.clone();
+        let model_files = processes::blocking(move || -> Result<Vec<PathBuf>> {
+            Ok(fs::read_dir(&dir)
+                .context("Failed to read models directory")?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("gguf"))
+                .collect())
+        })
+        .await?;
 
         let model_path = model_files
             .first()
             .ok_or_else(|| anyhow::anyhow!("No GGUF models found in {}", models_dir))?
-            .path();
+            .clone();
 
         let model_name = model_path
             .file_stem()
@@ -101,7 +194,6 @@ impl ServerManager {
         println!("{} {}", "🔧 Binary:".cyan(), binary.cyan());
 
         let cfg = &self.config.llamacpp;
-        let port = 8080;
 
         println!();
         println!("{}", "Configuration:".cyan().bold());
@@ -165,34 +257,57 @@ impl ServerManager {
             .spawn()
             .context("Failed to start llama-server")?;
 
+        let (pid, config) = (child.id(), self.config.clone());
+        if let Err(e) = processes::blocking(move || RunningServer::record(port, pid, &config)).await {
+            eprintln!("⚠️  Could not record the server for reuse: {}", e);
+        }
+
         // Don't store the process - let it run independently
         // This prevents it from being killed when ServerManager is dropped
         std::mem::forget(child);
 
         println!("{}", "⏳ Waiting for server to be ready...".yellow());
 
-        // Wait for server to be ready
-        let client = reqwest::Client::new();
-        let server_url = format!("http://localhost:{}", port);
+        Self::wait_for_health(&server_url, Duration::from_secs(30)).await?;
 
-        for i in 0..30 {
-            sleep(Duration::from_secs(1)).await;
+        println!("{}", "✓ Server is ready!".green().bold());
+        println!("{} {}", "🌐 URL:".cyan(), server_url.green());
+        println!();
+        Ok(server_url)
+    }
+
+    /// Poll `/health` until the server reports ready. llama-server answers 503
+    /// while the model is still loading, so this covers load time too.
+    pub async fn wait_for_health(url: &str, timeout: Duration) -> Result<()> {
+        let client = reqwest::Client::new();
+        let started = std::time::Instant::now();
 
-            if let Ok(response) = client.get(&format!("{}/health", server_url)).send().await {
+        while started.elapsed() < timeout {
+            if let Ok(response) = client.get(format!("{}/health", url)).send().await {
                 if response.status().is_success() {
-                    println!("{}", "✓ Server is ready!".green().bold());
-                    println!("{} {}", "🌐 URL:".cyan(), server_url.green());
-                    println!();
-                    return Ok(server_url);
+                    return Ok(());
                 }
             }
+            sleep(Duration::from_millis(250)).await;
+        }
+
+        anyhow::bail!("Server at {} was not ready within {}s", url, timeout.as_secs())
+    }
+
+    /// Wait until a server being shut down stops answering, so a health check
+    /// right after a restart cannot hit the old process
+    pub async fn wait_for_shutdown(url: &str, timeout: Duration) -> Result<()> {
+        let client = reqwest::Client::new();
+        let started = std::time::Instant::now();
 
-            if i % 5 == 0 && i > 0 {
-                println!("  Still waiting... ({}s)", i);
+        while started.elapsed() < timeout {
+            if client.get(format!("{}/health", url)).send().await.is_err() {
+                return Ok(());
             }
+            sleep(Duration::from_millis(100)).await;
         }
 
-        anyhow::bail!("Server failed to start within 30 seconds")
+        anyhow::bail!("Server at {} did not shut down within {}s", url, timeout.as_secs())
     }
 
     /// Check if server is running
@@ -200,7 +315,7 @@ impl ServerManager {
     pub async fn is_server_running(&self, url: &str) -> bool {
         let client = reqwest::Client::new();
         client
-            .get(&format!("{}/health", url))
+            .get(format!("{}/health", url))
             .send()
             .await
             .map(|r| r.status().is_success())
@@ -2,9 +2,11 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use chrono::{DateTime, Utc};
+use std::sync::OnceLock;
+use chrono::{DateTime, Local, Utc};
 
 use super::conversation::Conversation;
+use super::transcript;
 use crate::config::Config;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,18 +16,116 @@ pub struct Session {
     pub updated_at: DateTime<Utc>,
     pub conversation: Conversation,
     pub working_directory: PathBuf,
+    /// Given with --session, so the session can be resumed by name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
+static NAME: OnceLock<String> = OnceLock::new();
+
 impl Session {
     pub fn new(working_directory: PathBuf) -> Self {
         let now = Utc::now();
-        Self {
+        let mut session = Self {
             id: format!("{}", now.timestamp()),
             created_at: now,
             updated_at: now,
             conversation: Conversation::new(),
             working_directory,
+            name: NAME.get().cloned(),
+        };
+        session.start_transcript("started");
+        session
+    }
+
+    /// Name the sessions this run starts (from --session)
+    pub fn set_name(name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+            anyhow::bail!("Session name '{}' must contain a letter; numbers are session IDs", name);
+        }
+        if name.contains(['/', '\\']) {
+            anyhow::bail!("Session name '{}' cannot contain slashes", name);
         }
+        let _ = NAME.set(name.to_string());
+        Ok(())
+    }
+
+    /// The session named with --session when it exists, so running with
+    /// the same name again continues it; otherwise a new session
+    pub fn open(working_directory: PathBuf) -> Result<Self> {
+        if let Some(mut session) = NAME.get().map(|name| Self::find_named(name)).transpose()?.flatten() {
+            session.resume_transcript();
+            return Ok(session);
+        }
+        Ok(Self::new(working_directory))
+    }
+
+    /// The most recent session called `name`
+    pub fn find_named(name: &str) -> Result<Option<Self>> {
+        Ok(Self::list_sessions()?
+            .into_iter()
+            .find(|session| session.name.as_deref() == Some(name))
+            .map(|mut session| {
+                session.conversation.recalculate_tokens();
+                session
+            }))
+    }
+
+    /// The name if there is one, otherwise the ID
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Keep transcribing a saved session that is being continued
+    pub fn resume_transcript(&mut self) {
+        self.start_transcript("resumed");
+    }
+
+    fn start_transcript(&mut self, how: &str) {
+        let Some(path) = transcript::path_for(&self.id) else {
+            return;
+        };
+        let heading = format!(
+            "\n# vork session {} · {} {} in {}\n",
+            self.id,
+            how,
+            Local::now().format("%Y-%m-%d %H:%M"),
+            self.working_directory.display()
+        );
+        if transcript::append(&path, &heading).is_ok() {
+            self.conversation.tee_to(path);
+        }
+    }
+
+    /// Directory tools resolve relative paths against: where the session
+    /// started, unless that directory is gone
+    pub fn tool_dir(&self) -> PathBuf {
+        if self.working_directory.is_dir() {
+            return self.working_directory.clone();
+        }
+        std::env::current_dir().unwrap_or_else(|_| self.working_directory.clone())
+    }
+
+    /// Warning for a session continued somewhere other than where it started
+    pub fn working_dir_notice(&self) -> Option<String> {
+        let current = std::env::current_dir().ok()?;
+        if current == self.working_directory {
+            return None;
+        }
+        Some(if self.working_directory.is_dir() {
+            format!(
+                "⚠️  This session was started in {}; tools resolve relative paths there, not in {}",
+                self.working_directory.display(),
+                current.display()
+            )
+        } else {
+            format!(
+                "⚠️  This session was started in {}, which no longer exists; tools use {}",
+                self.working_directory.display(),
+                current.display()
+            )
+        })
     }
 
     pub fn sessions_dir() -> Result<PathBuf> {
@@ -50,10 +150,17 @@ impl Session {
         Ok(())
     }
 
+    /// A session by ID or by the name it was given with --session
     pub fn load(session_id: &str) -> Result<Self> {
         let path = Self::sessions_dir()?.join(format!("{}.json", session_id));
+        if !path.exists() {
+            if let Some(session) = Self::find_named(session_id)? {
+                return Ok(session);
+            }
+        }
         let json = fs::read_to_string(path)?;
-        let session = serde_json::from_str(&json)?;
+        let mut session: Session = serde_json::from_str(&json)?;
+        session.conversation.recalculate_tokens();
         Ok(session)
     }
 
@@ -80,7 +187,7 @@ impl Session {
         }
 
         // Sort by updated_at, most recent first
-        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
 
         Ok(sessions)
     }
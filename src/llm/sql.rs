@@ -0,0 +1,448 @@
+use anyhow::{Context, Result};
+use reqwest::Url;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::{environment, processes};
+use crate::config::ProjectConfig;
+
+/// First keywords of statements that only read
+const READ_STATEMENTS: &[&str] = &["select", "with", "explain", "show", "values", "pragma", "describe", "desc", "table"];
+
+/// Statements whose rows can be limited on the server side
+const ROW_STATEMENTS: &[&str] = &["select", "with", "values", "table"];
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cells longer than this are cut in the result table
+const MAX_CELL: usize = 200;
+
+pub const DEFAULT_ROWS: usize = 100;
+pub const MAX_ROWS: usize = 1000;
+
+enum Engine {
+    Sqlite(PathBuf),
+    Postgres(String),
+    Mysql(Url),
+}
+
+/// A database sql_query may use: a `[databases]` entry from .vork.toml or
+/// a SQLite file in the workspace
+pub struct Database {
+    name: String,
+    engine: Engine,
+    pub read_only: bool,
+    working_dir: PathBuf,
+}
+
+impl Database {
+    pub fn resolve(name: &str, working_dir: &Path) -> Result<Self> {
+        let project = ProjectConfig::load(working_dir)?;
+        if let Some(config) = project.databases.get(name) {
+            return Ok(Self {
+                name: name.to_string(),
+                engine: Engine::parse(&config.url, working_dir)?,
+                read_only: config.read_only,
+                working_dir: working_dir.to_path_buf(),
+            });
+        }
+        let path = working_dir.join(name);
+        if path.is_file() {
+            // Writes need a [databases] entry with read_only = false
+            return Ok(Self {
+                name: name.to_string(),
+                engine: Engine::Sqlite(path),
+                read_only: true,
+                working_dir: working_dir.to_path_buf(),
+            });
+        }
+        let configured: Vec<&str> = project.databases.keys().map(String::as_str).collect();
+        if configured.is_empty() {
+            anyhow::bail!(
+                "{} is neither a SQLite file nor a database in .vork.toml; add it under [databases.<name>] with a url",
+                name
+            );
+        }
+        anyhow::bail!("{} is neither a SQLite file nor a configured database ({})", name, configured.join(", "))
+    }
+
+    /// Name and engine, for results and approval prompts
+    pub fn describe(&self) -> String {
+        let engine = match self.engine {
+            Engine::Sqlite(_) => "sqlite",
+            Engine::Postgres(_) => "postgres",
+            Engine::Mysql(_) => "mysql",
+        };
+        format!("{} ({})", self.name, engine)
+    }
+
+    /// Run one statement and format its rows as a markdown table, at most
+    /// `max_rows` of them
+    pub fn query(&self, sql: &str, max_rows: usize) -> Result<String> {
+        let sql = single_statement(sql)?;
+        let keyword = first_keyword(sql);
+        if self.read_only && !is_read(sql) {
+            anyhow::bail!(
+                "{} is read-only; only SELECT, WITH, EXPLAIN, SHOW, PRAGMA and similar statements are allowed",
+                self.describe()
+            );
+        }
+        let limited = ROW_STATEMENTS.contains(&keyword.as_str());
+        // One extra row says whether there are more
+        let fetch = max_rows + 1;
+
+        // The clients get the same scrubbed environment as bash_exec; the
+        // settings below are added after it
+        let client = |program: &str| -> Result<Command> {
+            let mut command = Command::new(program);
+            environment::apply(command.current_dir(&self.working_dir), &self.working_dir)?;
+            Ok(command)
+        };
+        let (mut command, format) = match &self.engine {
+            Engine::Sqlite(path) => {
+                let mut command = client("sqlite3")?;
+                // -safe turns off writefile(), edit(), load_extension() and
+                // the like, which -readonly does not
+                command.arg("-safe");
+                if self.read_only {
+                    command.arg("-readonly");
+                }
+                let sql = match limited {
+                    // On its own line so a trailing -- comment ends there
+                    true => format!("SELECT * FROM (\n{}\n) LIMIT {}", sql, fetch),
+                    false => sql.to_string(),
+                };
+                command.args(["-bail", "-csv", "-header"]).arg(path).arg(sql);
+                (command, Format::Csv)
+            }
+            Engine::Postgres(url) => {
+                let mut command = client("psql")?;
+                command.arg(url).args(["-X", "--csv", "-v", "ON_ERROR_STOP=1"]).env("PGCONNECT_TIMEOUT", "10");
+                if self.read_only {
+                    command.env("PGOPTIONS", "-c default_transaction_read_only=on");
+                }
+                if limited {
+                    // A cursor stops after `fetch` rows whatever the query's ORDER BY
+                    command
+                        .arg("-q")
+                        .args(["-c", "BEGIN"])
+                        .args(["-c", &format!("DECLARE vork_rows NO SCROLL CURSOR FOR\n{}", sql)])
+                        .args(["-c", &format!("FETCH {} FROM vork_rows", fetch)]);
+                } else {
+                    command.args(["-c", sql]);
+                }
+                (command, Format::Csv)
+            }
+            Engine::Mysql(url) => {
+                let mut command = client("mysql")?;
+                command.args(["--batch", "--connect-timeout=10"]);
+                if let Some(host) = url.host_str() {
+                    command.args(["-h", host]);
+                }
+                if let Some(port) = url.port() {
+                    command.arg(format!("-P{}", port));
+                }
+                if !url.username().is_empty() {
+                    command.args(["-u", url.username()]);
+                }
+                if let Some(password) = url.password() {
+                    command.env("MYSQL_PWD", password);
+                }
+                let mut init = format!("SET SESSION sql_select_limit = {}", fetch);
+                if self.read_only {
+                    init.push_str("; SET SESSION TRANSACTION READ ONLY");
+                }
+                command.arg(format!("--init-command={}", init));
+                let database = url.path().trim_start_matches('/');
+                if !database.is_empty() {
+                    command.arg(database);
+                }
+                command.arg("-e").arg(mysql_safe(sql)?);
+                (command, Format::Tsv)
+            }
+        };
+
+        let program = command.get_program().to_string_lossy().into_owned();
+        let (output, timed_out) =
+            match processes::output_within(processes::mark(&mut command).stdin(Stdio::null()), Some(QUERY_TIMEOUT)) {
+                Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::NotFound) => {
+                    anyhow::bail!("sql_query needs the {} command-line client, which is not in PATH", program)
+                }
+                result => result.with_context(|| format!("Failed to run {}", program))?,
+            };
+        if timed_out {
+            anyhow::bail!("the query was stopped after {}s", QUERY_TIMEOUT.as_secs());
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("{} error: {}", program, stderr.trim());
+        }
+
+        let rows = match format {
+            Format::Csv => parse_csv(&stdout),
+            Format::Tsv => parse_tsv(&stdout),
+        };
+        let Some((header, rows)) = rows.split_first().filter(|_| !stdout.trim().is_empty()) else {
+            return Ok(format!("🗄️ {}: statement completed with no rows", self.describe()));
+        };
+        // Statements without a result set (e.g. psql's "UPDATE 3" tag) are shown as is
+        if rows.is_empty() && header.len() == 1 && !limited {
+            return Ok(format!("🗄️ {}: {}", self.describe(), stdout.trim()));
+        }
+        Ok(table(&self.describe(), header, rows, max_rows))
+    }
+}
+
+impl Engine {
+    fn parse(url: &str, working_dir: &Path) -> Result<Self> {
+        if let Some(path) = url.strip_prefix("sqlite://").or_else(|| url.strip_prefix("sqlite:")) {
+            return Ok(Engine::Sqlite(working_dir.join(path)));
+        }
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            return Ok(Engine::Postgres(url.to_string()));
+        }
+        if url.starts_with("mysql://") || url.starts_with("mariadb://") {
+            return Ok(Engine::Mysql(Url::parse(url).context("Invalid mysql url")?));
+        }
+        anyhow::bail!("Unsupported database url {} (use sqlite://, postgres:// or mysql://)", url)
+    }
+}
+
+enum Format {
+    Csv,
+    Tsv,
+}
+
+/// Whether the statement only reads
+pub fn is_read(sql: &str) -> bool {
+    READ_STATEMENTS.contains(&first_keyword(sql).as_str())
+}
+
+/// The query without its trailing semicolon; several statements (or a
+/// client command like psql's \! or sqlite3's .shell) are refused
+fn single_statement(sql: &str) -> Result<&str> {
+    let sql = sql.trim();
+    if sql.starts_with('\\') || sql.starts_with('.') {
+        anyhow::bail!("client commands are not allowed; send one SQL statement");
+    }
+    let mut end = sql.len();
+    for (at, c) in unquoted(sql) {
+        if c == ';' {
+            if !sql[at + 1..].trim().is_empty() {
+                anyhow::bail!("send one SQL statement per sql_query call");
+            }
+            end = at;
+            break;
+        }
+    }
+    let sql = sql[..end].trim();
+    if sql.is_empty() {
+        anyhow::bail!("the query is empty");
+    }
+    Ok(sql)
+}
+
+/// The mysql client runs its own commands (`\!`, or `system` at the start
+/// of a line) found in -e text. Its quoting differs from [`unquoted`] in
+/// backslash escapes, `#` comments and `--` needing a space, so those are
+/// refused anywhere and the statement is put on one line.
+fn mysql_safe(sql: &str) -> Result<String> {
+    if sql.contains('\\') || sql.contains('#') || sql.contains("--") {
+        anyhow::bail!("mysql statements may not contain backslashes, # or --; write it without escapes or comments");
+    }
+    let outside: Vec<usize> = unquoted(sql).into_iter().map(|(at, _)| at).collect();
+    let mut safe = String::with_capacity(sql.len());
+    for (at, c) in sql.char_indices() {
+        match c {
+            '\n' | '\r' if outside.binary_search(&at).is_err() => {
+                anyhow::bail!("mysql statements may not have line breaks inside quotes or comments")
+            }
+            '\n' | '\r' => safe.push(' '),
+            _ => safe.push(c),
+        }
+    }
+    Ok(safe)
+}
+
+/// Characters outside string literals, quoted identifiers and comments
+fn unquoted(sql: &str) -> Vec<(usize, char)> {
+    let mut chars = sql.char_indices().peekable();
+    let mut found = vec![];
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                // Doubled quotes are escapes and simply reopen the literal
+                for (_, inner) in chars.by_ref() {
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek().is_some_and(|(_, next)| *next == '-') => {
+                for (_, inner) in chars.by_ref() {
+                    if inner == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '*') => {
+                chars.next();
+                let mut star = false;
+                for (_, inner) in chars.by_ref() {
+                    if star && inner == '/' {
+                        break;
+                    }
+                    star = inner == '*';
+                }
+            }
+            _ => found.push((at, c)),
+        }
+    }
+    found
+}
+
+/// Lowercased first word, skipping comments and opening parentheses
+fn first_keyword(sql: &str) -> String {
+    let mut keyword = String::new();
+    let text: Vec<(usize, char)> = unquoted(sql);
+    for (_, c) in text.iter().skip_while(|(_, c)| c.is_whitespace() || *c == '(') {
+        if !c.is_alphabetic() {
+            break;
+        }
+        keyword.push(c.to_ascii_lowercase());
+    }
+    keyword
+}
+
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// mysql --batch output: tab-separated with backslash escapes
+fn parse_tsv(text: &str) -> Vec<Vec<String>> {
+    text.lines()
+        .map(|line| {
+            line.split('\t')
+                .map(|field| {
+                    let mut value = String::new();
+                    let mut chars = field.chars();
+                    while let Some(c) = chars.next() {
+                        if c != '\\' {
+                            value.push(c);
+                            continue;
+                        }
+                        match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('0') => value.push('\0'),
+                            Some(other) => value.push(other),
+                            None => value.push('\\'),
+                        }
+                    }
+                    value
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn table(database: &str, header: &[String], rows: &[Vec<String>], max_rows: usize) -> String {
+    let cell = |value: &str| {
+        let value = value.replace('|', "\\|").replace('\n', " ");
+        match value.chars().count() > MAX_CELL {
+            true => format!("{}…", value.chars().take(MAX_CELL - 1).collect::<String>()),
+            false => value,
+        }
+    };
+    let shown = &rows[..rows.len().min(max_rows)];
+    let mut text = match rows.len() > max_rows {
+        true => format!("🗄️ {}: first {} rows (there are more)\n\n", database, max_rows),
+        false => format!("🗄️ {}: {} row{}\n\n", database, shown.len(), if shown.len() == 1 { "" } else { "s" }),
+    };
+    text.push_str(&format!("| {} |\n", header.iter().map(|h| cell(h)).collect::<Vec<_>>().join(" | ")));
+    text.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+    for row in shown {
+        text.push_str(&format!("| {} |\n", row.iter().map(|v| cell(v)).collect::<Vec<_>>().join(" | ")));
+    }
+    if rows.len() > max_rows {
+        text.push_str(&format!(
+            "\nNarrow the query (WHERE, LIMIT) or raise max_rows (up to {}) to see more.",
+            MAX_ROWS
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_are_told_from_writes_by_the_first_keyword() {
+        assert!(is_read("SELECT * FROM users"));
+        assert!(is_read("  -- latest first\n(WITH recent AS (SELECT 1) SELECT * FROM recent)"));
+        assert!(is_read("/* why */ explain select 1"));
+        assert!(!is_read("DELETE FROM users"));
+        assert!(!is_read("'select' ; drop table users"));
+        assert!(!is_read("selectx"));
+    }
+
+    #[test]
+    fn one_statement_at_a_time() {
+        assert_eq!(single_statement("  SELECT 1;  ").unwrap(), "SELECT 1");
+        assert_eq!(single_statement("SELECT ';' AS semi").unwrap(), "SELECT ';' AS semi");
+        assert_eq!(single_statement("SELECT 1 -- ; not a second one").unwrap(), "SELECT 1 -- ; not a second one");
+        assert!(single_statement("SELECT 1; DROP TABLE users").is_err());
+        assert!(single_statement("\\! rm -rf /").is_err());
+        assert!(single_statement(".shell rm -rf /").is_err());
+        assert!(single_statement(" ; ").is_err());
+    }
+
+    #[test]
+    fn quotes_and_comments_are_skipped() {
+        let outside = |sql: &str| unquoted(sql).into_iter().map(|(_, c)| c).collect::<String>();
+        assert_eq!(outside("a 'b;c' d"), "a  d");
+        assert_eq!(outside("a 'it''s' b"), "a  b");
+        assert_eq!(outside("a \"x\" `y` b"), "a   b");
+        assert_eq!(outside("a -- b\nc"), "a c");
+        assert_eq!(outside("a /* b * / */c"), "a c");
+    }
+
+    #[test]
+    fn mysql_client_commands_cannot_be_hidden() {
+        assert_eq!(mysql_safe("SELECT id\nFROM users\r\nWHERE name = 'a'").unwrap(), "SELECT id FROM users  WHERE name = 'a'");
+        assert!(mysql_safe("SELECT 'a\\''\n\\! touch pwned").is_err());
+        assert!(mysql_safe("SELECT 1 # '\n\\! touch pwned\n-- '").is_err());
+        assert!(mysql_safe("SELECT 1--1\nsystem touch pwned").is_err());
+        assert!(mysql_safe("SELECT 'two\nlines'").is_err());
+        assert!(mysql_safe("SELECT 1 /* a\nb */").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::client::Usage;
+use crate::config::{Config, ModelPricing};
+
+/// One line of `~/.vork/stats.jsonl`, written when a session or run ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    /// Entry point that produced the usage (tui, exec, ask, fix, ...)
+    pub command: String,
+    pub project: PathBuf,
+    pub model: String,
+    #[serde(default)]
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub agent: Option<String>,
+    #[serde(default)]
+    pub requests: usize,
+    #[serde(default)]
+    pub prompt_tokens: usize,
+    #[serde(default)]
+    pub completion_tokens: usize,
+    #[serde(default)]
+    pub total_tokens: usize,
+    #[serde(default)]
+    pub tool_calls: usize,
+    #[serde(default)]
+    pub wall_time_secs: f64,
+    /// Estimated cost in USD, when the model has a pricing entry
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+impl UsageRecord {
+    pub fn stats_path() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("stats.jsonl"))
+    }
+
+    pub fn append(&self) -> Result<()> {
+        fs::create_dir_all(Config::config_dir()?)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::stats_path()?)
+            .context("Failed to open stats file")?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Load every record, skipping lines that fail to parse
+    pub fn load_all() -> Result<Vec<Self>> {
+        let path = Self::stats_path()?;
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let content = fs::read_to_string(&path).context("Failed to read stats file")?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Total recorded cost for the current calendar month (UTC)
+    pub fn month_to_date_cost() -> Result<f64> {
+        let now = Utc::now();
+        Ok(Self::load_all()?
+            .iter()
+            .filter(|r| r.timestamp.year() == now.year() && r.timestamp.month() == now.month())
+            .map(|r| r.cost_usd)
+            .sum())
+    }
+}
+
+/// Accumulates usage over a session and appends it to the stats file when dropped
+pub struct UsageTracker {
+    started: Instant,
+    record: UsageRecord,
+    pricing: Option<ModelPricing>,
+    session_budget: Option<f64>,
+    monthly_budget: Option<f64>,
+    /// Cost already recorded this month before this session started
+    month_spent: f64,
+}
+
+impl UsageTracker {
+    pub fn new(command: &str, session_id: &str, model: &str) -> Self {
+        let cost = Config::load().map(|c| c.cost).unwrap_or_default();
+        let pricing = cost.pricing.get(model).cloned();
+        let month_spent = match (&pricing, cost.monthly_budget) {
+            (Some(_), Some(_)) => UsageRecord::month_to_date_cost().unwrap_or(0.0),
+            _ => 0.0,
+        };
+
+        Self {
+            started: Instant::now(),
+            record: UsageRecord {
+                timestamp: Utc::now(),
+                session_id: session_id.to_string(),
+                command: command.to_string(),
+                project: env::current_dir().unwrap_or_default(),
+                model: model.to_string(),
+                preset: None,
+                agent: None,
+                requests: 0,
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+                tool_calls: 0,
+                wall_time_secs: 0.0,
+                cost_usd: 0.0,
+            },
+            pricing,
+            session_budget: cost.session_budget,
+            monthly_budget: cost.monthly_budget,
+            month_spent,
+        }
+    }
+
+    pub fn set_agent(&mut self, agent: Option<&str>) {
+        self.record.agent = agent.map(String::from);
+    }
+
+    /// Record the run against `dir` rather than the current directory
+    pub fn set_project(&mut self, dir: &Path) {
+        self.record.project = dir.to_path_buf();
+    }
+
+    pub fn set_preset(&mut self, preset: Option<&str>) {
+        self.record.preset = preset.map(String::from);
+    }
+
+    /// Count one completion request and the usage the server reported for it
+    pub fn record_response(&mut self, usage: Option<&Usage>) {
+        self.record.requests += 1;
+        if let Some(usage) = usage {
+            self.record.prompt_tokens += usage.prompt_tokens;
+            self.record.completion_tokens += usage.completion_tokens;
+            self.record.total_tokens += usage.total_tokens;
+            if let Some(pricing) = &self.pricing {
+                self.record.cost_usd += pricing.cost(usage.prompt_tokens, usage.completion_tokens);
+            }
+        }
+    }
+
+    /// Running cost of this session, or None for models without pricing
+    pub fn cost(&self) -> Option<f64> {
+        self.pricing.as_ref().map(|_| self.record.cost_usd)
+    }
+
+    /// Fail if the session or monthly budget is spent; call before each request
+    pub fn check_budget(&self) -> Result<()> {
+        if self.pricing.is_none() {
+            return Ok(());
+        }
+        if let Some(budget) = self.session_budget {
+            if self.record.cost_usd >= budget {
+                anyhow::bail!(
+                    "Session budget of ${:.2} reached (spent ${:.4})",
+                    budget,
+                    self.record.cost_usd
+                );
+            }
+        }
+        if let Some(budget) = self.monthly_budget {
+            let spent = self.month_spent + self.record.cost_usd;
+            if spent >= budget {
+                anyhow::bail!("Monthly budget of ${:.2} reached (spent ${:.4})", budget, spent);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record_tool_call(&mut self) {
+        self.record.tool_calls += 1;
+    }
+}
+
+impl Drop for UsageTracker {
+    /// Append the record to the stats file, including when a run ends in an
+    /// error. Sessions with no requests are skipped.
+    fn drop(&mut self) {
+        if self.record.requests == 0 {
+            return;
+        }
+        self.record.timestamp = Utc::now();
+        self.record.wall_time_secs = self.started.elapsed().as_secs_f64();
+        let _ = self.record.append();
+    }
+}
@@ -0,0 +1,155 @@
+//! Reusable prompts kept as ~/.vork/prompts/<name>.md, with `{{variable}}`
+//! placeholders filled in when they are used: `/prompt <name> [args]` in
+//! the TUI and `vork ask --template <name> --var key=value`.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+/// The placeholder that takes the free text after the variables; without
+/// one in the template, the text is added at the end
+pub const INPUT: &str = "input";
+
+pub struct Template {
+    pub name: String,
+    text: String,
+}
+
+impl Template {
+    pub fn dir() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("prompts"))
+    }
+
+    pub fn load(name: &str) -> Result<Self> {
+        let path = Self::dir()?.join(format!("{}.md", name));
+        if !path.is_file() {
+            let names: Vec<String> = Self::list()?.into_iter().map(|template| template.name).collect();
+            anyhow::bail!(
+                "No prompt template '{}' in {} ({})",
+                name,
+                Self::dir()?.display(),
+                if names.is_empty() { "there are none yet".to_string() } else { format!("have: {}", names.join(", ")) }
+            );
+        }
+        let text = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self { name: name.to_string(), text })
+    }
+
+    /// Every template, by name
+    pub fn list() -> Result<Vec<Self>> {
+        let Ok(entries) = fs::read_dir(Self::dir()?) else {
+            return Ok(vec![]);
+        };
+        let mut templates: Vec<Self> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?.to_string();
+                Some(Self { name, text: fs::read_to_string(&path).ok()? })
+            })
+            .collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// The first line, for listings
+    pub fn summary(&self) -> &str {
+        self.text.lines().map(|line| line.trim_start_matches('#').trim()).find(|line| !line.is_empty()).unwrap_or("")
+    }
+
+    /// The variables it asks for, in order of first use, without `input`
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = vec![];
+        for (_, name, _) in placeholders(&self.text) {
+            if name != INPUT && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The prompt with every placeholder filled in. A variable the template
+    /// asks for must be given, and one it does not know is taken for a typo.
+    pub fn render(&self, vars: &BTreeMap<String, String>, input: &str) -> Result<String> {
+        let wanted = self.variables();
+        let missing: Vec<&str> = wanted.iter().copied().filter(|name| !vars.contains_key(*name)).collect();
+        if !missing.is_empty() {
+            let example: Vec<String> = missing.iter().map(|name| format!("{}=...", name)).collect();
+            anyhow::bail!("Template '{}' needs {} (pass {})", self.name, missing.join(", "), example.join(" "));
+        }
+        if let Some(unknown) = vars.keys().find(|name| !wanted.contains(&name.as_str())) {
+            let takes = if wanted.is_empty() { "no variables".to_string() } else { wanted.join(", ") };
+            anyhow::bail!("Template '{}' has no {{{{{}}}}} (it takes {})", self.name, unknown, takes);
+        }
+
+        let mut text = String::new();
+        let mut rest = 0;
+        let mut used_input = false;
+        for (start, name, end) in placeholders(&self.text) {
+            text.push_str(&self.text[rest..start]);
+            if name == INPUT {
+                text.push_str(input);
+                used_input = true;
+            } else {
+                text.push_str(&vars[name]);
+            }
+            rest = end;
+        }
+        text.push_str(&self.text[rest..]);
+
+        let mut text = text.trim_end().to_string();
+        if !used_input && !input.trim().is_empty() {
+            text.push_str("\n\n");
+            text.push_str(input.trim());
+        }
+        Ok(text)
+    }
+}
+
+/// Each `{{name}}` in `text`: where it starts, the name and where it ends.
+/// Braces around anything but a plain name are left alone.
+fn placeholders(text: &str) -> Vec<(usize, &str, usize)> {
+    let mut found = vec![];
+    let mut from = 0;
+    while let Some(open) = text[from..].find("{{").map(|i| i + from) {
+        let Some(close) = text[open..].find("}}").map(|i| i + open) else {
+            break;
+        };
+        let name = text[open + 2..close].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            found.push((open, name, close + 2));
+            from = close + 2;
+        } else {
+            from = open + 2;
+        }
+    }
+    found
+}
+
+/// `key=value`, as given to `--var`
+pub fn parse_var(arg: &str) -> Result<(String, String)> {
+    let (key, value) = arg.split_once('=').with_context(|| format!("Expected key=value, got '{}'", arg))?;
+    Ok((key.trim().to_string(), value.to_string()))
+}
+
+/// `/prompt` arguments: leading `key=value` words are variables, the rest
+/// is the input text
+pub fn parse_args(args: &str) -> (BTreeMap<String, String>, String) {
+    let mut vars = BTreeMap::new();
+    let mut rest = args.trim_start();
+    while let Some(word) = rest.split_whitespace().next() {
+        let Ok((key, value)) = parse_var(word) else {
+            break;
+        };
+        if key.is_empty() {
+            break;
+        }
+        vars.insert(key, value);
+        rest = rest[word.len()..].trim_start();
+    }
+    (vars, rest.to_string())
+}
@@ -0,0 +1,87 @@
+// Always the clipboard of the machine vork runs on, remote or not
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Approval, Tool, ToolContext};
+
+pub struct ClipboardRead;
+
+#[async_trait]
+impl Tool for ClipboardRead {
+    fn name(&self) -> &str {
+        "clipboard_read"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "clipboard_read",
+                "description": "Read the text on the user's clipboard, e.g. an error message they just copied",
+                "parameters": {
+                    "type": "object",
+                    "properties": {}
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, _arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        Ok(Some(Approval::Clipboard { write: false }))
+    }
+
+    async fn execute(&self, _arguments: &Value, _context: &ToolContext) -> Result<String> {
+        let text = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .map_err(|e| anyhow::anyhow!("Failed to read the clipboard: {}", e))?;
+        if text.is_empty() {
+            return Ok("📋 The clipboard is empty".to_string());
+        }
+        Ok(format!("📋 Clipboard ({} characters):\n\n{}", text.chars().count(), text))
+    }
+}
+
+pub struct ClipboardWrite;
+
+#[async_trait]
+impl Tool for ClipboardWrite {
+    fn name(&self) -> &str {
+        "clipboard_write"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "clipboard_write",
+                "description": "Put text on the user's clipboard, replacing what is there",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "text": {
+                            "type": "string",
+                            "description": "The text to copy"
+                        }
+                    },
+                    "required": ["text"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, _arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        Ok(Some(Approval::Clipboard { write: true }))
+    }
+
+    async fn execute(&self, arguments: &Value, _context: &ToolContext) -> Result<String> {
+        let text = arguments["text"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'text' parameter"))?;
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|e| anyhow::anyhow!("Failed to write the clipboard: {}", e))?;
+        Ok(format!("📋 Copied {} characters to the clipboard", text.chars().count()))
+    }
+}
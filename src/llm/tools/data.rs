@@ -0,0 +1,251 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Approval, Tool, ToolContext};
+use crate::llm::findings::{self, Added, Findings, Status};
+use crate::llm::sql::{self, Database};
+
+pub struct SqlQuery;
+
+#[async_trait]
+impl Tool for SqlQuery {
+    fn name(&self) -> &str {
+        "sql_query"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "sql_query",
+                "description": "Run one SQL statement against a SQLite file in the workspace or a database configured in .vork.toml, and get the rows as a table. Read-only unless the database is configured otherwise",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "database": {
+                            "type": "string",
+                            "description": "A name from [databases] in .vork.toml, or the path of a SQLite file"
+                        },
+                        "query": {
+                            "type": "string",
+                            "description": "A single SQL statement"
+                        },
+                        "max_rows": {
+                            "type": "integer",
+                            "description": "Most rows to return (default: 100, at most 1000)"
+                        }
+                    },
+                    "required": ["database", "query"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, context: &ToolContext) -> Result<Option<Approval>> {
+        let (database, query) = target(arguments, context)?;
+        if database.read_only || sql::is_read(query) {
+            return Ok(None);
+        }
+        Ok(Some(Approval::Sql { database: database.describe(), statement: query.trim().to_string() }))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let (database, query) = target(arguments, context)?;
+        let max_rows = arguments["max_rows"].as_u64().map_or(sql::DEFAULT_ROWS, |rows| rows as usize).clamp(1, sql::MAX_ROWS);
+        database.query(query, max_rows)
+    }
+}
+
+/// The database a sql_query call names, and its statement
+fn target<'a>(arguments: &'a Value, context: &ToolContext) -> Result<(Database, &'a str)> {
+    let name = arguments["database"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'database' parameter"))?;
+    let query = arguments["query"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+    if let Some(remote) = context.remote {
+        anyhow::bail!("sql_query runs locally only; use bash_exec to query databases on {}", remote.describe());
+    }
+    Ok((Database::resolve(name, context.working_dir)?, query))
+}
+
+/// Findings are kept with the local session, remote target or not
+pub struct AddFinding;
+
+#[async_trait]
+impl Tool for AddFinding {
+    fn name(&self) -> &str {
+        "add_finding"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "add_finding",
+                "description": "Record an audit finding in the workspace's findings store (.vork/findings.json). Reporting the same issue again updates the existing finding instead of adding a duplicate",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "file": {
+                            "type": "string",
+                            "description": "The file the issue is in"
+                        },
+                        "line": {
+                            "type": "integer",
+                            "description": "The line the issue is on"
+                        },
+                        "severity": {
+                            "type": "string",
+                            "enum": ["error", "warning", "note"],
+                            "description": "error for bugs and vulnerabilities, warning for likely problems, note for the rest"
+                        },
+                        "rule": {
+                            "type": "string",
+                            "description": "Category: stub, unwrap, panic, error-handling, todo, security, performance, quality, or a CWE id"
+                        },
+                        "message": {
+                            "type": "string",
+                            "description": "What is wrong and why, in one or two sentences"
+                        }
+                    },
+                    "required": ["file", "severity", "rule", "message"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let field = |key: &str| {
+            arguments[key]
+                .as_str()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Missing '{}' parameter", key))
+        };
+        let report = findings::Report {
+            file: field("file")?,
+            line: arguments["line"].as_u64().map(|line| line as usize).filter(|line| *line > 0),
+            severity: arguments["severity"].as_str().unwrap_or("warning"),
+            rule: arguments["rule"].as_str().unwrap_or("quality"),
+            message: field("message")?,
+        };
+        let mut store = Findings::load(context.working_dir)?;
+        let (id, added) = store.add(report);
+        store.save()?;
+        let finding = store.get(id).map(|finding| finding.summary()).unwrap_or_default();
+        Ok(match added {
+            Added::New => format!("📌 Recorded {}", finding),
+            Added::Known => format!("📌 Already recorded, updated: {}", finding),
+            Added::Reopened => format!("📌 Reopened (it was marked resolved): {}", finding),
+        })
+    }
+}
+
+pub struct ListFindings;
+
+#[async_trait]
+impl Tool for ListFindings {
+    fn name(&self) -> &str {
+        "list_findings"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "list_findings",
+                "description": "List findings recorded by earlier audits, most severe first",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "status": {
+                            "type": "string",
+                            "enum": ["open", "resolved", "all"],
+                            "description": "Which findings to list (default: open)"
+                        },
+                        "file": {
+                            "type": "string",
+                            "description": "Only findings in files under this path"
+                        },
+                        "severity": {
+                            "type": "string",
+                            "enum": ["error", "warning", "note"],
+                            "description": "Only findings at this severity or worse"
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let status = match arguments["status"].as_str().unwrap_or("open") {
+            "open" => Some(Status::Open),
+            "resolved" => Some(Status::Resolved),
+            "all" => None,
+            other => anyhow::bail!("Unknown status {}; use open, resolved or all", other),
+        };
+        let store = Findings::load(context.working_dir)?;
+        let list = store.list(status, arguments["file"].as_str(), arguments["severity"].as_str());
+        let scope = match status {
+            Some(Status::Open) => "open ",
+            Some(Status::Resolved) => "resolved ",
+            None => "",
+        };
+        if list.is_empty() {
+            return Ok(format!("ℹ️  No {}findings in {}", scope, findings::FINDINGS_FILE));
+        }
+        let lines: Vec<String> = list
+            .iter()
+            .map(|finding| match (&finding.status, &finding.resolution) {
+                (Status::Resolved, Some(note)) => format!("{} (resolved: {})", finding.summary(), note),
+                (Status::Resolved, None) => format!("{} (resolved)", finding.summary()),
+                _ => finding.summary(),
+            })
+            .collect();
+        Ok(format!("📋 {} {}finding{}:\n\n{}", list.len(), scope, if list.len() == 1 { "" } else { "s" }, lines.join("\n")))
+    }
+}
+
+pub struct ResolveFinding;
+
+#[async_trait]
+impl Tool for ResolveFinding {
+    fn name(&self) -> &str {
+        "resolve_finding"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "resolve_finding",
+                "description": "Mark a finding as resolved once the issue is fixed or turns out not to be one",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "id": {
+                            "type": "integer",
+                            "description": "The finding's id from list_findings"
+                        },
+                        "note": {
+                            "type": "string",
+                            "description": "How it was resolved"
+                        }
+                    },
+                    "required": ["id"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let id = arguments["id"].as_u64().ok_or_else(|| anyhow::anyhow!("Missing 'id' parameter"))?;
+        let mut store = Findings::load(context.working_dir)?;
+        let summary = store.resolve(id, arguments["note"].as_str())?.summary();
+        store.save()?;
+        Ok(format!("✅ Resolved {}", summary))
+    }
+}
@@ -0,0 +1,625 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::process::Command;
+
+use super::{inline_output_limit, take_lines, Approval, Tool, ToolContext, OUTPUTS_DIR};
+use crate::llm::remote::shell_quote;
+use crate::llm::workspace;
+
+/// Directories that are never worth showing the model by default
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", ".git"];
+
+fn is_skipped_dir(name: &str) -> bool {
+    SKIPPED_DIRS.contains(&name)
+}
+
+/// Walk `root` the way the file tools see a project: honoring .gitignore
+/// (and .ignore) files and skipping build and VCS directories, unless
+/// `include_ignored` is set. Hidden files are listed either way.
+pub(crate) fn walk(root: &Path, include_ignored: bool) -> ignore::WalkBuilder {
+    let mut builder = ignore::WalkBuilder::new(root);
+    builder.hidden(false).require_git(false).sort_by_file_name(|a, b| a.cmp(b));
+    if include_ignored {
+        builder.standard_filters(false);
+    } else {
+        builder.filter_entry(|entry| {
+            entry.depth() == 0 || !(entry.path().is_dir() && entry.file_name().to_str().is_some_and(is_skipped_dir))
+        });
+    }
+    builder
+}
+
+/// Files passed to one grep when searching
+const SEARCH_CHUNK: usize = 500;
+
+/// The paths, sizes and modification times of `path` and every file under
+/// it that the file tools see, hashed. None when any of them cannot be read.
+fn files_fingerprint(path: &Path) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    for entry in walk(path, false).build() {
+        let entry = entry.ok()?;
+        let metadata = fs::metadata(entry.path()).ok()?;
+        // Adding or removing a file changes the list; a directory's own
+        // time also moves for ignored entries
+        if metadata.is_dir() {
+            continue;
+        }
+        (entry.path(), metadata.len(), metadata.modified().ok()?).hash(&mut hasher);
+    }
+    Some(hasher.finish())
+}
+
+/// Extensions that are binary whatever their first bytes look like
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "webp", "ico", "tiff", "pdf", "zip", "gz", "tgz", "bz2", "xz", "zst",
+    "7z", "rar", "tar", "jar", "war", "class", "exe", "dll", "so", "dylib", "o", "a", "lib", "rlib", "wasm",
+    "bin", "dat", "db", "sqlite", "gguf", "safetensors", "pt", "onnx", "mp3", "mp4", "wav", "flac", "ogg",
+    "mov", "avi", "mkv", "woff", "woff2", "ttf", "otf",
+];
+
+/// Null bytes or invalid UTF-8 near the start, or a known binary extension
+pub(crate) fn is_binary(path: &str, bytes: &[u8]) -> bool {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    if BINARY_EXTENSIONS.contains(&extension.as_str()) {
+        return true;
+    }
+    let head = &bytes[..bytes.len().min(8192)];
+    // A multi-byte character cut off at the end of the sample is fine
+    head.contains(&0) || std::str::from_utf8(head).is_err_and(|e| e.error_len().is_some())
+}
+
+/// Size and detected type of a binary file, with an optional hex dump
+fn describe_binary(shown: &str, bytes: &[u8], hexdump: bool) -> String {
+    let kind = match infer::get(bytes) {
+        Some(kind) => format!("{} (.{})", kind.mime_type(), kind.extension()),
+        None => "unknown type".to_string(),
+    };
+    let mut description = format!(
+        "📦 {} is a binary file: {} bytes, {}. Its contents are not shown as text.",
+        shown,
+        bytes.len(),
+        kind
+    );
+    if infer::is_image(bytes) {
+        description.push_str(" Use analyze_image to look at it.");
+    }
+    if !hexdump {
+        description.push_str(" Pass hexdump: true to see the first 256 bytes.");
+        return description;
+    }
+
+    description.push_str("\n\n");
+    for (row, chunk) in bytes.chunks(16).take(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let text: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        description.push_str(&format!("{:08x}  {:<47}  {}\n", row * 16, hex.join(" "), text));
+    }
+    description
+}
+pub struct ReadFile;
+
+#[async_trait]
+impl Tool for ReadFile {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "read_file",
+                "description": "Read the contents of a file. Binary files are described (size and type) instead of shown",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The path to the file to read"
+                        },
+                        "hexdump": {
+                            "type": "boolean",
+                            "description": "For a binary file, also show a hex dump of its first 256 bytes (default: false)"
+                        }
+                    },
+                    "required": ["path"]
+                }
+            }
+        })
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    fn fingerprint(&self, arguments: &Value, context: &ToolContext) -> Option<u64> {
+        files_fingerprint(&context.local(arguments["path"].as_str()?))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let path = arguments["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+
+        let bytes = match context.remote {
+            Some(remote) => remote.read(path)?,
+            None => fs::read(context.local(path))
+                .with_context(|| format!("Failed to read file: {}", path))?,
+        };
+        if is_binary(path, &bytes) {
+            let hexdump = arguments["hexdump"].as_bool().unwrap_or(false);
+            return Ok(describe_binary(&context.shown(path), &bytes, hexdump));
+        }
+        let content = String::from_utf8_lossy(&bytes);
+
+        let line_count = content.lines().count();
+        Ok(format!("📖 Read {} lines from {}\n\n{}", line_count, context.shown(path), content))
+    }
+}
+
+pub struct WriteFile;
+
+#[async_trait]
+impl Tool for WriteFile {
+    fn name(&self) -> &str {
+        "write_file"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "write_file",
+                "description": "Write content to a file (creates or overwrites)",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The path to the file to write"
+                        },
+                        "content": {
+                            "type": "string",
+                            "description": "The content to write to the file"
+                        }
+                    },
+                    "required": ["path", "content"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        let path = arguments["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        Ok(Some(Approval::Write(path.to_string())))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let path = arguments["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let content = arguments["content"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'content' parameter"))?;
+
+
+        if let Some(remote) = context.remote {
+            remote.write(path, content.as_bytes())?;
+        } else {
+            // Create parent directories if they don't exist
+            let target = context.local(path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create parent directories for: {}", path))?;
+            }
+
+            fs::write(&target, content)
+                .with_context(|| format!("Failed to write file: {}", path))?;
+        }
+
+        let line_count = content.lines().count();
+        Ok(format!("✅ Wrote {} bytes ({} lines) to {}", content.len(), line_count, context.shown(path)))
+    }
+}
+
+pub struct ListFiles;
+
+#[async_trait]
+impl Tool for ListFiles {
+    fn name(&self) -> &str {
+        "list_files"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "list_files",
+                "description": "List files in a directory. Entries matched by .gitignore and build/VCS directories (node_modules, target, .git) are left out unless include_ignored is true",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The directory path to list (default: current directory)"
+                        },
+                        "include_ignored": {
+                            "type": "boolean",
+                            "description": "Also list ignored files and build directories (default: false)"
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let path = arguments["path"]
+            .as_str()
+            .unwrap_or(".");
+        let include_ignored = arguments["include_ignored"].as_bool().unwrap_or(false);
+
+        let (files, skipped) = match context.remote {
+            Some(remote) => {
+                // No .gitignore matching over SSH; the build directories still go
+                let (files, skipped): (Vec<_>, Vec<_>) = remote
+                    .list_dir(path)?
+                    .into_iter()
+                    .partition(|name| include_ignored || !is_skipped_dir(name.trim_end_matches('/')));
+                (files, skipped.len())
+            }
+            None => {
+                let dir = context.local(path);
+                if !dir.is_dir() {
+                    anyhow::bail!("Failed to read directory: {}: not a directory", path);
+                }
+                let all = fs::read_dir(&dir)
+                    .with_context(|| format!("Failed to read directory: {}", path))?
+                    .count();
+
+                let mut files = vec![];
+                for entry in walk(&dir, include_ignored).max_depth(Some(1)).build() {
+                    let entry = entry?;
+                    if entry.depth() == 0 {
+                        continue;
+                    }
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let file_type = if entry.path().is_dir() { "/" } else { "" };
+                    files.push(format!("{}{}", name, file_type));
+                }
+                let skipped = all.saturating_sub(files.len());
+                (files, skipped)
+            }
+        };
+
+        let mut listing = format!("📁 Found {} items in {}:\n\n{}", files.len(), context.shown(path), files.join("\n"));
+        if skipped > 0 {
+            listing.push_str(&format!(
+                "\n\n({} ignored entries hidden; pass include_ignored: true to see them)",
+                skipped
+            ));
+        }
+        Ok(listing)
+    }
+}
+
+pub struct WorkspaceStats;
+
+#[async_trait]
+impl Tool for WorkspaceStats {
+    fn name(&self) -> &str {
+        "workspace_stats"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "workspace_stats",
+                "description": "Size up a project: lines of code per language, file counts, the largest files and the files changed most often in git. Use it to plan work on an unfamiliar codebase",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The directory to measure (default: current directory)"
+                        },
+                        "top": {
+                            "type": "integer",
+                            "description": "How many largest files and hotspots to list (default: 10)"
+                        },
+                        "churn_days": {
+                            "type": "integer",
+                            "description": "Count commits from this many days back for hotspots, 0 to skip git (default: 90)"
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let path = arguments["path"].as_str().unwrap_or(".");
+        if let Some(remote) = context.remote {
+            anyhow::bail!("workspace_stats runs locally only; use bash_exec with wc or tokei on {}", remote.describe());
+        }
+        let dir = context.local(path);
+        if !dir.is_dir() {
+            anyhow::bail!("{} is not a directory", path);
+        }
+        let top = arguments["top"].as_u64().unwrap_or(10).clamp(1, 100) as usize;
+        let churn_days = arguments["churn_days"].as_u64().unwrap_or(90);
+        Ok(workspace::WorkspaceStats::collect(&dir, churn_days)?.render(path, top))
+    }
+}
+
+pub struct ReadOutput;
+
+#[async_trait]
+impl Tool for ReadOutput {
+    fn name(&self) -> &str {
+        "read_output"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "read_output",
+                "description": "Page through a long tool output that was saved to .vork/outputs instead of shown in full",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "id": {
+                            "type": "string",
+                            "description": "The output id from the truncation notice"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "description": "First line to show, starting at 1 (default: 1)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Number of lines to show (default: 200)"
+                        }
+                    },
+                    "required": ["id"]
+                }
+            }
+        })
+    }
+
+    /// It pages through a saved output itself
+    fn spills(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let id = arguments["id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'id' parameter"))?;
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("Invalid output id: {}", id);
+        }
+        let offset = arguments["offset"].as_u64().unwrap_or(1).max(1) as usize;
+        let limit = arguments["limit"].as_u64().unwrap_or(200).max(1) as usize;
+
+        let path = context.working_dir.join(OUTPUTS_DIR).join(format!("{}.txt", id));
+        let output = fs::read_to_string(&path).with_context(|| format!("No saved output with id {}", id))?;
+        let total = output.lines().count();
+        if offset > total {
+            anyhow::bail!("Output {} has only {} lines", id, total);
+        }
+        // Pages stay under the inline limit however long the lines are
+        let page = take_lines(output.lines().skip(offset - 1).take(limit), inline_output_limit());
+        if page.is_empty() {
+            let line = output.lines().nth(offset - 1).unwrap_or_default();
+            let cut: String = line.chars().take(inline_output_limit()).collect();
+            return Ok(format!("📄 {} line {} of {} (cut to {} of {} characters):\n\n{}", id, offset, total, cut.len(), line.len(), cut));
+        }
+        let last = offset + page.len() - 1;
+        let mut text = format!("📄 {} lines {}-{} of {}:\n\n{}", id, offset, last, total, page.join("\n"));
+        if last < total {
+            text.push_str(&format!("\n\n[{} more lines; continue with offset {}]", total - last, last + 1));
+        }
+        Ok(text)
+    }
+}
+
+pub struct SearchFiles;
+
+#[async_trait]
+impl Tool for SearchFiles {
+    fn name(&self) -> &str {
+        "search_files"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "search_files",
+                "description": "Search for a pattern in files using grep, skipping .gitignore'd files and build directories",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pattern": {
+                            "type": "string",
+                            "description": "The pattern to search for"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "The path to search in (default: current directory)"
+                        }
+                    },
+                    "required": ["pattern"]
+                }
+            }
+        })
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    fn fingerprint(&self, arguments: &Value, context: &ToolContext) -> Option<u64> {
+        files_fingerprint(&context.local(arguments["path"].as_str().unwrap_or(".")))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let pattern = arguments["pattern"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'pattern' parameter"))?;
+        let path = arguments["path"]
+            .as_str()
+            .unwrap_or(".");
+
+        let stdout = match context.remote {
+            Some(remote) => {
+                remote
+                    .run(&format!("grep -r -n -e {} -- {}", shell_quote(pattern), shell_quote(path)))?
+                    .stdout
+            }
+            // The files the other file tools see, without ignored paths
+            None => {
+                let files: Vec<_> = walk(&context.local(path), false)
+                    .build()
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+                    .map(|entry| {
+                        let path = entry.path();
+                        path.strip_prefix(context.working_dir).unwrap_or(path).to_path_buf()
+                    })
+                    .collect();
+                let mut stdout = vec![];
+                for chunk in files.chunks(SEARCH_CHUNK) {
+                    let output = Command::new("grep")
+                        .args(["-n", "-H", "-e", pattern, "--"])
+                        .args(chunk)
+                        .current_dir(context.working_dir)
+                        .output()
+                        .with_context(|| format!("Failed to search for pattern: {}", pattern))?;
+                    stdout.extend(output.stdout);
+                }
+                stdout
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&stdout);
+        let line_count = stdout.lines().count();
+
+        if line_count > 0 {
+            Ok(format!("🔍 Found {} matches for '{}' in {}:\n\n{}", line_count, pattern, context.shown(path), stdout))
+        } else {
+            Ok(format!("ℹ️  No matches found for '{}' in {}", pattern, context.shown(path)))
+        }
+    }
+}
+
+pub struct AnalyzeImage;
+
+#[async_trait]
+impl Tool for AnalyzeImage {
+    fn name(&self) -> &str {
+        "analyze_image"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "analyze_image",
+                "description": "Analyze an image file and describe its contents. Supports common formats: PNG, JPG, JPEG, GIF, BMP, WebP. Returns base64-encoded image data for vision-capable models.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to the image file to analyze"
+                        },
+                        "question": {
+                            "type": "string",
+                            "description": "Optional specific question about the image (e.g., 'What text is visible?', 'Describe the UI layout')"
+                        }
+                    },
+                    "required": ["path"]
+                }
+            }
+        })
+    }
+
+    /// Image data has to reach the model whole
+    fn spills(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let path = arguments["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let question = arguments["question"]
+            .as_str()
+            .map(|s| s.to_string());
+
+        // Read image file
+        let image_data = match context.remote {
+            Some(remote) => remote.read(path)?,
+            None => fs::read(context.local(path))
+                .with_context(|| format!("Failed to read image file: {}", path))?,
+        };
+
+        // Detect image format from extension
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        let mime_type = match extension.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "bmp" => "image/bmp",
+            "webp" => "image/webp",
+            _ => "image/png", // default
+        };
+
+        // Encode to base64
+        let base64_data = general_purpose::STANDARD.encode(&image_data);
+
+        // Create data URL
+        let data_url = format!("data:{};base64,{}", mime_type, base64_data);
+
+        let size_kb = image_data.len() / 1024;
+        let question_text = question.as_deref().unwrap_or("Please describe what you see in this image");
+
+        // Return formatted response with image data and context
+        Ok(format!(
+            "🖼️  Loaded image: {} ({} KB, {})\n\nQuestion: {}\n\n[IMAGE_DATA: {}]\n\nNote: This image has been loaded and encoded. If your model supports vision, it will analyze the image based on the question.",
+            context.shown(path),
+            size_kb,
+            mime_type,
+            question_text,
+            data_url
+        ))
+    }
+}
@@ -0,0 +1,503 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use super::approval::ApprovalSystem;
+use super::container::DevContainer;
+use super::environment;
+use super::processes;
+use super::remote::RemoteTarget;
+use crate::config::Config;
+use crate::error::VorkError;
+
+mod clipboard;
+mod data;
+mod files;
+mod project;
+mod reverse;
+mod shell;
+mod web;
+
+pub(crate) use files::{is_binary, walk};
+
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct ToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[allow(dead_code)]
+pub struct ToolResult {
+    pub tool_call_id: String,
+    pub output: String,
+}
+
+/// Where a tool runs: the session's directory, or the remote target
+pub struct ToolContext<'a> {
+    pub working_dir: &'a Path,
+    /// File and shell tools run over SSH when a remote target is selected
+    pub remote: Option<&'static RemoteTarget>,
+}
+
+impl<'a> ToolContext<'a> {
+    pub fn new(working_dir: &'a Path) -> Self {
+        Self { working_dir, remote: RemoteTarget::active() }
+    }
+
+    /// A path as the user should see it, with the remote host when there is one
+    pub fn shown(&self, path: &str) -> String {
+        self.remote.map_or_else(|| path.to_string(), |remote| remote.label(path))
+    }
+
+    /// A path relative to the session's directory
+    pub fn local(&self, path: &str) -> PathBuf {
+        self.working_dir.join(path)
+    }
+}
+
+/// What a tool needs the user to allow before it runs
+pub enum Approval {
+    /// Writing a file
+    Write(String),
+    /// Running a shell command, shown the way the user would type it
+    Command(String),
+    /// Running a code snippet through an interpreter
+    Snippet { language: String, invocation: String },
+    /// A statement that may change a database
+    Sql { database: String, statement: String },
+    /// Stopping a process
+    Kill { process: String, started_here: bool },
+    Clipboard { write: bool },
+}
+
+impl Approval {
+    /// Ask the approval system; a refusal is `VorkError::ApprovalDenied`
+    fn ask(&self, approval: &ApprovalSystem) -> Result<()> {
+        let (approved, action) = match self {
+            Approval::Write(path) => (approval.should_approve_write(path)?, format!("Write to {}", path)),
+            Approval::Command(command) => (approval.should_approve_bash(command)?, format!("Command '{}'", command)),
+            Approval::Snippet { language, invocation } => {
+                (approval.should_approve_bash(invocation)?, format!("{} snippet", language))
+            }
+            Approval::Sql { database, statement } => {
+                (approval.should_approve_sql(database, statement)?, format!("Statement on {}", database))
+            }
+            Approval::Kill { process, started_here } => {
+                (approval.should_approve_kill(process, *started_here)?, format!("Stopping process {}", process))
+            }
+            Approval::Clipboard { write } => (
+                approval.should_approve_clipboard(*write)?,
+                if *write { "Writing the clipboard" } else { "Reading the clipboard" }.to_string(),
+            ),
+        };
+        if !approved {
+            return Err(VorkError::ApprovalDenied { action }.into());
+        }
+        Ok(())
+    }
+}
+
+/// Something the model can call. The built-in tools live in this module's
+/// submodules and are listed in `ToolRegistry::builtin`.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// The name the model calls it by
+    fn name(&self) -> &str;
+
+    /// The function definition sent to the model
+    fn schema(&self) -> Value;
+
+    /// What the user has to allow before `execute` runs with these
+    /// arguments. Calls that cannot work fail here, before anyone is asked.
+    fn required_approval(&self, _arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        Ok(None)
+    }
+
+    /// Whether a long result may be saved to .vork/outputs and cut down
+    fn spills(&self) -> bool {
+        true
+    }
+
+    /// Whether it only looks at things, so the same result twice means
+    /// nothing changed and the repeat can point at the earlier one
+    fn idempotent(&self) -> bool {
+        false
+    }
+
+    /// A hash of what the result depends on besides the arguments, such as
+    /// file modification times. A call with the same arguments and
+    /// fingerprint as an earlier one returns that result without running.
+    /// None, the default, runs every call.
+    fn fingerprint(&self, _arguments: &Value, _context: &ToolContext) -> Option<u64> {
+        None
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String>;
+}
+
+/// The tools offered to the model, in the order they are listed
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn Tool>>,
+    /// Results of fingerprinted calls by tool, arguments and directory, with
+    /// the fingerprint they were made at. Kept for the process, which runs
+    /// one session (`vork rpc` runs one per directory).
+    cache: Mutex<HashMap<CallKey, (u64, String)>>,
+}
+
+/// A tool name, its arguments as JSON and the directory it ran in
+type CallKey = (String, String, PathBuf);
+
+impl ToolRegistry {
+    pub fn builtin() -> Self {
+        let mut registry = Self { tools: vec![], cache: Mutex::default() };
+        registry.register(files::ReadFile);
+        registry.register(files::WriteFile);
+        registry.register(files::ListFiles);
+        registry.register(files::WorkspaceStats);
+        registry.register(files::ReadOutput);
+        registry.register(shell::RunSnippet);
+        for subcommand in ["check", "clippy", "test"] {
+            registry.register(project::Cargo { subcommand });
+        }
+        registry.register(project::CoverageReport);
+        registry.register(project::ProfileRun);
+        registry.register(reverse::BinaryInfo);
+        registry.register(reverse::BinaryStrings);
+        registry.register(reverse::DisassembleFunction);
+        registry.register(shell::BashBackground);
+        registry.register(shell::BackgroundOutput);
+        registry.register(shell::BackgroundStop);
+        registry.register(data::SqlQuery);
+        registry.register(shell::ListProcesses);
+        registry.register(shell::KillProcess);
+        registry.register(data::AddFinding);
+        registry.register(data::ListFindings);
+        registry.register(data::ResolveFinding);
+        registry.register(clipboard::ClipboardRead);
+        registry.register(clipboard::ClipboardWrite);
+        registry.register(shell::BashExec);
+        registry.register(files::SearchFiles);
+        registry.register(web::WebSearch);
+        registry.register(files::AnalyzeImage);
+        registry
+    }
+
+    /// Add a tool, replacing any with the same name
+    pub fn register(&mut self, tool: impl Tool + 'static) {
+        self.tools.retain(|existing| existing.name() != tool.name());
+        self.tools.push(Box::new(tool));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools.iter().find(|tool| tool.name() == name).map(|tool| tool.as_ref())
+    }
+
+    pub fn schemas(&self) -> Vec<Value> {
+        self.tools.iter().map(|tool| tool.schema()).collect()
+    }
+
+    /// Run a tool, resolving relative paths against `working_dir` (the
+    /// session's, not necessarily the process's). Failures come back as
+    /// `VorkError::ToolFailed`, or `VorkError::ApprovalDenied` when the
+    /// operation was refused. Tools wait on processes, files and approval
+    /// prompts, so they run off the async runtime's threads.
+    pub async fn execute(
+        &self,
+        name: &str,
+        arguments: Value,
+        approval_system: Option<&ApprovalSystem>,
+        working_dir: &Path,
+    ) -> Result<String> {
+        processes::run_blocking(self.run(name, arguments, approval_system, working_dir)).await
+    }
+
+    async fn run(
+        &self,
+        name: &str,
+        arguments: Value,
+        approval_system: Option<&ApprovalSystem>,
+        working_dir: &Path,
+    ) -> Result<String> {
+        let tool = self.get(name);
+        let context = ToolContext::new(working_dir);
+        let result = async {
+            let tool = tool.ok_or_else(|| anyhow::anyhow!("Unknown tool: {}", name))?;
+            if let Some(approval) = tool.required_approval(&arguments, &context)? {
+                if let Some(approval_system) = approval_system {
+                    approval.ask(approval_system)?;
+                }
+            }
+
+            // Remote files can change without us seeing it
+            let fingerprint = context.remote.is_none().then(|| tool.fingerprint(&arguments, &context)).flatten();
+            let key = (name.to_string(), arguments.to_string(), working_dir.to_path_buf());
+            let cached = self.cache.lock().ok().and_then(|entries| {
+                let (at, result) = entries.get(&key)?;
+                (Some(*at) == fingerprint).then(|| result.clone())
+            });
+            if let Some(result) = cached {
+                return Ok(result);
+            }
+            let result = tool.execute(&arguments, &context).await?;
+            if let (Some(fingerprint), Ok(mut entries)) = (fingerprint, self.cache.lock()) {
+                entries.insert(key, (fingerprint, result.clone()));
+            }
+            Ok(result)
+        }
+        .await
+        .map_err(|error| {
+            if VorkError::find(&error).is_some() {
+                return error;
+            }
+            VorkError::ToolFailed { tool: name.to_string(), message: format!("{:#}", error) }.into()
+        })?;
+
+        let limit = inline_output_limit();
+        if result.len() <= limit || tool.is_some_and(|tool| !tool.spills()) {
+            return Ok(result);
+        }
+        spill(name, &result, limit, working_dir)
+    }
+}
+
+/// The built-in tools
+pub fn registry() -> &'static ToolRegistry {
+    static REGISTRY: OnceLock<ToolRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ToolRegistry::builtin)
+}
+
+pub fn get_available_tools() -> Vec<Value> {
+    registry().schemas()
+}
+
+/// Run one of the built-in tools; see `ToolRegistry::execute`
+pub async fn execute_tool(
+    name: &str,
+    arguments: Value,
+    approval_system: Option<&ApprovalSystem>,
+    working_dir: &Path,
+) -> Result<String> {
+    registry().execute(name, arguments, approval_system, working_dir).await
+}
+
+/// Where long tool results are saved, relative to the session's directory
+const OUTPUTS_DIR: &str = ".vork/outputs";
+
+/// Run a command where bash_exec runs it: on the remote target, in the dev
+/// container or locally. Returns the output, whether it hit the timeout
+/// (in seconds, 0 for none) and where it ran, e.g. " (on host)".
+fn run_bash(command: &str, seconds: u64, working_dir: &Path) -> Result<(Output, bool, String)> {
+    let timeout = (seconds > 0).then(|| Duration::from_secs(seconds));
+    if let Some(remote) = RemoteTarget::active() {
+        let (output, timed_out) = remote.run_within(command, timeout)?;
+        return Ok((output, timed_out, format!(" (on {})", remote.describe())));
+    }
+    if let Some(container) = DevContainer::active() {
+        let (output, timed_out) = container.run(command, timeout)?;
+        return Ok((output, timed_out, format!(" (in {})", container.describe())));
+    }
+    let mut bash = Command::new("bash");
+    environment::apply(bash.arg("-c").arg(command), working_dir)?;
+    let (output, timed_out) =
+        processes::output_within(processes::mark(&mut bash).current_dir(working_dir).stdin(Stdio::null()), timeout)
+            .with_context(|| format!("Failed to execute command: {}", command))?;
+    Ok((output, timed_out, String::new()))
+}
+
+fn bash_timeout() -> u64 {
+    static TIMEOUT: OnceLock<u64> = OnceLock::new();
+    *TIMEOUT.get_or_init(|| Config::load().map(|config| config.tools.bash_timeout).unwrap_or(120))
+}
+
+fn inline_output_limit() -> usize {
+    static LIMIT: OnceLock<usize> = OnceLock::new();
+    *LIMIT.get_or_init(|| {
+        Config::load()
+            .map(|config| config.tools.inline_output_limit)
+            .unwrap_or(16_000)
+            .max(1_000)
+    })
+}
+
+/// Save a long tool result to .vork/outputs/<id>.txt and return its start
+/// and end, with the id to page through the rest with read_output
+fn spill(tool: &str, result: &str, limit: usize, working_dir: &Path) -> Result<String> {
+    static NEXT: AtomicUsize = AtomicUsize::new(1);
+    let dir = outputs_dir(working_dir)?;
+    let id = format!(
+        "{}-{}-{}",
+        tool,
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    fs::write(dir.join(format!("{}.txt", id)), result).context("Failed to save the tool output")?;
+
+    let lines: Vec<&str> = result.lines().collect();
+    let head = take_lines(lines.iter().copied(), limit * 2 / 3);
+    let mut tail = take_lines(lines[head.len()..].iter().rev().copied(), limit / 6);
+    tail.reverse();
+    let omitted_from = head.len() + 1;
+    let omitted_to = lines.len() - tail.len();
+
+    // A first line longer than the whole preview is cut rather than dropped
+    let head = match head.is_empty() {
+        true => vec![result.chars().take(limit * 2 / 3).collect::<String>() + " …"],
+        false => head.iter().map(|line| line.to_string()).collect(),
+    };
+    Ok(format!(
+        "{}\n\n[… lines {}-{} of {} omitted ({} bytes in total). The full output is in {}/{}.txt; \
+         read it with read_output {{\"id\": \"{}\", \"offset\": {}}}]\n\n{}",
+        head.join("\n"),
+        omitted_from,
+        omitted_to,
+        lines.len(),
+        result.len(),
+        OUTPUTS_DIR,
+        id,
+        id,
+        omitted_from,
+        tail.join("\n")
+    ))
+}
+
+/// The workspace's .vork/outputs, created on first use and kept out of git
+pub(super) fn outputs_dir(working_dir: &Path) -> Result<PathBuf> {
+    let dir = working_dir.join(OUTPUTS_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        let _ = fs::write(&ignore, "*\n");
+    }
+    Ok(dir)
+}
+
+/// Lines from `lines` while they fit in `budget` characters
+fn take_lines<'a>(lines: impl Iterator<Item = &'a str>, budget: usize) -> Vec<&'a str> {
+    let mut taken = vec![];
+    let mut used = 0;
+    for line in lines {
+        if used + line.len() + 1 > budget {
+            break;
+        }
+        used += line.len() + 1;
+        taken.push(line);
+    }
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Arc;
+
+    /// Counts its runs; the fingerprint is whatever the test sets
+    struct Counter {
+        runs: Arc<AtomicUsize>,
+        fingerprint: Arc<AtomicU64>,
+    }
+
+    #[async_trait]
+    impl Tool for Counter {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        fn schema(&self) -> Value {
+            Value::Null
+        }
+
+        fn fingerprint(&self, _arguments: &Value, _context: &ToolContext) -> Option<u64> {
+            Some(self.fingerprint.load(Ordering::SeqCst))
+        }
+
+        async fn execute(&self, arguments: &Value, _context: &ToolContext) -> Result<String> {
+            let runs = self.runs.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("{} run {}", arguments["n"], runs))
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_calls_are_answered_from_the_cache_until_the_fingerprint_changes() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let fingerprint = Arc::new(AtomicU64::new(1));
+        let mut registry = ToolRegistry { tools: vec![], cache: Mutex::default() };
+        registry.register(Counter { runs: runs.clone(), fingerprint: fingerprint.clone() });
+        let dir = tempfile::tempdir().unwrap();
+        let call = |n: u64| registry.run("counter", serde_json::json!({ "n": n }), None, dir.path());
+
+        assert_eq!(call(1).await.unwrap(), "1 run 1");
+        assert_eq!(call(1).await.unwrap(), "1 run 1");
+        assert_eq!(call(2).await.unwrap(), "2 run 2");
+        fingerprint.store(2, Ordering::SeqCst);
+        assert_eq!(call(1).await.unwrap(), "1 run 3");
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn file_fingerprints_follow_the_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ToolContext { working_dir: dir.path(), remote: None };
+        let read = |path: &str| files::ReadFile.fingerprint(&serde_json::json!({ "path": path }), &context);
+        let search = || files::SearchFiles.fingerprint(&serde_json::json!({ "pattern": "x" }), &context);
+        fs::write(dir.path().join("a.txt"), "one").unwrap();
+        let (a, tree) = (read("a.txt"), search());
+        assert!(a.is_some() && tree.is_some());
+        assert_eq!(read("a.txt"), a);
+
+        fs::write(dir.path().join("b.txt"), "two").unwrap();
+        assert_eq!(read("a.txt"), a);
+        assert_ne!(search(), tree);
+
+        fs::write(dir.path().join("a.txt"), "three").unwrap();
+        assert_ne!(read("a.txt"), a);
+        assert_eq!(read("missing.txt"), None);
+    }
+
+    #[test]
+    fn ignored_paths_do_not_change_fingerprints() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = ToolContext { working_dir: dir.path(), remote: None };
+        let search = || files::SearchFiles.fingerprint(&serde_json::json!({ "pattern": "x" }), &context);
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        let tree = search();
+
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("target/debug/out.txt"), "built").unwrap();
+        fs::write(dir.path().join("run.log"), "x").unwrap();
+        assert_eq!(search(), tree);
+
+        // Asking for an ignored file by name still sees its changes
+        let read = || files::ReadFile.fingerprint(&serde_json::json!({ "path": "run.log" }), &context);
+        let log = read();
+        assert!(log.is_some());
+        fs::write(dir.path().join("run.log"), "xx").unwrap();
+        assert_ne!(read(), log);
+    }
+
+    #[tokio::test]
+    async fn searches_skip_ignored_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.path().join("main.rs"), "// needle\n").unwrap();
+        fs::write(dir.path().join("run.log"), "needle\n").unwrap();
+        fs::create_dir_all(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/out.txt"), "needle\n").unwrap();
+
+        let context = ToolContext { working_dir: dir.path(), remote: None };
+        let result = files::SearchFiles.execute(&serde_json::json!({ "pattern": "needle" }), &context).await.unwrap();
+        assert!(result.starts_with("🔍 Found 1 matches"), "{}", result);
+        assert!(result.contains("main.rs:1:// needle"), "{}", result);
+    }
+}
@@ -0,0 +1,446 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::fs;
+
+use super::{bash_timeout, run_bash, Approval, Tool, ToolContext};
+use crate::llm::cargo;
+use crate::llm::coverage;
+use crate::llm::profile::{self, Profile};
+use crate::llm::remote::shell_quote;
+
+/// cargo check, clippy or test, with the diagnostics summarized
+pub struct Cargo {
+    pub subcommand: &'static str,
+}
+
+#[async_trait]
+impl Tool for Cargo {
+    fn name(&self) -> &str {
+        match self.subcommand {
+            "check" => "cargo_check",
+            "clippy" => "cargo_clippy",
+            _ => "cargo_test",
+        }
+    }
+
+    fn schema(&self) -> Value {
+        match self.subcommand {
+            "check" => json!({
+                "type": "function",
+                "function": {
+                    "name": "cargo_check",
+                    "description": "Run cargo check and get the compiler errors and warnings as file:line: message lines. Prefer this over cargo in bash_exec",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "package": {
+                                "type": "string",
+                                "description": "Only this workspace package (-p)"
+                            },
+                            "all_targets": {
+                                "type": "boolean",
+                                "description": "Also check tests, examples and benches (default: true)"
+                            }
+                        }
+                    }
+                }
+            }),
+            "clippy" => json!({
+                "type": "function",
+                "function": {
+                    "name": "cargo_clippy",
+                    "description": "Run cargo clippy and get its lints and errors as file:line: message lines, with suggested fixes",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "package": {
+                                "type": "string",
+                                "description": "Only this workspace package (-p)"
+                            },
+                            "all_targets": {
+                                "type": "boolean",
+                                "description": "Also lint tests, examples and benches (default: true)"
+                            }
+                        }
+                    }
+                }
+            }),
+            _ => json!({
+                "type": "function",
+                "function": {
+                    "name": "cargo_test",
+                    "description": "Run cargo test and get compile errors, failing tests with their panic output, and pass/fail totals",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "package": {
+                                "type": "string",
+                                "description": "Only this workspace package (-p)"
+                            },
+                            "filter": {
+                                "type": "string",
+                                "description": "Only run tests whose name contains this"
+                            },
+                            "timeout": {
+                                "type": "integer",
+                                "description": "Seconds before the run is stopped (default: at least 600)"
+                            }
+                        }
+                    }
+                }
+            }),
+        }
+    }
+
+    fn required_approval(&self, arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        Ok(Some(Approval::Command(self.command(arguments))))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let subcommand = self.subcommand;
+        // Added after approval: the prompt shows what the user would type
+        let command = self.command(arguments);
+        let command = command.replacen(subcommand, &format!("{} --message-format=json", subcommand), 1);
+
+        // Builds outlast the usual command timeout
+        let seconds = arguments["timeout"].as_u64().unwrap_or(match bash_timeout() {
+            0 => 0,
+            seconds => seconds.max(600),
+        });
+        let (output, timed_out, target) = run_bash(&command, seconds, context.working_dir)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut text = cargo::summarize(subcommand, &stdout, &stderr, output.status.code().unwrap_or(-1));
+        if !target.is_empty() {
+            text = text.replacen(&format!("cargo {}:", subcommand), &format!("cargo {}{}:", subcommand, target), 1);
+        }
+        if timed_out {
+            text = format!("⏱️ TIMEOUT after {}s; results so far:\n{}", seconds, text);
+        }
+        Ok(text)
+    }
+}
+
+impl Cargo {
+    /// The command as the user would type it
+    fn command(&self, arguments: &Value) -> String {
+        let mut command = format!("cargo {}", self.subcommand);
+        if let Some(package) = arguments["package"].as_str() {
+            command.push_str(&format!(" -p {}", shell_quote(package)));
+        }
+        if self.subcommand == "test" {
+            command.push_str(" --no-fail-fast");
+            if let Some(filter) = arguments["filter"].as_str() {
+                command.push_str(&format!(" {}", shell_quote(filter)));
+            }
+        } else if arguments["all_targets"].as_bool().unwrap_or(true) {
+            command.push_str(" --all-targets");
+        }
+        command
+    }
+}
+
+pub struct CoverageReport;
+
+#[async_trait]
+impl Tool for CoverageReport {
+    fn name(&self) -> &str {
+        "coverage_report"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "coverage_report",
+                "description": "Run the tests under a coverage tool (cargo llvm-cov, cargo tarpaulin or nyc) and get per-file coverage with the uncovered line ranges, least covered files first",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "tool": {
+                            "type": "string",
+                            "enum": ["auto", "llvm-cov", "tarpaulin", "nyc"],
+                            "description": "Coverage tool (default: auto, picked from the project and what is installed)"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Only report files under this path, e.g. src/parser"
+                        },
+                        "package": {
+                            "type": "string",
+                            "description": "Only this Cargo workspace package (-p)"
+                        },
+                        "command": {
+                            "type": "string",
+                            "description": "Test command for nyc to run (default: npm test)"
+                        },
+                        "lcov": {
+                            "type": "string",
+                            "description": "Summarize an existing LCOV file instead of running the tests"
+                        },
+                        "max_files": {
+                            "type": "integer",
+                            "description": "Most partly covered files to list (default: 30)"
+                        },
+                        "timeout": {
+                            "type": "integer",
+                            "description": "Seconds before the run is stopped (default: at least 600)"
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, context: &ToolContext) -> Result<Option<Approval>> {
+        if arguments["lcov"].is_string() {
+            return Ok(None);
+        }
+        let (_, command) = coverage_command(arguments, context)?;
+        let shown = command.replace("\"$dir/lcov.info\"", "<tmp>/lcov.info").replace("\"$dir", "\"<tmp>");
+        Ok(Some(Approval::Command(shown)))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let filter = arguments["path"].as_str();
+        let max_files = arguments["max_files"].as_u64().unwrap_or(30).max(1) as usize;
+        if let Some(lcov) = arguments["lcov"].as_str() {
+            let (text, root) = match context.remote {
+                Some(remote) => (String::from_utf8_lossy(&remote.read(lcov)?).into_owned(), String::new()),
+                None => (
+                    fs::read_to_string(context.local(lcov)).with_context(|| format!("Failed to read {}", lcov))?,
+                    context.working_dir.to_string_lossy().into_owned(),
+                ),
+            };
+            return Ok(coverage::summarize(lcov, &text, &root, filter, max_files));
+        }
+
+        let (tool, command) = coverage_command(arguments, context)?;
+        let seconds = arguments["timeout"].as_u64().unwrap_or(match bash_timeout() {
+            0 => 0,
+            seconds => seconds.max(600),
+        });
+        let (output, timed_out, target) = run_bash(&coverage::script(&command), seconds, context.working_dir)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let exit_code = output.status.code().unwrap_or(-1);
+        let label = format!("{}{}", if tool == "nyc" { "nyc".to_string() } else { format!("cargo {}", tool) }, target);
+        let tail = |text: &str| {
+            let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+            lines[lines.len().saturating_sub(30)..].join("\n")
+        };
+
+        let mut text = match coverage::split_output(&stdout) {
+            Some((_, root, lcov)) if !lcov.trim().is_empty() => {
+                let mut text = coverage::summarize(&label, lcov, root, filter, max_files);
+                if exit_code != 0 {
+                    text.push_str(&format!(
+                        "\n\n⚠️ The test run exited with code {}; coverage is from the tests that ran. \
+                         Use cargo_test (or bash_exec) to see the failures.",
+                        exit_code
+                    ));
+                }
+                text
+            }
+            run => {
+                let output = [tail(run.map_or(&stdout, |(run, _, _)| run)), tail(&stderr)]
+                    .into_iter()
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("❌ {} produced no coverage report (exit code {})\n\n{}", label, exit_code, output)
+            }
+        };
+        if timed_out {
+            text = format!("⏱️ TIMEOUT after {}s; the run was stopped.\n{}", seconds, text);
+        }
+        Ok(text)
+    }
+}
+
+/// The coverage tool a call runs, found out from the project unless it
+/// names one, and its command
+fn coverage_command(arguments: &Value, context: &ToolContext) -> Result<(String, String)> {
+    let tool = match arguments["tool"].as_str().filter(|tool| *tool != "auto") {
+        Some(tool) => tool.to_string(),
+        None => {
+            let (output, _, _) = run_bash(coverage::DETECT_SCRIPT, 60, context.working_dir)?;
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+    };
+    let package = arguments["package"].as_str();
+    let test_command = arguments["command"].as_str().unwrap_or("npm test");
+    let command = match (tool.as_str(), coverage::command(&tool, package, test_command)) {
+        (_, Some(command)) => command,
+        ("missing-rust", _) => anyhow::bail!(
+            "Neither cargo-llvm-cov nor cargo-tarpaulin is installed; install one with cargo install cargo-llvm-cov"
+        ),
+        ("missing-project", _) => anyhow::bail!(
+            "No Cargo.toml or package.json here; pass lcov with the path of an existing LCOV report"
+        ),
+        (tool, None) => anyhow::bail!("Unknown coverage tool {}; use llvm-cov, tarpaulin or nyc", tool),
+    };
+    Ok((tool, command))
+}
+
+pub struct ProfileRun;
+
+#[async_trait]
+impl Tool for ProfileRun {
+    fn name(&self) -> &str {
+        "profile_run"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "profile_run",
+                "description": "Profile a command with perf or flamegraph and get its hottest functions, or time it with hyperfine. Artifacts (perf.data, flamegraph.svg, hyperfine.json) are kept in .vork/profiles. Build Rust code with --release and debug symbols first.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "command": {
+                            "type": "string",
+                            "description": "The command to profile, e.g. ./target/release/app input.txt"
+                        },
+                        "tool": {
+                            "type": "string",
+                            "enum": ["auto", "perf", "flamegraph", "hyperfine"],
+                            "description": "Profiler (default: auto, flamegraph or perf when installed). hyperfine times the command instead of sampling it."
+                        },
+                        "compare": {
+                            "type": "string",
+                            "description": "hyperfine only: a second command to benchmark against the first"
+                        },
+                        "runs": {
+                            "type": "integer",
+                            "description": "hyperfine runs per command (default: 10)"
+                        },
+                        "frequency": {
+                            "type": "integer",
+                            "description": "Samples per second for perf and flamegraph (default: 999)"
+                        },
+                        "top": {
+                            "type": "integer",
+                            "description": "Hot symbols to list (default: 25)"
+                        },
+                        "timeout": {
+                            "type": "integer",
+                            "description": "Seconds before the run is stopped (default: at least 600)"
+                        }
+                    },
+                    "required": ["command"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, context: &ToolContext) -> Result<Option<Approval>> {
+        let tool = profiler(arguments, context)?;
+        let profile = profile(&tool, arguments)?;
+        // Fails before asking when there is no such profiler
+        profile_script(&profile, profile::PROFILES_DIR)?;
+        Ok(Some(Approval::Command(profile.shown())))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let tool = profiler(arguments, context)?;
+        let profile = profile(&tool, arguments)?;
+        let command = profile.command;
+        let out = format!("{}/{}-{}", profile::PROFILES_DIR, chrono::Local::now().format("%Y%m%d-%H%M%S"), tool);
+        let script = profile_script(&profile, &out)?;
+
+        let seconds = arguments["timeout"].as_u64().unwrap_or(match bash_timeout() {
+            0 => 0,
+            seconds => seconds.max(600),
+        });
+        let (output, timed_out, target) = run_bash(&script, seconds, context.working_dir)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let exit_code = output.status.code().unwrap_or(-1);
+        let top = arguments["top"].as_u64().unwrap_or(25).max(1) as usize;
+        let tail = |text: &str| {
+            let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+            lines[lines.len().saturating_sub(20)..].join("\n")
+        };
+
+        let (run, directory, report) = profile::split_output(&stdout).unwrap_or((&stdout, out.as_str(), ""));
+        let summary = match tool.as_str() {
+            "hyperfine" => profile::timings(report),
+            _ => profile::hot_symbols(report, top),
+        };
+        let profiled = match profile.compare {
+            Some(compare) if tool == "hyperfine" => format!("{} vs {}", command, compare),
+            _ => command.to_string(),
+        };
+        let mut text = match summary {
+            Some(summary) => {
+                let artifacts = match tool.as_str() {
+                    "hyperfine" => format!("{}/hyperfine.json, hyperfine.md", directory),
+                    "flamegraph" => format!("{}/flamegraph.svg, perf.data", directory),
+                    _ => format!("{}/perf.data (perf report -i {}/perf.data)", directory, directory),
+                };
+                let mut text = format!("🔥 {} of {}{}\n\n{}\n\nArtifacts: {}", tool, profiled, target, summary, artifacts);
+                if exit_code != 0 {
+                    text.push_str(&format!("\n\n⚠️ The command exited with code {}:\n{}", exit_code, tail(&stderr)));
+                }
+                text
+            }
+            None => {
+                let mut text = format!("❌ {} of {}{} failed (exit code {})", tool, profiled, target, exit_code);
+                for output in [tail(run), tail(&stderr)] {
+                    if !output.is_empty() {
+                        text.push_str(&format!("\n\n{}", output));
+                    }
+                }
+                if stderr.contains("perf_event_paranoid") {
+                    text.push_str(
+                        "\n\nperf is not allowed to sample here; lower kernel.perf_event_paranoid \
+                         (sudo sysctl kernel.perf_event_paranoid=1) or use tool hyperfine",
+                    );
+                }
+                text
+            }
+        };
+        if timed_out {
+            text = format!("⏱️ TIMEOUT after {}s; the run was stopped.\n{}", seconds, text);
+        }
+        Ok(text)
+    }
+}
+
+/// The profiler a call uses: the one it names, or the first installed
+fn profiler(arguments: &Value, context: &ToolContext) -> Result<String> {
+    Ok(match arguments["tool"].as_str().filter(|tool| *tool != "auto") {
+        Some(tool) => tool.to_string(),
+        None => {
+            let (output, _, _) = run_bash(profile::DETECT_SCRIPT, 60, context.working_dir)?;
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+    })
+}
+
+fn profile<'a>(tool: &'a str, arguments: &'a Value) -> Result<Profile<'a>> {
+    let command = arguments["command"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter"))?;
+    Ok(Profile {
+        tool,
+        command,
+        compare: arguments["compare"].as_str(),
+        runs: arguments["runs"].as_u64().unwrap_or(10).clamp(1, 1000),
+        frequency: arguments["frequency"].as_u64().unwrap_or(999).clamp(1, 20_000),
+    })
+}
+
+fn profile_script(profile: &Profile, out: &str) -> Result<String> {
+    match (profile.tool, profile.script(out)) {
+        (_, Some(script)) => Ok(script),
+        ("missing", _) => anyhow::bail!(
+            "No profiler installed; install perf (linux-tools), flamegraph (cargo install flamegraph) or hyperfine"
+        ),
+        (tool, None) => anyhow::bail!("Unknown profiler {}; use perf, flamegraph or hyperfine", tool),
+    }
+}
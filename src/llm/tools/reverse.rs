@@ -0,0 +1,214 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{bash_timeout, run_bash, Tool, ToolContext};
+use crate::llm::binary::{self, Backend};
+
+/// Run one of the binary tools with objdump/nm or radare2, whichever the
+/// call names or is installed
+fn inspect(name: &str, arguments: &Value, context: &ToolContext) -> Result<String> {
+    let path = arguments["path"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+    let backend = match arguments["backend"].as_str().filter(|backend| *backend != "auto") {
+        Some(name) => Backend::parse(name).ok_or_else(|| anyhow::anyhow!("Unknown backend {}; use objdump or r2", name))?,
+        None => {
+            let (output, _, _) = run_bash(binary::DETECT_SCRIPT, 60, context.working_dir)?;
+            Backend::parse(String::from_utf8_lossy(&output.stdout).trim())
+                .ok_or_else(|| anyhow::anyhow!("Neither binutils (objdump, nm) nor radare2 is installed"))?
+        }
+    };
+    let limit = |default: u64| arguments["limit"].as_u64().unwrap_or(default).clamp(1, 1000) as usize;
+    let min_length = arguments["min_length"].as_u64().unwrap_or(6).clamp(2, 200) as usize;
+    let max_lines = arguments["max_lines"].as_u64().unwrap_or(200).clamp(10, 5000) as usize;
+    let function = arguments["function"].as_str().unwrap_or("").trim();
+    let script = match name {
+        "binary_info" => binary::info_script(backend, path, limit(40)),
+        "binary_strings" => binary::strings_script(backend, path, min_length, arguments["wide"].as_bool().unwrap_or(false)),
+        _ if function.is_empty() => anyhow::bail!("Missing 'function' parameter"),
+        _ => binary::disassemble_script(backend, path, function, max_lines),
+    };
+
+    let seconds = bash_timeout();
+    let (output, timed_out, target) = run_bash(&script, seconds, context.working_dir)?;
+    if timed_out {
+        anyhow::bail!("{} on {}{} was stopped after {}s", backend.name(), path, target, seconds);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let failed = || {
+        let lines: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+        anyhow::anyhow!("{} could not read {}{}: {}", backend.name(), path, target, lines[lines.len().saturating_sub(5)..].join("\n"))
+    };
+    match name {
+        "binary_info" if output.status.success() => Ok(binary::info(backend, path, &stdout, limit(40))),
+        "binary_strings" if output.status.success() || !stdout.trim().is_empty() => Ok(binary::strings(
+            backend,
+            &stdout,
+            min_length,
+            arguments["contains"].as_str().filter(|text| !text.is_empty()),
+            arguments["offset"].as_u64().unwrap_or(0) as usize,
+            limit(100),
+        )),
+        // 2 is the missing file
+        "disassemble_function" if output.status.code() != Some(2) => match binary::disassembly(backend, &stdout, function, max_lines) {
+            Some(text) => Ok(text),
+            None => {
+                let similar = binary::similar(&stdout);
+                if similar.is_empty() {
+                    Ok(format!("❌ No function {} in {}{}", function, path, target))
+                } else {
+                    Ok(format!("❌ No function {} in {}{}. Similar names:\n{}", function, path, target, similar.join("\n")))
+                }
+            }
+        },
+        _ => Err(failed()),
+    }
+}
+
+pub struct BinaryInfo;
+
+#[async_trait]
+impl Tool for BinaryInfo {
+    fn name(&self) -> &str {
+        "binary_info"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "binary_info",
+                "description": "Summarize a binary: format, architecture, entry point, hardening (PIE, NX, RELRO, canary), sections, imports, exports and the largest functions. Use this instead of running r2 or objdump through bash_exec.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The binary to inspect"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Most entries listed per section, import, export and function list (default: 40)"
+                        },
+                        "backend": {
+                            "type": "string",
+                            "enum": ["auto", "objdump", "r2"],
+                            "description": "binutils (objdump, nm, readelf) or radare2 (default: auto, binutils when installed)"
+                        }
+                    },
+                    "required": ["path"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        inspect(self.name(), arguments, context)
+    }
+}
+
+pub struct BinaryStrings;
+
+#[async_trait]
+impl Tool for BinaryStrings {
+    fn name(&self) -> &str {
+        "binary_strings"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "binary_strings",
+                "description": "List the printable strings in a binary with their file offsets, optionally only those containing some text, a page at a time",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The binary to read"
+                        },
+                        "contains": {
+                            "type": "string",
+                            "description": "Only strings containing this, ignoring case (e.g. http, password, .so)"
+                        },
+                        "min_length": {
+                            "type": "integer",
+                            "description": "Shortest string to list (default: 6)"
+                        },
+                        "wide": {
+                            "type": "boolean",
+                            "description": "Look for UTF-16 strings, as in Windows binaries (default: false)"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "description": "Matching strings to skip, for paging (default: 0)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Most strings to list (default: 100)"
+                        },
+                        "backend": {
+                            "type": "string",
+                            "enum": ["auto", "objdump", "r2"],
+                            "description": "binutils strings or radare2 (default: auto)"
+                        }
+                    },
+                    "required": ["path"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        inspect(self.name(), arguments, context)
+    }
+}
+
+pub struct DisassembleFunction;
+
+#[async_trait]
+impl Tool for DisassembleFunction {
+    fn name(&self) -> &str {
+        "disassemble_function"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "disassemble_function",
+                "description": "Disassemble one function of a binary, by symbol name or by hex address (0x...), and list the functions it calls",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "The binary"
+                        },
+                        "function": {
+                            "type": "string",
+                            "description": "Symbol name (e.g. main) or start address (e.g. 0x401136)"
+                        },
+                        "max_lines": {
+                            "type": "integer",
+                            "description": "Most lines of disassembly to return (default: 200)"
+                        },
+                        "backend": {
+                            "type": "string",
+                            "enum": ["auto", "objdump", "r2"],
+                            "description": "objdump or radare2 (default: auto, objdump when installed)"
+                        }
+                    },
+                    "required": ["path", "function"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        inspect(self.name(), arguments, context)
+    }
+}
@@ -0,0 +1,407 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{bash_timeout, run_bash, Approval, Tool, ToolContext};
+use crate::llm::background;
+use crate::llm::container::DevContainer;
+use crate::llm::processes::{self, Processes};
+use crate::llm::remote::shell_quote;
+
+pub struct BashExec;
+
+#[async_trait]
+impl Tool for BashExec {
+    fn name(&self) -> &str {
+        "bash_exec"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "bash_exec",
+                "description": "Execute a bash command and return the output",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "command": {
+                            "type": "string",
+                            "description": "The bash command to execute"
+                        },
+                        "timeout": {
+                            "type": "integer",
+                            "description": "Seconds before the command is stopped (default: tools.bash_timeout, 120; 0 for no limit)"
+                        }
+                    },
+                    "required": ["command"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        let command = arguments["command"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter"))?;
+        Ok(Some(Approval::Command(command.to_string())))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let command = arguments["command"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter"))?;
+
+        let seconds = arguments["timeout"].as_u64().unwrap_or_else(bash_timeout);
+        let (output, timed_out, target) = run_bash(command, seconds, context.working_dir)?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let exit_code = output.status.code().unwrap_or(-1);
+        let status_icon = if exit_code == 0 { "✅" } else { "⚠️" };
+
+        if timed_out {
+            return Ok(format!(
+                "⏱️ TIMEOUT after {}s{}: {}\nThe command was stopped; this is its output up to then. \
+                 Start servers and watchers with bash_background, or pass a larger timeout.\n\nStdout:\n{}\n\nStderr:\n{}",
+                seconds, target, command, stdout, stderr
+            ));
+        }
+        Ok(format!(
+            "{} Executed{}: {}\nExit code: {}\n\nStdout:\n{}\n\nStderr:\n{}",
+            status_icon,
+            target,
+            command,
+            exit_code,
+            stdout,
+            stderr
+        ))
+    }
+}
+
+pub struct RunSnippet;
+
+#[async_trait]
+impl Tool for RunSnippet {
+    fn name(&self) -> &str {
+        "run_snippet"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "run_snippet",
+                "description": "Run a short Python or Node snippet in a fresh temporary directory and return its output. For checking an algorithm or transforming data without touching the workspace",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "language": {
+                            "type": "string",
+                            "enum": ["python", "node"],
+                            "description": "The interpreter to use"
+                        },
+                        "code": {
+                            "type": "string",
+                            "description": "The program to run"
+                        },
+                        "timeout": {
+                            "type": "integer",
+                            "description": "Seconds before the snippet is stopped (default: 30)"
+                        }
+                    },
+                    "required": ["language", "code"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, _context: &ToolContext) -> Result<Option<Approval>> {
+        let (invocation, label) = snippet(arguments)?;
+        Ok(Some(Approval::Snippet { language: label.to_string(), invocation }))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let (invocation, label) = snippet(arguments)?;
+
+        // A throwaway directory, so files the snippet writes never land in the
+        // workspace; it is removed even when the snippet is stopped at its timeout
+        let script = format!(
+            "dir=$(mktemp -d) || exit 1; trap 'cd / && rm -rf \"$dir\"' EXIT; trap 'exit 143' TERM; cd \"$dir\" && {}",
+            invocation
+        );
+        let seconds = arguments["timeout"].as_u64().unwrap_or(30);
+        let (output, timed_out, target) = run_bash(&script, seconds, context.working_dir)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let heading = match (timed_out, output.status.code()) {
+            (true, _) => format!("⏱️ TIMEOUT: the {} snippet{} was stopped after {}s", label, target, seconds),
+            (false, Some(0)) => format!("✅ Ran {} snippet{}", label, target),
+            (false, code) => format!("⚠️ {} snippet{} failed with exit code {}", label, target, code.unwrap_or(-1)),
+        };
+        Ok(format!("{}\n\nStdout:\n{}\n\nStderr:\n{}", heading, stdout, stderr))
+    }
+}
+
+/// The interpreter command line for a run_snippet call, and the language
+/// as shown to the user
+fn snippet(arguments: &Value) -> Result<(String, &'static str)> {
+    let language = arguments["language"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'language' parameter"))?;
+    let code = arguments["code"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'code' parameter"))?;
+    let (interpreter, flag, label) = match language.to_lowercase().as_str() {
+        "python" | "python3" | "py" => ("python3", "-c", "Python"),
+        "node" | "javascript" | "js" => ("node", "-e", "Node"),
+        _ => anyhow::bail!("Unsupported language {}; use python or node", language),
+    };
+    Ok((format!("{} {} {}", interpreter, flag, shell_quote(code)), label))
+}
+
+pub struct BashBackground;
+
+#[async_trait]
+impl Tool for BashBackground {
+    fn name(&self) -> &str {
+        "bash_background"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "bash_background",
+                "description": "Start a long-running command (dev server, watcher) without waiting for it. Returns a handle for background_output and background_stop",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "command": {
+                            "type": "string",
+                            "description": "The bash command to start"
+                        }
+                    },
+                    "required": ["command"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, context: &ToolContext) -> Result<Option<Approval>> {
+        Ok(Some(Approval::Command(background_command(arguments, context)?.to_string())))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        background::start(background_command(arguments, context)?, context.working_dir)
+    }
+}
+
+/// The command of a bash_background call, which has to run on this machine
+fn background_command<'a>(arguments: &'a Value, context: &ToolContext) -> Result<&'a str> {
+    let command = arguments["command"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter"))?;
+    if let Some(remote) = context.remote {
+        anyhow::bail!("bash_background runs locally only; use bash_exec with nohup on {}", remote.describe());
+    }
+    if let Some(container) = DevContainer::active() {
+        anyhow::bail!("bash_background runs locally only; use bash_exec with nohup in {}", container.describe());
+    }
+    Ok(command)
+}
+
+pub struct BackgroundOutput;
+
+#[async_trait]
+impl Tool for BackgroundOutput {
+    fn name(&self) -> &str {
+        "background_output"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "background_output",
+                "description": "Check whether a background command is still running and show the end of its output",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "handle": {
+                            "type": "string",
+                            "description": "The handle from bash_background, e.g. bg1"
+                        },
+                        "lines": {
+                            "type": "integer",
+                            "description": "Number of output lines to show (default: 50)"
+                        }
+                    },
+                    "required": ["handle"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, _context: &ToolContext) -> Result<String> {
+        let handle = arguments["handle"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'handle' parameter"))?;
+        background::output(handle, arguments["lines"].as_u64().unwrap_or(50).max(1) as usize)
+    }
+}
+
+pub struct BackgroundStop;
+
+#[async_trait]
+impl Tool for BackgroundStop {
+    fn name(&self) -> &str {
+        "background_stop"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "background_stop",
+                "description": "Stop a background command and everything it started",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "handle": {
+                            "type": "string",
+                            "description": "The handle from bash_background"
+                        }
+                    },
+                    "required": ["handle"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, _context: &ToolContext) -> Result<String> {
+        let handle = arguments["handle"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'handle' parameter"))?;
+        background::stop(handle)
+    }
+}
+
+pub struct ListProcesses;
+
+#[async_trait]
+impl Tool for ListProcesses {
+    fn name(&self) -> &str {
+        "list_processes"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "list_processes",
+                "description": "List running processes started in this session (e.g. dev servers), with pid, memory and command line",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "filter": {
+                            "type": "string",
+                            "description": "Only show processes whose command line contains this text"
+                        },
+                        "all": {
+                            "type": "boolean",
+                            "description": "List every process on the machine, not just this session's (default: false)"
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let processes = local_processes(self.name(), context)?;
+        Ok(processes.list(arguments["filter"].as_str(), arguments["all"].as_bool().unwrap_or(false)))
+    }
+}
+
+/// The processes on this machine; the process tools cannot see past a
+/// remote target or dev container
+fn local_processes(tool: &str, context: &ToolContext) -> Result<Processes> {
+    if let Some(remote) = context.remote {
+        anyhow::bail!("{} only sees local processes; use bash_exec to manage processes on {}", tool, remote.describe());
+    }
+    if let Some(container) = DevContainer::active() {
+        anyhow::bail!("{} only sees local processes; use bash_exec to manage processes in {}", tool, container.describe());
+    }
+    Ok(Processes::snapshot())
+}
+
+pub struct KillProcess;
+
+impl KillProcess {
+    /// The process a call names, shown as "pid (command)", and whether this
+    /// session started it. Others need outside_session.
+    fn target(&self, arguments: &Value, context: &ToolContext) -> Result<(Processes, u32, String, bool)> {
+        let processes = local_processes(self.name(), context)?;
+        let pid = arguments["pid"]
+            .as_u64()
+            .and_then(|pid| u32::try_from(pid).ok())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'pid' parameter"))?;
+        let process = processes.find(pid).ok_or_else(|| anyhow::anyhow!("No process with pid {}", pid))?;
+        let label = format!("{} ({})", pid, processes::label(process));
+        let started_here = processes.started_here(process);
+        if !started_here && !arguments["outside_session"].as_bool().unwrap_or(false) {
+            anyhow::bail!(
+                "process {} was not started in this session. Call kill_process with outside_session: true if it should be stopped anyway",
+                label
+            );
+        }
+        Ok((processes, pid, label, started_here))
+    }
+}
+
+#[async_trait]
+impl Tool for KillProcess {
+    fn name(&self) -> &str {
+        "kill_process"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "kill_process",
+                "description": "Stop a process by pid (SIGTERM, or SIGKILL with force). Use instead of running kill in bash",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pid": {
+                            "type": "integer",
+                            "description": "The process id from list_processes"
+                        },
+                        "force": {
+                            "type": "boolean",
+                            "description": "Kill immediately with SIGKILL (default: false)"
+                        },
+                        "outside_session": {
+                            "type": "boolean",
+                            "description": "Allow stopping a process this session did not start (default: false)"
+                        }
+                    },
+                    "required": ["pid"]
+                }
+            }
+        })
+    }
+
+    fn required_approval(&self, arguments: &Value, context: &ToolContext) -> Result<Option<Approval>> {
+        let (_, _, process, started_here) = self.target(arguments, context)?;
+        Ok(Some(Approval::Kill { process, started_here }))
+    }
+
+    async fn execute(&self, arguments: &Value, context: &ToolContext) -> Result<String> {
+        let (mut processes, pid, _, _) = self.target(arguments, context)?;
+        processes.kill(pid, arguments["force"].as_bool().unwrap_or(false))
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Tool, ToolContext};
+
+pub struct WebSearch;
+
+#[async_trait]
+impl Tool for WebSearch {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "web_search",
+                "description": "Search the web for information using DuckDuckGo. Returns summarized results with titles, URLs, and snippets.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "The search query"
+                        },
+                        "max_results": {
+                            "type": "number",
+                            "description": "Maximum number of results to return (default: 5)"
+                        }
+                    },
+                    "required": ["query"]
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, arguments: &Value, _context: &ToolContext) -> Result<String> {
+        let query = arguments["query"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+        let max_results = arguments["max_results"]
+            .as_u64()
+            .unwrap_or(5) as usize;
+
+        // Use DuckDuckGo HTML search (no API key needed)
+        let search_url = format!(
+            "https://html.duckduckgo.com/html/?q={}",
+            urlencoding::encode(query)
+        );
+
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36")
+            .build()?;
+
+        let response = client
+            .get(&search_url)
+            .send()
+            .await
+            .context("Failed to fetch search results")?;
+
+        let html = response.text().await?;
+
+        // Parse results from HTML (simple parsing)
+        let mut results = Vec::new();
+        let lines: Vec<&str> = html.lines().collect();
+
+        for i in 0..lines.len() {
+            if lines[i].contains("result__a") && results.len() < max_results {
+                // Extract title
+                if let Some(title_start) = lines[i].find(">") {
+                    if let Some(title_end) = lines[i][title_start..].find("</a>") {
+                        let title = &lines[i][title_start + 1..title_start + title_end];
+                        let title = html_escape::decode_html_entities(title);
+
+                        // Extract URL
+                        if let Some(url_start) = lines[i].find("href=\"") {
+                            if let Some(url_end) = lines[i][url_start + 6..].find("\"") {
+                                let url = &lines[i][url_start + 6..url_start + 6 + url_end];
+
+                                // Find snippet in next few lines
+                                let mut snippet = String::new();
+                                for next in lines.iter().take(std::cmp::min(i + 10, lines.len())).skip(i + 1) {
+                                    if next.contains("result__snippet") {
+                                        if let Some(snip_start) = next.find(">") {
+                                            if let Some(snip_end) = next[snip_start..].find("</") {
+                                                snippet = next[snip_start + 1..snip_start + snip_end].to_string();
+                                                snippet = html_escape::decode_html_entities(&snippet).to_string();
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
+
+                                results.push(format!(
+                                    "Title: {}\nURL: {}\nSnippet: {}\n",
+                                    title, url, snippet
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if results.is_empty() {
+            Ok(format!("ℹ️  No search results found for '{}'", query))
+        } else {
+            Ok(format!("🌐 Found {} search results for '{}':\n\n{}", results.len(), query, results.join("\n---\n\n")))
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+/// Lines of a tool result copied into the transcript
+const MAX_TOOL_LINES: usize = 40;
+
+static OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Append every session of this run to `path` (from --transcript), checking
+/// up front that it can be written
+pub fn set_override(path: PathBuf) -> Result<()> {
+    append(&path, "")?;
+    let _ = OVERRIDE.set(path);
+    Ok(())
+}
+
+/// Where session `id` is transcribed: the --transcript file, or a file per
+/// session in assistant.transcript_dir
+pub fn path_for(id: &str) -> Option<PathBuf> {
+    if let Some(path) = OVERRIDE.get() {
+        return Some(path.clone());
+    }
+    let dir = Config::load().ok()?.assistant.transcript_dir?;
+    Some(PathBuf::from(shellexpand::tilde(&dir).as_ref()).join(format!("{}.md", id)))
+}
+
+pub fn append(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open transcript {}", path.display()))?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// The transcript entry for a message; tool results are cut to their first
+/// lines
+pub fn entry(role: &str, content: &str, tool: Option<&str>) -> String {
+    let time = Local::now().format("%H:%M:%S");
+    if let Some(tool) = tool {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut shown = lines.iter().take(MAX_TOOL_LINES).copied().collect::<Vec<_>>().join("\n");
+        if lines.len() > MAX_TOOL_LINES {
+            shown.push_str(&format!("\n[… {} more lines]", lines.len() - MAX_TOOL_LINES));
+        }
+        return format!("\n**🔧 {}** · {}\n\n{}\n", tool, time, fenced(&shown, "text"));
+    }
+    let speaker = if role == "assistant" { "🤖 Assistant" } else { "🧑 You" };
+    format!("\n## {} · {}\n\n{}\n", speaker, time, content.trim())
+}
+
+/// A code block fenced so the text cannot close it early
+pub fn fenced(text: &str, language: &str) -> String {
+    let mut fence = "```".to_string();
+    while text.contains(&fence) {
+        fence.push('`');
+    }
+    format!("{}{}\n{}\n{}", fence, language, text.trim_end(), fence)
+}
@@ -0,0 +1,241 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::tools::{is_binary, walk};
+
+/// Files bigger than this are counted but not read
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Commits looked at for churn, newest first
+const MAX_COMMITS: usize = 2000;
+
+/// A language: its name, file extensions (or exact file names) and how its
+/// comments start. Block comments are only tracked as whole lines.
+struct Language {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    line_comment: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+const C_STYLE: Option<(&str, &str)> = Some(("/*", "*/"));
+
+const LANGUAGES: &[Language] = &[
+    Language { name: "Rust", extensions: &["rs"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "Python", extensions: &["py", "pyi"], line_comment: &["#"], block_comment: None },
+    Language { name: "JavaScript", extensions: &["js", "mjs", "cjs", "jsx"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "TypeScript", extensions: &["ts", "mts", "cts", "tsx"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "Go", extensions: &["go"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "C", extensions: &["c", "h"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "C++", extensions: &["cc", "cpp", "cxx", "hh", "hpp", "hxx"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "C#", extensions: &["cs"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "Java", extensions: &["java"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "Kotlin", extensions: &["kt", "kts"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "Swift", extensions: &["swift"], line_comment: &["//"], block_comment: C_STYLE },
+    Language { name: "Ruby", extensions: &["rb"], line_comment: &["#"], block_comment: None },
+    Language { name: "PHP", extensions: &["php"], line_comment: &["//", "#"], block_comment: C_STYLE },
+    Language { name: "Lua", extensions: &["lua"], line_comment: &["--"], block_comment: None },
+    Language { name: "Shell", extensions: &["sh", "bash", "zsh"], line_comment: &["#"], block_comment: None },
+    Language { name: "SQL", extensions: &["sql"], line_comment: &["--"], block_comment: C_STYLE },
+    Language { name: "HTML", extensions: &["html", "htm"], line_comment: &[], block_comment: Some(("<!--", "-->")) },
+    Language { name: "CSS", extensions: &["css", "scss", "sass", "less"], line_comment: &[], block_comment: C_STYLE },
+    Language { name: "Vue", extensions: &["vue"], line_comment: &["//"], block_comment: Some(("<!--", "-->")) },
+    Language { name: "Svelte", extensions: &["svelte"], line_comment: &["//"], block_comment: Some(("<!--", "-->")) },
+    Language { name: "Markdown", extensions: &["md", "markdown"], line_comment: &[], block_comment: None },
+    Language { name: "TOML", extensions: &["toml"], line_comment: &["#"], block_comment: None },
+    Language { name: "YAML", extensions: &["yml", "yaml"], line_comment: &["#"], block_comment: None },
+    Language { name: "JSON", extensions: &["json"], line_comment: &[], block_comment: None },
+    Language { name: "Makefile", extensions: &["Makefile", "makefile", "mk"], line_comment: &["#"], block_comment: None },
+    Language { name: "Dockerfile", extensions: &["Dockerfile"], line_comment: &["#"], block_comment: None },
+    Language { name: "Nix", extensions: &["nix"], line_comment: &["#"], block_comment: None },
+];
+
+fn language_of(path: &Path) -> Option<&'static Language> {
+    let name = path.file_name()?.to_str()?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or(name);
+    LANGUAGES
+        .iter()
+        .find(|language| language.extensions.contains(&name) || language.extensions.contains(&extension))
+}
+
+#[derive(Default, Clone, Copy)]
+struct Lines {
+    code: usize,
+    comments: usize,
+    blank: usize,
+}
+
+impl Lines {
+    fn count(text: &str, language: &Language) -> Self {
+        let mut lines = Self::default();
+        let mut in_block = false;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                lines.blank += 1;
+            } else if in_block {
+                lines.comments += 1;
+                in_block = !language.block_comment.is_some_and(|(_, end)| line.contains(end));
+            } else if language.line_comment.iter().any(|prefix| line.starts_with(prefix)) {
+                lines.comments += 1;
+            } else if let Some((start, end)) = language.block_comment.filter(|(start, _)| line.starts_with(start)) {
+                lines.comments += 1;
+                in_block = !line[start.len()..].contains(end);
+            } else {
+                lines.code += 1;
+            }
+        }
+        lines
+    }
+
+    fn total(&self) -> usize {
+        self.code + self.comments + self.blank
+    }
+
+    fn add(&mut self, other: Lines) {
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blank += other.blank;
+    }
+}
+
+/// Size and shape of a directory tree, as the file tools see it (ignored
+/// files and build directories left out)
+pub struct WorkspaceStats {
+    /// Per language: file count and lines
+    languages: BTreeMap<&'static str, (usize, Lines)>,
+    /// Files in no known language, binaries included
+    other_files: usize,
+    /// Every counted file with its line total, relative to the root
+    files: Vec<(PathBuf, usize)>,
+    /// Commits touching each existing file, when the tree is in git
+    churn: Option<HashMap<PathBuf, usize>>,
+    churn_days: u64,
+}
+
+impl WorkspaceStats {
+    pub fn collect(root: &Path, churn_days: u64) -> Result<Self> {
+        let mut stats = Self {
+            languages: BTreeMap::new(),
+            other_files: 0,
+            files: vec![],
+            churn: None,
+            churn_days,
+        };
+        for entry in walk(root, false).build() {
+            let entry = entry?;
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+            let readable = entry.metadata().is_ok_and(|metadata| metadata.len() <= MAX_FILE_SIZE);
+            let source = language_of(path).filter(|_| readable).and_then(|language| {
+                let bytes = fs::read(path).ok().filter(|bytes| !is_binary(&relative.to_string_lossy(), bytes))?;
+                Some((language, bytes))
+            });
+            let Some((language, bytes)) = source else {
+                stats.other_files += 1;
+                continue;
+            };
+            let lines = Lines::count(&String::from_utf8_lossy(&bytes), language);
+            let (files, total) = stats.languages.entry(language.name).or_default();
+            *files += 1;
+            total.add(lines);
+            stats.files.push((relative, lines.total()));
+        }
+        stats.files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        if churn_days > 0 {
+            stats.churn = churn(root, churn_days);
+        }
+        Ok(stats)
+    }
+
+    /// Files ordered by commits in the churn window, then by size
+    fn hotspots(&self) -> Vec<(&Path, usize, usize)> {
+        let Some(churn) = &self.churn else {
+            return vec![];
+        };
+        let mut hotspots: Vec<(&Path, usize, usize)> = self
+            .files
+            .iter()
+            .filter_map(|(path, lines)| churn.get(path).map(|commits| (path.as_path(), *commits, *lines)))
+            .collect();
+        hotspots.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)));
+        hotspots
+    }
+
+    pub fn render(&self, shown: &str, top: usize) -> String {
+        let mut totals = Lines::default();
+        for (_, lines) in self.languages.values() {
+            totals.add(*lines);
+        }
+        let mut text = format!(
+            "📊 {}: {} files, {} lines of code ({} comment, {} blank)",
+            shown,
+            self.files.len() + self.other_files,
+            totals.code,
+            totals.comments,
+            totals.blank
+        );
+        if self.languages.is_empty() {
+            text.push_str("\n\nNo source files in a known language.");
+            return text;
+        }
+
+        let mut languages: Vec<_> = self.languages.iter().collect();
+        languages.sort_by_key(|(_, (_, lines))| std::cmp::Reverse(lines.code));
+        text.push_str("\n\n| Language | Files | Code | Comments | Blank |\n|---|---:|---:|---:|---:|\n");
+        for (name, (files, lines)) in languages {
+            text.push_str(&format!("| {} | {} | {} | {} | {} |\n", name, files, lines.code, lines.comments, lines.blank));
+        }
+        if self.other_files > 0 {
+            text.push_str(&format!("| (other) | {} | | | |\n", self.other_files));
+        }
+
+        text.push_str("\nLargest files (lines):\n");
+        for (path, lines) in self.files.iter().take(top) {
+            text.push_str(&format!("{:>7}  {}\n", lines, path.display()));
+        }
+
+        match &self.churn {
+            None if self.churn_days > 0 => text.push_str("\nNo git history, so no churn hotspots."),
+            None => {}
+            Some(_) => {
+                let hotspots = self.hotspots();
+                if hotspots.is_empty() {
+                    text.push_str(&format!("\nNo commits in the last {} days touch these files.", self.churn_days));
+                } else {
+                    text.push_str(&format!("\nChurn hotspots (commits in the last {} days, lines):\n", self.churn_days));
+                    for (path, commits, lines) in hotspots.into_iter().take(top) {
+                        text.push_str(&format!("{:>4} {:>7}  {}\n", commits, lines, path.display()));
+                    }
+                }
+            }
+        }
+        text.trim_end().to_string()
+    }
+}
+
+/// Commits per file over the last `days`, with paths relative to `root`;
+/// None when `root` is not in a git repository
+fn churn(root: &Path, days: u64) -> Option<HashMap<PathBuf, usize>> {
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--relative", "--name-only", "--format="])
+        .arg(format!("--since={} days ago", days))
+        .arg(format!("--max-count={}", MAX_COMMITS))
+        .current_dir(root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut churn = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines().filter(|line| !line.is_empty()) {
+        *churn.entry(PathBuf::from(line)).or_insert(0) += 1;
+    }
+    Some(churn)
+}
@@ -1,11 +1,14 @@
 use clap::{Parser, Subcommand};
 use anyhow::Result;
+use std::io::IsTerminal;
 
 mod config;
 mod backends;
 mod commands;
 mod llm;
 mod agents;
+mod voice;
+mod error;
 
 #[derive(Parser)]
 #[command(name = "vork")]
@@ -29,6 +32,26 @@ struct Cli {
     /// Agent to use (e.g., rust-expert, reviewer, debugger)
     #[arg(short, long, global = true)]
     agent: Option<String>,
+
+    /// Config profile to use (from [profiles.<name>], or set VORK_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Run file and shell tools on another machine over SSH (user@host[:workdir], or "local")
+    #[arg(long, global = true)]
+    remote: Option<String>,
+
+    /// Run shell commands in the project's dev container: ask, always or never (default: container.mode)
+    #[arg(long, global = true)]
+    container: Option<String>,
+
+    /// Also append the conversation to this Markdown file as it happens
+    #[arg(long, global = true, value_name = "PATH")]
+    transcript: Option<std::path::PathBuf>,
+
+    /// Name the session, or continue the one already given this name
+    #[arg(long, global = true, value_name = "NAME")]
+    session: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -47,6 +70,28 @@ enum Commands {
         #[arg(short, long, default_value = "auto")]
         backend: String,
     },
+    /// Find and manage models across backends
+    Models {
+        #[command(subcommand)]
+        action: ModelsAction,
+    },
+    /// Quantize a GGUF model in models_dir with llama.cpp's llama-quantize
+    Quantize {
+        /// Model alias, file name or path (e.g. qwen3-8b-f16)
+        model: String,
+        /// Target quantization (Q4_K_M, Q5_K_M, Q8_0, IQ4_XS, ...)
+        #[arg(long, default_value = "Q4_K_M")]
+        to: String,
+        /// Output file (default: next to the source, named for the new quant)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Importance matrix file, recommended for IQ1/IQ2 quants
+        #[arg(long)]
+        imatrix: Option<std::path::PathBuf>,
+        /// Overwrite the output if it exists
+        #[arg(short, long)]
+        force: bool,
+    },
     /// Run/serve a model
     Run {
         /// Model name
@@ -65,6 +110,8 @@ enum Commands {
         /// Show config file path
         #[arg(short, long)]
         path: bool,
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
     },
     /// Interactive configuration setup
     Setup,
@@ -78,9 +125,19 @@ enum Commands {
         create: bool,
         /// Show details for a specific agent
         agent_name: Option<String>,
+        /// Rewrite the bundled agents from this version (custom agents are untouched)
+        #[arg(long)]
+        force_recreate_defaults: bool,
+        #[command(subcommand)]
+        action: Option<AgentsAction>,
     },
     /// Check status of LLM backends
     Status,
+    /// Load a model ahead of time (starting llama-server if needed), e.g. from a shell profile
+    Warm {
+        /// Switch to this preset first
+        preset: Option<String>,
+    },
     /// Interactive chat with AI coding assistant
     Chat {
         /// Server URL (default: http://localhost:8080)
@@ -92,21 +149,36 @@ enum Commands {
     },
     /// Ask a one-off question to the AI assistant
     Ask {
-        /// The question to ask
-        question: String,
+        /// The question to ask; with --template, the text for its {{input}}
+        #[arg(required_unless_present = "template")]
+        question: Option<String>,
         /// Disable tool calling (get direct response only)
         #[arg(long)]
         no_tools: bool,
+        /// Plain LLM: send only the question, without the coding-assistant
+        /// system prompt or tools
+        #[arg(long, conflicts_with = "no_tools")]
+        raw: bool,
+        /// Use the prompt template ~/.vork/prompts/<NAME>.md
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// A template variable, filling in {{key}} (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE", requires = "template")]
+        vars: Vec<String>,
     },
     /// Resume a previous session
     Resume {
-        /// Session ID to resume
+        /// Session ID or name to resume
         session_id: Option<String>,
         /// Resume the last session
         #[arg(short, long)]
         last: bool,
     },
     /// Non-interactive mode (read-only by default)
+    ///
+    /// Exit codes: 0 success, 2 denied by policy, 3 iteration limit, 4 server error,
+    /// 5 timeout, 6 token/tool-call budget exceeded, 7 server unavailable,
+    /// 8 model not found, 9 context overflow
     Exec {
         /// The task to execute
         prompt: String,
@@ -116,17 +188,497 @@ enum Commands {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
+        /// Continue an existing session (ID or name) instead of starting fresh
+        #[arg(long, value_name = "SESSION")]
+        resume: Option<String>,
+        /// Abort the run after this many seconds
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Abort once this many tokens (prompt + completion) have been used
+        #[arg(long)]
+        max_tokens: Option<usize>,
+        /// Abort before executing more than this many tool calls
+        #[arg(long)]
+        max_tool_calls: Option<usize>,
+        /// Allow edits in the workspace and safe commands, and send anything
+        /// that needs approval to the [remote_approval] page and webhook
+        #[arg(long)]
+        approve_remotely: bool,
+        /// Consensus mode: ask these agents in parallel (name or name@model) and
+        /// let the --agent judge pick or merge the best answer
+        #[arg(long, value_delimiter = ',', value_name = "AGENTS")]
+        agents: Vec<String>,
+        /// Show the model's plan and ask once before any tool runs
+        #[arg(long)]
+        require_plan_approval: bool,
+        /// Sampling temperature (default 0.7)
+        #[arg(long)]
+        temperature: Option<f32>,
+        /// Fix the sampling seed so repeated runs can be compared
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Shortcut for --temperature 0 --seed 0 (or the given --seed)
+        #[arg(long, conflicts_with = "temperature")]
+        deterministic: bool,
+    },
+    /// Serve editor plugins (Neovim, VS Code) with JSON-RPC over stdio
+    Rpc,
+    /// Answer mentions in a Slack or Discord channel, one session per thread
+    Bot {
+        /// slack or discord (default: bot.platform)
+        #[arg(long)]
+        platform: Option<String>,
+        /// Channel id to listen in (default: bot.channel)
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// Run prompts on a cron schedule (nightly audits, dependency checks, changelog drafts)
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Audit the workspace with the code-auditor agent and write SARIF and Markdown reports
+    Audit {
+        /// Files or directories to audit (default: the whole workspace)
+        paths: Vec<std::path::PathBuf>,
+        /// SARIF output file; the Markdown report is written next to it
+        #[arg(short, long, default_value = "vork-audit.sarif")]
+        output: std::path::PathBuf,
+        /// Exit with status 1 if any finding is at or above this level (note, warning, error)
+        #[arg(long, value_name = "LEVEL")]
+        fail_on: Option<String>,
+    },
+    /// Scan the workspace for committed secrets and identify licenses, no model needed
+    Scan {
+        /// Files or directories to scan (default: the whole workspace)
+        paths: Vec<std::path::PathBuf>,
+        /// JSON output file; the Markdown report is written next to it
+        #[arg(short, long, default_value = "vork-scan.json")]
+        output: std::path::PathBuf,
+        /// Have the security-auditor agent (or --agent) sort real secrets from false positives
+        #[arg(long)]
+        triage: bool,
+        /// Exit with status 1 if a secret is found that triage did not rule out
+        #[arg(long)]
+        fail_on_secrets: bool,
+    },
+    /// List past sessions or export one as Markdown or a Jupyter notebook
+    Sessions {
+        #[command(subcommand)]
+        action: Option<SessionsAction>,
+    },
+    /// Search the prompts of all past sessions, then run one again or resume its session
+    History {
+        /// Words every prompt shown must contain
+        query: Vec<String>,
+        /// Only prompts from sessions started in the current directory
+        #[arg(long)]
+        project: bool,
+        /// Most prompts to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Browse and resolve the findings auditors recorded in .vork/findings.json
+    Findings {
+        #[command(subcommand)]
+        action: Option<FindingsAction>,
+    },
+    /// Explain a file, line range, or symbol (e.g. src/main.rs:10-40, src/lib.rs::parse)
+    Explain {
+        /// Target as <path>, <path>:<start>-<end>, or <path>::<symbol>
+        target: String,
+    },
+    /// Run a build/test command and let the agent fix failures until it passes
+    Fix {
+        /// Check command to run (auto-detected from the project if omitted)
+        #[arg(long)]
+        cmd: Option<String>,
+        /// Maximum number of fix attempts
+        #[arg(long, default_value = "5")]
+        max_iterations: usize,
+        /// Auto-approve edits inside the workspace instead of following approval_policy
+        #[arg(long)]
+        auto: bool,
+    },
+    /// Watch the workspace and run checks on save, annotating or fixing failures
+    Watch {
+        /// Check command(s) to run on change (defaults to [watch].checks or auto-detect)
+        #[arg(long = "cmd")]
+        cmds: Vec<String>,
+        /// Only react to paths matching these globs (defaults to [watch].globs)
+        #[arg(long = "glob")]
+        globs: Vec<String>,
+        /// Let the agent fix new failures instead of only annotating them
+        #[arg(long)]
+        fix: bool,
+        /// Maximum fix attempts per failure
+        #[arg(long, default_value = "3")]
+        max_iterations: usize,
+        /// With --fix, auto-approve edits inside the workspace instead of following approval_policy
+        #[arg(long)]
+        auto: bool,
+    },
+    /// Generate a PR title and description from the current branch
+    Pr {
+        /// Branch to compare against (defaults to the upstream or remote default branch)
+        #[arg(long)]
+        base: Option<String>,
+        /// Open the PR with the GitHub CLI (gh)
+        #[arg(long)]
+        create: bool,
+        /// Open the PR as a draft (with --create)
+        #[arg(long)]
+        draft: bool,
+    },
+    /// Update CHANGELOG.md from git history since the last tag
+    Changelog {
+        /// Tag or commit to start from (defaults to the most recent tag)
+        #[arg(long)]
+        since: Option<String>,
+        /// Changelog file to update
+        #[arg(long, default_value = "CHANGELOG.md")]
+        file: String,
+        /// Write without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Manage git hooks that call into vork
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+    /// Show token, tool call and time usage statistics
+    Stats {
+        /// Only include the last N days
+        #[arg(long)]
+        days: Option<u32>,
+        /// Only include sessions started in the current directory
+        #[arg(long)]
+        project: bool,
+    },
+    /// Record from the microphone and print the whisper.cpp transcript
+    Dictate {
+        /// Also copy the transcript to the clipboard
+        #[arg(long)]
+        copy: bool,
     },
     /// Benchmark model presets for speed and quality
-    Benchmark,
-    /// Show preset performance and agent assignments
-    Presets,
+    Benchmark {
+        /// Also score each answer (judge prompt plus a rustc compile check)
+        #[arg(long)]
+        quality: bool,
+        /// Server that judges answers (default: the preset being tested)
+        #[arg(long)]
+        judge_server: Option<String>,
+        /// Model name to send to the judge server
+        #[arg(long)]
+        judge_model: Option<String>,
+        /// Only benchmark these presets (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        presets: Vec<String>,
+        /// Only run these tests: codegen, bugfix, design, algorithm, refactor
+        #[arg(long, value_delimiter = ',')]
+        tests: Vec<String>,
+        /// Stress-test long prompts instead: needle retrieval at 4k/16k/32k/64k tokens
+        #[arg(long, conflicts_with_all = ["tests", "quality", "judge_server", "judge_model"])]
+        context: bool,
+        /// Continue an interrupted run, skipping presets and tests already done
+        #[arg(long)]
+        resume: bool,
+        /// Directory for the JSON/CSV/HTML reports (default: ~/.vork/benchmarks)
+        #[arg(long)]
+        report_dir: Option<std::path::PathBuf>,
+        /// Sampling temperature (default 0.7)
+        #[arg(long)]
+        temperature: Option<f32>,
+        /// Fix the sampling seed so repeated runs can be compared
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Shortcut for --temperature 0 --seed 0 (or the given --seed)
+        #[arg(long, conflicts_with = "temperature")]
+        deterministic: bool,
+    },
+    /// Show preset performance and agent assignments, or manage presets
+    Presets {
+        #[command(subcommand)]
+        action: Option<PresetsAction>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print a setting by dotted key (e.g. llamacpp.context_size)
+    Get {
+        key: String,
+    },
+    /// Change a setting by dotted key, validating its type
+    Set {
+        key: String,
+        value: String,
+    },
+    /// Validate the config and list every problem with a suggested fix
+    Check,
+}
+
+#[derive(Subcommand)]
+enum ModelsAction {
+    /// Search the Ollama library and HuggingFace for models (GGUF)
+    Search {
+        /// Search term (e.g. qwen coder)
+        term: String,
+        /// Where to search: all, ollama or hf
+        #[arg(long, default_value = "all")]
+        source: String,
+        /// Results per source
+        #[arg(short, long, default_value = "5")]
+        limit: usize,
+    },
+    /// Show disk usage and last use of installed models, and delete the ones you pick
+    Prune {
+        /// Models unused for this many days (and in no config or preset) are flagged stale
+        #[arg(long, default_value = "30")]
+        stale_days: u32,
+        /// Only show the report, never prompt to delete
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    /// Schedule a prompt (--agent picks the agent), e.g. vork schedule add "0 7 * * *" "audit the repo"
+    Add {
+        /// Five-field cron expression in local time, or @hourly, @daily, @weekly, @monthly
+        cron: String,
+        /// The task to run
+        prompt: String,
+        /// Name to refer to the task by (default: from the prompt)
+        #[arg(long)]
+        name: Option<String>,
+        /// Directory to run in (default: the current directory)
+        #[arg(long)]
+        workspace: Option<String>,
+        /// URL to POST the result to, or a shell command that gets it on stdin
+        #[arg(long)]
+        notify: Option<String>,
+        /// Allow edits and commands that would otherwise need approval
+        #[arg(long)]
+        full_auto: bool,
+    },
+    /// List scheduled tasks with their next and last runs
+    List,
+    /// Remove a scheduled task
+    Remove {
+        name: String,
+    },
+    /// Run due tasks every minute until stopped, or one task now
+    Run {
+        /// Run this task immediately and exit
+        name: Option<String>,
+        /// Run whatever came due since the last check and exit (for cron or systemd timers)
+        #[arg(long, conflicts_with = "name")]
+        once: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AgentsAction {
+    /// Edit an agent in $EDITOR (validated before saving)
+    Edit {
+        name: String,
+    },
+    /// Delete an agent
+    Delete {
+        name: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Restore a built-in agent to its bundled definition
+    Reset {
+        name: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// List previous versions of an agent
+    History {
+        name: String,
+    },
+    /// Restore a previous version of an agent (the newest by default)
+    Rollback {
+        name: String,
+        /// Number from `vork agents history` or the version's timestamp
+        version: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PresetsAction {
+    /// List preset files (● marks the one last switched to)
+    List,
+    /// Show a preset and how it differs from the current config
+    Show {
+        name: String,
+    },
+    /// Create a preset from the current config and open it in $EDITOR
+    New {
+        name: String,
+        /// Start from an existing preset instead of the current config
+        #[arg(long)]
+        from: Option<String>,
+        /// Save without opening the editor
+        #[arg(long)]
+        no_edit: bool,
+    },
+    /// Edit a preset in $EDITOR (validated before saving)
+    Edit {
+        name: String,
+    },
+    /// Copy a preset under a new name
+    Copy {
+        from: String,
+        to: String,
+    },
+    /// Delete a preset
+    Delete {
+        name: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HooksAction {
+    /// Install prepare-commit-msg (AI commit messages) and optionally pre-push review
+    Install {
+        /// Also install a pre-push hook that reviews the outgoing diff
+        #[arg(long)]
+        pre_push: bool,
+        /// Replace existing hooks that were not installed by vork
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove hooks installed by vork
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+enum SessionsAction {
+    /// List sessions, most recent first
+    List {
+        /// Only sessions started in the current directory
+        #[arg(long)]
+        project: bool,
+        /// Most sessions to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Browse a session in a read-only viewer, with tool results collapsed
+    View {
+        /// Session ID or name (default: the most recent)
+        session_id: Option<String>,
+    },
+    /// Write a session as Markdown or a notebook, with commands as code cells
+    Export {
+        /// Session ID (default: the most recent)
+        session_id: Option<String>,
+        /// markdown or ipynb
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Output file, or - for stdout (default: vork-session-<id>.<md|ipynb>)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FindingsAction {
+    /// List findings, open ones unless --all or --resolved
+    List {
+        /// Include resolved findings
+        #[arg(long)]
+        all: bool,
+        /// Only resolved findings
+        #[arg(long, conflicts_with = "all")]
+        resolved: bool,
+        /// Only findings in files under this path
+        #[arg(long)]
+        file: Option<String>,
+        /// Only findings at this severity or worse (error, warning, note)
+        #[arg(long)]
+        severity: Option<String>,
+    },
+    /// Show a finding in full
+    Show {
+        id: u64,
+    },
+    /// Mark a finding as resolved
+    Resolve {
+        id: u64,
+        /// How it was resolved
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Open a resolved finding again
+    Reopen {
+        id: u64,
+    },
+    /// Create issues from open findings with gh or glab, or write them as JSON lines
+    Export {
+        /// github, gitlab or jsonl
+        #[arg(long)]
+        to: String,
+        /// Only findings in files under this path
+        #[arg(long)]
+        file: Option<String>,
+        /// Only findings at this severity or worse (error, warning, note)
+        #[arg(long)]
+        severity: Option<String>,
+        /// Extra label for every issue (repeatable)
+        #[arg(long = "label")]
+        labels: Vec<String>,
+        /// File for jsonl output (default: stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Also export findings that already have an issue
+        #[arg(long)]
+        again: bool,
+        /// Show the issues without creating them
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(profile) = cli.profile.clone() {
+        config::Config::set_active_profile(profile);
+    }
+    if let Some(remote) = cli.remote.clone() {
+        llm::remote::RemoteTarget::set_override(remote);
+    }
+    if let Some(mode) = cli.container.clone() {
+        llm::container::DevContainer::set_mode(mode);
+    }
+    if let Some(name) = &cli.session {
+        llm::Session::set_name(name)?;
+    }
+    if let Some(path) = cli.transcript.clone() {
+        llm::transcript::set_override(path)?;
+    }
+
+    // Runs nobody is waiting on yield the shared server to interactive ones
+    if matches!(
+        cli.command,
+        Some(Commands::Exec { .. } | Commands::Audit { .. } | Commands::Scan { .. } | Commands::Schedule { .. } | Commands::Watch { .. } | Commands::Benchmark { .. })
+    ) {
+        llm::queue::set_priority(llm::queue::Priority::Batch);
+    }
+
     // If no subcommand, default to TUI mode with auto-server-start
     let command = cli.command.unwrap_or_else(|| {
         // Default to TUI mode (will auto-start server)
@@ -136,6 +688,30 @@ async fn main() -> Result<()> {
         }
     });
 
+    // First run at a terminal: ask for the essentials instead of writing
+    // defaults that may not work on this machine
+    let interactive = !matches!(command, Commands::Setup | Commands::Config { .. } | Commands::Exec { .. } | Commands::Rpc | Commands::Bot { .. } | Commands::Schedule { .. });
+    if interactive && std::io::stdin().is_terminal() && !config::Config::config_path()?.exists() {
+        commands::setup::first_run()?;
+    }
+
+    // Commands whose tools run shell commands can use the project's dev container
+    let runs_tools = matches!(
+        command,
+        Commands::Chat { .. } | Commands::Ask { raw: false, .. } | Commands::Resume { .. } | Commands::Exec { .. } | Commands::Fix { .. } | Commands::Watch { .. } | Commands::Rpc
+    );
+    if runs_tools && llm::remote::RemoteTarget::active().is_none() {
+        if let Ok(config) = config::Config::load() {
+            let can_ask = !matches!(command, Commands::Exec { .. } | Commands::Rpc);
+            if let Err(e) = llm::container::DevContainer::select(&config, can_ask) {
+                eprintln!("⚠️  Dev container check skipped: {}", e);
+            }
+        }
+    }
+
+    // Commands the model started with bash_background end with the session
+    let background = llm::background::StopOnExit;
+
     match command {
         Commands::List { installed } => {
             commands::list::execute(installed).await?;
@@ -143,24 +719,47 @@ async fn main() -> Result<()> {
         Commands::Install { model, backend } => {
             commands::install::execute(&model, &backend).await?;
         }
+        Commands::Quantize { model, to, output, imatrix, force } => {
+            let options = commands::quantize::QuantizeOptions { to, output, imatrix, force };
+            commands::quantize::execute(&model, options)?;
+        }
+        Commands::Models { action } => match action {
+            ModelsAction::Search { term, source, limit } => {
+                commands::models::search(&term, &source, limit).await?;
+            }
+            ModelsAction::Prune { stale_days, dry_run } => {
+                commands::models::prune(stale_days, dry_run).await?;
+            }
+        },
         Commands::Run { model, port } => {
             commands::run::execute(&model, port).await?;
         }
         Commands::Remove { model } => {
             commands::remove::execute(&model).await?;
         }
-        Commands::Config { path } => {
-            commands::config::execute(path)?;
-        }
+        Commands::Config { path, action } => match action {
+            Some(ConfigAction::Get { key }) => commands::config::get(&key)?,
+            Some(ConfigAction::Set { key, value }) => commands::config::set(&key, &value)?,
+            Some(ConfigAction::Check) => commands::config::check()?,
+            None => commands::config::execute(path)?,
+        },
         Commands::Setup => {
             commands::setup::execute()?;
         }
-        Commands::Agents { list, create, agent_name } => {
-            commands::agents::execute(list, create, agent_name)?;
-        }
+        Commands::Agents { list, create, agent_name, force_recreate_defaults, action } => match action {
+            None => commands::agents::execute(list, create, agent_name, force_recreate_defaults)?,
+            Some(AgentsAction::Edit { name }) => commands::agents::edit(&name)?,
+            Some(AgentsAction::Delete { name, yes }) => commands::agents::delete(&name, yes)?,
+            Some(AgentsAction::Reset { name, yes }) => commands::agents::reset(&name, yes)?,
+            Some(AgentsAction::History { name }) => commands::agents::history(&name)?,
+            Some(AgentsAction::Rollback { name, version }) => commands::agents::rollback(&name, version)?,
+        },
         Commands::Status => {
             commands::status::execute().await?;
         }
+        Commands::Warm { preset } => {
+            commands::warm::execute(preset, cli.server.clone(), cli.model.clone()).await?;
+        }
         Commands::Chat { server, model } => {
             // Use TUI mode by default, only fall back to old chat if explicitly requested
             if cli.prompt.is_some() {
@@ -174,8 +773,15 @@ async fn main() -> Result<()> {
         Commands::Ask {
             question,
             no_tools,
+            raw,
+            template,
+            vars,
         } => {
-            commands::ask::execute(&question, cli.server, cli.model, no_tools).await?;
+            let question = match template {
+                Some(name) => commands::ask::from_template(&name, &vars, question.as_deref())?,
+                None => question.unwrap_or_default(),
+            };
+            commands::ask::execute(&question, cli.server, cli.model, no_tools, raw).await?;
         }
         Commands::Resume { session_id, last } => {
             commands::resume::execute(session_id, last).await?;
@@ -184,15 +790,171 @@ async fn main() -> Result<()> {
             prompt,
             full_auto,
             json,
+            resume,
+            timeout,
+            max_tokens,
+            max_tool_calls,
+            approve_remotely,
+            agents,
+            require_plan_approval,
+            temperature,
+            seed,
+            deterministic,
         } => {
-            commands::exec::execute(&prompt, cli.server, cli.model, full_auto, json).await?;
+            let options = commands::exec::ExecOptions {
+                full_auto,
+                json_output: json,
+                resume,
+                agent: cli.agent,
+                timeout: timeout.map(std::time::Duration::from_secs),
+                max_tokens,
+                max_tool_calls,
+                approve_remotely,
+                require_plan_approval,
+                temperature: if deterministic { Some(0.0) } else { temperature },
+                seed: seed.or(deterministic.then_some(0)),
+            };
+            let code = if agents.is_empty() {
+                commands::exec::execute(&prompt, cli.server, cli.model, options).await?
+            } else {
+                commands::consensus::execute(&prompt, &agents, cli.server, cli.model, options).await?
+            };
+            if code != 0 {
+                drop(background);
+                std::process::exit(code);
+            }
+        }
+        Commands::Rpc => {
+            commands::rpc::execute(cli.server, cli.model, cli.agent).await?;
+        }
+        Commands::Bot { platform, channel } => {
+            commands::bot::execute(platform, channel, cli.server, cli.model, cli.agent).await?;
         }
-        Commands::Benchmark => {
-            commands::benchmark::execute().await?;
+        Commands::Schedule { action } => match action {
+            ScheduleAction::Add { cron, prompt, name, workspace, notify, full_auto } => {
+                let options = commands::schedule::AddOptions {
+                    cron,
+                    prompt,
+                    name,
+                    agent: cli.agent,
+                    workspace,
+                    notify,
+                    full_auto,
+                };
+                commands::schedule::add(options)?;
+            }
+            ScheduleAction::List => commands::schedule::list()?,
+            ScheduleAction::Remove { name } => commands::schedule::remove(&name)?,
+            ScheduleAction::Run { name, once } => {
+                commands::schedule::run(name, once, cli.server, cli.model).await?;
+            }
+        },
+        Commands::Audit { paths, output, fail_on } => {
+            let options = commands::audit::AuditOptions { paths, output, fail_on };
+            let code = commands::audit::execute(options, cli.server, cli.model, cli.agent).await?;
+            if code != 0 {
+                drop(background);
+                std::process::exit(code);
+            }
         }
-        Commands::Presets => {
-            commands::presets::execute()?;
+        Commands::Scan { paths, output, triage, fail_on_secrets } => {
+            let options = commands::scan::ScanOptions { paths, output, triage, fail_on_secrets };
+            let code = commands::scan::execute(options, cli.server, cli.model, cli.agent).await?;
+            if code != 0 {
+                drop(background);
+                std::process::exit(code);
+            }
+        }
+        Commands::Sessions { action } => match action {
+            None => commands::sessions::list(20, false)?,
+            Some(SessionsAction::List { project, limit }) => commands::sessions::list(limit, project)?,
+            Some(SessionsAction::View { session_id }) => commands::session_view::execute(session_id)?,
+            Some(SessionsAction::Export { session_id, format, output }) => commands::sessions::export(session_id, &format, output)?,
+        },
+        Commands::History { query, project, limit } => {
+            match commands::history::execute(&query.join(" "), project, limit)? {
+                Some(commands::history::Choice::Run(prompt)) => {
+                    commands::chat::execute(cli.server.clone(), cli.model.clone(), Some(prompt)).await?
+                }
+                Some(commands::history::Choice::Resume(id)) => commands::resume::execute(Some(id), false).await?,
+                None => {}
+            }
+        }
+        Commands::Findings { action } => match action {
+            None => commands::findings::list(commands::findings::ListOptions { all: false, resolved: false, file: None, severity: None })?,
+            Some(FindingsAction::List { all, resolved, file, severity }) => {
+                commands::findings::list(commands::findings::ListOptions { all, resolved, file, severity })?
+            }
+            Some(FindingsAction::Show { id }) => commands::findings::show(id)?,
+            Some(FindingsAction::Resolve { id, note }) => commands::findings::resolve(id, note)?,
+            Some(FindingsAction::Reopen { id }) => commands::findings::reopen(id)?,
+            Some(FindingsAction::Export { to, file, severity, labels, output, again, dry_run }) => {
+                let options = commands::findings::ExportOptions { to, file, severity, labels, output, again, dry_run };
+                commands::findings::export(options)?
+            }
+        },
+        Commands::Explain { target } => {
+            commands::explain::execute(&target, cli.server, cli.model).await?;
+        }
+        Commands::Fix { cmd, max_iterations, auto } => {
+            commands::fix::execute(cmd, max_iterations, auto, cli.server, cli.model, cli.agent).await?;
+        }
+        Commands::Watch { cmds, globs, fix, max_iterations, auto } => {
+            let options = commands::watch::WatchOptions { cmds, globs, fix, max_iterations, auto };
+            commands::watch::execute(options, cli.server, cli.model, cli.agent).await?;
+        }
+        Commands::Pr { base, create, draft } => {
+            commands::pr::execute(base, create, draft, cli.server, cli.model, cli.agent).await?;
+        }
+        Commands::Changelog { since, file, yes } => {
+            commands::changelog::execute(since, file, yes, cli.server, cli.model, cli.agent).await?;
+        }
+        Commands::Hooks { action } => match action {
+            HooksAction::Install { pre_push, force } => commands::hooks::install(pre_push, force)?,
+            HooksAction::Uninstall => commands::hooks::uninstall()?,
+        },
+        Commands::Stats { days, project } => {
+            commands::stats::execute(days, project)?;
+        }
+        Commands::Dictate { copy } => {
+            commands::dictate::execute(copy).await?;
+        }
+        Commands::Benchmark {
+            quality,
+            judge_server,
+            judge_model,
+            presets,
+            tests,
+            context,
+            resume,
+            report_dir,
+            temperature,
+            seed,
+            deterministic,
+        } => {
+            let options = commands::benchmark::BenchmarkOptions {
+                quality: quality || judge_server.is_some() || judge_model.is_some(),
+                judge_server,
+                judge_model,
+                presets,
+                tests,
+                resume,
+                report_dir,
+                context,
+                temperature: if deterministic { Some(0.0) } else { temperature },
+                seed: seed.or(deterministic.then_some(0)),
+            };
+            commands::benchmark::execute(options).await?;
         }
+        Commands::Presets { action } => match action {
+            None => commands::presets::execute()?,
+            Some(PresetsAction::List) => commands::presets::list()?,
+            Some(PresetsAction::Show { name }) => commands::presets::show(&name)?,
+            Some(PresetsAction::New { name, from, no_edit }) => commands::presets::new(&name, from, !no_edit)?,
+            Some(PresetsAction::Edit { name }) => commands::presets::edit(&name)?,
+            Some(PresetsAction::Copy { from, to }) => commands::presets::copy(&from, &to)?,
+            Some(PresetsAction::Delete { name, yes }) => commands::presets::delete(&name, yes)?,
+        },
     }
 
     Ok(())
@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use crate::config::{find_in_path, VoiceConfig};
+
+/// Recorders tried in order when `voice.record_command` is not set. `{file}` is
+/// replaced with the output path; whisper wants 16 kHz mono WAV.
+const RECORDERS: &[(&str, &str)] = &[
+    ("arecord", "arecord -q -f S16_LE -r 16000 -c 1 {file}"),
+    ("rec", "rec -q -r 16000 -c 1 -b 16 {file}"),
+    (
+        "ffmpeg",
+        if cfg!(target_os = "macos") {
+            "ffmpeg -loglevel quiet -f avfoundation -i :0 -ar 16000 -ac 1 -y {file}"
+        } else {
+            "ffmpeg -loglevel quiet -f pulse -i default -ar 16000 -ac 1 -y {file}"
+        },
+    ),
+];
+
+/// whisper.cpp CLI names, newest first
+const WHISPER_BINARIES: &[&str] = &["whisper-cli", "whisper-cpp", "whisper"];
+
+/// System TTS commands that read text from stdin, tried in order
+const SYSTEM_TTS: &[(&str, &str)] = &[
+    ("espeak-ng", "espeak-ng --stdin"),
+    ("espeak", "espeak --stdin"),
+    ("say", "say -f -"),
+    ("spd-say", "spd-say -e"),
+];
+
+/// Longest text read aloud; replies are summarized down to their prose
+const MAX_SPOKEN_CHARS: usize = 600;
+
+/// A microphone recording in progress
+pub struct Recording {
+    child: Child,
+    path: PathBuf,
+}
+
+impl Recording {
+    pub fn start(config: &VoiceConfig) -> Result<Self> {
+        let template = match &config.record_command {
+            Some(command) => command.clone(),
+            None => RECORDERS
+                .iter()
+                .find(|(binary, _)| find_in_path(binary).is_some())
+                .map(|(_, command)| command.to_string())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("No audio recorder found; install arecord, sox or ffmpeg, or set voice.record_command")
+                })?,
+        };
+
+        let path = std::env::temp_dir().join(format!("vork-dictation-{}.wav", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let file = path.display().to_string();
+        let mut parts = template.split_whitespace().map(|part| part.replace("{file}", &file));
+        let program = parts.next().ok_or_else(|| anyhow::anyhow!("voice.record_command is empty"))?;
+
+        let child = Command::new(&program)
+            .args(parts)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start recorder '{}'", program))?;
+
+        Ok(Self { child, path })
+    }
+
+    /// Stop recording and return the audio file
+    pub fn stop(mut self) -> Result<PathBuf> {
+        // SIGINT lets recorders finish writing the WAV header
+        #[cfg(unix)]
+        let _ = Command::new("kill")
+            .arg("-INT")
+            .arg(self.child.id().to_string())
+            .status();
+
+        for _ in 0..20 {
+            if self.child.try_wait()?.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        // A WAV header alone is 44 bytes
+        if fs::metadata(&self.path).map(|m| m.len() <= 44).unwrap_or(true) {
+            anyhow::bail!("No audio was recorded; check your microphone or voice.record_command");
+        }
+        Ok(self.path.clone())
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Transcribe a WAV file with the whisper.cpp server or binary from the config
+pub async fn transcribe(config: &VoiceConfig, audio: &Path) -> Result<String> {
+    let text = match &config.whisper_url {
+        Some(url) => transcribe_with_server(url, config.language.as_deref(), audio).await?,
+        None => transcribe_with_binary(config, audio).await?,
+    };
+
+    // whisper marks silence with tags like [BLANK_AUDIO]
+    Ok(text
+        .split_whitespace()
+        .filter(|word| !(word.starts_with('[') && word.ends_with(']')))
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+async fn transcribe_with_binary(config: &VoiceConfig, audio: &Path) -> Result<String> {
+    let binary = match &config.whisper_binary {
+        Some(binary) => shellexpand::tilde(binary).to_string(),
+        None => WHISPER_BINARIES
+            .iter()
+            .find_map(|name| find_in_path(name))
+            .map(|p| p.display().to_string())
+            .ok_or_else(|| {
+                anyhow::anyhow!("whisper.cpp not found; set voice.whisper_binary or voice.whisper_url")
+            })?,
+    };
+    let model = config.whisper_model.as_ref().ok_or_else(|| {
+        anyhow::anyhow!("Set voice.whisper_model to a ggml model file (e.g. ggml-base.en.bin)")
+    })?;
+
+    let mut cmd = tokio::process::Command::new(&binary);
+    cmd.arg("-m")
+        .arg(shellexpand::tilde(model).as_ref())
+        .arg("-f")
+        .arg(audio)
+        .arg("-nt") // no timestamps
+        .arg("-np"); // no progress or system info
+    if let Some(ref language) = config.language {
+        cmd.arg("-l").arg(language);
+    }
+
+    let output = cmd
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}", binary))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed: {}",
+            binary,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// POST the audio to a whisper.cpp server's /inference endpoint
+async fn transcribe_with_server(url: &str, language: Option<&str>, audio: &Path) -> Result<String> {
+    const BOUNDARY: &str = "----vork-dictation";
+
+    let mut fields = vec![("response_format", "text")];
+    if let Some(language) = language {
+        fields.push(("language", language));
+    }
+
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", BOUNDARY, name, value).as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\n",
+            BOUNDARY
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(&fs::read(audio).context("Failed to read recording")?);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/inference", url.trim_end_matches('/')))
+        .header("Content-Type", format!("multipart/form-data; boundary={}", BOUNDARY))
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach whisper server at {}", url))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        anyhow::bail!("Whisper server error {}: {}", status, text);
+    }
+
+    Ok(response.text().await?)
+}
+
+/// Reads text aloud in the background; starting a new utterance stops the
+/// previous one
+#[derive(Default)]
+pub struct Speaker {
+    current: Option<Child>,
+}
+
+impl Speaker {
+    pub fn say(&mut self, config: &VoiceConfig, text: &str) -> Result<()> {
+        self.stop();
+
+        let text = spoken_summary(text);
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let mut cmd = tts_command(config)?;
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start text-to-speech")?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        self.current = Some(child);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut child) = self.current.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn tts_command(config: &VoiceConfig) -> Result<Command> {
+    if let Some(ref template) = config.tts_command {
+        let mut parts = template.split_whitespace();
+        let program = parts.next().ok_or_else(|| anyhow::anyhow!("voice.tts_command is empty"))?;
+        let mut cmd = Command::new(program);
+        cmd.args(parts);
+        return Ok(cmd);
+    }
+
+    // piper writes raw 16-bit audio; play it with aplay
+    if let Some(ref model) = config.piper_model {
+        if find_in_path("piper").is_some() && find_in_path("aplay").is_some() {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c")
+                .arg("piper --model \"$1\" --output-raw | aplay -q -r 22050 -f S16_LE -t raw -")
+                .arg("sh")
+                .arg(shellexpand::tilde(model).as_ref());
+            return Ok(cmd);
+        }
+    }
+
+    let template = SYSTEM_TTS
+        .iter()
+        .find(|(binary, _)| find_in_path(binary).is_some())
+        .map(|(_, command)| *command)
+        .ok_or_else(|| {
+            anyhow::anyhow!("No text-to-speech found; install piper or espeak-ng, or set voice.tts_command")
+        })?;
+    let mut parts = template.split_whitespace();
+    let mut cmd = Command::new(parts.next().unwrap_or_default());
+    cmd.args(parts);
+    Ok(cmd)
+}
+
+/// The prose of a reply without code blocks or markdown markup, cut at a
+/// sentence boundary so long answers stay short when spoken
+pub fn spoken_summary(text: &str) -> String {
+    let mut in_code = false;
+    let mut prose = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || trimmed.is_empty() || trimmed.starts_with('|') {
+            continue;
+        }
+        let cleaned: String = trimmed
+            .trim_start_matches(['#', '>', '-', '*', ' '])
+            .chars()
+            .filter(|c| !matches!(c, '*' | '`' | '_'))
+            .collect();
+        if !cleaned.is_empty() {
+            prose.push(cleaned);
+        }
+    }
+
+    let prose = prose.join(" ");
+    if prose.chars().count() <= MAX_SPOKEN_CHARS {
+        return prose;
+    }
+
+    let cut: String = prose.chars().take(MAX_SPOKEN_CHARS).collect();
+    match cut.rfind(['.', '!', '?']) {
+        Some(end) => cut[..=end].to_string(),
+        None => cut,
+    }
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{text, MockLlamaServer, Workspace};
+use serde_json::json;
+
+#[test]
+fn raw_questions_go_to_the_model_alone() {
+    let workspace = Workspace::new();
+    let server = MockLlamaServer::start(vec![text("Paris.")]);
+
+    let output = workspace.vork(&server, &["ask", "--raw", "What is the capital of France?"], &[]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "Paris.");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["messages"], json!([{ "role": "user", "content": "What is the capital of France?" }]));
+    assert!(requests[0]["tools"].is_null());
+}
+
+#[test]
+fn templates_are_filled_in_before_asking() {
+    let workspace = Workspace::new();
+    let prompts = workspace.home().join(".vork/prompts");
+    std::fs::create_dir_all(&prompts).unwrap();
+    std::fs::write(prompts.join("translate.md"), "Translate into {{lang}}:\n\n{{input}}\n").unwrap();
+    let server = MockLlamaServer::start(vec![text("Bonjour.")]);
+
+    let args = ["ask", "--raw", "--template", "translate", "--var", "lang=French", "good morning"];
+    let output = workspace.vork(&server, &args, &[]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "Bonjour.");
+    assert_eq!(server.requests()[0]["messages"][0]["content"], "Translate into French:\n\ngood morning");
+
+    let output = workspace.vork(&server, &["ask", "--template", "translate", "good morning"], &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("needs lang"));
+    assert_eq!(server.requests().len(), 1);
+}
@@ -0,0 +1,205 @@
+//! A stand-in for llama-server and a throwaway workspace, so the agent
+//! pipeline can be driven end to end without a GPU or a model.
+
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tempfile::TempDir;
+
+/// Reply sent once the scripted replies run out
+const FALLBACK_REPLY: &str = "Done.";
+
+/// Marks a scripted reply as an error response, see [`error`]
+const STATUS_KEY: &str = "mock_status";
+
+/// An OpenAI-compatible server on a free local port that answers chat
+/// completions from a script, in order, and records every request
+pub struct MockLlamaServer {
+    pub url: String,
+    replies: Arc<Mutex<VecDeque<Value>>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockLlamaServer {
+    pub fn start(replies: Vec<Value>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind the mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let replies = Arc::new(Mutex::new(VecDeque::from(replies)));
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let (script, log) = (replies.clone(), requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (script, log) = (script.clone(), log.clone());
+                thread::spawn(move || serve(stream, &script, &log));
+            }
+        });
+        Self { url, replies, requests }
+    }
+
+    /// The chat completion requests received so far
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Scripted replies not asked for yet
+    pub fn remaining(&self) -> usize {
+        self.replies.lock().unwrap().len()
+    }
+}
+
+/// A final answer
+pub fn text(content: &str) -> Value {
+    json!({
+        "choices": [{ "message": { "role": "assistant", "content": content } }],
+        "usage": { "prompt_tokens": 100, "completion_tokens": 10, "total_tokens": 110 }
+    })
+}
+
+/// A reply asking for one tool call
+pub fn tool_call(name: &str, arguments: Value) -> Value {
+    json!({
+        "choices": [{
+            "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": format!("call-{}", name),
+                    "type": "function",
+                    "function": { "name": name, "arguments": arguments.to_string() }
+                }]
+            }
+        }],
+        "usage": { "prompt_tokens": 100, "completion_tokens": 10, "total_tokens": 110 }
+    })
+}
+
+/// An error response with `status`, like llama-server rejecting a prompt
+/// larger than its context
+pub fn error(status: u16, body: Value) -> Value {
+    json!({ STATUS_KEY: status, "body": body })
+}
+
+/// The tool results sent back in a request, as "Tool: name\nResult:\n..."
+pub fn tool_results(request: &Value) -> Vec<String> {
+    request["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|message| message["content"].as_str())
+        .filter_map(|content| content.strip_prefix("Tool execution result:\n"))
+        .map(str::to_string)
+        .collect()
+}
+
+fn serve(stream: TcpStream, replies: &Mutex<VecDeque<Value>>, requests: &Mutex<Vec<Value>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).is_err() || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (status, reply) = match path {
+        "/health" => ("200 OK".to_string(), json!({ "status": "ok" })),
+        "/v1/models" => ("200 OK".to_string(), json!({ "data": [{ "id": "mock-model" }] })),
+        "/v1/chat/completions" => {
+            requests.lock().unwrap().push(serde_json::from_slice(&body).unwrap_or(Value::Null));
+            let reply = replies.lock().unwrap().pop_front().unwrap_or_else(|| text(FALLBACK_REPLY));
+            match reply.get(STATUS_KEY) {
+                Some(status) => (format!("{} Error", status), reply["body"].clone()),
+                None => ("200 OK".to_string(), reply),
+            }
+        }
+        _ => ("404 Not Found".to_string(), json!({})),
+    };
+    let body = reply.to_string();
+    let _ = write!(
+        &stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+}
+
+/// A project directory and a home directory of its own, so runs never see
+/// the real ~/.vork
+pub struct Workspace {
+    home: TempDir,
+    dir: TempDir,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self { home: TempDir::new().unwrap(), dir: TempDir::new().unwrap() }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn write(&self, path: &str, content: &str) {
+        let path = self.dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    pub fn home(&self) -> &Path {
+        self.home.path()
+    }
+
+    pub fn read(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.dir.path().join(path)).ok()
+    }
+
+    /// The saved sessions, oldest first
+    pub fn sessions(&self) -> Vec<PathBuf> {
+        let mut sessions: Vec<PathBuf> = fs::read_dir(self.home.path().join(".vork/sessions"))
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        sessions.sort();
+        sessions
+    }
+
+    /// Run vork against `server` in this workspace, with no terminal and
+    /// the given config overrides (e.g. ("LLAMACPP__CONTEXT_LIMIT", "4096"))
+    pub fn vork(&self, server: &MockLlamaServer, args: &[&str], config: &[(&str, &str)]) -> Output {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_vork"));
+        command
+            .args(args)
+            .args(["--server", &server.url])
+            .current_dir(self.dir.path())
+            .env("HOME", self.home.path())
+            .env("VORK_ASSISTANT__BACKEND", "llamacpp")
+            .stdin(Stdio::null());
+        for (key, value) in config {
+            command.env(format!("VORK_{}", key), value);
+        }
+        command.output().expect("run vork")
+    }
+}
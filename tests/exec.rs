@@ -0,0 +1,242 @@
+mod common;
+
+use common::{error, text, tool_call, tool_results, MockLlamaServer, Workspace};
+use serde_json::{json, Value};
+
+fn report(output: &std::process::Output) -> Value {
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "exec --json printed no report ({}):\n{}\n{}",
+            e,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    })
+}
+
+#[test]
+fn tool_results_go_back_to_the_model() {
+    let workspace = Workspace::new();
+    workspace.write("notes.txt", "the answer is 42\n");
+    let server = MockLlamaServer::start(vec![
+        tool_call("read_file", json!({ "path": "notes.txt" })),
+        text("The notes say 42."),
+    ]);
+
+    let output = workspace.vork(&server, &["exec", "--json", "What do the notes say?"], &[]);
+    let report = report(&output);
+    assert_eq!(report["status"], "success");
+    assert_eq!(report["message"], "The notes say 42.");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let offered: Vec<&str> = requests[0]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|tool| tool["function"]["name"].as_str())
+        .collect();
+    assert!(offered.contains(&"read_file") && offered.contains(&"bash_exec"));
+    let results = tool_results(&requests[1]);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].starts_with("Tool: read_file"));
+    assert!(results[0].contains("the answer is 42"));
+    assert_eq!(workspace.sessions().len(), 1);
+}
+
+#[test]
+fn json_report_lists_changes_and_usage() {
+    let workspace = Workspace::new();
+    let server = MockLlamaServer::start(vec![
+        tool_call("write_file", json!({ "path": "src/hello.txt", "content": "hello\n" })),
+        tool_call("bash_exec", json!({ "command": "cat src/hello.txt" })),
+        text("Wrote and checked it."),
+    ]);
+
+    let output = workspace.vork(&server, &["exec", "--json", "--full-auto", "Write hello"], &[]);
+    assert_eq!(output.status.code(), Some(0));
+    let report = report(&output);
+    assert_eq!(report["status"], "success");
+    assert_eq!(report["files_changed"], json!(["src/hello.txt"]));
+    assert_eq!(report["commands_run"], json!(["cat src/hello.txt"]));
+    assert_eq!(report["token_usage"]["total_tokens"], 330);
+    assert_eq!(workspace.read("src/hello.txt").as_deref(), Some("hello\n"));
+
+    let results = tool_results(&server.requests()[2]);
+    assert!(results[1].contains("Exit code: 0") && results[1].contains("hello"));
+}
+
+#[test]
+fn writes_are_denied_without_full_auto() {
+    let workspace = Workspace::new();
+    let server = MockLlamaServer::start(vec![
+        tool_call("write_file", json!({ "path": "blocked.txt", "content": "no" })),
+        text("I could not write it."),
+    ]);
+
+    let output = workspace.vork(&server, &["exec", "--json", "Write a file"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(report(&output)["status"], "denied");
+    assert_eq!(workspace.read("blocked.txt"), None);
+    let results = tool_results(&server.requests()[1]);
+    assert!(results[0].contains("Write to blocked.txt was denied"), "{}", results[0]);
+}
+
+#[test]
+fn unknown_tools_are_reported_to_the_model() {
+    let workspace = Workspace::new();
+    let server = MockLlamaServer::start(vec![tool_call("teleport", json!({})), text("Never mind.")]);
+
+    let output = workspace.vork(&server, &["exec", "--json", "--full-auto", "Go"], &[]);
+    assert_eq!(report(&output)["status"], "success");
+    let results = tool_results(&server.requests()[1]);
+    assert!(results[0].contains("Unknown tool: teleport"), "{}", results[0]);
+}
+
+#[test]
+fn long_runs_are_summarized_to_fit_the_context() {
+    let workspace = Workspace::new();
+    let mut replies: Vec<Value> = (0..10).map(|_| tool_call("list_files", json!({}))).collect();
+    replies.push(text("Listed the files ten times."));
+    replies.push(text("Finished."));
+    let server = MockLlamaServer::start(replies);
+
+    let output = workspace.vork(
+        &server,
+        &["exec", "--json", "--full-auto", "List the files, a lot"],
+        &[("LLAMACPP__CONTEXT_LIMIT", "1000")],
+    );
+    assert_eq!(report(&output)["message"], "Finished.");
+    assert_eq!(server.remaining(), 0);
+
+    let requests = server.requests();
+    let summary = &requests[requests.len() - 2];
+    assert!(summary["tools"].is_null());
+    assert!(summary["messages"][0]["content"]
+        .as_str()
+        .unwrap()
+        .starts_with("Summarize the following conversation history"));
+    let last = requests.last().unwrap().to_string();
+    assert!(last.contains("[Conversation summary of 1 messages]"));
+    assert!(last.contains("Listed the files ten times."));
+}
+
+#[test]
+fn oversized_requests_are_trimmed_and_retried() {
+    let workspace = Workspace::new();
+    workspace.write("big.txt", &"lorem ipsum dolor sit amet\n".repeat(200));
+    let server = MockLlamaServer::start(vec![
+        tool_call("read_file", json!({ "path": "big.txt" })),
+        tool_call("list_files", json!({})),
+        error(
+            400,
+            json!({ "error": {
+                "type": "exceed_context_size_error",
+                "message": "the request exceeds the available context size",
+                "n_prompt_tokens": 6000,
+                "n_ctx": 4096
+            } }),
+        ),
+        text("Fits now."),
+    ]);
+
+    let output = workspace.vork(&server, &["exec", "--json", "--full-auto", "Read big.txt"], &[]);
+    assert_eq!(report(&output)["message"], "Fits now.");
+
+    // The retry keeps the system prompt, the task and the newest result
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    let rejected = requests[2]["messages"].as_array().unwrap();
+    let retried = requests[3]["messages"].as_array().unwrap();
+    assert_eq!(rejected.len(), 4);
+    assert_eq!(retried[..], [rejected[0].clone(), rejected[1].clone(), rejected[3].clone()]);
+}
+
+#[test]
+fn repeated_reads_point_at_the_earlier_result() {
+    let workspace = Workspace::new();
+    workspace.write("notes.txt", &"the answer is 42\n".repeat(20));
+    let server = MockLlamaServer::start(vec![
+        tool_call("read_file", json!({ "path": "notes.txt" })),
+        tool_call("read_file", json!({ "path": "notes.txt" })),
+        tool_call("write_file", json!({ "path": "notes.txt", "content": "the answer is 43\n".repeat(20) })),
+        tool_call("read_file", json!({ "path": "notes.txt" })),
+        text("Read it."),
+    ]);
+
+    workspace.vork(&server, &["exec", "--json", "--full-auto", "Read the notes twice"], &[]);
+    let results = tool_results(&server.requests()[4]);
+    assert_eq!(results.len(), 4);
+    assert!(results[0].contains("the answer is 42"));
+    assert!(results[1].starts_with("Tool: read_file\nResult:\n📖 Read 20 lines from notes.txt\n\n[Unchanged:"), "{}", results[1]);
+    assert!(!results[1].contains("the answer is 42"));
+    assert!(results[3].contains("the answer is 43"));
+}
+
+#[test]
+fn summaries_go_to_the_compaction_model() {
+    let workspace = Workspace::new();
+    let mut replies: Vec<Value> = (0..10).map(|_| tool_call("list_files", json!({}))).collect();
+    replies.push(text("Finished."));
+    let server = MockLlamaServer::start(replies);
+    let summarizer = MockLlamaServer::start(vec![text("Listed the files ten times.")]);
+
+    let output = workspace.vork(
+        &server,
+        &["exec", "--json", "--full-auto", "List the files, a lot"],
+        &[
+            ("LLAMACPP__CONTEXT_LIMIT", "1000"),
+            ("ASSISTANT__COMPACTION__URL", &summarizer.url),
+            ("ASSISTANT__COMPACTION__MODEL", "small-model"),
+        ],
+    );
+    assert_eq!(report(&output)["message"], "Finished.");
+
+    let summaries = summarizer.requests();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0]["model"], "small-model");
+    assert!(server.requests().iter().all(|request| !request["tools"].is_null()));
+    assert!(server.requests().last().unwrap().to_string().contains("Listed the files ten times."));
+}
+
+#[test]
+fn sqlite_reads_cannot_write_files_or_run_commands() {
+    if std::process::Command::new("sqlite3").arg("-version").output().is_err() {
+        eprintln!("sqlite3 is not installed; skipping");
+        return;
+    }
+    let workspace = Workspace::new();
+    // An empty file is an empty SQLite database
+    workspace.write("app.db", "");
+    let server = MockLlamaServer::start(vec![
+        tool_call("sql_query", json!({ "database": "app.db", "query": "SELECT writefile('written.txt', 'x')" })),
+        tool_call("sql_query", json!({ "database": "app.db", "query": "SELECT edit('', 'touch edited.txt')" })),
+        text("Both were refused."),
+    ]);
+
+    // Reads are not asked about, so nothing but sqlite3 stands in the way
+    workspace.vork(&server, &["exec", "--json", "Query the database"], &[]);
+    let results = tool_results(&server.requests()[2]);
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.contains("safe mode")), "{:?}", results);
+    assert_eq!(workspace.read("written.txt"), None);
+    assert_eq!(workspace.read("edited.txt"), None);
+}
+
+#[test]
+fn the_timeout_stops_a_hung_tool() {
+    let workspace = Workspace::new();
+    let server = MockLlamaServer::start(vec![
+        tool_call("bash_exec", json!({ "command": "sleep 30" })),
+        text("Never reached."),
+    ]);
+
+    let started = std::time::Instant::now();
+    let output = workspace.vork(&server, &["exec", "--json", "--full-auto", "--timeout", "2", "Wait"], &[]);
+    assert!(started.elapsed() < std::time::Duration::from_secs(20), "took {:?}", started.elapsed());
+    assert_eq!(output.status.code(), Some(5));
+    let report = report(&output);
+    assert_eq!(report["status"], "timeout");
+    assert_eq!(report["error"], "Timed out after 2s");
+    assert_eq!(workspace.sessions().len(), 1);
+}
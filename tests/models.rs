@@ -0,0 +1,32 @@
+mod common;
+
+use common::{MockLlamaServer, Workspace};
+
+fn listed(workspace: &Workspace, server: &MockLlamaServer) -> String {
+    let models = workspace.path().join("models").display().to_string();
+    let output = workspace.vork(
+        server,
+        &["list"],
+        &[("LLAMACPP__MODELS_DIR", &models), ("LLAMACPP__BINARY_PATH", env!("CARGO_BIN_EXE_vork"))],
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn model_list_follows_the_models_directory() {
+    let workspace = Workspace::new();
+    let server = MockLlamaServer::start(vec![]);
+    workspace.write("models/qwen/qwen-7b-q4.gguf", "");
+    workspace.write("models/notes.txt", "");
+    // Old enough for the scan to be kept, so the second listing reuses it
+    // unless it notices the changes
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    let first = listed(&workspace, &server);
+    assert!(first.contains("qwen-7b-q4") && !first.contains("notes"), "{}", first);
+
+    workspace.write("models/llama/llama-3b-q8.gguf", "");
+    std::fs::remove_file(workspace.path().join("models/qwen/qwen-7b-q4.gguf")).unwrap();
+    let second = listed(&workspace, &server);
+    assert!(workspace.home().join(".vork/model_scan.json").exists());
+    assert!(second.contains("llama-3b-q8") && !second.contains("qwen-7b-q4"), "{}", second);
+}